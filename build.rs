@@ -0,0 +1,34 @@
+fn main() {
+    println!("cargo::rerun-if-changed=build.rs");
+    #[cfg(feature = "grpc")]
+    grpc();
+}
+
+/// Generate the server of the gRPC service described in `proto/nomenclate.proto`, whose messages
+/// are defined by hand in `src/grpc.rs`.
+#[cfg(feature = "grpc")]
+fn grpc() {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    let method = |name: &str, route_name: &str| {
+        Method::builder()
+            .name(name)
+            .route_name(route_name)
+            .input_type("crate::grpc::ExtractRequest")
+            .output_type("crate::grpc::ExtractResponse")
+            .codec_path("tonic_prost::ProstCodec")
+    };
+    let service = Service::builder()
+        .name("Extractor")
+        .package("nomenclate")
+        .method(method("extract", "Extract").build())
+        .method(
+            method("extract_batch", "ExtractBatch")
+                .client_streaming()
+                .server_streaming()
+                .build(),
+        )
+        .build();
+
+    Builder::new().build_client(false).compile(&[service]);
+}
@@ -0,0 +1,205 @@
+## Umbenennen
+
+renamed = { $from } -> { $to }
+changes = {"  "}Änderungen: { $changes }
+unchanged = { $path } unverändert
+skipping = überspringe { $path }: { $reason }
+already-exists = { $path } existiert bereits
+case-only-rename = Umbenennungen, die nur die Gross- und Kleinschreibung ändern, verwirren Sync-Clients
+sync-temporary-name = { $name } sieht wie eine temporäre Datei eines Sync-Clients aus
+sync-conflict-name = { $name } sieht wie eine Konfliktkopie eines Sync-Clients aus
+error-move-back = konnte { $from } nicht zurück nach { $to } verschieben: { $error }
+error-invalid-file-name = { $path }: Titel { $title } ist kein gültiger Dateiname
+error-rename = konnte { $path } nicht umbenennen: { $error }
+error-counter = konnte { $path } nicht nummerieren: { $error }
+error-write-sort-key = konnte Sortierschlüssel für { $path } nicht schreiben: { $error }
+error-write-opf = konnte OPF-Datei für { $path } nicht schreiben: { $error }
+error-write-json-sidecar = konnte JSON-Begleitdatei für { $path } nicht schreiben: { $error }
+error-output = konnte das Ergebnis für { $path } nicht senden: { $error }
+
+## Titel extrahieren
+
+file-empty = Datei ist leer
+not-a-pdf = kein PDF-Dokument
+html-page = als PDF-Dokument gespeicherte HTML-Seite, möglicherweise eine Fehlerseite
+html-page-without-title = HTML-Seite ohne Titel
+already-templated = bereits nach der Vorlage benannt
+type-not-selected = als { $class } erkannt, was nicht zu { $types } gehört
+type-not-recognized = nicht als { $types } erkannt
+sandbox-too-large = zu groß, um mit --sandbox verarbeitet zu werden
+warn-sandbox = konnte die Verarbeitung nicht vollständig abschotten, verarbeite ohne einige Einschränkungen: { $error }
+error-sandbox-output = --sandbox erlaubt nicht, Ergebnisse mit --output { $output } über das Netzwerk zu senden
+sandbox-unsupported = auf diesem System nicht unterstützt
+error-document = { $path }: { $error }
+error-read = konnte { $path } nicht lesen: { $error }
+error-read-directory = konnte Verzeichnis { $path } nicht lesen: { $error }
+error-temporary-directory = konnte kein temporäres Verzeichnis erstellen: { $error }
+error-no-candidate = kein Titelkandidat in { $path } gefunden
+error-load-overrides = konnte Overrides nicht laden
+error-load-trust-policy = konnte Vertrauensrichtlinie nicht laden
+error-load-acronyms = konnte Akronyme nicht laden
+warn-lookup-overrides = konnte Overrides für { $path } nicht nachschlagen: { $error }
+warn-lookup-identifier = konnte { $id } nicht nachschlagen: { $error }
+warn-no-cache-directory = kein Cache-Verzeichnis gefunden, Kandidaten werden nicht zwischengespeichert
+warn-read-cache = konnte zwischengespeicherte Kandidaten für { $path } nicht lesen: { $error }
+error-changed-since = konnte nicht bestimmen, welche Dokumente sich geändert haben: { $error }
+error-no-journal = kein Zustandsverzeichnis für das Journal gefunden, --changed-since braucht eines
+warn-write-journal = konnte diesen Durchlauf nicht ins Journal schreiben: { $error }
+warn-write-audit-log = konnte das Audit-Log nicht schreiben: { $error }
+error-lock = konnte nicht sicherstellen, dass kein anderer Durchlauf läuft: { $error }
+error-watch = konnte nicht auf Dokumente warten: { $error }
+warn-watch-event = konnte einige Änderungen an Dokumenten nicht verfolgen: { $error }
+error-watch-signal = konnte nicht auf Aufforderungen zum Beenden der Überwachung warten: { $error }
+warn-notify-systemd = konnte systemd nicht benachrichtigen: { $error }
+warn-notify-desktop = konnte keine Desktop-Benachrichtigung anzeigen: { $error }
+notification-renamed = { $count ->
+        [one] Dokument umbenannt
+       *[other] { $count } Dokumente umbenannt
+    }
+notification-undo = Rückgängig
+error-undo-rename = konnte die Umbenennung von { $path } nicht rückgängig machen: { $error }
+error-ask-password = konnte das Passwort nicht lesen: { $error }
+error-serve = konnte nicht auf { $address } lauschen: { $error }
+warn-serve-respond = konnte eine Anfrage nicht beantworten: { $error }
+serve-no-preview = konnte keine Vorschau erstellen: { $error }
+warn-write-cache = konnte Kandidaten für { $path } nicht zwischenspeichern: { $error }
+progress-documents = Dokumente
+warn-no-text = Seite { $page } von { $path } enthält keinen Text, sie ist vielleicht gescannt
+warn-no-corrections-directory = kein Zustandsverzeichnis gefunden, aus gewählten Titeln wird nicht gelernt
+warn-read-corrections = konnte die gelernten Titel nicht lesen: { $error }
+warn-write-correction = konnte den Titel von { $path } nicht lernen: { $error }
+
+## Fehlschläge überprüfen
+
+review-prompt = Titel>{" "}
+password-prompt = Passwort>{" "}
+review-candidate = #{ $number }: { $title } (Seite { $page }, y { $y }, Schriftgröße { $font_size }, Bewertung { $score })
+review-skipping = überspringe { $path }
+review-skipping-remaining = überspringe verbleibende Dokumente
+error-review = konnte Fehlschläge nicht überprüfen: { $error }
+
+## Exportieren
+
+error-bibliography-format = unbekanntes Bibliografieformat von { $path }, verwende --bibliography-format
+error-write-bibliography = konnte Bibliografie { $path } nicht schreiben: { $error }
+error-serialize = konnte { $path } nicht serialisieren: { $error }
+error-paperless-upload = konnte { $path } nicht zu paperless-ngx hochladen: { $error }
+paperless-unknown = { $name } ist nicht unter den { $kind } von paperless-ngx und wird weggelassen
+
+## Objektspeicher
+
+error-s3 = konnte nicht auf den Objektspeicher zugreifen: { $error }
+error-s3-list = konnte { $location } nicht auflisten: { $error }
+error-s3-download = konnte { $location } nicht herunterladen: { $error }
+error-s3-rename = konnte { $from } nicht in { $to } umbenennen: { $error }
+
+## Anhänge speichern
+
+error-mail-output = konnte { $path } nicht erstellen: { $error }
+error-mail-source = konnte die E-Mails in { $source } nicht lesen: { $error }
+error-mail-save = konnte { $path } nicht speichern: { $error }
+
+## Inhaltsadressierte Bibliothek
+
+library-linked = { $link } -> { $object }
+error-library-directory = konnte { $path } nicht erstellen: { $error }
+error-library-store = konnte { $path } nicht ablegen: { $error }
+error-library-link = konnte { $path } nicht verknüpfen: { $error }
+
+## Journale übertragen
+
+journal-exported = { $count } Durchläufe nach { $path } exportiert
+warn-export-outside = { $count } Umbenennungen außerhalb von { $root } ausgelassen
+error-journal-directory = kein Zustandsverzeichnis für das Journal gefunden
+error-export-journal = konnte das Journal nicht exportieren: { $error }
+error-import-journal = konnte das Journal nicht importieren: { $error }
+
+## Abstimmungen teilen
+
+tuning-exported = die Optionen und { $count } gelernte Titel nach { $path } exportiert
+tuning-imported = { $count } gelernte Titel hinzugefügt, ausführen mit: { $arguments }
+tuning-file-written = die Datei von { $option } nach { $path } geschrieben
+error-export-tuning = konnte die Abstimmung nicht exportieren: { $error }
+error-import-tuning = konnte die Abstimmung nicht importieren: { $error }
+
+## Konfigurationen vergleichen
+
+diff-config-changed = { $path }: { $old } → { $new }
+diff-config-unchanged = unverändert
+diff-config-review = Titel unsicher
+diff-config-summary = { $changed } von { $count } Dokumenten würden anders benannt
+error-diff-config = konnte die Optionen von { $path } nicht lesen: { $error }
+
+## Namen überprüfen
+
+verify-mismatch = { $path } passt nicht zu seinem Titel { $title } (Abstand { $distance })
+verify-uncertain = konnte { $path } nicht überprüfen, sein Titel ist unsicher
+verify-summary = { $mismatched } von { $count } Dokumenten passen nicht zu ihren Namen, { $uncertain } konnten nicht überprüft werden
+
+## Snapshots wiederholen
+
+replay-title = { $document }: { $title } (Konfidenz { $confidence })
+replay-no-candidate = { $document }: kein Titelkandidat
+error-replay = konnte { $path } nicht wiederholen: { $error }
+error-create-snapshot = konnte die Snapshot-Datei nicht erstellen
+warn-write-snapshot = konnte den Snapshot von { $path } nicht schreiben: { $error }
+
+## Bücher aufteilen
+
+split-invalid-title = überspringe Kapitel mit ungültigem Titel { $title }
+split-exists = überspringe Kapitel { $title }: { $path } existiert bereits
+error-split = konnte { $path } nicht aufteilen: { $error }
+
+## Berichte und Statistiken
+
+report-summary = { $count ->
+        [one] ein Dokument
+       *[other] { $count } Dokumente
+    } in { $elapsed } verarbeitet
+report-coverage = { $percent } % der Glyphen dekodiert ({ $unmapped } von { $glyphs } nicht zugeordnet)
+report-font-coverage = { $font }: { $percent } % ({ $unmapped } von { $glyphs } nicht zugeordnet)
+stats-documents = Dokumente: { $documents } ({ $failed } konnten nicht geladen werden, { $untitled } ohne Titel)
+stats-sources = Titelquellen:
+stats-failed-fonts = Schriften mit fehlender oder nicht unterstützter Kodierung: { $count }
+stats-font-documents = { $count ->
+        [one] ein Dokument
+       *[other] { $count } Dokumente
+    }
+stats-unmapped = nicht zugeordnete Glyphen: { $unmapped } von { $glyphs } ({ $percent } %)
+stats-ambiguous = Dokumente mit fast gleichauf liegenden Titelkandidaten (weniger als { $percent } % Abstand): { $count }
+error-panic = Verarbeitung von { $path } abgestürzt, wird übersprungen: { $error }
+
+## Vorlagen
+
+error-template = ungültige Vorlage: { $error }
+template-fields = Felder: { $fields }
+template-no-fields = Felder: keine
+template-unguarded = Warnung: Dokumente ohne { $field } erhalten ein leeres, ein Standardwert oder eine Bedingung wäre sinnvoll
+template-missing = Warnung: { $path } hat kein { $field }
+template-preview = Vorschau: { $name }
+
+## Konfigurationsdateien prüfen
+
+config-valid = { $path } ist gültig
+config-sources-default = die Reihenfolge, in der die Quellen gelesen werden
+config-overrides = { $hashes } Titel nach Hash und { $paths } nach Pfad
+config-override = { $path } heißt { $title }
+config-no-override = { $path } hat keinen Override
+config-unknown-key = { $path }: unbekannter Schlüssel { $key }
+config-unknown-key-suggestion = { $path }: unbekannter Schlüssel { $key }, war { $suggestion } gemeint?
+error-config-syntax = { $path } ist kein gültiges TOML: { $error }
+error-config = { $error }
+
+## Selbsttest
+
+selftest-passed = ok          { $name }
+selftest-failed = FEHLER      { $name }: { $reason }
+selftest-skipped = übersprungen { $name }: { $reason }
+selftest-feature-ocr = Texterkennung (OCR)
+selftest-feature-lookup = Identifikatoren online nachschlagen
+selftest-wrong-title = Titel { $title } statt { $expected } erhalten
+selftest-no-title = kein Titel gefunden, erwartet war { $expected }
+selftest-needs-ocr = benötigt OCR
+selftest-missing-programs = { $programs } nicht installiert
+selftest-not-built = nicht eingebaut, dafür das Feature { $feature } aktivieren
+selftest-offline = offline
@@ -0,0 +1,205 @@
+## Renaming
+
+renamed = { $from } -> { $to }
+changes = {"  "}changes: { $changes }
+unchanged = { $path } unchanged
+skipping = skipping { $path }: { $reason }
+already-exists = { $path } already exists
+case-only-rename = case-only renames confuse sync clients
+sync-temporary-name = { $name } looks like a temporary file of a sync client
+sync-conflict-name = { $name } looks like a conflicting copy of a sync client
+error-move-back = could not move { $from } back to { $to }: { $error }
+error-invalid-file-name = { $path }: title { $title } is not a valid file name
+error-rename = could not rename { $path }: { $error }
+error-counter = could not number { $path }: { $error }
+error-write-sort-key = could not write sort key for { $path }: { $error }
+error-write-opf = could not write OPF file for { $path }: { $error }
+error-write-json-sidecar = could not write JSON sidecar file for { $path }: { $error }
+error-output = could not send the outcome for { $path }: { $error }
+
+## Extracting titles
+
+file-empty = file is empty
+not-a-pdf = not a PDF document
+html-page = HTML page saved as a PDF document, possibly an error page
+html-page-without-title = HTML page without a title
+already-templated = already named after the template
+type-not-selected = recognized as { $class }, which is not one of { $types }
+type-not-recognized = not recognized as one of { $types }
+sandbox-too-large = too large to parse with --sandbox
+warn-sandbox = could not fully sandbox parsing, parsing without some restrictions: { $error }
+error-sandbox-output = --sandbox does not allow sending outcomes over the network with --output { $output }
+sandbox-unsupported = not supported on this system
+error-document = { $path }: { $error }
+error-read = could not read { $path }: { $error }
+error-read-directory = could not read directory { $path }: { $error }
+error-temporary-directory = could not create a temporary directory: { $error }
+error-no-candidate = no title candidate found in { $path }
+error-load-overrides = could not load overrides
+error-load-trust-policy = could not load trust policy
+error-load-acronyms = could not load acronyms
+warn-lookup-overrides = could not look up overrides for { $path }: { $error }
+warn-lookup-identifier = could not look up { $id }: { $error }
+warn-no-cache-directory = could not find a cache directory, not caching candidates
+warn-read-cache = could not read cached candidates for { $path }: { $error }
+error-changed-since = could not determine which documents changed: { $error }
+error-no-journal = could not find a state directory for the journal, --changed-since needs one
+warn-write-journal = could not journal this run: { $error }
+warn-write-audit-log = could not write the audit log: { $error }
+error-lock = could not make sure no other run is in progress: { $error }
+error-watch = could not watch for documents: { $error }
+warn-watch-event = could not watch for some changes to documents: { $error }
+error-watch-signal = could not listen for requests to stop watching: { $error }
+warn-notify-systemd = could not notify systemd: { $error }
+warn-notify-desktop = could not show a desktop notification: { $error }
+notification-renamed = { $count ->
+        [one] Renamed document
+       *[other] Renamed { $count } documents
+    }
+notification-undo = Undo
+error-undo-rename = could not undo the rename of { $path }: { $error }
+error-ask-password = could not read the password: { $error }
+error-serve = could not listen on { $address }: { $error }
+warn-serve-respond = could not answer a request: { $error }
+serve-no-preview = could not render a preview: { $error }
+warn-write-cache = could not cache candidates for { $path }: { $error }
+progress-documents = documents
+warn-no-text = page { $page } of { $path } has no text, it might be scanned
+warn-no-corrections-directory = could not find a state directory, not learning from chosen titles
+warn-read-corrections = could not read the learned titles: { $error }
+warn-write-correction = could not learn the title of { $path }: { $error }
+
+## Reviewing failures
+
+review-prompt = title>{" "}
+password-prompt = password>{" "}
+review-candidate = #{ $number }: { $title } (page { $page }, y { $y }, font size { $font_size }, score { $score })
+review-skipping = skipping { $path }
+review-skipping-remaining = skipping remaining documents
+error-review = could not review failures: { $error }
+
+## Exporting
+
+error-bibliography-format = unknown bibliography format of { $path }, use --bibliography-format
+error-write-bibliography = could not write bibliography { $path }: { $error }
+error-serialize = could not serialize { $path }: { $error }
+error-paperless-upload = could not upload { $path } to paperless-ngx: { $error }
+paperless-unknown = { $name } is not in the { $kind } of paperless-ngx, leaving it out
+
+## Object storage
+
+error-s3 = could not access object storage: { $error }
+error-s3-list = could not list { $location }: { $error }
+error-s3-download = could not download { $location }: { $error }
+error-s3-rename = could not rename { $from } to { $to }: { $error }
+
+## Saving attachments
+
+error-mail-output = could not create { $path }: { $error }
+error-mail-source = could not read the emails in { $source }: { $error }
+error-mail-save = could not save { $path }: { $error }
+
+## Content-addressed library
+
+library-linked = { $link } -> { $object }
+error-library-directory = could not create { $path }: { $error }
+error-library-store = could not store { $path }: { $error }
+error-library-link = could not link { $path }: { $error }
+
+## Moving journals
+
+journal-exported = exported { $count } runs to { $path }
+warn-export-outside = left out { $count } renames outside of { $root }
+error-journal-directory = could not find a state directory for the journal
+error-export-journal = could not export the journal: { $error }
+error-import-journal = could not import the journal: { $error }
+
+## Sharing tunings
+
+tuning-exported = exported the options and { $count } learned titles to { $path }
+tuning-imported = added { $count } learned titles, run with: { $arguments }
+tuning-file-written = wrote the file of { $option } to { $path }
+error-export-tuning = could not export the tuning: { $error }
+error-import-tuning = could not import the tuning: { $error }
+
+## Comparing configurations
+
+diff-config-changed = { $path }: { $old } → { $new }
+diff-config-unchanged = unchanged
+diff-config-review = title uncertain
+diff-config-summary = { $changed } of { $count } documents would be named differently
+error-diff-config = could not read the options of { $path }: { $error }
+
+## Verifying names
+
+verify-mismatch = { $path } does not match its title { $title } (distance { $distance })
+verify-uncertain = could not verify { $path }, its title is uncertain
+verify-summary = { $mismatched } of { $count } documents do not match their names, { $uncertain } could not be verified
+
+## Replaying snapshots
+
+replay-title = { $document }: { $title } (confidence { $confidence })
+replay-no-candidate = { $document }: no title candidate
+error-replay = could not replay { $path }: { $error }
+error-create-snapshot = could not create the snapshot file
+warn-write-snapshot = could not write the snapshot of { $path }: { $error }
+
+## Splitting books
+
+split-invalid-title = skipping chapter with invalid title { $title }
+split-exists = skipping chapter { $title }: { $path } already exists
+error-split = could not split { $path }: { $error }
+
+## Reports and statistics
+
+report-summary = processed { $count ->
+        [one] one document
+       *[other] { $count } documents
+    } in { $elapsed }
+report-coverage = { $percent }% of glyphs decoded ({ $unmapped } of { $glyphs } unmapped)
+report-font-coverage = { $font }: { $percent }% ({ $unmapped } of { $glyphs } unmapped)
+stats-documents = documents: { $documents } ({ $failed } could not be loaded, { $untitled } without a title)
+stats-sources = title sources:
+stats-failed-fonts = fonts with a missing or unsupported encoding: { $count }
+stats-font-documents = { $count ->
+        [one] one document
+       *[other] { $count } documents
+    }
+stats-unmapped = unmapped glyphs: { $unmapped } of { $glyphs } ({ $percent }%)
+stats-ambiguous = documents with nearly tied title candidates (less than { $percent }% apart): { $count }
+error-panic = processing { $path } crashed, skipping it: { $error }
+
+## Templates
+
+error-template = invalid template: { $error }
+template-fields = fields: { $fields }
+template-no-fields = fields: none
+template-unguarded = warning: documents without { $field } get an empty one, consider a default or a conditional
+template-missing = warning: { $path } has no { $field }
+template-preview = preview: { $name }
+
+## Checking configuration files
+
+config-valid = { $path } is valid
+config-sources-default = the order the sources are read in
+config-overrides = { $hashes } titles by hash and { $paths } by path
+config-override = { $path } is named { $title }
+config-no-override = { $path } has no override
+config-unknown-key = { $path }: unknown key { $key }
+config-unknown-key-suggestion = { $path }: unknown key { $key }, did you mean { $suggestion }?
+error-config-syntax = { $path } is not valid TOML: { $error }
+error-config = { $error }
+
+## Self-test
+
+selftest-passed = ok      { $name }
+selftest-failed = FAILED  { $name }: { $reason }
+selftest-skipped = skipped { $name }: { $reason }
+selftest-feature-ocr = OCR
+selftest-feature-lookup = looking up identifiers online
+selftest-wrong-title = got the title { $title } instead of { $expected }
+selftest-no-title = found no title, expected { $expected }
+selftest-needs-ocr = needs OCR
+selftest-missing-programs = { $programs } not installed
+selftest-not-built = not built in, enable the { $feature } feature
+selftest-offline = offline
@@ -0,0 +1,169 @@
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use nomenclate::pdf::Provenance;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("could not write {path}")]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("could not rotate {path}")]
+    Rotate {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("could not serialize audit log entry")]
+    Serialize(#[from] serde_json::Error),
+}
+
+/// What was done with a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Renamed,
+    /// The document already had the name it would have been renamed to.
+    Unchanged,
+    /// The document was not renamed because no title was found or renaming it failed.
+    Skipped,
+    /// The title found was too uncertain and no title was given in the review.
+    Uncertain,
+}
+
+/// A decision about a document, with what it was based on.
+#[derive(Clone, Debug, Serialize)]
+pub struct Decision {
+    /// When the decision was made, in RFC 3339 format.
+    pub time: String,
+    pub path: PathBuf,
+    pub action: Action,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Provenance>,
+    /// The confidence in the title found in the layout, see [`nomenclate::pdf::confidence`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+impl Decision {
+    /// A decision made now to take `action` for the document at `path`.
+    pub fn new(path: &Path, action: Action) -> Self {
+        Self {
+            time: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            path: std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+            action,
+            renamed: None,
+            title: None,
+            source: None,
+            confidence: None,
+        }
+    }
+}
+
+/// A log of every decision made about a document, with one line of JSON per decision.
+///
+/// Unlike the journal, which is used to undo renames, the audit log is only meant to be read by
+/// people. Once it grows past its maximum size, it is rotated: `audit.jsonl` is moved to
+/// `audit.jsonl.1`, which is moved to `audit.jsonl.2` and so on, dropping the oldest file.
+pub struct AuditLog {
+    path: PathBuf,
+    max_size: u64,
+    /// How many rotated files to keep.
+    keep: usize,
+}
+
+impl AuditLog {
+    pub const fn new(path: PathBuf, max_size: u64, keep: usize) -> Self {
+        Self {
+            path,
+            max_size,
+            keep,
+        }
+    }
+
+    /// The audit log in the user's state directory, `$XDG_STATE_HOME/nomenclate/audit.jsonl` or
+    /// `~/.local/state/nomenclate/audit.jsonl`.
+    pub fn user_path() -> Option<PathBuf> {
+        let base = env::var_os("XDG_STATE_HOME")
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("state"))
+            })?;
+
+        Some(base.join("nomenclate").join("audit.jsonl"))
+    }
+
+    /// Add `decisions` to the log, rotating it first if it is too large.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the log could not be rotated or written.
+    pub fn append(&self, decisions: &[Decision]) -> Result<(), Error> {
+        if decisions.is_empty() {
+            return Ok(());
+        }
+        let mut lines = String::new();
+        for decision in decisions {
+            lines.push_str(&serde_json::to_string(decision)?);
+            lines.push('\n');
+        }
+        let write_error = |source| Error::Write {
+            path: self.path.clone(),
+            source,
+        };
+
+        if let Some(directory) = self.path.parent() {
+            fs::create_dir_all(directory).map_err(write_error)?;
+        }
+        if fs::metadata(&self.path).is_ok_and(|metadata| metadata.len() >= self.max_size) {
+            self.rotate().map_err(|source| Error::Rotate {
+                path: self.path.clone(),
+                source,
+            })?;
+        }
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .map_err(write_error)
+    }
+
+    fn rotate(&self) -> io::Result<()> {
+        let rotated = |index: usize| {
+            let mut path = self.path.clone().into_os_string();
+            path.push(format!(".{index}"));
+            PathBuf::from(path)
+        };
+
+        if self.keep == 0 {
+            return fs::remove_file(&self.path);
+        }
+        match fs::remove_file(rotated(self.keep)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+        for index in (1..self.keep).rev() {
+            match fs::rename(rotated(index), rotated(index + 1)) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+
+        fs::rename(&self.path, rotated(1))
+    }
+}
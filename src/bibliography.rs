@@ -0,0 +1,297 @@
+use std::{
+    collections::HashSet,
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use nomenclate::pdf::Metadata;
+use serde::Serialize;
+
+/// The format of a bibliography of the renamed documents.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// BibTeX, for LaTeX.
+    Bibtex,
+    /// RIS, for reference managers like Zotero and Mendeley.
+    Ris,
+    /// CSL-JSON, for pandoc and other citation processors.
+    CslJson,
+}
+
+impl Format {
+    /// Guess the format from the extension of `path`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "bib" => Some(Self::Bibtex),
+            "ris" => Some(Self::Ris),
+            "json" => Some(Self::CslJson),
+            _ => None,
+        }
+    }
+}
+
+/// A renamed document as it appears in a bibliography.
+pub struct Entry {
+    /// Where the document was renamed to.
+    pub path: PathBuf,
+    pub title: String,
+    pub metadata: Metadata,
+}
+
+/// The authors in `metadata`, split if the author field lists several of them.
+pub fn authors(metadata: &Metadata) -> Vec<&str> {
+    metadata.author.first().map_or_else(Vec::new, |author| {
+        author
+            .value
+            .split(';')
+            .flat_map(|authors| authors.split(" and "))
+            .map(str::trim)
+            .filter(|author| !author.is_empty())
+            .collect()
+    })
+}
+
+/// The family names of the authors in `metadata`.
+pub fn family_names(metadata: &Metadata) -> Vec<&str> {
+    authors(metadata)
+        .into_iter()
+        .map(|author| Name::parse(author).family)
+        .collect()
+}
+
+impl Entry {
+    fn authors(&self) -> Vec<&str> {
+        authors(&self.metadata)
+    }
+
+    fn year(&self) -> Option<u16> {
+        self.metadata.year.first().map(|year| year.value)
+    }
+
+    fn doi(&self) -> Option<&str> {
+        self.metadata.doi.first().map(|doi| doi.value.as_str())
+    }
+
+    /// A citation key like `doe2019layout` from the first author, the year and the title.
+    fn key(&self) -> String {
+        let author = self
+            .authors()
+            .first()
+            .map_or("", |author| Name::parse(author).family);
+        let word = self
+            .title
+            .split_whitespace()
+            .find(|word| word.chars().count() > 3)
+            .unwrap_or_default();
+        let key: String = deunicode::deunicode(&format!(
+            "{author}{}{word}",
+            self.year().map(|year| year.to_string()).unwrap_or_default()
+        ))
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect();
+
+        if key.is_empty() {
+            String::from("document")
+        } else {
+            key.to_lowercase()
+        }
+    }
+}
+
+/// A personal name split into its parts, as far as that can be guessed.
+pub struct Name<'a> {
+    pub family: &'a str,
+    pub given: Option<&'a str>,
+}
+
+impl<'a> Name<'a> {
+    /// Split `Doe, Jane` or `Jane Doe` into the family and given name.
+    pub fn parse(name: &'a str) -> Self {
+        if let Some((family, given)) = name.split_once(',') {
+            Self {
+                family: family.trim(),
+                given: Some(given.trim()).filter(|given| !given.is_empty()),
+            }
+        } else if let Some((given, family)) = name.trim().rsplit_once(' ') {
+            Self {
+                family,
+                given: Some(given.trim()),
+            }
+        } else {
+            Self {
+                family: name.trim(),
+                given: None,
+            }
+        }
+    }
+}
+
+/// Write `entries` as a bibliography in `format` to `path`.
+///
+/// # Errors
+///
+/// This function will return an error if the bibliography could not be written.
+pub fn write(path: &Path, format: Format, entries: &[Entry]) -> io::Result<()> {
+    let content = match format {
+        Format::Bibtex => bibtex(entries),
+        Format::Ris => ris(entries),
+        Format::CslJson => csl_json(entries)?,
+    };
+
+    fs::write(path, content)
+}
+
+/// Make sure every key is only used once by appending `a`, `b`, … (and numbers after `z`) to
+/// duplicates.
+fn unique_keys(entries: &[Entry]) -> Vec<String> {
+    let mut used = HashSet::new();
+
+    entries
+        .iter()
+        .map(|entry| {
+            let key = entry.key();
+            let mut unique = key.clone();
+            let mut suffixes = ('a'..='z').map(String::from);
+            let mut number = 0;
+            while used.contains(&unique) {
+                let suffix = suffixes.next().unwrap_or_else(|| {
+                    number += 1;
+                    number.to_string()
+                });
+                unique = format!("{key}{suffix}");
+            }
+            used.insert(unique.clone());
+
+            unique
+        })
+        .collect()
+}
+
+fn bibtex(entries: &[Entry]) -> String {
+    /// Escape the characters that have a special meaning in LaTeX.
+    fn escape(text: &str) -> String {
+        text.chars().fold(String::new(), |mut escaped, c| {
+            if matches!(c, '{' | '}' | '&' | '%' | '$' | '#' | '_') {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+            escaped
+        })
+    }
+
+    /// Escape the characters in a path that would end the field or start a command.
+    fn escape_path(path: &Path) -> String {
+        path.display()
+            .to_string()
+            .chars()
+            .fold(String::new(), |mut escaped, c| {
+                if matches!(c, '{' | '}' | '\\') {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+                escaped
+            })
+    }
+
+    let mut bibtex = String::new();
+    for (entry, key) in entries.iter().zip(unique_keys(entries)) {
+        let _ = writeln!(bibtex, "@misc{{{key},");
+        let _ = writeln!(bibtex, "  title = {{{}}},", escape(&entry.title));
+        let authors = entry.authors();
+        if !authors.is_empty() {
+            let _ = writeln!(bibtex, "  author = {{{}}},", escape(&authors.join(" and ")));
+        }
+        if let Some(year) = entry.year() {
+            let _ = writeln!(bibtex, "  year = {{{year}}},");
+        }
+        if let Some(doi) = entry.doi() {
+            let _ = writeln!(bibtex, "  doi = {{{doi}}},");
+        }
+        let _ = writeln!(bibtex, "  file = {{{}}},", escape_path(&entry.path));
+        bibtex.push_str("}\n\n");
+    }
+
+    bibtex
+}
+
+fn ris(entries: &[Entry]) -> String {
+    let mut ris = String::new();
+    for entry in entries {
+        ris.push_str("TY  - GEN\n");
+        let _ = writeln!(ris, "TI  - {}", entry.title);
+        for author in entry.authors() {
+            let _ = writeln!(ris, "AU  - {author}");
+        }
+        if let Some(year) = entry.year() {
+            let _ = writeln!(ris, "PY  - {year}");
+        }
+        if let Some(doi) = entry.doi() {
+            let _ = writeln!(ris, "DO  - {doi}");
+        }
+        let _ = writeln!(ris, "L1  - {}", entry.path.display());
+        ris.push_str("ER  - \n\n");
+    }
+
+    ris
+}
+
+#[derive(Serialize)]
+struct CslItem<'a> {
+    id: String,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    title: &'a str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    author: Vec<CslName<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issued: Option<CslDate>,
+    #[serde(rename = "DOI", skip_serializing_if = "Option::is_none")]
+    doi: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct CslName<'a> {
+    family: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    given: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct CslDate {
+    #[serde(rename = "date-parts")]
+    date_parts: [[u16; 1]; 1],
+}
+
+fn csl_json(entries: &[Entry]) -> io::Result<String> {
+    let items: Vec<_> = entries
+        .iter()
+        .zip(unique_keys(entries))
+        .map(|(entry, id)| CslItem {
+            id,
+            kind: "document",
+            title: &entry.title,
+            author: entry
+                .authors()
+                .into_iter()
+                .map(|author| {
+                    let name = Name::parse(author);
+                    CslName {
+                        family: name.family,
+                        given: name.given,
+                    }
+                })
+                .collect(),
+            issued: entry.year().map(|year| CslDate {
+                date_parts: [[year]],
+            }),
+            doi: entry.doi(),
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&items)
+        .map(|json| json + "\n")
+        .map_err(io::Error::other)
+}
@@ -0,0 +1,95 @@
+use nomenclate::pdf::Candidate;
+use whatlang::{Detector, Lang};
+
+/// The languages titles are told apart in. Restricting detection to common languages of
+/// documents makes it more reliable for text as short as a title.
+const LANGUAGES: [Lang; 16] = [
+    Lang::Eng,
+    Lang::Deu,
+    Lang::Fra,
+    Lang::Ita,
+    Lang::Spa,
+    Lang::Por,
+    Lang::Nld,
+    Lang::Swe,
+    Lang::Dan,
+    Lang::Nob,
+    Lang::Fin,
+    Lang::Pol,
+    Lang::Ces,
+    Lang::Hun,
+    Lang::Ell,
+    Lang::Rus,
+];
+
+/// How sure (between 0 and 1) the language detection has to be for a title.
+const MIN_CONFIDENCE: f64 = 0.15;
+
+/// A title given in two languages.
+pub struct Bilingual {
+    pub title: (String, Lang),
+    pub parallel: (String, Lang),
+}
+
+impl Bilingual {
+    /// Detect whether `candidate` and the text below it are the same title in two languages, as
+    /// on the title pages of theses and EU reports.
+    pub fn detect(candidate: &Candidate) -> Option<Self> {
+        let parallel = candidate.parallel.as_ref()?;
+        let title_language = language(&candidate.text)?;
+        let parallel_language = language(parallel)?;
+        log::debug!("title in {title_language}, parallel title in {parallel_language}");
+
+        (title_language != parallel_language).then(|| Self {
+            title: (candidate.text.clone(), title_language),
+            parallel: (parallel.clone(), parallel_language),
+        })
+    }
+
+    /// The title in `language` (if given and it is one of the two) and the one in the other
+    /// language, otherwise the upper one first.
+    pub fn select(self, language: Option<Lang>) -> (String, String) {
+        if language.is_some_and(|language| language == self.parallel.1) {
+            (self.parallel.0, self.title.0)
+        } else {
+            (self.title.0, self.parallel.0)
+        }
+    }
+}
+
+/// The language `text` is written in, if it can be told with enough confidence.
+pub fn language(text: &str) -> Option<Lang> {
+    Detector::with_allowlist(LANGUAGES.to_vec())
+        .detect(text)
+        .filter(|info| info.confidence() >= MIN_CONFIDENCE)
+        .map(|info| info.lang())
+}
+
+/// Remove the `candidates` written in none of the `languages`. Candidates whose language cannot
+/// be told, e.g. because they are too short, are kept.
+pub fn retain_languages(candidates: &mut Vec<Candidate>, languages: &[Lang]) {
+    if languages.is_empty() {
+        return;
+    }
+    candidates.retain(|candidate| match language(&candidate.text) {
+        Some(language) if !languages.contains(&language) => {
+            log::info!("dropping candidate {:?} in {language}", candidate.text);
+            false
+        }
+        _ => true,
+    });
+}
+
+/// Parse an ISO 639-1 (`de`) or ISO 639-3 (`deu`) language code.
+///
+/// # Errors
+///
+/// This function will return an error if the code is not one of a supported language.
+pub fn parse_language(code: &str) -> Result<Lang, String> {
+    let code = code.to_lowercase();
+    isolang::Language::from_639_1(&code)
+        .and_then(|language| Lang::from_code(language.to_639_3()))
+        .or_else(|| Lang::from_code(&code))
+        .filter(|language| LANGUAGES.contains(language))
+        .ok_or_else(|| format!("unsupported language {code:?}"))
+}
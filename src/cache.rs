@@ -0,0 +1,153 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use nomenclate::pdf::{self, Candidate, Metadata};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::report::Decoding;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("could not read {path}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not write {path}")]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not parse cache entry {path}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("could not serialize cache entry")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// The version of the entries written, increased whenever the candidates or metadata extracted
+/// from a document change.
+const VERSION: u32 = 10;
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    /// Entries of another version are ignored, missing in entries written before candidates were
+    /// scored.
+    #[serde(default)]
+    version: u32,
+    /// The options the candidates were extracted with.
+    options: pdf::Options,
+    candidates: Vec<Candidate>,
+    /// Missing in entries written before metadata was extracted, which are ignored.
+    metadata: Option<Metadata>,
+    /// Missing in entries written before decoding was reported.
+    #[serde(default)]
+    decoding: Option<Decoding>,
+}
+
+/// What was extracted from a document, as stored in the cache.
+pub struct Extracted {
+    pub candidates: Vec<Candidate>,
+    pub metadata: Metadata,
+    /// Missing for entries written before decoding was reported.
+    pub decoding: Option<Decoding>,
+}
+
+/// Extraction results stored on disk, keyed by the SHA-256 hash of the document content.
+///
+/// Every document has its own entry file in the cache directory. Entries are only used if they
+/// were extracted with the same options.
+pub struct Cache {
+    directory: PathBuf,
+}
+
+impl Cache {
+    /// Use the cache in the user's cache directory, `$XDG_CACHE_HOME/nomenclate` or
+    /// `~/.cache/nomenclate`.
+    pub fn user() -> Option<Self> {
+        let base = env::var_os("XDG_CACHE_HOME")
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+
+        Some(Self {
+            directory: base.join("nomenclate"),
+        })
+    }
+
+    /// Get the cached candidates, metadata and decoding report of the document with content
+    /// `hash`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the entry exists but could not be read.
+    pub fn get(&self, hash: &str, options: &pdf::Options) -> Result<Option<Extracted>, Error> {
+        let path = self.entry_path(hash);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(Error::Read { path, source }),
+        };
+        let mut entry: Entry =
+            toml::from_str(&content).map_err(|source| Error::Parse { path, source })?;
+        // The password is not stored, and does not change what is extracted.
+        entry.options.password.clone_from(&options.password);
+
+        Ok(entry
+            .metadata
+            .filter(|_| entry.version == VERSION && entry.options == *options)
+            .map(|metadata| Extracted {
+                candidates: entry.candidates,
+                metadata,
+                decoding: entry.decoding,
+            }))
+    }
+
+    /// Store the candidates, metadata and decoding report of the document with content `hash`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the entry could not be written.
+    pub fn insert(
+        &self,
+        hash: &str,
+        options: &pdf::Options,
+        candidates: &[Candidate],
+        metadata: &Metadata,
+        decoding: &Decoding,
+    ) -> Result<(), Error> {
+        let content = toml::to_string(&Entry {
+            version: VERSION,
+            options: options.clone(),
+            // The spans are only of interest to library users and would bloat the cache.
+            candidates: candidates
+                .iter()
+                .map(|candidate| Candidate {
+                    spans: Vec::new(),
+                    ..candidate.clone()
+                })
+                .collect(),
+            metadata: Some(metadata.clone()),
+            decoding: Some(decoding.clone()),
+        })?;
+        fs::create_dir_all(&self.directory).map_err(|source| Error::Write {
+            path: self.directory.clone(),
+            source,
+        })?;
+        let path = self.entry_path(hash);
+
+        fs::write(&path, content).map_err(|source| Error::Write { path, source })
+    }
+
+    fn entry_path(&self, hash: &str) -> PathBuf {
+        self.directory.join(format!("{hash}.toml"))
+    }
+}
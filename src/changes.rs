@@ -0,0 +1,60 @@
+use std::fmt::Write;
+
+use similar::{ChangeTag, TextDiff};
+use unicode_properties::{GeneralCategory, UnicodeGeneralCategory};
+
+/// Mark the characters that differ between the `original` title and the `renamed` file name in
+/// the style of `git diff --word-diff`: `[-removed-]{+added+}`.
+///
+/// Characters that are invisible or easily confused in a terminal are written as their code point
+/// (`<U+200B>`). Returns `None` if nothing changed.
+pub fn highlight(original: &str, renamed: &str) -> Option<String> {
+    if original == renamed {
+        return None;
+    }
+
+    let diff = TextDiff::from_chars(original, renamed);
+    let mut highlighted = String::new();
+    let mut open = None;
+
+    for change in diff.iter_all_changes() {
+        let tag = change.tag();
+        if open != Some(tag) {
+            close(&mut highlighted, open);
+            highlighted.push_str(match tag {
+                ChangeTag::Delete => "[-",
+                ChangeTag::Insert => "{+",
+                ChangeTag::Equal => "",
+            });
+            open = Some(tag);
+        }
+        for c in change.value().chars() {
+            push_visible(&mut highlighted, c);
+        }
+    }
+    close(&mut highlighted, open);
+
+    Some(highlighted)
+}
+
+fn close(highlighted: &mut String, tag: Option<ChangeTag>) {
+    highlighted.push_str(match tag {
+        Some(ChangeTag::Delete) => "-]",
+        Some(ChangeTag::Insert) => "+}",
+        Some(ChangeTag::Equal) | None => "",
+    });
+}
+
+fn push_visible(text: &mut String, c: char) {
+    let is_visible = c == ' ' || !(c.is_whitespace() || c.is_control() || is_format(c));
+    if is_visible {
+        text.push(c);
+    } else {
+        let _ = write!(text, "<U+{:04X}>", u32::from(c));
+    }
+}
+
+/// Whether `c` is an invisible formatting character like a zero-width space or a soft hyphen.
+fn is_format(c: char) -> bool {
+    c.general_category() == GeneralCategory::Format
+}
@@ -0,0 +1,926 @@
+use std::{
+    borrow::Cow, io, net::SocketAddr, num::NonZeroUsize, ops::Deref, path::PathBuf, time::Duration,
+};
+
+use clap::{Parser, Subcommand};
+use nomenclate::pdf;
+
+use crate::{
+    audit::AuditLog,
+    bibliography, bilingual, counter, journal, normalize, rename, report,
+    sink::{self, OutputSink},
+    sort_key,
+    template::Template,
+    watch,
+};
+
+/// A tool to automatically rename document files according to their title.
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Print the JSON Schema of the records of `--format json`, `--output` and `--json-sidecar`.
+    #[arg(long, exclusive = true)]
+    pub schema: bool,
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+impl Args {
+    /// The options of the run, or of the subcommand that renames documents.
+    pub fn run_mut(&mut self) -> &mut RunArgs {
+        match &mut self.command {
+            Some(Command::Watch(watch_args)) => &mut watch_args.run,
+            Some(Command::Serve(serve_args)) => &mut serve_args.run,
+            Some(Command::Mail(mail_args)) => &mut mail_args.run,
+            Some(Command::Library(library_args)) => &mut library_args.run,
+            Some(Command::Migrate(migrate_args)) => &mut migrate_args.run,
+            Some(Command::Verify(verify_args)) => &mut verify_args.run,
+            _ => &mut self.run,
+        }
+    }
+}
+
+impl Deref for Args {
+    type Target = RunArgs;
+
+    fn deref(&self) -> &Self::Target {
+        &self.run
+    }
+}
+
+/// How to find and rename documents, both for a single run and for watching directories.
+#[derive(clap::Args, Clone, Debug)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct RunArgs {
+    /// The documents to rename, emails (`.eml`) whose PDF attachments to save next to them and
+    /// rename, or directories to rename all PDF documents in, including subdirectories.
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+    /// Only print what would be renamed without touching any files.
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Only process documents added or modified since a time (`2024-05-01`,
+    /// `2024-05-01 18:30:00`, in UTC), the start of a journaled run given by its id, or the start
+    /// of the `last` run. Every run that is not a dry run is journaled.
+    #[arg(long, value_name = "SINCE")]
+    pub changed_since: Option<journal::Since>,
+    /// Lock the directories of the documents and quietly exit if another run with this option is
+    /// already in progress in one of them, e.g. for scheduled runs.
+    #[arg(long)]
+    pub single_instance: bool,
+    /// Report how long each document took and where its title came from.
+    #[arg(long, short)]
+    pub verbose: bool,
+    /// How to print the outcome for every document.
+    #[arg(long, value_name = "FORMAT", default_value = "text")]
+    pub format: report::Format,
+    /// Print the outcome for every document as a line of JSON, the same as `--format json`.
+    #[arg(long)]
+    json: bool,
+    /// Also send the outcome for every renamed document as JSON somewhere else: `stdout`,
+    /// `file:PATH` to append it as a line to a file, or `webhook:URL` to post it to a URL. Can be
+    /// given more than once.
+    #[arg(long, value_name = "OUTPUT")]
+    pub output: Vec<sink::Output>,
+    /// Leave a document alone if its current name is at least this similar (between 0 and 1) to
+    /// the new one, ignoring case, punctuation and whitespace.
+    #[arg(long, default_value_t = 0.9)]
+    pub similarity_threshold: f64,
+    /// What to do if a file with the new name of a document already exists.
+    #[arg(long, value_name = "STRATEGY", default_value = "skip")]
+    pub on_conflict: rename::Conflict,
+    /// Avoid renames that confuse sync clients like Syncthing and Nextcloud, e.g. renames that only
+    /// change the case of a name.
+    #[arg(long)]
+    pub sync_safe: bool,
+    /// Steps of cleaning up the typography of titles to leave out, e.g. `hyphenation` to keep
+    /// hyphens followed by a space.
+    #[arg(long, value_name = "STEP", value_delimiter = ',')]
+    pub skip_typography: Vec<normalize::typography::Step>,
+    /// What to do with non-text symbols like math operators and emoji in titles.
+    #[arg(long, value_name = "POLICY", default_value = "keep")]
+    pub symbols: normalize::symbols::Policy,
+    /// Spell out Greek letters in titles (`α-synuclein` becomes `alpha-synuclein`), independently
+    /// of `--symbols`.
+    #[arg(long)]
+    pub greek: bool,
+    /// Write powers of ten in titles in E notation (`10^6` becomes `1e6`), e.g. for superscripts.
+    #[arg(long)]
+    pub e_notation: bool,
+    /// Write the numbering of parts in a series (`Part II`, `Volume 4`) in this style.
+    #[arg(long, value_name = "STYLE")]
+    pub numbering: Option<normalize::numbering::Style>,
+    /// Capitalize titles in this style.
+    #[arg(long, value_name = "STYLE")]
+    pub case: Option<normalize::case::Style>,
+    /// A file with additional acronyms and proper nouns (one per line) whose capitalization is
+    /// preserved when capitalizing titles.
+    #[arg(long, value_name = "FILE")]
+    pub acronyms: Option<PathBuf>,
+    /// Transliterate titles to ASCII (`Ærøskøbing` becomes `AEroskobing`), e.g. for file systems
+    /// or sync clients that mangle other characters.
+    #[arg(long)]
+    pub ascii: bool,
+    /// The name to give documents, with fields in braces: `{title}`, for documents with a title
+    /// in two languages `{title_alt}` for the one in the other language, `{author}` and `{year}`
+    /// from the metadata (the document information dictionary or XMP), `{authors}` for the
+    /// family names of the authors (`Doe & Roe`, or `Doe et al.` if there are more than two,
+    /// `{authors:3}` for up to three), `{n}` or `{n:3}` for a number counting up per directory,
+    /// `{date}` for the date of the document, `{publication}` and `{issue_date}` for issues of
+    /// periodicals with `--profile magazine`, `{standard_id}` for standards with
+    /// `--profile standard`, `{case_no}` and `{doc_type}` for court filings with `--profile legal`,
+    /// `{institution}`, `{period}` and `{account}` (the last four digits of the account) for
+    /// statements with `--profile statement`, `{mfr}` and `{part}` for datasheets with
+    /// `--profile datasheet`, `{vendor}`, `{amount}` and `{currency}` (like `EUR`) for receipts
+    /// with `--profile receipt`, `{composer}` and `{piece}` for sheet music with
+    /// `--profile music`, `{preset}` for the profile or else the type the document was recognized
+    /// as, and `{confidence}` for the confidence in a title found in the layout.
+    ///
+    /// Fields can be followed by the filters `default:<text>`, `upper`, `lower` and `max:<length>`
+    /// (`{year|default:unknown}`, `{title|max:60}`), and parts can be left out if a field is
+    /// missing (`{author?{author} - }{title}`) or a number is out of range
+    /// (`{confidence<0.6?_review/}{title}`). Slashes put documents into subdirectories.
+    ///
+    /// [default: `{title}`, or with `--profile standard` `{standard_id?{standard_id} - }{title}`,
+    /// with `--profile legal` `{date?{date} - }{case_no?{case_no} - }{title}`, with
+    /// `--profile receipt` `{date?{date} - }{title}{amount? {amount}}{currency? {currency}}` and
+    /// with `--profile music` `{composer?{composer} - }{title}`]
+    #[arg(long)]
+    pub template: Option<Template>,
+    /// Read the title and metadata of documents from their current names, which were given after
+    /// this template, e.g. `{year} - {author} - {title}` for documents named by another program.
+    /// What is read from the names is trusted more than what is found in the documents.
+    #[arg(long, value_name = "TEMPLATE")]
+    pub name_template: Option<Template>,
+    /// Where the numbers for `{n}` continue from.
+    #[arg(long, value_name = "MODE", default_value = "run")]
+    pub counter: counter::Mode,
+    /// The first number for `{n}` in directories that have not been numbered before.
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub counter_start: u64,
+    /// For documents with a title in two languages, use the one in this language (e.g. `en`) as
+    /// `{title}`. Otherwise, the upper one is used.
+    #[arg(long, value_name = "LANG", value_parser = bilingual::parse_language)]
+    pub title_lang: Option<whatlang::Lang>,
+    /// Only take titles in these languages, joined with commas like `en,de`. Candidates detected
+    /// to be in another language are dropped, ones too short to tell are kept.
+    #[arg(
+        long,
+        value_name = "LANGS",
+        value_delimiter = ',',
+        value_parser = bilingual::parse_language
+    )]
+    pub language: Vec<whatlang::Lang>,
+    /// What to do with the colon between a title and its subtitle, which is not allowed in file
+    /// names on Windows.
+    #[arg(long, value_name = "REPLACEMENT", default_value = "remove")]
+    pub colon: rename::Colon,
+    /// How to separate the words of file names, applied after all other cleanup.
+    #[arg(long, value_name = "SEPARATOR", default_value = "space")]
+    pub separator: rename::Separator,
+    /// Show which characters of each title were changed by normalization and sanitization on the
+    /// way to its file name, marked as `[-removed-]{+added+}`.
+    #[arg(long)]
+    pub show_changes: bool,
+    /// Emit a key for each title that sorts sensibly in file managers, with leading articles moved
+    /// to the end and transliterated to ASCII.
+    #[arg(long, value_name = "MODE")]
+    pub sort_key: Option<sort_key::Mode>,
+    /// Write the title and metadata of every renamed document to an `.opf` file with the same name
+    /// next to it, for importing the documents into Calibre.
+    #[arg(long)]
+    pub opf: bool,
+    /// Write everything extracted from every renamed document (its metadata, the title candidates
+    /// and where each field came from) to a `.nomenclate.json` file with the same name next to it,
+    /// for other tools to read.
+    #[arg(long)]
+    pub json_sidecar: bool,
+    /// Write everything found in every parsed document before its title was chosen (the options,
+    /// the candidates with their scores and the positioned pieces of text they are made of, and how
+    /// well each font could be decoded) to this file, one line of JSON per document. Attach it to
+    /// bug reports about wrong titles, it can be scored again with `replay` without the documents.
+    #[arg(long, value_name = "FILE")]
+    pub snapshot: Option<PathBuf>,
+    /// The language whose articles are ignored in sort keys.
+    #[arg(long, value_name = "LANG", default_value = "en")]
+    pub sort_language: String,
+    /// Write a bibliography of the renamed documents to this file, e.g. to import them into a
+    /// reference manager. Nothing is written on a dry run.
+    #[arg(long, value_name = "FILE")]
+    pub bibliography: Option<PathBuf>,
+    /// The format of the bibliography. Defaults to the one matching its extension (`.bib`, `.ris`
+    /// or `.json`).
+    #[arg(long, value_name = "FORMAT")]
+    pub bibliography_format: Option<bibliography::Format>,
+    /// Print the fields paperless-ngx expects (title, created date, correspondent and tags) for
+    /// every renamed document as a line of JSON.
+    #[arg(long, conflicts_with_all = ["json", "format"])]
+    pub paperless: bool,
+    /// Upload the renamed documents to the paperless-ngx instance at this URL.
+    #[arg(long, value_name = "URL", requires = "paperless_token")]
+    pub paperless_url: Option<String>,
+    /// The API token for uploading to paperless-ngx.
+    #[arg(
+        long,
+        value_name = "TOKEN",
+        env = "PAPERLESS_TOKEN",
+        hide_env_values = true
+    )]
+    pub paperless_token: Option<String>,
+    /// A tag to assign to documents in paperless-ngx. Can be given several times.
+    #[arg(long = "paperless-tag", value_name = "NAME")]
+    pub paperless_tags: Vec<String>,
+    /// The number of pages to search for a title [default: 2, or as many as the profile needs]
+    #[arg(long)]
+    pub page_count: Option<usize>,
+    /// Tune the heuristics for a kind of document.
+    ///
+    /// `book` prefers titles on sparse title pages over larger text on the copyright page or
+    /// chapter openers. `magazine` names issues after the masthead and the date of the issue.
+    /// `standard` puts the identifier of standards like `ISO 9001:2015` in front of their title.
+    /// `legal` names court filings after the date, case number and type of document in their
+    /// caption. `statement` names statements of accounts and bills after the institution that sent
+    /// them and the period they cover. `datasheet` names datasheets after the maker and number of
+    /// the part. `receipt` names receipts (usually scanned, see `--ocr`) after the date, the vendor
+    /// and the total. `music` names sheet music after the composer and the piece.
+    #[arg(long, value_name = "PROFILE")]
+    pub profile: Option<pdf::Profile>,
+    /// The line spacing as a multiple of the font size that is assumed for documents that move to
+    /// the next line without setting one.
+    #[arg(long, value_name = "FACTOR", default_value_t = pdf::DEFAULT_LEADING)]
+    pub default_leading: f32,
+    /// The password to decrypt encrypted documents with if the empty password does not work.
+    #[arg(
+        long,
+        value_name = "PASSWORD",
+        env = "NOMENCLATE_PASSWORD",
+        hide_env_values = true
+    )]
+    pub password: Option<String>,
+    /// Ask for the password to decrypt encrypted documents with before processing them.
+    #[arg(long)]
+    pub ask_password: bool,
+    /// Recognize the text of pages that have none, like those of scanned documents. Needs
+    /// `pdftoppm` and `tesseract` to be installed.
+    #[cfg(feature = "ocr")]
+    #[arg(long)]
+    pub ocr: bool,
+    /// The resolution to render pages at for recognizing their text, in dots per inch. Lower
+    /// resolutions are faster, higher ones recognize small print better.
+    #[cfg(feature = "ocr")]
+    #[arg(
+        long,
+        value_name = "DPI",
+        default_value_t = 300,
+        value_parser = clap::value_parser!(u16).range(1..),
+        requires = "ocr"
+    )]
+    pub ocr_dpi: u16,
+    /// The part of the page to recognize the text of: `full`, `top` for the upper third, where
+    /// covers and title pages have their title, or LEFT,TOP,WIDTH,HEIGHT in fractions of the page.
+    #[cfg(feature = "ocr")]
+    #[arg(long, value_name = "REGION", default_value = "full", requires = "ocr")]
+    pub ocr_region: pdf::Region,
+    /// Only recognize the text of the first N of the pages that are parsed.
+    #[cfg(feature = "ocr")]
+    #[arg(long, value_name = "N", requires = "ocr")]
+    pub ocr_pages: Option<NonZeroUsize>,
+    /// The languages to recognize text in, joined with `+` like `deu+eng`, or `auto` to detect the
+    /// script of every page and use the installed languages written in it.
+    #[cfg(feature = "ocr")]
+    #[arg(
+        long,
+        value_name = "LANGUAGES",
+        default_value = "default",
+        requires = "ocr"
+    )]
+    pub ocr_lang: pdf::Language,
+    /// Recognize rendered pages as they are, instead of straightening skewed ones, enlarging
+    /// those with small text and binarizing those with little contrast first.
+    #[cfg(feature = "ocr")]
+    #[arg(long, requires = "ocr")]
+    pub no_ocr_preprocessing: bool,
+    /// Use less memory for huge documents like scanned archives, at the cost of speed and of
+    /// skipping pages with huge content streams.
+    #[arg(long)]
+    pub low_memory: bool,
+    /// Parse documents with both PDF parsers and take the title they agree on, or else the one of
+    /// the parser more confident in it. Helps with malformed documents, but takes about twice as
+    /// long.
+    #[arg(long)]
+    pub ensemble: bool,
+    /// Limit a stage of finding the title to a time per document, like `decode=2s`, or to a number
+    /// of operations per page, like `operations=100000`. The stages are load, fonts, operations,
+    /// decode, score and cleanup. Once a stage ran out of time, the pages left are skipped and the
+    /// title is chosen from the candidates found so far. Can be given more than once.
+    #[arg(long, value_name = "STAGE=LIMIT", value_parser = parse_budget)]
+    pub budget: Vec<(pdf::Stage, Limit)>,
+    /// Parse documents from untrusted sources more defensively: look nothing up online, recognize
+    /// no text in rendered pages, skip documents over 256 MiB and pages with huge content streams,
+    /// limit how long parsing may take unless `--budget` says otherwise and, on Linux, keep the
+    /// code parsing a document from writing files, opening network connections and running
+    /// programs. Can't be combined with `--output webhook:URL`.
+    #[arg(long, conflicts_with = "paperless_url")]
+    #[cfg_attr(feature = "lookup", arg(conflicts_with = "lookup"))]
+    #[cfg_attr(feature = "ocr", arg(conflicts_with = "ocr"))]
+    pub sandbox: bool,
+    /// Use the Nth title candidate (ordered by score, starting at 1) instead of the first one.
+    #[arg(long, value_name = "N")]
+    pub use_candidate: Option<NonZeroUsize>,
+    /// Also rename documents whose names already match the template with more than a title, like
+    /// `2019 - Doe - Some Title` for `{year} - {author} - {title}`. These are skipped otherwise, so
+    /// running again does not rename them back and forth when the extracted title varies a bit.
+    #[arg(long)]
+    pub rename_templated: bool,
+    /// Only rename documents recognized as one of these types, joined with commas like
+    /// `paper,book`, and leave everything else untouched. The types are paper, book, magazine,
+    /// standard, legal, statement, datasheet, receipt, music and scan.
+    #[arg(long, value_name = "TYPES", value_delimiter = ',')]
+    pub only_types: Vec<pdf::DocumentClass>,
+    /// When the first title candidate is not plausible as a title, because it is too short, too
+    /// long or a placeholder like `Untitled`, use the next candidate that is instead of the first.
+    #[arg(long, conflicts_with = "use_candidate")]
+    pub fallback_titles: bool,
+    /// Only search page P (starting at 1) for a title.
+    #[arg(long, value_name = "P")]
+    pub title_page: Option<NonZeroUsize>,
+    /// A TOML file mapping file hashes or paths to titles that are used instead of extracting one.
+    #[arg(long, value_name = "FILE")]
+    pub overrides: Option<PathBuf>,
+    /// Read up to N documents ahead in the background while the current one is parsed, which helps
+    /// on slow disks and network shares.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub prefetch: usize,
+    /// Extract N documents at the same time, e.g. one for every processor when renaming a large
+    /// directory.
+    #[arg(long, short, value_name = "N", default_value = "1")]
+    pub jobs: NonZeroUsize,
+    /// Show how many documents have been extracted while a large directory is being processed.
+    #[arg(long)]
+    pub progress: bool,
+    /// A TOML file listing which sources to trust for each metadata field, e.g. to prefer the title
+    /// stored in the document over the one found in its layout.
+    #[arg(long, value_name = "FILE")]
+    pub trust_policy: Option<PathBuf>,
+    /// Look up the DOI or arXiv identifier found in a document on Crossref or arXiv and use the
+    /// title, authors and year found there.
+    #[cfg(feature = "lookup")]
+    #[arg(long)]
+    pub lookup: bool,
+    /// Cache the title candidates of every document by its content in the user's cache directory,
+    /// so running again on the same documents does not parse them again.
+    #[arg(long)]
+    pub cache: bool,
+    /// Use the title of HTML pages saved as PDF documents (e.g. error pages of paywalls) instead of
+    /// skipping them.
+    #[arg(long)]
+    pub html_titles: bool,
+    /// After all documents are processed, ask for the titles of documents whose extracted title
+    /// has a confidence below `--min-confidence` instead of renaming them right away.
+    #[arg(long)]
+    pub review_failures: bool,
+    /// The confidence (between 0 and 1) below which an extracted title is considered a failure.
+    #[arg(long, default_value_t = 0.6)]
+    pub min_confidence: f32,
+    /// After all documents are processed, ask for the title of every document whose title comes
+    /// from its layout, regardless of the confidence in it.
+    #[arg(long, conflicts_with = "use_candidate")]
+    pub interactive: bool,
+    /// How many title candidates to offer when asking for a title.
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    pub candidate_count: usize,
+    /// Remember where in the layout the candidates chosen when asking for a title were, and prefer
+    /// candidates in the same place in documents made with the same applications on later runs.
+    #[arg(long)]
+    pub learn: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Split a book into one document per chapter using its outline.
+    Split(SplitArgs),
+    /// Summarize a library of documents: where titles come from and how well their text decodes.
+    Stats(StatsArgs),
+    /// Work with templates for `--template`.
+    #[command(subcommand)]
+    Template(TemplateCommand),
+    /// Work with the files given with `--trust-policy` and `--overrides`.
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Keep watching directories and rename documents as they are added or modified.
+    Watch(Box<WatchArgs>),
+    /// Rename documents like `--review-failures`, but serve an HTTP API for reviewing the
+    /// documents whose title is uncertain instead of asking for their titles.
+    Serve(Box<ServeArgs>),
+    /// Save the PDF attachments of the emails in maildirs or IMAP folders to a directory and
+    /// rename them.
+    Mail(Box<MailArgs>),
+    /// Move documents into a store named by the hash of their content and link to them by name
+    /// from a directory, so renaming never moves a document.
+    Library(Box<LibraryArgs>),
+    /// Move journaled renames between machines with synced copies of the same library.
+    #[command(subcommand)]
+    Journal(JournalCommand),
+    /// Share a setup tuned for a kind of documents: the options to run with and the titles learned
+    /// with `--learn`.
+    #[command(subcommand)]
+    Tuning(TuningCommand),
+    /// Extract the titles of a few bundled miniature documents and report which optional
+    /// features, like OCR and looking up identifiers online, work in this build.
+    Selftest(SelftestArgs),
+    /// Rename documents named after one template after another, reading the fields from their
+    /// names and only parsing documents whose names lack a field the new template uses.
+    Migrate(Box<MigrateArgs>),
+    /// Show which documents would be named differently when run with the options of one tuning
+    /// file instead of another, without renaming anything.
+    DiffConfig(DiffConfigArgs),
+    /// Extract the titles of documents again and report the ones whose names no longer match
+    /// them, e.g. after files were mixed up by hand, without renaming anything.
+    Verify(Box<VerifyArgs>),
+    /// Score the candidates in a file written with `--snapshot` again and show the titles that
+    /// would be chosen, without the documents the snapshots were taken of.
+    Replay(ReplayArgs),
+    /// Serve the extraction of titles over gRPC, as described in `proto/nomenclate.proto`.
+    #[cfg(feature = "grpc")]
+    Grpc(GrpcArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct WatchArgs {
+    /// How long a document has to be left alone before it is renamed, so documents that are still
+    /// being written, e.g. downloaded, are not renamed yet.
+    #[arg(long, value_name = "DURATION", default_value = "2s", value_parser = humantime::parse_duration)]
+    pub settle: Duration,
+    /// Look for changes to documents every `--interval` instead of being told about them by the
+    /// operating system, e.g. on network shares where that does not work.
+    #[arg(long)]
+    pub poll: bool,
+    /// How long to wait between looking for changes to documents with `--poll`, e.g. `5s` or `1m`.
+    #[arg(long, value_name = "DURATION", default_value = "5s", value_parser = humantime::parse_duration)]
+    pub interval: Duration,
+    /// Who to tell about the progress of the watch, e.g. `systemd` to report readiness and answer
+    /// watchdog pings when running as a service with `Type=notify`, or `desktop` to show a
+    /// notification for every renamed document.
+    #[arg(long, value_name = "TARGET", value_delimiter = ',')]
+    pub notify: Vec<watch::Notify>,
+    #[command(flatten)]
+    pub audit: AuditLogArgs,
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+/// Where the commands that keep running log their decisions about documents.
+#[derive(clap::Args, Clone, Debug)]
+pub struct AuditLogArgs {
+    /// The file to log every decision about a document to, with the title, its source and the
+    /// confidence in it. Defaults to `audit.jsonl` in the user's state directory.
+    #[arg(long, value_name = "FILE")]
+    pub audit_log: Option<PathBuf>,
+    /// Don't log decisions about documents.
+    #[arg(long, conflicts_with = "audit_log")]
+    pub no_audit_log: bool,
+    /// The size in MiB after which the audit log is rotated.
+    #[arg(long, value_name = "MIB", default_value_t = 10)]
+    pub audit_log_max_size: u64,
+    /// How many rotated audit logs to keep.
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    pub audit_log_keep: usize,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ServeArgs {
+    /// The address and port to listen on.
+    #[arg(long, value_name = "ADDRESS", default_value = "127.0.0.1:7878")]
+    pub listen: SocketAddr,
+    #[command(flatten)]
+    pub audit: AuditLogArgs,
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct MailArgs {
+    /// The directory to save the attachments to.
+    #[arg(long, short, value_name = "DIR")]
+    pub output: PathBuf,
+    /// The password of the account of IMAP folders, which are given as
+    /// `imaps://user@host[:port]/folder` instead of the path of a maildir.
+    #[cfg(feature = "imap")]
+    #[arg(
+        long,
+        env = "NOMENCLATE_IMAP_PASSWORD",
+        hide_env_values = true,
+        hide = true
+    )]
+    pub imap_password: Option<String>,
+    /// Which emails of IMAP folders to fetch, as IMAP search criteria like `ALL`,
+    /// `SINCE 1-May-2024` or `UID 1200:*`. The emails are not marked as read, so unread ones are
+    /// fetched again on the next run.
+    #[cfg(feature = "imap")]
+    #[arg(long, value_name = "CRITERIA", default_value = "UNSEEN")]
+    pub imap_search: String,
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct MigrateArgs {
+    /// The template the documents are named after now, e.g. `{year} - {author} - {title}`.
+    #[arg(long, value_name = "TEMPLATE")]
+    pub from: Template,
+    /// The template to name the documents after instead, which replaces `--template`.
+    #[arg(long, value_name = "TEMPLATE")]
+    pub to: Template,
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DiffConfigArgs {
+    /// The tuning file with the options documents are renamed with now, as exported with `tuning
+    /// export`.
+    pub old: PathBuf,
+    /// The tuning file with the proposed options.
+    pub new: PathBuf,
+    /// The documents or directories of documents to compare the names of.
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct VerifyArgs {
+    /// Report a document if its name differs from the one it would get by more than this
+    /// normalized edit distance (between 0 and 1), ignoring case, punctuation and whitespace.
+    #[arg(long, value_name = "DISTANCE", default_value_t = 0.5)]
+    pub max_distance: f64,
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ReplayArgs {
+    /// The file written with `--snapshot`.
+    pub snapshot: PathBuf,
+    /// Score with the heuristics of this profile instead of the one the snapshots were taken with,
+    /// see `run --profile`.
+    #[arg(long, value_name = "PROFILE")]
+    pub profile: Option<pdf::Profile>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct LibraryArgs {
+    /// The directory to store the documents in.
+    #[arg(long, value_name = "DIR")]
+    pub store: PathBuf,
+    /// The directory to create the links named after the documents in. Several directories with
+    /// different templates can link to the same store.
+    #[arg(long, value_name = "DIR")]
+    pub links: PathBuf,
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[cfg(feature = "grpc")]
+#[derive(clap::Args, Debug)]
+pub struct GrpcArgs {
+    /// The address and port to listen on.
+    #[arg(long, value_name = "ADDRESS", default_value = "127.0.0.1:50051")]
+    pub listen: SocketAddr,
+    /// The number of pages to search for a title, unless a request asks for another number.
+    #[arg(long, default_value_t = 2)]
+    pub page_count: usize,
+    /// How many documents of a batch to extract at the same time. Defaults to the number of CPUs.
+    #[arg(long, value_name = "N")]
+    pub concurrency: Option<NonZeroUsize>,
+    /// The size in MiB of the largest document that can be sent.
+    #[arg(long, value_name = "MIB", default_value_t = 100)]
+    pub max_document_size: usize,
+    /// Allow requests to give documents by their path on this machine instead of sending them.
+    #[arg(long)]
+    pub allow_paths: bool,
+}
+
+impl AuditLogArgs {
+    /// The audit log to write to, unless it was turned off or there is no state directory.
+    pub fn audit_log(&self) -> Option<AuditLog> {
+        if self.no_audit_log {
+            return None;
+        }
+        let path = self.audit_log.clone().or_else(AuditLog::user_path)?;
+
+        Some(AuditLog::new(
+            path,
+            self.audit_log_max_size.saturating_mul(1024 * 1024),
+            self.audit_log_keep,
+        ))
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TemplateCommand {
+    /// Validate a template, list the fields it uses and preview the name it gives a document.
+    Check(TemplateCheckArgs),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum JournalCommand {
+    /// Write journaled renames in a library to a file, with their paths relative to the library.
+    Export(JournalExportArgs),
+    /// Replay the renames of an exported journal on a copy of the library, or undo them.
+    Import(JournalImportArgs),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Validate the files, report unknown keys with the ones they were probably meant to be and
+    /// print what the files amount to.
+    Check(ConfigCheckArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ConfigCheckArgs {
+    /// A trust policy to check, see `run --trust-policy`.
+    #[arg(long, value_name = "FILE", required_unless_present = "overrides")]
+    pub trust_policy: Option<PathBuf>,
+    /// An overrides file to check, see `run --overrides`.
+    #[arg(long, value_name = "FILE")]
+    pub overrides: Option<PathBuf>,
+    /// A document to print the title the overrides give it for.
+    #[arg(long, value_name = "FILE", requires = "overrides")]
+    pub document: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SelftestArgs {
+    /// Do not check whether Crossref can be reached to look up identifiers.
+    #[arg(long)]
+    pub offline: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TuningCommand {
+    /// Write the options given after `--`, the files they refer to and the titles learned with
+    /// `--learn` to a file, e.g. `nomenclate tuning export legal.toml -- --profile legal
+    /// --page-count 2`.
+    Export(TuningExportArgs),
+    /// Add the titles learned in an exported file to the ones learned here, write out the files
+    /// its options refer to and print the options it was exported with.
+    Import(TuningImportArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TuningExportArgs {
+    /// The file to export the setup to.
+    pub output: PathBuf,
+    /// The options of a run to export, without documents.
+    #[arg(last = true, value_name = "OPTIONS")]
+    pub arguments: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TuningImportArgs {
+    /// The file the setup was exported to.
+    pub input: PathBuf,
+    /// The directory to write the files the options refer to, like the trust policy, to. Defaults
+    /// to the directory of the exported file.
+    #[arg(long, value_name = "DIR")]
+    pub files: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct JournalExportArgs {
+    /// The file to export the renames to.
+    pub output: PathBuf,
+    /// The directory of the library. Renames outside of it are left out.
+    #[arg(long, value_name = "DIR")]
+    pub root: PathBuf,
+    /// Only export the runs that started at or after a time (`2024-05-01`, `2024-05-01 18:30:00`,
+    /// in UTC), a journaled run given by its id, or the `last` run.
+    #[arg(long, value_name = "SINCE")]
+    pub since: Option<journal::Since>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct JournalImportArgs {
+    /// The file the renames were exported to.
+    pub input: PathBuf,
+    /// The directory of the copy of the library to replay the renames in.
+    #[arg(long, value_name = "DIR")]
+    pub root: PathBuf,
+    /// Move the renamed documents back to where they were instead, newest rename first.
+    #[arg(long)]
+    pub undo: bool,
+    /// Only print what would be renamed without touching any files.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TemplateCheckArgs {
+    /// The template to check, e.g. `{year} - {title}`.
+    pub template: String,
+    /// A document to render a preview of the template for.
+    #[arg(long, value_name = "FILE")]
+    pub sample: Option<PathBuf>,
+    /// The number of pages of the sample to search for a title.
+    #[arg(long, default_value_t = 2)]
+    pub page_count: usize,
+    /// Tune the heuristics for the kind of document the sample is, see `run --profile`.
+    #[arg(long, value_name = "PROFILE")]
+    pub profile: Option<pdf::Profile>,
+    /// How to separate the words of the previewed file name.
+    #[arg(long, value_name = "SEPARATOR", default_value = "space")]
+    pub separator: rename::Separator,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct StatsArgs {
+    /// The directories or documents to analyze.
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+    /// The number of pages to search for a title.
+    #[arg(long, default_value_t = 2)]
+    pub page_count: usize,
+    /// A TOML file mapping file hashes or paths to titles that are used instead of extracting one.
+    #[arg(long, value_name = "FILE")]
+    pub overrides: Option<PathBuf>,
+    /// List the documents whose runner-up title candidate scores at most this much lower (between 0
+    /// and 1) than the first one, as they are worth reviewing.
+    #[arg(long, value_name = "MARGIN", default_value_t = 0.05)]
+    pub ambiguity_threshold: f32,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SplitArgs {
+    /// The book to split.
+    pub path: PathBuf,
+    /// The directory to write the chapters to. Defaults to the directory of the book.
+    #[arg(long, short, value_name = "DIR")]
+    pub output: Option<PathBuf>,
+    /// The outline level (starting at 1) whose entries are the chapters.
+    #[arg(long, default_value_t = 1)]
+    pub level: usize,
+    /// Only print the chapters without writing any files.
+    #[arg(long)]
+    pub dry_run: bool,
+    /// What to do with the colon between the title and subtitle of chapters.
+    #[arg(long, value_name = "REPLACEMENT", default_value = "remove")]
+    pub colon: rename::Colon,
+    /// How to separate the words of the chapter file names.
+    #[arg(long, value_name = "SEPARATOR", default_value = "space")]
+    pub separator: rename::Separator,
+}
+
+impl RunArgs {
+    /// Whether to print the outcome for every document as JSON.
+    pub fn json(&self) -> bool {
+        self.json || self.format == report::Format::Json
+    }
+
+    /// The first `--output` that sends outcomes over the network if `--sandbox` is given, which
+    /// does not allow that.
+    pub fn sandboxed_network_output(&self) -> Option<&sink::Output> {
+        self.output
+            .iter()
+            .find(|output| self.sandbox && matches!(output, sink::Output::Webhook(_)))
+    }
+
+    /// Where to send the outcome for every renamed document, from `--output` and `--format json`.
+    pub fn sinks(&self) -> Vec<Box<dyn OutputSink + '_>> {
+        let stdout = self.json() && !self.output.contains(&sink::Output::Stdout);
+        stdout
+            .then(|| Box::new(sink::Stdout) as Box<dyn OutputSink>)
+            .into_iter()
+            .chain(self.output.iter().map(sink::Output::sink))
+            .collect()
+    }
+
+    /// The template given with `--template`, or the one of the profile.
+    pub fn template(&self) -> Cow<'_, Template> {
+        self.template.as_ref().map_or_else(
+            || Cow::Owned(Template::for_profile(self.profile)),
+            Cow::Borrowed,
+        )
+    }
+
+    pub fn parse_options(&self) -> pdf::Options {
+        pdf::Options {
+            page_count: self
+                .page_count
+                .unwrap_or_else(|| self.profile.map_or(2, pdf::Profile::page_count)),
+            page: self.title_page.map(|page| page.get() - 1),
+            default_leading: self.default_leading,
+            low_memory: self.low_memory || self.sandbox,
+            ensemble: self.ensemble,
+            password: self.password.clone(),
+            #[cfg(feature = "ocr")]
+            ocr: self.ocr,
+            #[cfg(feature = "ocr")]
+            rendering: pdf::Rendering {
+                dpi: self.ocr_dpi,
+                region: self.ocr_region,
+                max_pages: self.ocr_pages.map(NonZeroUsize::get),
+                language: self.ocr_lang.clone(),
+                preprocess: !self.no_ocr_preprocessing,
+            },
+            profile: self.profile,
+            budgets: self.budgets(),
+        }
+    }
+
+    pub fn normalize_options(&self) -> io::Result<normalize::Options> {
+        let mut dictionary = normalize::case::Dictionary::default();
+        if let Some(path) = &self.acronyms {
+            dictionary.extend_from_file(path)?;
+        }
+
+        Ok(normalize::Options {
+            skip_typography: self.skip_typography.clone(),
+            symbols: self.symbols,
+            greek: self.greek,
+            e_notation: self.e_notation,
+            numbering: self.numbering,
+            case: self.case,
+            dictionary,
+            ascii: self.ascii,
+        })
+    }
+
+    pub const fn rename_options(&self) -> rename::Options {
+        rename::Options {
+            dry_run: self.dry_run,
+            similarity_threshold: self.similarity_threshold,
+            sync_safe: self.sync_safe,
+            on_conflict: self.on_conflict,
+        }
+    }
+
+    pub fn candidate_index(&self) -> usize {
+        self.use_candidate.map_or(0, |n| n.get() - 1)
+    }
+
+    /// The budgets of the stages given with `--budget`, on top of the ones of `--sandbox`.
+    fn budgets(&self) -> pdf::Budgets {
+        let mut budgets = pdf::Budgets::default();
+        if self.sandbox {
+            for stage in [pdf::Stage::Load, pdf::Stage::Fonts, pdf::Stage::Decode] {
+                budgets.set_time(stage, SANDBOX_TIME);
+            }
+            for stage in [pdf::Stage::Operations, pdf::Stage::Decode] {
+                budgets.set_operations(stage, SANDBOX_OPERATIONS);
+            }
+        }
+        for (stage, limit) in &self.budget {
+            match limit {
+                Limit::Time(time) => budgets.set_time(*stage, *time),
+                Limit::Operations(operations) => budgets.set_operations(*stage, *operations),
+            }
+        }
+
+        budgets
+    }
+}
+
+/// How long the stages that are limited with `--sandbox` may take for a document.
+const SANDBOX_TIME: Duration = Duration::from_secs(10);
+
+/// How many operations the stages that are limited with `--sandbox` may work through on a page.
+const SANDBOX_OPERATIONS: usize = 1_000_000;
+
+/// What a stage is limited to with `--budget`.
+#[derive(Clone, Copy, Debug)]
+pub enum Limit {
+    Time(Duration),
+    Operations(usize),
+}
+
+/// Parse a budget like `decode=2s` or `operations=100000`.
+fn parse_budget(budget: &str) -> Result<(pdf::Stage, Limit), String> {
+    let (stage, limit) = budget
+        .split_once('=')
+        .ok_or_else(|| format!("expected STAGE=LIMIT, got {budget:?}"))?;
+    let stage = stage.parse()?;
+    let limit = match limit.parse() {
+        Ok(operations) => Limit::Operations(operations),
+        Err(_) => Limit::Time(humantime::parse_duration(limit).map_err(|err| err.to_string())?),
+    };
+    if matches!(limit, Limit::Operations(_))
+        && !matches!(stage, pdf::Stage::Operations | pdf::Stage::Decode)
+    {
+        return Err(format!(
+            "the {stage} stage can only be limited in time, operations can only be limited for \
+             the {} and {} stages",
+            pdf::Stage::Operations,
+            pdf::Stage::Decode
+        ));
+    }
+
+    Ok((stage, limit))
+}
@@ -0,0 +1,132 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use nomenclate::pdf::Candidate;
+
+/// The kind of a document that accompanies a main document, like a paper.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Supplementary,
+    Appendix,
+}
+
+impl Kind {
+    /// The suffix that is appended to the title of the main document to name the companion.
+    pub const fn suffix(self) -> &'static str {
+        match self {
+            Self::Supplementary => "Supplementary",
+            Self::Appendix => "Appendix",
+        }
+    }
+}
+
+/// Title prefixes that identify companion documents.
+const PREFIXES: &[(&str, Kind)] = &[
+    ("supplementary", Kind::Supplementary),
+    ("supplemental", Kind::Supplementary),
+    ("supporting information", Kind::Supplementary),
+    ("supporting material", Kind::Supplementary),
+    ("electronic supplementary", Kind::Supplementary),
+    ("online supplement", Kind::Supplementary),
+    ("appendix", Kind::Appendix),
+    ("appendices", Kind::Appendix),
+    ("online appendix", Kind::Appendix),
+];
+
+/// The similarity above which a title is considered to be the title of the main document.
+const MIN_SIMILARITY: f64 = 0.8;
+
+/// Check whether `title` is the title of a companion document.
+pub fn kind(title: &str) -> Option<Kind> {
+    let title = normalize(title);
+
+    PREFIXES
+        .iter()
+        .find(|(prefix, _)| title.starts_with(prefix))
+        .map(|(_, kind)| *kind)
+}
+
+/// Find the title of the main document a companion document belongs to among `titles`.
+///
+/// A title matches if it is contained in any of the companion's `candidates` (supplements often
+/// repeat the title of the paper) or if it is similar to the companion title without its prefix
+/// (`Supplementary Material for: <title>`).
+pub fn find_main<'a>(
+    title: &str,
+    candidates: &[Candidate],
+    titles: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let rest = normalize(title);
+    let rest = PREFIXES
+        .iter()
+        .find_map(|(prefix, _)| rest.strip_prefix(prefix))
+        .unwrap_or(&rest);
+    let rest = rest
+        .trim_start()
+        .split_once(' ')
+        .filter(|(word, _)| ["material", "materials", "information", "data"].contains(word))
+        .map_or(rest, |(_, rest)| rest)
+        .trim_start();
+    let rest = ["for ", "to ", "of "]
+        .iter()
+        .find_map(|word| rest.strip_prefix(word))
+        .unwrap_or(rest);
+    let candidates: Vec<String> = candidates
+        .iter()
+        .map(|candidate| normalize(&candidate.text))
+        .collect();
+
+    titles
+        .into_iter()
+        .filter(|main| kind(main).is_none())
+        .filter_map(|main| {
+            let normalized = normalize(main);
+            if normalized.len() < 10 {
+                return None;
+            }
+            if candidates
+                .iter()
+                .any(|candidate| candidate.contains(&normalized))
+            {
+                return Some((main, 1.));
+            }
+
+            let similarity = strsim::normalized_levenshtein(rest, &normalized);
+            (similarity >= MIN_SIMILARITY).then_some((main, similarity))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(main, _)| main)
+}
+
+/// The titles of the other PDF documents in the directory of `path`, taken from their file names.
+pub fn sibling_titles(path: &Path) -> Vec<String> {
+    let directory = path
+        .parent()
+        .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+    let Ok(entries) = fs::read_dir(&directory) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|sibling| {
+            sibling.file_name() != path.file_name()
+                && sibling
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"))
+        })
+        .filter_map(|sibling| Some(sibling.file_stem()?.to_string_lossy().into_owned()))
+        .collect()
+}
+
+/// Lowercase `text` and reduce it to its words, separated by single spaces.
+fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,230 @@
+use std::{fmt::Write, fs, path::Path};
+
+use nomenclate::pdf::TrustPolicy;
+
+use crate::{cli::ConfigCheckArgs, i18n::tr, overrides::Overrides, trust};
+
+/// The fields a trust policy can list sources for.
+const TRUST_POLICY_FIELDS: &[&str] = &["title", "author", "year", "created", "doi", "arxiv"];
+
+/// The keys of the table of a field in a trust policy.
+const PREFERENCE_KEYS: &[&str] = &["prefer"];
+
+/// The tables of an overrides file.
+const OVERRIDES_KEYS: &[&str] = &["hashes", "paths"];
+
+/// The most edits a key may be away from a known one to suggest it.
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+/// A key of a file that is not known, with the known key it was probably meant to be.
+struct Unknown {
+    key: String,
+    suggestion: Option<String>,
+}
+
+/// Check the files given with `--trust-policy` and `--overrides`, report unknown keys and print
+/// what they amount to. Exits with an error if any of them has a problem.
+pub fn check(args: &ConfigCheckArgs) {
+    let mut valid = true;
+
+    if let Some(path) = &args.trust_policy {
+        valid &= check_trust_policy(path);
+    }
+    if let Some(path) = &args.overrides {
+        valid &= check_overrides(path, args.document.as_deref());
+    }
+
+    if !valid {
+        std::process::exit(1);
+    }
+}
+
+/// Check the trust policy at `path` and print the sources trusted for every field.
+fn check_trust_policy(path: &Path) -> bool {
+    let unknown = read_table(path).map(|table| {
+        let mut unknown = unknown_in(&table, TRUST_POLICY_FIELDS, "");
+        for (field, value) in &table {
+            if let Some(preference) = value.as_table() {
+                unknown.extend(unknown_in(
+                    preference,
+                    PREFERENCE_KEYS,
+                    &format!("{field}."),
+                ));
+            }
+        }
+        unknown
+    });
+    if !report(path, unknown) {
+        return false;
+    }
+
+    match trust::load(path) {
+        Ok(policy) => {
+            println!("{}", tr!("config-valid", path = path.display().to_string()));
+            print_trust_policy(&policy);
+            true
+        }
+        Err(err) => {
+            eprintln!("{}", tr!("error-config", error = chain(&err)));
+            false
+        }
+    }
+}
+
+/// Print the sources trusted for every field of `policy`, most trusted first.
+fn print_trust_policy(policy: &TrustPolicy) {
+    let preferences = [
+        &policy.title,
+        &policy.author,
+        &policy.year,
+        &policy.created,
+        &policy.doi,
+        &policy.arxiv,
+    ];
+    for (field, preference) in TRUST_POLICY_FIELDS.iter().zip(preferences) {
+        let sources = if preference.prefer.is_empty() {
+            tr!("config-sources-default")
+        } else {
+            preference
+                .prefer
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        println!("  {field}: {sources}");
+    }
+}
+
+/// Check the overrides at `path`, print how many titles they have and the one they give the
+/// `document`, if one is given.
+fn check_overrides(path: &Path, document: Option<&Path>) -> bool {
+    let unknown = read_table(path).map(|table| unknown_in(&table, OVERRIDES_KEYS, ""));
+    if !report(path, unknown) {
+        return false;
+    }
+
+    let overrides = match Overrides::load(path) {
+        Ok(overrides) => overrides,
+        Err(err) => {
+            eprintln!("{}", tr!("error-config", error = chain(&err)));
+            return false;
+        }
+    };
+    println!("{}", tr!("config-valid", path = path.display().to_string()));
+    let (hashes, paths) = overrides.len();
+    println!(
+        "  {}",
+        tr!("config-overrides", hashes = hashes, paths = paths)
+    );
+
+    let Some(document) = document else {
+        return true;
+    };
+    let document_path = document.display().to_string();
+    match overrides.get(document) {
+        Ok(Some(title)) => println!(
+            "  {}",
+            tr!(
+                "config-override",
+                path = document_path,
+                title = format!("{title:?}")
+            )
+        ),
+        Ok(None) => println!("  {}", tr!("config-no-override", path = document_path)),
+        Err(err) => {
+            eprintln!("{}", tr!("error-config", error = chain(&err)));
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Read the TOML file at `path` as a table, or the message of why it could not be read.
+fn read_table(path: &Path) -> Result<toml::Table, String> {
+    let content = fs::read_to_string(path).map_err(|err| {
+        tr!(
+            "error-read",
+            path = path.display().to_string(),
+            error = err.to_string()
+        )
+    })?;
+
+    content.parse().map_err(|err: toml::de::Error| {
+        tr!(
+            "error-config-syntax",
+            path = path.display().to_string(),
+            error = err.message().to_string()
+        )
+    })
+}
+
+/// The keys of `table` that are not `known`, prefixed with `prefix`.
+fn unknown_in(table: &toml::Table, known: &[&str], prefix: &str) -> Vec<Unknown> {
+    table
+        .keys()
+        .filter(|key| !known.contains(&key.as_str()))
+        .map(|key| Unknown {
+            key: format!("{prefix}{key}"),
+            suggestion: suggest(key, known).map(|suggestion| format!("{prefix}{suggestion}")),
+        })
+        .collect()
+}
+
+/// Print the `unknown` keys of the file at `path`, or why it could not be read. Returns whether
+/// it could be read and there were none.
+fn report(path: &Path, unknown: Result<Vec<Unknown>, String>) -> bool {
+    let unknown = match unknown {
+        Ok(unknown) => unknown,
+        Err(message) => {
+            eprintln!("{message}");
+            return false;
+        }
+    };
+    let path_name = path.display().to_string();
+    for Unknown { key, suggestion } in &unknown {
+        let message = suggestion.as_ref().map_or_else(
+            || {
+                tr!(
+                    "config-unknown-key",
+                    path = path_name.as_str(),
+                    key = key.as_str()
+                )
+            },
+            |suggestion| {
+                tr!(
+                    "config-unknown-key-suggestion",
+                    path = path_name.as_str(),
+                    key = key.as_str(),
+                    suggestion = suggestion.as_str()
+                )
+            },
+        );
+        eprintln!("{message}");
+    }
+
+    unknown.is_empty()
+}
+
+/// The one of the `known` keys closest to `key`, if it is close enough to be a typo of it.
+fn suggest<'a>(key: &str, known: &[&'a str]) -> Option<&'a str> {
+    known
+        .iter()
+        .map(|candidate| (strsim::levenshtein(key, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The message of `err` followed by the messages of its sources.
+fn chain(err: &dyn std::error::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        let _ = write!(message, ": {err}");
+        source = err.source();
+    }
+
+    message
+}
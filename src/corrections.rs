@@ -0,0 +1,278 @@
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use approx::relative_eq;
+use nomenclate::pdf::{Candidate, Metadata};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("could not read {path}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not write {path}")]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not parse line {line} of corrections {path}")]
+    Parse {
+        path: PathBuf,
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("could not serialize correction")]
+    Serialize(#[from] serde_json::Error),
+}
+
+/// How similar a title the user typed has to be to a candidate to be taken as a correction of it,
+/// e.g. with a typo fixed or a subtitle added.
+const MIN_SIMILARITY: f64 = 0.6;
+
+/// How much the score of a candidate is raised if it is where the titles of every learned
+/// document from the same applications were.
+const CORRECTION_WEIGHT: f32 = 0.5;
+
+/// How much the font sizes of a candidate and a learned title may differ relatively to still be
+/// alike.
+const FONT_SIZE_TOLERANCE: f32 = 0.1;
+
+/// How far down the page a candidate and a learned title may be apart, as a fraction of the
+/// height of the page, to still be alike.
+const POSITION_TOLERANCE: f32 = 0.05;
+
+/// Where in the layout of a document the title the user chose for it was.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Correction {
+    /// The applications the document was made with, see [`fingerprint`].
+    fingerprint: String,
+    /// The zero-based index of the page the title was on.
+    page: usize,
+    font_size: f32,
+    /// How far down the page the title was, see [`Candidate::position`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position: Option<f32>,
+}
+
+impl Correction {
+    /// Whether `candidate` is in the same place and font size as the title was.
+    fn is_like(&self, candidate: &Candidate) -> bool {
+        let position = match (self.position, candidate.position) {
+            (Some(a), Some(b)) => (a - b).abs() <= POSITION_TOLERANCE,
+            _ => true,
+        };
+
+        self.page == candidate.page
+            && relative_eq!(
+                self.font_size,
+                candidate.font_size,
+                max_relative = FONT_SIZE_TOLERANCE
+            )
+            && position
+    }
+}
+
+/// The titles the user chose when asked for them, remembered by where they were in the layout of
+/// their documents, in a file with one line of JSON per title.
+///
+/// Documents made with the same applications often have the same layout, so a candidate in the
+/// place the user picked the title from before is more likely the title.
+pub struct Corrections {
+    path: PathBuf,
+}
+
+impl Corrections {
+    /// Use the corrections in the user's state directory,
+    /// `$XDG_STATE_HOME/nomenclate/corrections.jsonl` or
+    /// `~/.local/state/nomenclate/corrections.jsonl`.
+    pub fn user() -> Option<Self> {
+        let base = env::var_os("XDG_STATE_HOME")
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("state"))
+            })?;
+
+        Some(Self {
+            path: base.join("nomenclate").join("corrections.jsonl"),
+        })
+    }
+
+    /// Read all remembered titles.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the corrections exist but could not be read.
+    pub fn load(&self) -> Result<Learned, Error> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Learned::default()),
+            Err(source) => {
+                return Err(Error::Read {
+                    path: self.path.clone(),
+                    source,
+                })
+            }
+        };
+
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|source| Error::Parse {
+                    path: self.path.clone(),
+                    line: index + 1,
+                    source,
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(Learned)
+    }
+
+    /// Remember where the `title` the user chose for a document with `metadata` is among its
+    /// `candidates`, or where the candidate most similar to it is if the user typed the title.
+    /// Nothing is remembered if the title is like none of the candidates or the applications the
+    /// document was made with are unknown.
+    ///
+    /// Returns whether the title was remembered.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the corrections could not be written.
+    pub fn record(
+        &self,
+        metadata: &Metadata,
+        candidates: &[Candidate],
+        title: &str,
+    ) -> Result<bool, Error> {
+        let Some(fingerprint) = fingerprint(metadata) else {
+            return Ok(false);
+        };
+        let Some(candidate) = closest(candidates, title) else {
+            return Ok(false);
+        };
+
+        self.append(&[Correction {
+            fingerprint,
+            page: candidate.page,
+            font_size: candidate.font_size,
+            position: candidate.position,
+        }])?;
+
+        Ok(true)
+    }
+
+    /// Remember the `corrections`, e.g. ones learned on another machine.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the corrections could not be written.
+    pub fn append(&self, corrections: &[Correction]) -> Result<(), Error> {
+        let mut lines = String::new();
+        for correction in corrections {
+            lines.push_str(&serde_json::to_string(correction)?);
+            lines.push('\n');
+        }
+        let write_error = |source| Error::Write {
+            path: self.path.clone(),
+            source,
+        };
+
+        if let Some(directory) = self.path.parent() {
+            fs::create_dir_all(directory).map_err(write_error)?;
+        }
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .map_err(write_error)
+    }
+}
+
+/// The candidate that is `title`, or else the one most similar to it, if any is similar enough.
+fn closest<'a>(candidates: &'a [Candidate], title: &str) -> Option<&'a Candidate> {
+    if let Some(candidate) = candidates.iter().find(|candidate| candidate.text == title) {
+        return Some(candidate);
+    }
+
+    let title = title.to_lowercase();
+    candidates
+        .iter()
+        .map(|candidate| {
+            let similarity = strsim::normalized_levenshtein(&candidate.text.to_lowercase(), &title);
+            (candidate, similarity)
+        })
+        .filter(|(_, similarity)| *similarity >= MIN_SIMILARITY)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(candidate, _)| candidate)
+}
+
+/// The remembered titles, see [`Corrections`].
+#[derive(Default)]
+pub struct Learned(Vec<Correction>);
+
+impl Learned {
+    /// The remembered titles, oldest first.
+    pub fn corrections(&self) -> &[Correction] {
+        &self.0
+    }
+
+    /// Raise the scores of the `candidates` of a document with `metadata` that are where the
+    /// titles of documents made with the same applications were, and rank them again.
+    pub fn apply(&self, metadata: &Metadata, candidates: &mut [Candidate]) {
+        let Some(fingerprint) = fingerprint(metadata) else {
+            return;
+        };
+        let learned: Vec<_> = self
+            .0
+            .iter()
+            .filter(|correction| correction.fingerprint == fingerprint)
+            .collect();
+        if learned.is_empty() {
+            return;
+        }
+
+        for candidate in candidates.iter_mut() {
+            let alike = learned
+                .iter()
+                .filter(|correction| correction.is_like(candidate))
+                .count();
+            if alike > 0 {
+                #[allow(clippy::cast_precision_loss)]
+                let share = alike as f32 / learned.len() as f32;
+                candidate.score = CORRECTION_WEIGHT.mul_add(share, candidate.score).min(1.);
+                log::debug!(
+                    "{:?} is like {alike} learned titles of {fingerprint:?}",
+                    candidate.text
+                );
+            }
+        }
+        candidates.sort_by(Candidate::rank);
+    }
+}
+
+/// The applications a document with `metadata` was made with, or `None` if they are unknown.
+fn fingerprint(metadata: &Metadata) -> Option<String> {
+    let creator = metadata.creator.first().map(|field| field.value.as_str());
+    let producer = metadata.producer.first().map(|field| field.value.as_str());
+    if creator.is_none() && producer.is_none() {
+        return None;
+    }
+
+    Some(format!(
+        "{} / {}",
+        creator.unwrap_or_default(),
+        producer.unwrap_or_default()
+    ))
+}
@@ -0,0 +1,115 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use thiserror::Error;
+
+/// The file in a directory the last number used there is kept in.
+const COUNTER_FILE: &str = ".nomenclate-counter";
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("could not read {path}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not parse counter {path}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: std::num::ParseIntError,
+    },
+    #[error("could not write {path}")]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Where the numbers for `{n}` in templates continue from.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Start counting from the start in every run, separately for every directory.
+    #[default]
+    Run,
+    /// Continue from the last number used in the directory, which is kept in a
+    /// `.nomenclate-counter` file there.
+    Directory,
+}
+
+/// The next number to give a document, per directory.
+pub struct Counters {
+    mode: Mode,
+    start: u64,
+    next: HashMap<PathBuf, u64>,
+}
+
+impl Counters {
+    pub fn new(mode: Mode, start: u64) -> Self {
+        Self {
+            mode,
+            start,
+            next: HashMap::new(),
+        }
+    }
+
+    /// The number the next document in `directory` gets.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the persisted counter of the directory exists but
+    /// could not be read.
+    pub fn get(&mut self, directory: &Path) -> Result<u64, Error> {
+        if let Some(next) = self.next.get(directory) {
+            return Ok(*next);
+        }
+
+        let next = match self.mode {
+            Mode::Run => self.start,
+            Mode::Directory => load(directory)?.map_or(self.start, |last| last + 1),
+        };
+        self.next.insert(directory.to_path_buf(), next);
+
+        Ok(next)
+    }
+
+    /// Count the number of `directory` as used by a document, persisting it unless `dry_run`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the counter of the directory could not be persisted.
+    pub fn advance(&mut self, directory: &Path, dry_run: bool) -> Result<(), Error> {
+        let used = self.get(directory)?;
+        self.next.insert(directory.to_path_buf(), used + 1);
+
+        if self.mode == Mode::Directory && !dry_run {
+            let path = directory.join(COUNTER_FILE);
+            fs::write(&path, format!("{used}\n"))
+                .map_err(|source| Error::Write { path, source })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Load the last number used in `directory`, if any.
+fn load(directory: &Path) -> Result<Option<u64>, Error> {
+    let path = directory.join(COUNTER_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(source) => return Err(Error::Read { path, source }),
+    };
+
+    content
+        .trim()
+        .parse()
+        .map(Some)
+        .map_err(|source| Error::Parse { path, source })
+}
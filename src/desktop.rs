@@ -0,0 +1,122 @@
+use std::{
+    fs,
+    path::Path,
+    sync::mpsc::{self, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use notify_rust::Notification;
+
+use crate::{i18n::tr, journal::Rename};
+
+/// How long to collect renames after the first one before showing a notification, so documents
+/// renamed close together share one notification.
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Shows desktop notifications for renamed documents on a single thread, from which the user can
+/// undo the renames on desktops that support actions on notifications.
+pub struct Notifications {
+    sender: Sender<Vec<Rename>>,
+}
+
+impl Notifications {
+    /// Start the thread showing the notifications, which ends when this is dropped.
+    pub fn spawn() -> Self {
+        let (sender, receiver) = mpsc::channel::<Vec<Rename>>();
+        thread::spawn(move || {
+            while let Ok(mut renames) = receiver.recv() {
+                let deadline = Instant::now() + DEBOUNCE;
+                while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+                    match receiver.recv_timeout(left) {
+                        Ok(more) => renames.extend(more),
+                        Err(_) => break,
+                    }
+                }
+                // Renames while a notification is shown are collected into the next one.
+                if let Err(err) = notify_renamed(&renames) {
+                    log::warn!("{}", tr!("warn-notify-desktop", error = err.to_string()));
+                }
+            }
+        });
+
+        Self { sender }
+    }
+
+    /// Show a notification for the `renames`, together with the ones that happen shortly after.
+    pub fn renamed(&self, renames: Vec<Rename>) {
+        if !renames.is_empty() {
+            // The thread only ends once this is dropped.
+            let _ = self.sender.send(renames);
+        }
+    }
+}
+
+/// Show a desktop notification that the documents were renamed, and undo the `renames` if the user
+/// asks for it.
+fn notify_renamed(renames: &[Rename]) -> Result<(), notify_rust::error::Error> {
+    let name = |path: &Path| {
+        path.file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .into_owned()
+    };
+    let body = renames
+        .iter()
+        .map(|rename| format!("{} → {}", name(&rename.from), name(&rename.to)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut notification = Notification::new();
+    notification
+        .appname(env!("CARGO_PKG_NAME"))
+        .summary(&tr!("notification-renamed", count = renames.len()))
+        .body(&body)
+        .action("undo", &tr!("notification-undo"));
+
+    show(&notification, renames)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn show(notification: &Notification, renames: &[Rename]) -> Result<(), notify_rust::error::Error> {
+    // Waiting blocks until the notification is closed.
+    notification.show()?.wait_for_action(|action| {
+        if action == "undo" {
+            renames.iter().for_each(undo);
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn show(notification: &Notification, _renames: &[Rename]) -> Result<(), notify_rust::error::Error> {
+    notification.show().map(|_| ())
+}
+
+/// Move a renamed document back to where it was, unless another file took its place.
+///
+/// Renaming keeps the modification time, so the watch won't rename the document again.
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+fn undo(rename: &Rename) {
+    let result = if rename.from.exists() {
+        Err(std::io::ErrorKind::AlreadyExists.into())
+    } else {
+        fs::rename(&rename.to, &rename.from)
+    };
+
+    match result {
+        Ok(()) => log::info!(
+            "undid the rename of {} to {}",
+            rename.from.display(),
+            rename.to.display()
+        ),
+        Err(err) => log::error!(
+            "{}",
+            tr!(
+                "error-undo-rename",
+                path = rename.to.display().to_string(),
+                error = err.to_string()
+            )
+        ),
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    cli::{DiffConfigArgs, RunArgs},
+    counter::Counters,
+    i18n::tr,
+    rename, tuning,
+};
+
+/// What a run would do with a document.
+#[derive(PartialEq, Eq)]
+enum Name {
+    /// Rename it to the path.
+    Renamed(PathBuf),
+    /// Leave it as it is, e.g. because it is already named like that or was skipped.
+    Unchanged,
+    /// Ask for its title, because the title found is uncertain.
+    Review,
+}
+
+impl Name {
+    fn describe(&self) -> String {
+        match self {
+            Self::Renamed(target) => target
+                .file_name()
+                .unwrap_or(target.as_os_str())
+                .to_string_lossy()
+                .into_owned(),
+            Self::Unchanged => tr!("diff-config-unchanged"),
+            Self::Review => tr!("diff-config-review"),
+        }
+    }
+}
+
+/// Print the documents that would be named differently with the options of the `new` tuning file
+/// than with the ones of the `old` one. Nothing is renamed.
+pub fn diff_config(args: &DiffConfigArgs) {
+    let options = |path: &Path| {
+        tuning::options(path, &args.paths).map_err(|err| {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-diff-config",
+                    path = path.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+        })
+    };
+    let (Ok(old), Ok(new)) = (options(&args.old), options(&args.new)) else {
+        return;
+    };
+
+    let old = names(&old);
+    let new = names(&new);
+    let mut paths: Vec<_> = old.keys().chain(new.keys()).collect();
+    paths.sort();
+    paths.dedup();
+
+    let mut changed = 0;
+    for path in &paths {
+        let before = old.get(*path).unwrap_or(&Name::Unchanged);
+        let after = new.get(*path).unwrap_or(&Name::Unchanged);
+        if before != after {
+            changed += 1;
+            println!(
+                "{}",
+                tr!(
+                    "diff-config-changed",
+                    path = path.display().to_string(),
+                    old = before.describe(),
+                    new = after.describe()
+                )
+            );
+        }
+    }
+    println!(
+        "{}",
+        tr!(
+            "diff-config-summary",
+            changed = changed,
+            count = paths.len()
+        )
+    );
+}
+
+/// What a run with `args` would do with each of its documents.
+fn names(args: &RunArgs) -> BTreeMap<PathBuf, Name> {
+    let Some(paths) = crate::documents_to_process(args, None) else {
+        return BTreeMap::new();
+    };
+    let (documents, failures) = crate::extract_documents(args, &paths, &HashMap::new());
+    let mut counters = Counters::new(args.counter, args.counter_start);
+
+    let mut names: BTreeMap<_, _> = paths
+        .into_iter()
+        .map(|path| (path, Name::Unchanged))
+        .collect();
+    for failure in failures {
+        names.insert(failure.path, Name::Review);
+    }
+    for document in &documents {
+        let directory = document.path.parent().unwrap_or_else(|| Path::new(""));
+        let counter = if args.template().uses_counter() {
+            match counters.get(directory) {
+                Ok(counter) => {
+                    // Dry runs only count in memory.
+                    let _ = counters.advance(directory, true);
+                    Some(counter)
+                }
+                Err(err) => {
+                    crate::counter_error(&document.path, &err);
+                    continue;
+                }
+            }
+        } else {
+            None
+        };
+        let name = crate::file_name(args, document, counter);
+        let name = match rename::target(&document.path, &name, args.separator) {
+            Some(target) if target != document.path => Name::Renamed(target),
+            _ => Name::Unchanged,
+        };
+        names.insert(document.path.clone(), name);
+    }
+
+    names
+}
@@ -0,0 +1,298 @@
+use std::{
+    io::Read,
+    num::NonZeroUsize,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+use ::pdf::{
+    any::AnySync,
+    file::{Cache, File, Log},
+    object::PageRc,
+    PdfError,
+};
+
+use crate::pdf::{self, error::Error, Callbacks, Candidate, Metadata, Options, Parsed, Statistics};
+
+/// Extracts the titles of documents with the same options.
+///
+/// ```no_run
+/// # use nomenclate::Extractor;
+/// let document = Extractor::new().page_count(3).extract_path("paper.pdf")?;
+/// if let Some(title) = document.title() {
+///     println!("{} (page {})", title.text, title.page + 1);
+/// }
+/// # Ok::<(), nomenclate::pdf::error::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Extractor {
+    options: Options,
+    /// How many documents [`Self::extract_each`] extracts at the same time.
+    jobs: Option<NonZeroUsize>,
+}
+
+impl Default for Extractor {
+    fn default() -> Self {
+        Self {
+            options: Options {
+                page_count: 2,
+                page: None,
+                default_leading: pdf::DEFAULT_LEADING,
+                low_memory: false,
+                ensemble: false,
+                password: None,
+                #[cfg(feature = "ocr")]
+                ocr: false,
+                #[cfg(feature = "ocr")]
+                rendering: pdf::Rendering::default(),
+                profile: None,
+                budgets: pdf::Budgets::default(),
+            },
+            jobs: None,
+        }
+    }
+}
+
+impl From<Options> for Extractor {
+    fn from(options: Options) -> Self {
+        Self {
+            options,
+            jobs: None,
+        }
+    }
+}
+
+impl Extractor {
+    /// An extractor looking at the first two pages of documents.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look at the first `page_count` pages of documents.
+    #[must_use]
+    pub const fn page_count(mut self, page_count: usize) -> Self {
+        self.options.page_count = page_count;
+        self
+    }
+
+    /// Only look at the page with this zero-based index.
+    #[must_use]
+    pub const fn page(mut self, page: usize) -> Self {
+        self.options.page = Some(page);
+        self
+    }
+
+    /// See [`Options::low_memory`].
+    #[must_use]
+    pub const fn low_memory(mut self, low_memory: bool) -> Self {
+        self.options.low_memory = low_memory;
+        self
+    }
+
+    /// See [`Options::ensemble`].
+    #[must_use]
+    pub const fn ensemble(mut self, ensemble: bool) -> Self {
+        self.options.ensemble = ensemble;
+        self
+    }
+
+    /// See [`Options::ocr`].
+    #[cfg(feature = "ocr")]
+    #[must_use]
+    pub const fn ocr(mut self, ocr: bool) -> Self {
+        self.options.ocr = ocr;
+        self
+    }
+
+    /// See [`Options::rendering`].
+    #[cfg(feature = "ocr")]
+    #[must_use]
+    pub fn rendering(mut self, rendering: pdf::Rendering) -> Self {
+        self.options.rendering = rendering;
+        self
+    }
+
+    /// See [`Options::profile`].
+    #[must_use]
+    pub const fn profile(mut self, profile: pdf::Profile) -> Self {
+        self.options.profile = Some(profile);
+        self
+    }
+
+    /// Decrypt encrypted documents with `password` if the empty password does not work.
+    #[must_use]
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.options.password = Some(password.into());
+        self
+    }
+
+    /// Extract `jobs` documents at the same time in [`Self::extract_all`], [`Self::extract_each`]
+    /// and [`Self::for_each`] instead of one for every processor.
+    #[must_use]
+    pub const fn jobs(mut self, jobs: NonZeroUsize) -> Self {
+        self.jobs = Some(jobs);
+        self
+    }
+
+    /// The options documents are parsed with.
+    #[must_use]
+    pub const fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Extract the title of the document at `path`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the document could not be loaded.
+    pub fn extract_path(&self, path: impl AsRef<Path>) -> Result<Document, Error> {
+        pdf::parse_pdf(path, &self.options, &mut Callbacks::default()).map(Document::from)
+    }
+
+    /// Extract the title of a document that is already loaded into memory.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the document could not be loaded.
+    pub fn extract_bytes(&self, data: &[u8]) -> Result<Document, Error> {
+        pdf::parse_pdf_bytes(data, &self.options, &mut Callbacks::default()).map(Document::from)
+    }
+
+    /// Extract the title of a document read from `reader`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the document could not be read or loaded.
+    pub fn extract_reader(&self, reader: impl Read) -> Result<Document, Error> {
+        pdf::parse_pdf_reader(reader, &self.options, &mut Callbacks::default()).map(Document::from)
+    }
+
+    /// Extract the title of a document the caller already loaded with the `pdf` crate, see
+    /// [`pdf::parse_pdf_file`].
+    pub fn extract_file<B, OC, SC, L>(&self, file: &File<B, OC, SC, L>) -> Document
+    where
+        B: ::pdf::backend::Backend + Sync,
+        OC: Cache<Result<AnySync, Arc<PdfError>>> + Sync,
+        SC: Cache<Result<Arc<[u8]>, Arc<PdfError>>> + Sync,
+        L: Log + Sync,
+    {
+        pdf::parse_pdf_file(file, &self.options, &mut Callbacks::default()).into()
+    }
+
+    /// Extract the title from the `pages` of a document the caller already loaded with the `pdf`
+    /// crate, see [`pdf::parse_pdf_pages`].
+    pub fn extract_pages<B, OC, SC, L>(
+        &self,
+        file: &File<B, OC, SC, L>,
+        pages: Vec<PageRc>,
+    ) -> Document
+    where
+        B: ::pdf::backend::Backend + Sync,
+        OC: Cache<Result<AnySync, Arc<PdfError>>> + Sync,
+        SC: Cache<Result<Arc<[u8]>, Arc<PdfError>>> + Sync,
+        L: Log + Sync,
+    {
+        pdf::parse_pdf_pages(file, pages, &self.options, &mut Callbacks::default()).into()
+    }
+
+    /// Extract the titles of the documents at `paths` on several threads, see [`Self::jobs`].
+    ///
+    /// The results are in the order of `paths`. A document that could not be loaded does not stop
+    /// the others from being extracted.
+    pub fn extract_all<P: AsRef<Path> + Sync>(&self, paths: &[P]) -> Vec<Result<Document, Error>> {
+        let mut results: Vec<_> = paths.iter().map(|_| None).collect();
+        self.extract_each(paths, |index, result| results[index] = Some(result));
+
+        results.into_iter().flatten().collect()
+    }
+
+    /// Like [`Self::extract_all`], but call `on_extracted` with the index of each document in
+    /// `paths` and its result as soon as it is done, e.g. to report progress.
+    ///
+    /// `on_extracted` is called on the current thread in the order the documents are done, which
+    /// is not necessarily the order of `paths`.
+    pub fn extract_each<P: AsRef<Path> + Sync>(
+        &self,
+        paths: &[P],
+        on_extracted: impl FnMut(usize, Result<Document, Error>),
+    ) {
+        self.for_each(paths, |path| self.extract_path(path), on_extracted);
+    }
+
+    /// Like [`Self::extract_each`], but process each of `paths` with `extract` instead, e.g. to
+    /// extract the documents with other options or to also look at other files.
+    pub fn for_each<P: Sync, T: Send>(
+        &self,
+        paths: &[P],
+        extract: impl Fn(&P) -> T + Sync,
+        mut on_extracted: impl FnMut(usize, T),
+    ) {
+        let jobs = self
+            .jobs
+            .or_else(|| thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get)
+            .min(paths.len());
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for _ in 0..jobs {
+                let sender = sender.clone();
+                let (next, extract) = (&next, &extract);
+                scope.spawn(move || loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = paths.get(index) else {
+                        break;
+                    };
+                    if sender.send((index, extract(path))).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(sender);
+
+            for (index, result) in receiver {
+                on_extracted(index, result);
+            }
+        });
+    }
+}
+
+/// What was extracted from a document.
+#[derive(Clone, Debug)]
+pub struct Document {
+    /// The title candidates, the most likely title first. Every candidate keeps the positioned
+    /// pieces of text it was put together from.
+    pub candidates: Vec<Candidate>,
+    pub metadata: Metadata,
+    pub statistics: Statistics,
+}
+
+impl From<Parsed> for Document {
+    fn from(parsed: Parsed) -> Self {
+        Self {
+            candidates: parsed.candidates,
+            metadata: parsed.metadata,
+            statistics: parsed.statistics,
+        }
+    }
+}
+
+impl Document {
+    /// The most likely title, with the page it was found on and its font size.
+    #[must_use]
+    pub fn title(&self) -> Option<&Candidate> {
+        self.candidates.first()
+    }
+
+    /// How confident we are that [`Self::title`] is the title, see [`pdf::confidence`].
+    #[must_use]
+    pub fn confidence(&self) -> f32 {
+        pdf::confidence(&self.candidates)
+    }
+}
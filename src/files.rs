@@ -0,0 +1,108 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use tempfile::TempDir;
+
+use crate::i18n::tr;
+
+/// Create a directory with a new random name starting with `prefix` in the temporary directory,
+/// which only the current user can access and which is removed when the returned value is dropped.
+///
+/// The temporary directory is shared with other users, who could otherwise create a directory
+/// with a predictable name first or place links in it.
+///
+/// # Errors
+///
+/// This function will return an error if the directory could not be created.
+pub fn temporary_directory(prefix: &str) -> io::Result<TempDir> {
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(prefix);
+    #[cfg(unix)]
+    builder.permissions(std::os::unix::fs::PermissionsExt::from_mode(0o700));
+
+    builder.tempdir()
+}
+
+/// Collect the PDF documents at `path`, descending into subdirectories.
+///
+/// If `path` is a file, it is returned as is. The documents are sorted by path.
+pub fn documents(path: &Path) -> Vec<PathBuf> {
+    if !path.is_dir() {
+        return vec![path.to_path_buf()];
+    }
+
+    let mut documents = Vec::new();
+    let mut directories = vec![path.to_path_buf()];
+
+    while let Some(directory) = directories.pop() {
+        let entries = match fs::read_dir(&directory) {
+            Ok(entries) => entries,
+            Err(err) => {
+                log::warn!(
+                    "{}",
+                    tr!(
+                        "error-read-directory",
+                        path = directory.display().to_string(),
+                        error = err.to_string()
+                    )
+                );
+                continue;
+            }
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            if path.is_dir() {
+                directories.push(path);
+            } else if is_pdf(&path) {
+                documents.push(path);
+            }
+        }
+    }
+
+    documents.sort();
+    documents
+}
+
+/// Whether the file at `path` was created, modified or, on Unix, had its status changed (like
+/// being moved into place with its modification time kept) at or after `time`. Files whose times
+/// cannot be read count as changed.
+pub fn changed_since(path: &Path, time: SystemTime) -> bool {
+    let Ok(metadata) = fs::metadata(path) else {
+        return true;
+    };
+
+    [
+        metadata.modified().ok(),
+        metadata.created().ok(),
+        status_changed(&metadata),
+    ]
+    .into_iter()
+    .flatten()
+    .max()
+    .is_none_or(|changed| changed >= time)
+}
+
+/// When the status of a file last changed (its ctime), which is also when it was last renamed or
+/// moved.
+#[cfg(unix)]
+fn status_changed(metadata: &fs::Metadata) -> Option<SystemTime> {
+    use std::os::unix::fs::MetadataExt;
+
+    let seconds = u64::try_from(metadata.ctime()).ok()?;
+    let nanoseconds = u32::try_from(metadata.ctime_nsec()).ok()?;
+    SystemTime::UNIX_EPOCH.checked_add(std::time::Duration::new(seconds, nanoseconds))
+}
+
+#[cfg(not(unix))]
+const fn status_changed(_metadata: &fs::Metadata) -> Option<SystemTime> {
+    None
+}
+
+pub fn is_pdf(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"))
+}
@@ -0,0 +1,274 @@
+use std::{num::NonZeroUsize, sync::Arc};
+
+use nomenclate::{pdf::Field, Document, Extractor};
+use tokio::sync::{mpsc, Semaphore};
+use tokio_stream::{wrappers::ReceiverStream, StreamExt};
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::{cli::GrpcArgs, i18n::tr};
+
+#[allow(clippy::all, clippy::pedantic, clippy::nursery)]
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/nomenclate.Extractor.rs"));
+}
+
+use generated::extractor_server::{self, ExtractorServer};
+
+/// How many bytes are in a MiB.
+const MIB: usize = 1024 * 1024;
+
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct ExtractRequest {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(oneof = "Source", tags = "2, 3")]
+    pub document: Option<Source>,
+    #[prost(uint32, tag = "4")]
+    pub page_count: u32,
+    #[prost(string, optional, tag = "5")]
+    pub password: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Eq, prost::Oneof)]
+pub enum Source {
+    #[prost(bytes, tag = "2")]
+    Data(Vec<u8>),
+    #[prost(string, tag = "3")]
+    Path(String),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ExtractResponse {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(message, repeated, tag = "2")]
+    pub candidates: Vec<Candidate>,
+    #[prost(float, tag = "3")]
+    pub confidence: f32,
+    #[prost(message, repeated, tag = "4")]
+    pub metadata: Vec<MetadataField>,
+    #[prost(string, optional, tag = "5")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Candidate {
+    #[prost(string, tag = "1")]
+    pub text: String,
+    #[prost(uint32, tag = "2")]
+    pub page: u32,
+    #[prost(float, tag = "3")]
+    pub y: f32,
+    #[prost(float, tag = "4")]
+    pub font_size: f32,
+    #[prost(float, tag = "5")]
+    pub score: f32,
+}
+
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct MetadataField {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub value: String,
+    #[prost(string, tag = "3")]
+    pub provenance: String,
+}
+
+/// Serve the extraction of titles over gRPC until the process is stopped.
+///
+/// The service is described in `proto/nomenclate.proto`.
+pub fn serve(args: &GrpcArgs) {
+    let service = Service {
+        page_count: args.page_count,
+        allow_paths: args.allow_paths,
+        concurrency: args.concurrency.map_or_else(
+            || std::thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            NonZeroUsize::get,
+        ),
+    };
+    let server = ExtractorServer::new(service)
+        .max_decoding_message_size(args.max_document_size.saturating_mul(MIB));
+
+    let result = tokio::runtime::Runtime::new().and_then(|runtime| {
+        runtime.block_on(async {
+            log::info!("serving the extraction of titles on {}", args.listen);
+            tonic::transport::Server::builder()
+                .add_service(server)
+                .serve(args.listen)
+                .await
+                .map_err(std::io::Error::other)
+        })
+    });
+
+    if let Err(err) = result {
+        log::error!(
+            "{}",
+            tr!(
+                "error-serve",
+                address = args.listen.to_string(),
+                error = err.to_string()
+            )
+        );
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Service {
+    /// The number of pages to search for a title if a request does not ask for another number.
+    page_count: usize,
+    /// Whether documents can be given by their path on the server.
+    allow_paths: bool,
+    /// How many documents of a batch are extracted at the same time.
+    concurrency: usize,
+}
+
+impl Service {
+    fn extract(self, request: ExtractRequest) -> ExtractResponse {
+        let failed = |error: String| ExtractResponse {
+            id: request.id.clone(),
+            error: Some(error),
+            ..ExtractResponse::default()
+        };
+        let mut extractor = Extractor::new().page_count(match request.page_count {
+            0 => self.page_count,
+            page_count => page_count as usize,
+        });
+        if let Some(password) = &request.password {
+            extractor = extractor.password(password.as_str());
+        }
+
+        let document = match &request.document {
+            Some(Source::Data(data)) => extractor.extract_bytes(data),
+            Some(Source::Path(path)) if self.allow_paths => extractor.extract_path(path),
+            Some(Source::Path(_)) => {
+                return failed("documents can only be given by path with --allow-paths".into())
+            }
+            None => return failed("no document was given".into()),
+        };
+
+        match document {
+            Ok(document) => response(request.id, &document),
+            Err(err) => failed(err.to_string()),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl extractor_server::Extractor for Service {
+    type ExtractBatchStream = ReceiverStream<Result<ExtractResponse, Status>>;
+
+    async fn extract(
+        &self,
+        request: Request<ExtractRequest>,
+    ) -> Result<Response<ExtractResponse>, Status> {
+        let service = *self;
+        let request = request.into_inner();
+
+        tokio::task::spawn_blocking(move || service.extract(request))
+            .await
+            .map(Response::new)
+            .map_err(|err| Status::internal(err.to_string()))
+    }
+
+    async fn extract_batch(
+        &self,
+        request: Request<Streaming<ExtractRequest>>,
+    ) -> Result<Response<Self::ExtractBatchStream>, Status> {
+        let service = *self;
+        let mut requests = request.into_inner();
+        let (sender, receiver) = mpsc::channel(service.concurrency);
+        let permits = Arc::new(Semaphore::new(service.concurrency));
+
+        // A permit is held until the response is sent, so no more documents are read while the
+        // client does not read the responses.
+        tokio::spawn(async move {
+            while let Some(request) = requests.next().await {
+                let request = match request {
+                    Ok(request) => request,
+                    Err(status) => {
+                        let _ = sender.send(Err(status)).await;
+                        break;
+                    }
+                };
+                let Ok(permit) = Arc::clone(&permits).acquire_owned().await else {
+                    break;
+                };
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    let response = tokio::task::spawn_blocking(move || service.extract(request))
+                        .await
+                        .map_err(|err| Status::internal(err.to_string()));
+                    let _ = sender.send(response).await;
+                    drop(permit);
+                });
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+fn response(id: String, document: &Document) -> ExtractResponse {
+    let metadata = &document.metadata;
+    let fields = |name: &str, fields: &[Field<String>]| -> Vec<MetadataField> {
+        fields
+            .iter()
+            .map(|field| MetadataField {
+                name: name.to_string(),
+                value: field.value.clone(),
+                provenance: field.provenance.to_string(),
+            })
+            .collect()
+    };
+    let years: Vec<_> = metadata
+        .year
+        .iter()
+        .map(|year| Field::new(year.value.to_string(), year.provenance))
+        .collect();
+
+    ExtractResponse {
+        id,
+        candidates: document
+            .candidates
+            .iter()
+            .map(|candidate| Candidate {
+                text: candidate.text.clone(),
+                page: u32::try_from(candidate.page).unwrap_or(u32::MAX),
+                y: candidate.y,
+                font_size: candidate.font_size,
+                score: candidate.score,
+            })
+            .collect(),
+        confidence: document.confidence(),
+        metadata: [
+            fields("title", &metadata.title),
+            fields("author", &metadata.author),
+            fields("year", &years),
+            fields("created", &metadata.created),
+            fields("doi", &metadata.doi),
+            fields("arxiv", &metadata.arxiv),
+            fields("publication", &metadata.publication),
+            fields("issue_date", &metadata.issue_date),
+            fields("standard_id", &metadata.standard_id),
+            fields("case_number", &metadata.case_number),
+            fields("filed", &metadata.filed),
+            fields("document_type", &metadata.document_type),
+            fields("institution", &metadata.institution),
+            fields("period", &metadata.period),
+            fields("account_suffix", &metadata.account_suffix),
+            fields("manufacturer", &metadata.manufacturer),
+            fields("part_number", &metadata.part_number),
+            fields("vendor", &metadata.vendor),
+            fields("purchased", &metadata.purchased),
+            fields("amount", &metadata.amount),
+            fields("currency", &metadata.currency),
+            fields("composer", &metadata.composer),
+            fields("piece", &metadata.piece),
+            fields("creator", &metadata.creator),
+            fields("producer", &metadata.producer),
+        ]
+        .concat(),
+        error: None,
+    }
+}
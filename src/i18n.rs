@@ -0,0 +1,107 @@
+use std::sync::OnceLock;
+
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
+use unic_langid::LanguageIdentifier;
+
+/// The translations of the user-facing messages, by language. The first one is used for messages
+/// missing in the others.
+const LOCALES: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en.ftl")),
+    ("de", include_str!("../locales/de.ftl")),
+];
+
+/// The bundles of messages to look messages up in, the preferred language first.
+static BUNDLES: OnceLock<Vec<FluentBundle<FluentResource>>> = OnceLock::new();
+
+/// Look up the message `id` in the language of the user, formatted with `args`.
+///
+/// Messages missing in the user's language fall back to English, messages missing entirely to
+/// their id.
+pub fn message(id: &str, args: Option<&FluentArgs>) -> String {
+    BUNDLES
+        .get_or_init(|| bundles(&requested_languages()))
+        .iter()
+        .find_map(|bundle| {
+            let pattern = bundle.get_message(id)?.value()?;
+            let mut errors = Vec::new();
+            let message = bundle.format_pattern(pattern, args, &mut errors);
+            for error in errors {
+                log::debug!("could not format message {id}: {error}");
+            }
+
+            Some(message.into_owned())
+        })
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// The languages the user prefers according to their locale settings (e.g. `LANG`), the most
+/// preferred first.
+fn requested_languages() -> Vec<LanguageIdentifier> {
+    sys_locale::get_locales()
+        // POSIX locales look like `de_CH.UTF-8`
+        .filter_map(|locale| {
+            locale
+                .split(['.', '@'])
+                .next()?
+                .replace('_', "-")
+                .parse()
+                .ok()
+        })
+        .collect()
+}
+
+/// Create the bundles of the available languages that best match the `requested` ones.
+fn bundles(requested: &[LanguageIdentifier]) -> Vec<FluentBundle<FluentResource>> {
+    let available: Vec<LanguageIdentifier> = LOCALES
+        .iter()
+        .filter_map(|(language, _)| language.parse().ok())
+        .collect();
+    let default = available.first().cloned();
+    let mut languages = negotiate_languages(
+        requested,
+        &available,
+        default.as_ref(),
+        NegotiationStrategy::Filtering,
+    );
+    if let Some(default) = &default {
+        if !languages.contains(&default) {
+            languages.push(default);
+        }
+    }
+
+    languages
+        .into_iter()
+        .filter_map(|language| {
+            let (_, source) = LOCALES
+                .iter()
+                .find(|(name, _)| name.parse().ok().as_ref() == Some(language))?;
+            let resource = FluentResource::try_new((*source).to_string())
+                .inspect_err(|(_, errors)| {
+                    log::debug!("could not parse messages for {language}: {errors:?}");
+                })
+                .ok()?;
+            let mut bundle = FluentBundle::new_concurrent(vec![language.clone()]);
+            // the isolation marks around arguments show up as garbage in many terminals
+            bundle.set_use_isolating(false);
+            bundle.add_resource(resource).ok()?;
+
+            Some(bundle)
+        })
+        .collect()
+}
+
+/// Look up a user-facing message in the language of the user, e.g.
+/// `tr!("renamed", from = from.display().to_string(), to = to.display().to_string())`.
+macro_rules! tr {
+    ($id:literal) => {
+        $crate::i18n::message($id, None)
+    };
+    ($id:literal, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::message($id, Some(&args))
+    }};
+}
+
+pub(crate) use tr;
@@ -0,0 +1,344 @@
+use std::{
+    env, fs,
+    io::Write,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+    time::SystemTime,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("could not read {path}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not write {path}")]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not parse line {line} of journal {path}")]
+    Parse {
+        path: PathBuf,
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("could not serialize journal entry")]
+    Serialize(#[from] serde_json::Error),
+    #[error("invalid time {time:?} in journal {path}")]
+    Time { path: PathBuf, time: String },
+    #[error("there is no run {0} in the journal")]
+    UnknownRun(u64),
+    #[error("no run has been journaled yet")]
+    Empty,
+    #[error("{path} was exported by a newer version (format {version})")]
+    UnsupportedVersion { path: PathBuf, version: u32 },
+    #[error("{0} is not an exported journal")]
+    NotAnExport(PathBuf),
+}
+
+/// The version of the format of exported journals, which is increased on changes older versions
+/// cannot read.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// A file that was renamed, with absolute paths.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Rename {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+impl Rename {
+    pub fn new(from: &Path, to: &Path) -> Self {
+        let absolute =
+            |path: &Path| std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+
+        Self {
+            from: absolute(from),
+            to: absolute(to),
+        }
+    }
+}
+
+/// A run of nomenclate that was not a dry run.
+#[derive(Serialize, Deserialize)]
+pub struct Run {
+    pub id: u64,
+    /// When the run started, in RFC 3339 format.
+    pub started: String,
+    pub renames: Vec<Rename>,
+}
+
+impl Run {
+    /// Start a run now, with no renames yet.
+    pub fn start() -> Self {
+        Self {
+            id: 0,
+            started: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            renames: Vec::new(),
+        }
+    }
+}
+
+/// The runs of nomenclate, oldest first, in a file with one line of JSON per run.
+///
+/// The format is stable: Fields are only ever added with defaults, so journals written by older
+/// versions can always be read.
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    /// Use the journal in the user's state directory, `$XDG_STATE_HOME/nomenclate/journal.jsonl`
+    /// or `~/.local/state/nomenclate/journal.jsonl`.
+    pub fn user() -> Option<Self> {
+        let base = env::var_os("XDG_STATE_HOME")
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("state"))
+            })?;
+
+        Some(Self {
+            path: base.join("nomenclate").join("journal.jsonl"),
+        })
+    }
+
+    /// Read all journaled runs.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the journal exists but could not be read.
+    pub fn runs(&self) -> Result<Vec<Run>, Error> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(source) => {
+                return Err(Error::Read {
+                    path: self.path.clone(),
+                    source,
+                })
+            }
+        };
+
+        parse_lines(&self.path, &content).collect()
+    }
+
+    /// Read the journaled runs that started at or after `since`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the journal could not be read or does not contain the
+    /// run `since` refers to.
+    pub fn runs_since(&self, since: &Since) -> Result<Vec<Run>, Error> {
+        let time = self.resolve(since)?;
+
+        Ok(self
+            .runs()?
+            .into_iter()
+            .filter(|run| {
+                humantime::parse_rfc3339(&run.started).is_ok_and(|started| started >= time)
+            })
+            .collect())
+    }
+
+    /// Add `run` to the journal with the id following the last journaled one.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the journal could not be read or written.
+    pub fn append(&self, mut run: Run) -> Result<(), Error> {
+        run.id = self.runs()?.last().map_or(1, |last| last.id + 1);
+        let line = serde_json::to_string(&run)?;
+        let write_error = |source| Error::Write {
+            path: self.path.clone(),
+            source,
+        };
+
+        if let Some(directory) = self.path.parent() {
+            fs::create_dir_all(directory).map_err(write_error)?;
+        }
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{line}"))
+            .map_err(write_error)
+    }
+
+    /// The point in time `since` refers to, looking up runs in the journal.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the journal could not be read or does not contain the
+    /// run `since` refers to.
+    pub fn resolve(&self, since: &Since) -> Result<SystemTime, Error> {
+        let run = match since {
+            Since::Time(time) => return Ok(*time),
+            Since::Run(id) => self
+                .runs()?
+                .into_iter()
+                .find(|run| run.id == *id)
+                .ok_or(Error::UnknownRun(*id))?,
+            Since::LastRun => self.runs()?.pop().ok_or(Error::Empty)?,
+        };
+
+        humantime::parse_rfc3339(&run.started).map_err(|_| Error::Time {
+            path: self.path.clone(),
+            time: run.started,
+        })
+    }
+}
+
+/// The first line of an exported journal, followed by a line for every exported run.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    /// The directory the paths of the renames were relative to on the exporting machine.
+    root: PathBuf,
+}
+
+/// Export the `runs` to `path` with the paths of their renames relative to `root`, so they can be
+/// replayed on a copy of `root` elsewhere, see [`import`]. Renames outside of `root` are left out.
+///
+/// Returns the number of renames that were left out.
+///
+/// # Errors
+///
+/// This function will return an error if the export could not be written.
+pub fn export(runs: Vec<Run>, root: &Path, path: &Path) -> Result<usize, Error> {
+    let root = std::path::absolute(root).unwrap_or_else(|_| root.to_path_buf());
+    let mut left_out = 0;
+    let mut lines = vec![serde_json::to_string(&Header {
+        version: EXPORT_VERSION,
+        root: root.clone(),
+    })?];
+
+    for mut run in runs {
+        let count = run.renames.len();
+        run.renames = run
+            .renames
+            .into_iter()
+            .filter_map(|rename| {
+                Some(Rename {
+                    from: rename.from.strip_prefix(&root).ok()?.to_path_buf(),
+                    to: rename.to.strip_prefix(&root).ok()?.to_path_buf(),
+                })
+            })
+            .collect();
+        left_out += count - run.renames.len();
+        if !run.renames.is_empty() {
+            lines.push(serde_json::to_string(&run)?);
+        }
+    }
+
+    fs::write(path, lines.join("\n") + "\n").map_err(|source| Error::Write {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    Ok(left_out)
+}
+
+/// Read the runs exported to `path` with [`export`], with the paths of their renames in `root`.
+/// Renames with paths that would leave `root` are left out.
+///
+/// # Errors
+///
+/// This function will return an error if the export could not be read, is not an export or was
+/// written by a newer version.
+pub fn import(path: &Path, root: &Path) -> Result<Vec<Run>, Error> {
+    let content = fs::read_to_string(path).map_err(|source| Error::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let (header, runs) = content.split_once('\n').unwrap_or((&content, ""));
+    let header: Header =
+        serde_json::from_str(header).map_err(|_| Error::NotAnExport(path.to_path_buf()))?;
+    if header.version > EXPORT_VERSION {
+        return Err(Error::UnsupportedVersion {
+            path: path.to_path_buf(),
+            version: header.version,
+        });
+    }
+
+    let inside = |path: &Path| {
+        path.components()
+            .all(|component| matches!(component, Component::Normal(_)))
+            .then(|| root.join(path))
+    };
+    parse_lines(path, runs)
+        .map(|run| {
+            let mut run: Run = run?;
+            run.renames = run
+                .renames
+                .iter()
+                .filter_map(|rename| {
+                    Some(Rename {
+                        from: inside(&rename.from)?,
+                        to: inside(&rename.to)?,
+                    })
+                })
+                .collect();
+            Ok(run)
+        })
+        .collect()
+}
+
+/// Parse the non-empty lines of `content`, which was read from `path`, as JSON.
+fn parse_lines<'a, T: DeserializeOwned>(
+    path: &'a Path,
+    content: &'a str,
+) -> impl Iterator<Item = Result<T, Error>> + 'a {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|source| Error::Parse {
+                path: path.to_path_buf(),
+                line: index + 1,
+                source,
+            })
+        })
+}
+
+/// A point in time given by the user.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Since {
+    /// `2024-05-01` or `2024-05-01 18:30:00`, in UTC.
+    Time(SystemTime),
+    /// The start of the journaled run with this id.
+    Run(u64),
+    /// The start of the last journaled run, `last`.
+    LastRun,
+}
+
+impl FromStr for Since {
+    type Err = String;
+
+    fn from_str(since: &str) -> Result<Self, Self::Err> {
+        if since == "last" {
+            return Ok(Self::LastRun);
+        }
+        if let Ok(id) = since.parse() {
+            return Ok(Self::Run(id));
+        }
+
+        let time = if since.len() == "YYYY-MM-DD".len() {
+            format!("{since} 00:00:00")
+        } else {
+            since.to_string()
+        };
+        humantime::parse_rfc3339_weak(&time)
+            .map(Self::Time)
+            .map_err(|err| format!("expected `last`, a run id or a time: {err}"))
+    }
+}
@@ -0,0 +1,15 @@
+//! Find the titles of PDF documents.
+//!
+//! An [`Extractor`] finds the title of a document along with the page it is on, its font size and
+//! the positioned pieces of text it is made of. The [`pdf`] module underneath parses documents
+//! from files, memory, any reader or a `pdf::file::File` the caller already loaded, and collects
+//! title candidates from the text set in the largest fonts, ranked by how likely they are the
+//! title.
+
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![allow(clippy::multiple_crate_versions)]
+
+mod extractor;
+pub mod pdf;
+
+pub use extractor::{Document, Extractor};
@@ -0,0 +1,232 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+use tempfile::NamedTempFile;
+
+use crate::{cli::LibraryArgs, files, i18n::tr, overrides, rename, review, Titled};
+
+/// Add documents to a content-addressed store and link to them by their titles.
+///
+/// Every document is moved to `<store>/<first two characters of its hash>/<hash>.pdf`, unless the
+/// store already has a document with the same content, and a symbolic link named after the
+/// document is created in the links directory. Stored documents are never moved again: Renaming a
+/// document replaces its link, and other link directories with other templates can be built from
+/// the same store by passing it as the path. Journals, audit logs, counters and sidecar files are
+/// not supported.
+pub fn library(args: &LibraryArgs) {
+    if !args.run.dry_run {
+        for directory in [&args.store, &args.links] {
+            if let Err(err) = fs::create_dir_all(directory) {
+                log::error!(
+                    "{}",
+                    tr!(
+                        "error-library-directory",
+                        path = directory.display().to_string(),
+                        error = err.to_string()
+                    )
+                );
+                return;
+            }
+        }
+    }
+
+    // The documents to extract the titles from and the stored documents they are.
+    let mut objects = HashMap::new();
+    let mut paths = Vec::new();
+    for path in args
+        .run
+        .paths
+        .iter()
+        .flat_map(|path| files::documents(path))
+    {
+        match store(args, &path) {
+            Ok(object) => {
+                let path = if args.run.dry_run {
+                    path
+                } else {
+                    object.clone()
+                };
+                if objects.insert(path.clone(), object).is_none() {
+                    paths.push(path);
+                }
+            }
+            Err(err) => log::error!(
+                "{}",
+                tr!(
+                    "error-library-store",
+                    path = path.display().to_string(),
+                    error = err.to_string()
+                )
+            ),
+        }
+    }
+
+    let (documents, failures) = crate::extract_documents(&args.run, &paths, &HashMap::new());
+    let mut links = existing_links(&args.links);
+    for document in &documents {
+        link(args, &objects[&document.path], document, &mut links);
+    }
+    if failures.is_empty() {
+        return;
+    }
+    match review::review(failures, args.run.candidate_count) {
+        Ok(titles) => {
+            for (failure, title) in titles {
+                let object = objects[&failure.path].clone();
+                link(args, &object, &Titled::reviewed(failure, title), &mut links);
+            }
+        }
+        Err(err) => log::error!("{}", tr!("error-review", error = err.to_string())),
+    }
+}
+
+/// Move the document at `path` into the store and return where it is stored.
+///
+/// A document whose content is already stored is left where it is.
+fn store(args: &LibraryArgs, path: &Path) -> io::Result<PathBuf> {
+    let hash = overrides::hash(&fs::read(path)?);
+    let object = absolute(&args.store)
+        .join(&hash[..2])
+        .join(hash)
+        .with_extension("pdf");
+    if args.run.dry_run || object.exists() {
+        if object.exists() && absolute(path) != object {
+            log::info!(
+                "{} is already stored as {}",
+                path.display(),
+                object.display()
+            );
+        }
+        return Ok(object);
+    }
+
+    move_file(path, &object)?;
+    log::info!("stored {} as {}", path.display(), object.display());
+
+    Ok(object)
+}
+
+/// Move the file at `from` to `to`, copying it if it is on another file system.
+///
+/// A copy is written to a temporary file next to `to` first, since a document at `to` is trusted
+/// to be complete: An interrupted copy must not leave a truncated document there.
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    let parent = to.parent().unwrap_or_else(|| Path::new(""));
+    fs::create_dir_all(parent)?;
+    if fs::rename(from, to).is_err() {
+        let mut copy = NamedTempFile::new_in(parent)?;
+        let mut original = File::open(from)?;
+        io::copy(&mut original, &mut copy)?;
+        copy.as_file()
+            .set_permissions(original.metadata()?.permissions())?;
+        copy.as_file().sync_all()?;
+        copy.persist(to).map_err(|err| err.error)?;
+        fs::remove_file(from)?;
+    }
+
+    Ok(())
+}
+
+/// The symbolic links in `directory` by the stored document they point to.
+fn existing_links(directory: &Path) -> HashMap<PathBuf, Vec<PathBuf>> {
+    let mut links: HashMap<_, Vec<_>> = HashMap::new();
+    let Ok(entries) = fs::read_dir(directory) else {
+        return links;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let link = entry.path();
+        if let Ok(object) = fs::read_link(&link) {
+            links.entry(absolute(&object)).or_default().push(link);
+        }
+    }
+
+    links
+}
+
+/// Link to the stored `object` of `document` by its title, replacing the links to it that no
+/// longer match its title.
+fn link(
+    args: &LibraryArgs,
+    object: &Path,
+    document: &Titled,
+    links: &mut HashMap<PathBuf, Vec<PathBuf>>,
+) {
+    let name = crate::file_name(&args.run, document, None);
+    let Some(target) = rename::target(
+        &args.links.join(object.file_name().unwrap_or_default()),
+        &name,
+        args.run.separator,
+    ) else {
+        log::error!(
+            "{}",
+            tr!(
+                "error-invalid-file-name",
+                path = document.path.display().to_string(),
+                title = format!("{name:?}")
+            )
+        );
+        return;
+    };
+
+    let previous = links.remove(object).unwrap_or_default();
+    if previous.contains(&target) {
+        println!("{}", tr!("unchanged", path = target.display().to_string()));
+        links.insert(object.to_path_buf(), previous);
+        return;
+    }
+    if target.symlink_metadata().is_ok() {
+        crate::skip(
+            &document.path,
+            &tr!("already-exists", path = target.display().to_string()),
+        );
+        links.insert(object.to_path_buf(), previous);
+        return;
+    }
+
+    println!(
+        "{}",
+        tr!(
+            "library-linked",
+            link = target.display().to_string(),
+            object = object.display().to_string()
+        )
+    );
+    if args.run.dry_run {
+        return;
+    }
+    let result = previous
+        .iter()
+        .try_for_each(fs::remove_file)
+        .and_then(|()| symlink(object, &target));
+    match result {
+        Ok(()) => {
+            links.insert(object.to_path_buf(), vec![target]);
+        }
+        Err(err) => log::error!(
+            "{}",
+            tr!(
+                "error-library-link",
+                path = target.display().to_string(),
+                error = err.to_string()
+            )
+        ),
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(unix)]
+fn symlink(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn symlink(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(original, link)
+}
@@ -0,0 +1,83 @@
+use std::{
+    collections::BTreeSet,
+    fs::{File, OpenOptions, TryLockError},
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+/// The file in a directory that runs with `--single-instance` hold a lock on.
+const LOCK_FILE: &str = ".nomenclate-lock";
+
+#[derive(Error, Debug)]
+#[error("could not lock {path}")]
+pub struct Error {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+}
+
+/// Locks on the directories a run renames documents in, released when dropped.
+#[derive(Default)]
+pub struct Locks {
+    _files: Vec<File>,
+}
+
+/// The result of trying to lock directories.
+pub enum Acquired {
+    Locked(Locks),
+    /// Another run holds the lock on this directory.
+    Busy(PathBuf),
+}
+
+impl Locks {
+    /// Lock each of the `directories`, without waiting for other runs to release theirs.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a lock file could not be created or locked for
+    /// another reason than another run holding the lock.
+    pub fn acquire<'a>(directories: impl IntoIterator<Item = &'a Path>) -> Result<Acquired, Error> {
+        let directories: BTreeSet<_> = directories
+            .into_iter()
+            .map(|directory| {
+                directory
+                    .canonicalize()
+                    .unwrap_or_else(|_| directory.into())
+            })
+            .collect();
+        let mut files = Vec::with_capacity(directories.len());
+
+        for directory in directories {
+            let path = directory.join(LOCK_FILE);
+            let file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&path)
+                .map_err(|source| Error {
+                    path: path.clone(),
+                    source,
+                })?;
+
+            match file.try_lock() {
+                Ok(()) => files.push(file),
+                Err(TryLockError::WouldBlock) => return Ok(Acquired::Busy(directory)),
+                Err(TryLockError::Error(source)) => return Err(Error { path, source }),
+            }
+        }
+
+        Ok(Acquired::Locked(Self { _files: files }))
+    }
+}
+
+/// The directory a document at `path` is renamed in, or `path` itself if it is a directory.
+pub fn target_directory(path: &Path) -> &Path {
+    if path.is_dir() {
+        path
+    } else {
+        path.parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."))
+    }
+}
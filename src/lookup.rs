@@ -0,0 +1,233 @@
+use std::{sync::LazyLock, time::Duration};
+
+use nomenclate::pdf::{Field, Metadata, Provenance};
+use serde::Deserialize;
+use thiserror::Error;
+use ureq::Agent;
+
+use crate::i18n::tr;
+
+/// How long to wait for Crossref or arXiv to answer.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Crossref asks clients to identify themselves so they can be contacted about problems.
+const USER_AGENT: &str = concat!(
+    "nomenclate/",
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("CARGO_PKG_REPOSITORY"),
+    ")"
+);
+
+static AGENT: LazyLock<Agent> = LazyLock::new(|| {
+    Agent::config_builder()
+        .timeout_global(Some(TIMEOUT))
+        .user_agent(USER_AGENT)
+        .build()
+        .into()
+});
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("request failed")]
+    Request(#[from] ureq::Error),
+    #[error("no work with this identifier was found")]
+    NotFound,
+}
+
+/// The bibliographic data of a work.
+struct Work {
+    title: Option<String>,
+    authors: Vec<String>,
+    /// The year and the date the work was published, as `YYYY-MM-DD`.
+    published: Option<(u16, String)>,
+    provenance: Provenance,
+}
+
+/// Look up the DOI or arXiv identifier of a document on Crossref or arXiv and put the title,
+/// authors and year found there before the other values of `metadata`.
+pub fn complete(metadata: &mut Metadata) {
+    let work = metadata
+        .doi
+        .first()
+        .and_then(|doi| lookup(&doi.value, crossref))
+        .or_else(|| {
+            metadata
+                .arxiv
+                .first()
+                .and_then(|id| lookup(&id.value, arxiv))
+        });
+    let Some(work) = work else {
+        return;
+    };
+
+    let field = |value| Field::new(value, work.provenance);
+    if let Some(title) = work.title {
+        metadata.title.insert(0, field(title));
+    }
+    if !work.authors.is_empty() {
+        metadata.author.insert(0, field(work.authors.join("; ")));
+    }
+    if let Some((year, created)) = work.published {
+        metadata.year.insert(0, Field::new(year, work.provenance));
+        metadata.created.insert(0, field(created));
+    }
+}
+
+/// A DOI Crossref knows, to check whether it can be reached.
+const KNOWN_DOI: &str = "10.1038/nature14539";
+
+/// Check whether works can be looked up by looking up one Crossref knows.
+///
+/// # Errors
+///
+/// This function will return an error if Crossref could not be reached or did not find the work.
+pub fn check() -> Result<(), Error> {
+    crossref(KNOWN_DOI).map(drop)
+}
+
+fn lookup(id: &str, fetch: fn(&str) -> Result<Work, Error>) -> Option<Work> {
+    log::info!("looking up {id}");
+    fetch(id)
+        .inspect_err(|err| {
+            log::warn!(
+                "{}",
+                tr!(
+                    "warn-lookup-identifier",
+                    id = id.to_string(),
+                    error = err.to_string()
+                )
+            );
+        })
+        .ok()
+}
+
+#[derive(Deserialize)]
+struct CrossrefResponse {
+    message: CrossrefWork,
+}
+
+#[derive(Deserialize)]
+struct CrossrefWork {
+    #[serde(default)]
+    title: Vec<String>,
+    #[serde(default)]
+    author: Vec<CrossrefAuthor>,
+    issued: Option<CrossrefDate>,
+}
+
+#[derive(Deserialize)]
+struct CrossrefAuthor {
+    given: Option<String>,
+    family: Option<String>,
+    /// The name of an organization.
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CrossrefDate {
+    /// The year, month and day, of which the month and day may be missing.
+    #[serde(rename = "date-parts")]
+    date_parts: Vec<Vec<Option<u16>>>,
+}
+
+/// Look up the work with `doi` on Crossref.
+fn crossref(doi: &str) -> Result<Work, Error> {
+    let response: CrossrefResponse = AGENT
+        .get(format!("https://api.crossref.org/works/{}", encode(doi)))
+        .call()
+        .map_err(|err| match err {
+            ureq::Error::StatusCode(404) => Error::NotFound,
+            err => err.into(),
+        })?
+        .body_mut()
+        .read_json()?;
+    let work = response.message;
+
+    Ok(Work {
+        title: work.title.first().map(String::as_str).map(collapse),
+        authors: work
+            .author
+            .into_iter()
+            .filter_map(|author| {
+                author.name.or_else(|| match (author.given, author.family) {
+                    (Some(given), Some(family)) => Some(format!("{given} {family}")),
+                    (given, family) => family.or(given),
+                })
+            })
+            .collect(),
+        published: work
+            .issued
+            .and_then(|date| date.date_parts.into_iter().next())
+            .and_then(|parts| {
+                let part = |index: usize| parts.get(index).copied().flatten();
+                let year = part(0)?;
+                Some((
+                    year,
+                    format!(
+                        "{year:04}-{:02}-{:02}",
+                        part(1).unwrap_or(1),
+                        part(2).unwrap_or(1)
+                    ),
+                ))
+            }),
+        provenance: Provenance::Crossref,
+    })
+}
+
+/// Look up the preprint with the arXiv identifier `id` on arXiv.
+fn arxiv(id: &str) -> Result<Work, Error> {
+    let feed = AGENT
+        .get("https://export.arxiv.org/api/query")
+        .query("id_list", id)
+        .call()?
+        .body_mut()
+        .read_to_string()?;
+    // The feed has a title of its own, so only the entry is searched.
+    let entry = feed
+        .split_once("<entry>")
+        .map(|(_, entry)| entry)
+        .filter(|entry| !elements(entry, "id").is_empty())
+        .ok_or(Error::NotFound)?;
+    let published = elements(entry, "published").first().and_then(|date| {
+        let year = date.get(..4)?.parse().ok()?;
+        Some((year, date.get(..10)?.to_string()))
+    });
+
+    Ok(Work {
+        title: elements(entry, "title").first().copied().map(collapse),
+        authors: elements(entry, "name").into_iter().map(collapse).collect(),
+        published,
+        provenance: Provenance::Arxiv,
+    })
+}
+
+/// The text of the elements called `name` in `xml`.
+fn elements<'a>(xml: &'a str, name: &str) -> Vec<&'a str> {
+    let opening = format!("<{name}>");
+    let closing = format!("</{name}>");
+
+    xml.split(&opening)
+        .skip(1)
+        .filter_map(move |element| element.split_once(&closing))
+        .map(|(text, _)| text.trim())
+        .collect()
+}
+
+/// Replace runs of whitespace, including line breaks, in `text` with a single space.
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Percent-encode the characters of `doi` that have a meaning in the path of a URL.
+fn encode(doi: &str) -> String {
+    doi.bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() || b"-._~/:()".contains(&byte) {
+                char::from(byte).to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect()
+}
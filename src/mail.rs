@@ -0,0 +1,434 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use mail_parser::{Message, MessageParser, MimeHeaders};
+use nomenclate::pdf::{Field, Metadata, Provenance};
+use tempfile::TempDir;
+use thiserror::Error;
+
+use crate::{
+    cli::{MailArgs, RunArgs},
+    counter::Counters,
+    files,
+    i18n::tr,
+    journal::Journal,
+    overrides,
+    report::Report,
+};
+
+/// The prefixes email clients put before the subject of replies and forwarded emails.
+const SUBJECT_PREFIXES: [&str; 6] = ["re:", "fwd:", "fw:", "aw:", "wg:", "tr:"];
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("not a maildir, it has neither a `cur` nor a `new` directory")]
+    NotMaildir,
+    #[cfg(not(feature = "imap"))]
+    #[error("IMAP folders need nomenclate to be built with the `imap` feature")]
+    NoImap,
+    #[cfg(feature = "imap")]
+    #[error("invalid IMAP folder, use imaps://user@host/folder")]
+    InvalidImapFolder,
+    #[cfg(feature = "imap")]
+    #[error("set the password of the IMAP account with NOMENCLATE_IMAP_PASSWORD")]
+    NoImapPassword,
+    #[cfg(feature = "imap")]
+    #[error(transparent)]
+    Imap(#[from] imap::Error),
+    #[cfg(feature = "imap")]
+    #[error(transparent)]
+    Tls(#[from] native_tls::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// A PDF document attached to an email.
+struct Attachment {
+    /// The file name the sender gave the document.
+    name: String,
+    data: Vec<u8>,
+}
+
+/// Save the PDF attachments of the emails in the maildirs and IMAP folders given as paths to the
+/// output directory and rename them like any other document.
+///
+/// Attachments that are already in the output directory are left out, so the same folders can be
+/// processed again as new emails arrive. With `--dry-run`, the attachments are saved to a
+/// temporary directory instead.
+pub fn mail(args: &MailArgs) {
+    let temporary = if args.run.dry_run {
+        match files::temporary_directory("nomenclate-mail-") {
+            Ok(directory) => Some(directory),
+            Err(err) => {
+                log::error!(
+                    "{}",
+                    tr!("error-temporary-directory", error = err.to_string())
+                );
+                return;
+            }
+        }
+    } else {
+        None
+    };
+    let directory = temporary
+        .as_ref()
+        .map_or_else(|| args.output.clone(), |directory| directory.path().into());
+    if let Err(err) = fs::create_dir_all(&directory) {
+        log::error!(
+            "{}",
+            tr!(
+                "error-mail-output",
+                path = directory.display().to_string(),
+                error = err.to_string()
+            )
+        );
+        return;
+    }
+
+    let mut saved: HashSet<_> = files::documents(&args.output)
+        .iter()
+        .filter_map(|path| overrides::hash_file(path).ok())
+        .collect();
+    let mut paths = Vec::new();
+    for source in &args.run.paths {
+        let attachments = match attachments(args, source) {
+            Ok(attachments) => attachments,
+            Err(err) => {
+                log::error!(
+                    "{}",
+                    tr!(
+                        "error-mail-source",
+                        source = source.display().to_string(),
+                        error = err.to_string()
+                    )
+                );
+                continue;
+            }
+        };
+        for attachment in attachments {
+            if !saved.insert(overrides::hash(&attachment.data)) {
+                continue;
+            }
+            let path = unique_path(&directory, &attachment.name);
+            match fs::write(&path, &attachment.data) {
+                Ok(()) => paths.push(path),
+                Err(err) => log::error!(
+                    "{}",
+                    tr!(
+                        "error-mail-save",
+                        path = path.display().to_string(),
+                        error = err.to_string()
+                    )
+                ),
+            }
+        }
+    }
+    log::info!("saved {} new attachments", paths.len());
+
+    let mut counters = Counters::new(args.run.counter, args.run.counter_start);
+    let mut report = Report::default();
+    crate::process(
+        &args.run,
+        &paths,
+        Journal::user().as_ref(),
+        None,
+        &mut counters,
+        &mut report,
+        &AtomicBool::new(false),
+    );
+    if args.run.verbose {
+        report.summarize();
+    }
+
+    if let Some(Err(err)) = temporary.map(TempDir::close) {
+        log::debug!("could not remove {}: {err}", directory.display());
+    }
+}
+
+/// The documents to process after replacing the emails among them with their PDF attachments.
+pub struct Emails {
+    pub paths: Vec<PathBuf>,
+    /// The metadata of the email each attachment came from.
+    pub metadata: HashMap<PathBuf, Metadata>,
+    /// The directory the attachments are saved to with `--dry-run`, which is removed when done.
+    temporary: Option<TempDir>,
+}
+
+impl Emails {
+    /// Replace the `.eml` files among `paths` with their PDF attachments, which are saved next to
+    /// the emails unless the same document is there already.
+    pub fn expand(args: &RunArgs, paths: &[PathBuf]) -> Self {
+        let temporary = if args.dry_run && paths.iter().any(|path| is_email(path)) {
+            match files::temporary_directory("nomenclate-email-") {
+                Ok(directory) => Some(directory),
+                Err(err) => {
+                    log::error!(
+                        "{}",
+                        tr!("error-temporary-directory", error = err.to_string())
+                    );
+                    // Without a directory to save the attachments to, the emails are left out.
+                    return Self {
+                        paths: paths
+                            .iter()
+                            .filter(|path| !is_email(path))
+                            .cloned()
+                            .collect(),
+                        metadata: HashMap::new(),
+                        temporary: None,
+                    };
+                }
+            }
+        } else {
+            None
+        };
+        let mut emails = Self {
+            paths: Vec::new(),
+            metadata: HashMap::new(),
+            temporary,
+        };
+
+        for path in paths {
+            if !is_email(path) {
+                emails.paths.push(path.clone());
+                continue;
+            }
+            if let Err(err) = emails.save_attachments(path) {
+                log::error!(
+                    "{}",
+                    tr!(
+                        "error-mail-source",
+                        source = path.display().to_string(),
+                        error = err.to_string()
+                    )
+                );
+            }
+        }
+
+        emails
+    }
+
+    fn save_attachments(&mut self, email: &Path) -> io::Result<()> {
+        let content = fs::read(email)?;
+        let Some(message) = MessageParser::default().parse(&content) else {
+            return Ok(());
+        };
+        let mut attachments = Vec::new();
+        collect_pdfs(&message, &mut attachments);
+        let metadata = email_metadata(&message);
+
+        let directory = self
+            .temporary
+            .as_ref()
+            .map(TempDir::path)
+            .or_else(|| email.parent())
+            .unwrap_or_else(|| Path::new(""));
+        fs::create_dir_all(directory)?;
+        let mut saved = saved_documents(directory)?;
+        for attachment in attachments {
+            if !saved.insert(overrides::hash(&attachment.data)) {
+                log::info!(
+                    "{} of {} is saved already",
+                    attachment.name,
+                    email.display()
+                );
+                continue;
+            }
+            let path = unique_path(directory, &attachment.name);
+            fs::write(&path, &attachment.data)?;
+            self.metadata.insert(path.clone(), metadata.clone());
+            self.paths.push(path);
+        }
+
+        Ok(())
+    }
+}
+
+fn is_email(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("eml"))
+}
+
+/// The hashes of the PDF documents directly in `directory`.
+fn saved_documents(directory: &Path) -> io::Result<HashSet<String>> {
+    Ok(fs::read_dir(directory)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| files::is_pdf(path))
+        .filter_map(|path| overrides::hash_file(path).ok())
+        .collect())
+}
+
+/// The subject and date of an email as metadata of its attachments.
+fn email_metadata(message: &Message) -> Metadata {
+    let mut metadata = Metadata::default();
+
+    metadata.title.extend(
+        message
+            .subject()
+            .map(strip_subject_prefixes)
+            .filter(|subject| !subject.is_empty())
+            .map(|subject| Field::new(subject.to_string(), Provenance::Email)),
+    );
+    if let Some(date) = message.date() {
+        metadata.year.push(Field::new(date.year, Provenance::Email));
+        metadata.created.push(Field::new(
+            format!("{:04}-{:02}-{:02}", date.year, date.month, date.day),
+            Provenance::Email,
+        ));
+    }
+
+    metadata
+}
+
+/// The subject of an email without the prefixes of replies and forwards, like `Re: Fwd: `.
+fn strip_subject_prefixes(mut subject: &str) -> &str {
+    loop {
+        subject = subject.trim_start();
+        let Some(prefix) = SUBJECT_PREFIXES.iter().find(|prefix| {
+            subject
+                .get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+        }) else {
+            return subject.trim_end();
+        };
+        subject = &subject[prefix.len()..];
+    }
+}
+
+/// The PDF attachments of the emails in the maildir or IMAP folder at `source`.
+fn attachments(args: &MailArgs, source: &Path) -> Result<Vec<Attachment>, Error> {
+    let Some(folder) = source
+        .to_str()
+        .and_then(|source| source.strip_prefix("imaps://"))
+    else {
+        return maildir_attachments(source);
+    };
+
+    imap_attachments(args, folder)
+}
+
+fn maildir_attachments(maildir: &Path) -> Result<Vec<Attachment>, Error> {
+    let directories: Vec<_> = ["new", "cur"]
+        .into_iter()
+        .map(|directory| maildir.join(directory))
+        .filter(|directory| directory.is_dir())
+        .collect();
+    if directories.is_empty() {
+        return Err(Error::NotMaildir);
+    }
+
+    let mut attachments = Vec::new();
+    for directory in directories {
+        let mut emails: Vec<_> = fs::read_dir(directory)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .collect();
+        emails.sort();
+        for email in emails {
+            match fs::read(&email) {
+                Ok(email) => pdf_attachments(&email, &mut attachments),
+                Err(err) => log::warn!("could not read {}: {err}", email.display()),
+            }
+        }
+    }
+
+    Ok(attachments)
+}
+
+/// How many emails are fetched from an IMAP server at a time, so not all of them are held in
+/// memory at once.
+#[cfg(feature = "imap")]
+const IMAP_FETCH_SIZE: usize = 20;
+
+/// Fetch the emails in `folder`, given as `user@host[:port]/folder`, that match `--imap-search`,
+/// without marking them as read.
+#[cfg(feature = "imap")]
+fn imap_attachments(args: &MailArgs, folder: &str) -> Result<Vec<Attachment>, Error> {
+    let (account, folder) = folder.split_once('/').unwrap_or((folder, "INBOX"));
+    let (user, server) = account.rsplit_once('@').ok_or(Error::InvalidImapFolder)?;
+    let (host, port) = match server.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().map_err(|_| Error::InvalidImapFolder)?),
+        None => (server, 993),
+    };
+    let password = args.imap_password.as_deref().ok_or(Error::NoImapPassword)?;
+
+    let tls = native_tls::TlsConnector::new()?;
+    let mut session = imap::connect((host, port), host, &tls)?
+        .login(user, password)
+        .map_err(|(err, _)| err)?;
+    session.select(if folder.is_empty() { "INBOX" } else { folder })?;
+    let mut uids: Vec<_> = session.uid_search(&args.imap_search)?.into_iter().collect();
+    uids.sort_unstable();
+
+    let mut attachments = Vec::new();
+    for uids in uids.chunks(IMAP_FETCH_SIZE) {
+        let uids: Vec<_> = uids.iter().map(u32::to_string).collect();
+        for email in &session.uid_fetch(uids.join(","), "BODY.PEEK[]")? {
+            if let Some(email) = email.body() {
+                pdf_attachments(email, &mut attachments);
+            }
+        }
+    }
+    session.logout()?;
+
+    Ok(attachments)
+}
+
+#[cfg(not(feature = "imap"))]
+const fn imap_attachments(_args: &MailArgs, _folder: &str) -> Result<Vec<Attachment>, Error> {
+    Err(Error::NoImap)
+}
+
+/// Add the PDF attachments of `email`, including the ones of forwarded emails, to `attachments`.
+fn pdf_attachments(email: &[u8], attachments: &mut Vec<Attachment>) {
+    if let Some(message) = MessageParser::default().parse(email) {
+        collect_pdfs(&message, attachments);
+    }
+}
+
+fn collect_pdfs(message: &Message, attachments: &mut Vec<Attachment>) {
+    for part in message.attachments() {
+        if let Some(forwarded) = part.message() {
+            collect_pdfs(forwarded, attachments);
+            continue;
+        }
+
+        let name = part.attachment_name();
+        let is_pdf = part.content_type().is_some_and(|content_type| {
+            content_type
+                .subtype()
+                .is_some_and(|subtype| subtype.eq_ignore_ascii_case("pdf"))
+        }) || name.is_some_and(|name| {
+            Path::new(name)
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"))
+        });
+        if is_pdf {
+            attachments.push(Attachment {
+                name: name.unwrap_or("attachment.pdf").to_string(),
+                data: part.contents().to_vec(),
+            });
+        }
+    }
+}
+
+/// A path in `directory` for the attachment called `name` that is not taken yet.
+fn unique_path(directory: &Path, name: &str) -> PathBuf {
+    let name = PathBuf::from(sanitize_filename::sanitize(name));
+    let stem = name
+        .file_stem()
+        .map_or_else(|| "attachment".into(), |stem| stem.to_string_lossy());
+
+    let mut path = directory.join(format!("{stem}.pdf"));
+    let mut number = 2;
+    while path.exists() {
+        path = directory.join(format!("{stem} ({number}).pdf"));
+        number += 1;
+    }
+
+    path
+}
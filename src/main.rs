@@ -1,14 +1,1764 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
 #![allow(clippy::multiple_crate_versions)]
 
-mod pdf;
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs,
+    num::NonZeroUsize,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use audit::AuditLog;
+use bilingual::Bilingual;
+use cache::{Cache, Extracted};
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use cli::{
+    Args, Command, ConfigCommand, JournalCommand, RunArgs, SplitArgs, StatsArgs, TemplateCheckArgs,
+    TemplateCommand, TuningCommand,
+};
+use corrections::{Corrections, Learned};
+use counter::Counters;
+use i18n::tr;
+use journal::Journal;
+use nomenclate::{
+    pdf::{self, Candidate, Metadata, Provenance, TrustPolicy},
+    Extractor,
+};
+use overrides::Overrides;
+use prefetch::Prefetcher;
+use rename::Outcome;
+use report::{Decoding, Record, Report, Skipped};
+use review::Failure;
+use snapshot::Snapshots;
+use template::Template;
+
+mod audit;
+mod bibliography;
+mod bilingual;
+mod cache;
+mod changes;
+mod cli;
+mod companion;
+mod config;
+mod corrections;
+mod counter;
+mod desktop;
+mod diff_config;
+mod files;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod i18n;
+mod journal;
+mod library;
+mod lock;
+#[cfg(feature = "lookup")]
+mod lookup;
+mod mail;
+mod migrate;
+mod normalize;
+mod opf;
+mod overrides;
+mod panics;
+mod paperless;
+mod prefetch;
+mod progress;
+mod rename;
+mod replay;
+mod report;
+mod review;
+#[cfg(feature = "s3")]
+mod s3;
+mod sandbox;
+mod selftest;
+mod serve;
+mod sink;
+mod snapshot;
+mod sniff;
+mod sort_key;
+mod split;
+mod stats;
+mod systemd;
+mod template;
+mod trust;
+mod tuning;
+mod verify;
+mod watch;
+
+enum Extraction {
+    Title {
+        title: String,
+        /// The title in another language, for documents with a title in two languages.
+        title_alt: Option<String>,
+        /// The title as it was found, before it was normalized.
+        original: String,
+        candidates: Vec<Candidate>,
+        metadata: Metadata,
+        source: Provenance,
+        /// How well the text of the document could be decoded, if its layout was parsed.
+        decoding: Option<Decoding>,
+        /// The candidates passed over before the one the title is from.
+        skipped: Vec<Skipped>,
+    },
+    Uncertain(Vec<Candidate>, Metadata),
+}
+
+/// A document together with the title it will be renamed to.
+struct Titled {
+    path: PathBuf,
+    title: String,
+    title_alt: Option<String>,
+    /// The title as it was found, before it was normalized.
+    original: String,
+    candidates: Vec<Candidate>,
+    metadata: Metadata,
+    source: Provenance,
+    decoding: Option<Decoding>,
+    skipped: Vec<Skipped>,
+    /// How long it took to find the title.
+    elapsed: Duration,
+}
+
+impl Titled {
+    /// The confidence in the title, if it was found in the layout.
+    fn confidence(&self) -> Option<f32> {
+        (self.source == Provenance::Layout && !self.candidates.is_empty())
+            .then(|| pdf::confidence(&self.candidates))
+    }
+
+    /// The document of `failure` with the `title` the user chose for it.
+    fn reviewed(failure: Failure, title: String) -> Self {
+        Self {
+            path: failure.path,
+            original: title.clone(),
+            title,
+            title_alt: None,
+            candidates: failure.candidates,
+            metadata: failure.metadata,
+            source: Provenance::User,
+            decoding: None,
+            skipped: Vec::new(),
+            elapsed: failure.elapsed,
+        }
+    }
+}
 
 fn main() {
-    pretty_env_logger::init();
+    progress::init_logger();
+
+    let mut args = Args::parse();
+    if args.schema {
+        print!("{}", report::SCHEMA);
+        return;
+    }
+    if let Some(output) = args.run_mut().sandboxed_network_output() {
+        Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                tr!("error-sandbox-output", output = output.to_string()),
+            )
+            .exit();
+    }
+    if !ask_password(&mut args) {
+        return;
+    }
+
+    if let Some(command) = &args.command {
+        match command {
+            Command::Split(split_args) => split(split_args),
+            Command::Stats(stats_args) => stats(stats_args),
+            Command::Template(TemplateCommand::Check(check_args)) => check_template(check_args),
+            Command::Config(ConfigCommand::Check(check_args)) => config::check(check_args),
+            Command::Watch(watch_args) => watch::watch(watch_args),
+            Command::Serve(serve_args) => serve::serve(serve_args),
+            Command::Mail(mail_args) => mail::mail(mail_args),
+            Command::Library(library_args) => library::library(library_args),
+            Command::Journal(JournalCommand::Export(export_args)) => replay::export(export_args),
+            Command::Journal(JournalCommand::Import(import_args)) => replay::import(import_args),
+            Command::Tuning(TuningCommand::Export(export_args)) => tuning::export(export_args),
+            Command::Tuning(TuningCommand::Import(import_args)) => tuning::import(import_args),
+            Command::Selftest(selftest_args) => selftest::selftest(selftest_args),
+            Command::Migrate(migrate_args) => migrate::migrate(migrate_args),
+            Command::DiffConfig(diff_args) => diff_config::diff_config(diff_args),
+            Command::Verify(verify_args) => verify::verify(verify_args),
+            Command::Replay(replay_args) => snapshot::replay(replay_args),
+            #[cfg(feature = "grpc")]
+            Command::Grpc(grpc_args) => grpc::serve(grpc_args),
+        }
+        return;
+    }
+
+    #[cfg(feature = "s3")]
+    {
+        let locations = s3::take_locations(&mut args.run.paths);
+        if !locations.is_empty() {
+            s3::process(&args, &locations);
+            if args.paths.is_empty() {
+                return;
+            }
+        }
+    }
+
+    run(&args);
+}
+
+/// Rename the documents given by `args` once.
+fn run(args: &RunArgs) {
+    let Some(_locks) = lock_directories(args) else {
+        return;
+    };
+    let journal = Journal::user();
+    let Some(paths) = documents_to_process(args, journal.as_ref()) else {
+        return;
+    };
+    let mut counters = Counters::new(args.counter, args.counter_start);
+    let mut report = Report::default();
+
+    process(
+        args,
+        &paths,
+        journal.as_ref(),
+        None,
+        &mut counters,
+        &mut report,
+        &AtomicBool::new(false),
+    );
+
+    if args.verbose {
+        report.summarize();
+    }
+}
+
+/// Ask for the password to decrypt documents with if `--ask-password` was given. Returns whether
+/// processing can go on.
+fn ask_password(args: &mut Args) -> bool {
+    let run = args.run_mut();
+    if !run.ask_password {
+        return true;
+    }
+
+    match review::ask_password() {
+        Ok(password) => {
+            run.password = Some(password);
+            true
+        }
+        Err(err) => {
+            log::error!("{}", tr!("error-ask-password", error = err.to_string()));
+            false
+        }
+    }
+}
+
+/// Rename the documents at `paths` and journal the renames. Once `stop` is set, the document being
+/// renamed is finished and the rest are left alone.
+///
+/// Returns the renames that were made.
+fn process(
+    args: &RunArgs,
+    paths: &[PathBuf],
+    journal: Option<&Journal>,
+    audit_log: Option<&AuditLog>,
+    counters: &mut Counters,
+    report: &mut Report,
+    stop: &AtomicBool,
+) -> Vec<journal::Rename> {
+    let (mut processed, failures) = rename_confident(args, paths, counters, report, stop);
+
+    if stop.load(Ordering::Relaxed) {
+        for failure in &failures {
+            leave_uncertain(
+                args,
+                uncertain(failure),
+                failure.elapsed,
+                &mut processed,
+                report,
+            );
+        }
+    } else if !failures.is_empty() {
+        review_failures(args, failures, counters, &mut processed, report);
+    }
+
+    processed.finish(args, paths, journal, audit_log)
+}
+
+/// Rename the documents at `paths` whose title is certain enough and return the others. Once
+/// `stop` is set, the document being renamed is finished and the rest are left alone.
+fn rename_confident(
+    args: &RunArgs,
+    paths: &[PathBuf],
+    counters: &mut Counters,
+    report: &mut Report,
+    stop: &AtomicBool,
+) -> (Processed, Vec<Failure>) {
+    let mut processed = Processed::new();
+    let emails = mail::Emails::expand(args, paths);
+    let (mut documents, failures) = extract_documents(args, &emails.paths, &emails.metadata);
+
+    name_companions(&mut documents);
+    rename_documents(args, &documents, counters, &mut processed, report, stop);
+
+    (processed, failures)
+}
+
+/// What happened to the documents of a call to [`process`].
+struct Processed {
+    run: journal::Run,
+    entries: Vec<bibliography::Entry>,
+    decisions: Vec<audit::Decision>,
+}
+
+impl Processed {
+    fn new() -> Self {
+        Self {
+            run: journal::Run::start(),
+            entries: Vec::new(),
+            decisions: Vec::new(),
+        }
+    }
+
+    /// Journal and log what happened to the documents at `paths`, and write or send their
+    /// bibliographic data where asked for.
+    ///
+    /// Returns the renames that were made.
+    fn finish(
+        self,
+        args: &RunArgs,
+        paths: &[PathBuf],
+        journal: Option<&Journal>,
+        audit_log: Option<&AuditLog>,
+    ) -> Vec<journal::Rename> {
+        let Self {
+            run,
+            entries,
+            mut decisions,
+        } = self;
+        let renames = run.renames.clone();
+        if let Some(journal) = journal.filter(|_| !args.dry_run) {
+            if let Err(err) = journal.append(run) {
+                log::warn!("{}", tr!("warn-write-journal", error = err.to_string()));
+            }
+        }
+        if let Some(audit_log) = audit_log.filter(|_| !args.dry_run) {
+            // Documents without a decision were skipped before a title was found.
+            for path in paths {
+                let decided = decisions.iter().any(|decision| {
+                    std::path::absolute(path).is_ok_and(|absolute| decision.path == absolute)
+                });
+                if !decided {
+                    decisions.push(audit::Decision::new(path, audit::Action::Skipped));
+                }
+            }
+            if let Err(err) = audit_log.append(&decisions) {
+                log::warn!("{}", tr!("warn-write-audit-log", error = err.to_string()));
+            }
+        }
+
+        if let Some(path) = args.bibliography.as_ref().filter(|_| !args.dry_run) {
+            write_bibliography(args, path, &entries);
+        }
+
+        send_to_paperless(args, &entries);
+
+        renames
+    }
+
+    /// Record that `document` was renamed to `renamed`, or could not be renamed if it is `None`.
+    fn record(&mut self, document: &Titled, renamed: Option<PathBuf>) {
+        let rename = renamed
+            .as_ref()
+            .filter(|renamed| **renamed != document.path)
+            .map(|renamed| journal::Rename::new(&document.path, renamed));
+        let action = match (&renamed, &rename) {
+            (_, Some(_)) => audit::Action::Renamed,
+            (Some(_), None) => audit::Action::Unchanged,
+            (None, _) => audit::Action::Skipped,
+        };
+        self.decisions.push(audit::Decision {
+            renamed: rename.as_ref().map(|rename| rename.to.clone()),
+            title: Some(document.title.clone()),
+            source: Some(document.source),
+            confidence: (!document.candidates.is_empty())
+                .then(|| pdf::confidence(&document.candidates)),
+            ..audit::Decision::new(&document.path, action)
+        });
+
+        let Some(renamed) = renamed else {
+            return;
+        };
+        self.run.renames.extend(rename);
+        self.entries.push(bibliography::Entry {
+            path: renamed,
+            title: document.title.clone(),
+            metadata: document.metadata.clone(),
+        });
+    }
+}
+
+/// The decision to leave a document alone because its title was too uncertain.
+fn uncertain(failure: &Failure) -> audit::Decision {
+    audit::Decision {
+        confidence: Some(pdf::confidence(&failure.candidates)),
+        ..audit::Decision::new(&failure.path, audit::Action::Uncertain)
+    }
+}
+
+/// Record the `decision` to leave a document alone because its title was too uncertain and report
+/// that extracting its candidates took `elapsed`.
+fn leave_uncertain(
+    args: &RunArgs,
+    decision: audit::Decision,
+    elapsed: Duration,
+    processed: &mut Processed,
+    report: &mut Report,
+) {
+    if args.verbose {
+        report.record_uncertain(&decision.path, elapsed);
+    }
+    processed.decisions.push(decision);
+}
+
+/// Report that processing the document at `path` ended in a `panic`, with its backtrace in the
+/// JSON output if it was asked for.
+fn panicked(args: &RunArgs, path: &Path, panic: &panics::Panic) {
+    log::error!(
+        "{}",
+        tr!(
+            "error-panic",
+            path = path.display().to_string(),
+            error = panic.message.as_str()
+        )
+    );
+    log::debug!("{}", panic.backtrace);
+
+    if args.json() {
+        report::Panicked {
+            schema_version: report::SCHEMA_VERSION,
+            path,
+            error: &panic.message,
+            location: panic.location.as_deref(),
+            backtrace: &panic.backtrace,
+        }
+        .print();
+    }
+}
+
+/// Extract the titles of the documents at `paths`, separating the ones whose title is uncertain.
+///
+/// `fallback` is the metadata to use for documents that lack it in themselves, like the subject of
+/// the email a document was attached to.
+fn extract_documents(
+    args: &RunArgs,
+    paths: &[PathBuf],
+    fallback: &HashMap<PathBuf, Metadata>,
+) -> (Vec<Titled>, Vec<Failure>) {
+    let sources = Sources::load(args);
+    let prefetcher =
+        (args.prefetch > 0).then(|| Mutex::new(Prefetcher::spawn(paths.to_vec(), args.prefetch)));
+
+    let extractions = extract_parallel(paths, args.jobs, args.progress, |path| {
+        if let Some(Ok(prefetcher)) = prefetcher.as_ref().map(Mutex::lock) {
+            prefetcher.next();
+        }
+        let start = Instant::now();
+        panics::catch(|| extract(args, &sources, path, fallback.get(path)))
+            .unwrap_or_else(|panic| {
+                panicked(args, path, &panic);
+                None
+            })
+            .map(|extraction| (extraction, start.elapsed()))
+    });
+
+    let mut documents = Vec::new();
+    let mut failures = Vec::new();
+    for (path, extraction) in paths.iter().zip(extractions) {
+        match extraction {
+            Some((
+                Extraction::Title {
+                    title,
+                    title_alt,
+                    original,
+                    candidates,
+                    metadata,
+                    source,
+                    decoding,
+                    skipped,
+                },
+                elapsed,
+            )) => documents.push(Titled {
+                path: path.clone(),
+                title,
+                title_alt,
+                original,
+                candidates,
+                metadata,
+                source,
+                decoding,
+                skipped,
+                elapsed,
+            }),
+            Some((Extraction::Uncertain(candidates, metadata), elapsed)) => {
+                failures.push(Failure {
+                    path: path.clone(),
+                    candidates,
+                    metadata,
+                    elapsed,
+                });
+            }
+            None => {}
+        }
+    }
+
+    (documents, failures)
+}
+
+/// Call `extract` for each of `paths` on `jobs` threads, showing a progress bar if `progress` is
+/// given. The results are in the order of `paths`.
+fn extract_parallel<T: Send>(
+    paths: &[PathBuf],
+    jobs: NonZeroUsize,
+    progress: bool,
+    extract: impl Fn(&Path) -> T + Sync,
+) -> Vec<T> {
+    let bar = progress.then(|| progress::documents(paths.len()));
+    let mut results: Vec<_> = paths.iter().map(|_| None).collect();
+    Extractor::new().jobs(jobs).for_each(
+        paths,
+        |path| extract(path),
+        |index, result| {
+            results[index] = Some(result);
+            if let Some(bar) = &bar {
+                bar.inc(1);
+            }
+        },
+    );
+    if let Some(bar) = bar {
+        bar.finish_and_clear();
+    }
+
+    results.into_iter().flatten().collect()
+}
+
+/// Lock the directories of the documents if `--single-instance` is given. Returns `None` if the
+/// run should not go on.
+fn lock_directories(args: &RunArgs) -> Option<lock::Locks> {
+    if !args.single_instance {
+        return Some(lock::Locks::default());
+    }
+
+    let directories = args.paths.iter().map(|path| lock::target_directory(path));
+    match lock::Locks::acquire(directories) {
+        Ok(lock::Acquired::Locked(locks)) => Some(locks),
+        Ok(lock::Acquired::Busy(directory)) => {
+            log::info!(
+                "another run is in progress in {}, exiting",
+                directory.display()
+            );
+            None
+        }
+        Err(err) => {
+            log::error!("{}", tr!("error-lock", error = err.to_string()));
+            None
+        }
+    }
+}
+
+/// The documents at the paths given by the user, leaving out the ones that were not added or
+/// modified since `--changed-since`. Returns `None` if the time could not be determined.
+fn documents_to_process(args: &RunArgs, journal: Option<&Journal>) -> Option<Vec<PathBuf>> {
+    let documents = args.paths.iter().flat_map(|path| files::documents(path));
+    let Some(since) = &args.changed_since else {
+        return Some(documents.collect());
+    };
+
+    let time = match (since, journal) {
+        (journal::Since::Time(time), _) => *time,
+        (_, Some(journal)) => match journal.resolve(since) {
+            Ok(time) => time,
+            Err(err) => {
+                log::error!("{}", tr!("error-changed-since", error = err.to_string()));
+                return None;
+            }
+        },
+        (_, None) => {
+            log::error!("{}", tr!("error-no-journal"));
+            return None;
+        }
+    };
+    log::info!(
+        "only processing documents changed since {}",
+        humantime::format_rfc3339_seconds(time)
+    );
+
+    Some(
+        documents
+            .filter(|path| files::changed_since(path, time))
+            .collect(),
+    )
+}
+
+fn write_bibliography(args: &RunArgs, path: &Path, entries: &[bibliography::Entry]) {
+    let Some(format) = args
+        .bibliography_format
+        .or_else(|| bibliography::Format::from_path(path))
+    else {
+        log::error!(
+            "{}",
+            tr!(
+                "error-bibliography-format",
+                path = path.display().to_string()
+            )
+        );
+        return;
+    };
+
+    if let Err(err) = bibliography::write(path, format, entries) {
+        log::error!(
+            "{}",
+            tr!(
+                "error-write-bibliography",
+                path = path.display().to_string(),
+                error = err.to_string()
+            )
+        );
+    }
+}
+
+/// Print the fields of the renamed documents for paperless-ngx and upload them, if requested.
+fn send_to_paperless(args: &RunArgs, entries: &[bibliography::Entry]) {
+    let client = args
+        .paperless_url
+        .as_deref()
+        .zip(args.paperless_token.clone())
+        .map(|(url, token)| paperless::Client::new(url, token));
+
+    for entry in entries {
+        let document = paperless::Document::new(entry, &args.paperless_tags);
+        if args.paperless {
+            document.print();
+        }
+        if let Some(client) = client.as_ref().filter(|_| !args.dry_run) {
+            if let Err(err) = client.upload(&document) {
+                log::error!(
+                    "{}",
+                    tr!(
+                        "error-paperless-upload",
+                        path = entry.path.display().to_string(),
+                        error = err.to_string()
+                    )
+                );
+            }
+        }
+    }
+}
+
+/// Rename the `documents` after their titles.
+fn rename_documents(
+    args: &RunArgs,
+    documents: &[Titled],
+    counters: &mut Counters,
+    processed: &mut Processed,
+    report: &mut Report,
+    stop: &AtomicBool,
+) {
+    for document in documents {
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        let start = Instant::now();
+        processed.record(document, rename(args, counters, document));
+
+        if args.verbose {
+            report.record(
+                &document.path,
+                document.elapsed + start.elapsed(),
+                document.source,
+                document.decoding.as_ref(),
+            );
+        }
+    }
+}
+
+/// Ask the user for the titles of the `failures` and rename them.
+fn review_failures(
+    args: &RunArgs,
+    failures: Vec<Failure>,
+    counters: &mut Counters,
+    processed: &mut Processed,
+    report: &mut Report,
+) {
+    let mut unreviewed: Vec<_> = failures
+        .iter()
+        .map(|failure| (uncertain(failure), failure.elapsed))
+        .collect();
+    let titles = match review::review(failures, args.candidate_count) {
+        Ok(titles) => titles,
+        Err(err) => {
+            log::error!("{}", tr!("error-review", error = err.to_string()));
+            Vec::new()
+        }
+    };
+    unreviewed.retain(|(decision, _)| {
+        !titles
+            .iter()
+            .any(|(failure, _)| failure.path == decision.path)
+    });
+    for (decision, elapsed) in unreviewed {
+        leave_uncertain(args, decision, elapsed, processed, report);
+    }
+
+    for (failure, title) in titles {
+        rename_reviewed(args, failure, title, counters, processed, report);
+    }
+}
+
+/// Rename the document of `failure` to the `title` the user chose for it.
+///
+/// Returns where the document was renamed to.
+fn rename_reviewed(
+    args: &RunArgs,
+    failure: Failure,
+    title: String,
+    counters: &mut Counters,
+    processed: &mut Processed,
+    report: &mut Report,
+) -> Option<PathBuf> {
+    let start = Instant::now();
+    if args.learn {
+        if let Some(corrections) = corrections() {
+            match corrections.record(&failure.metadata, &failure.candidates, &title) {
+                Ok(true) => log::info!("learned the title of {}", failure.path.display()),
+                Ok(false) => {}
+                Err(err) => log::warn!(
+                    "{}",
+                    tr!(
+                        "warn-write-correction",
+                        path = failure.path.display().to_string(),
+                        error = err.to_string()
+                    )
+                ),
+            }
+        }
+    }
+    let document = Titled::reviewed(failure, title);
+    let renamed = rename(args, counters, &document);
+    processed.record(&document, renamed.clone());
+
+    if args.verbose {
+        report.record(
+            &document.path,
+            document.elapsed + start.elapsed(),
+            Provenance::User,
+            None,
+        );
+    }
+
+    renamed
+}
+
+/// Complete the metadata of the document at `path` with the metadata looked up online, if asked
+/// for, and the `fallback` metadata, put what its name says first and merge it by the trust
+/// policy.
+fn merge_metadata(
+    args: &RunArgs,
+    trust_policy: &TrustPolicy,
+    path: &Path,
+    mut metadata: Metadata,
+    fallback: Option<&Metadata>,
+) -> Metadata {
+    #[cfg(feature = "lookup")]
+    if args.lookup {
+        lookup::complete(&mut metadata);
+    }
+    if let Some(fallback) = fallback {
+        metadata.append(fallback);
+    }
+    if let Some(mut named) = parse_name(args, path).map(|parsed| parsed.metadata()) {
+        named.append(&metadata);
+        metadata = named;
+    }
+
+    trust_policy.merge(metadata)
+}
+
+/// The fields read from the name of the document at `path` with `--name-template`, if its name
+/// matches the template.
+fn parse_name(args: &RunArgs, path: &Path) -> Option<template::Parsed> {
+    let template = args.name_template.as_ref()?;
+    let name = args.separator.restore(&path.file_stem()?.to_string_lossy());
+    let Some(parsed) = template.parse(&name) else {
+        log::info!("the name of {} does not match the template", path.display());
+        return None;
+    };
+    log::info!(
+        "read {:?} from the name of {}",
+        parsed.fields,
+        path.display()
+    );
+
+    Some(parsed)
+}
+
+/// The title and metadata of the document at `path` read from its name with `--name-template`, if
+/// the name has every field the template uses, so the document does not need to be parsed.
+fn named_title(args: &RunArgs, path: &Path) -> Option<Extraction> {
+    // The type of a document is only known once it is parsed.
+    if !args.only_types.is_empty() {
+        return None;
+    }
+    let parsed = parse_name(args, path)?;
+    let title = parsed.get(template::Field::Title)?;
+    let title_alt = parsed.get(template::Field::TitleAlt);
+    let metadata = parsed.metadata();
+    let values = template::Values {
+        title,
+        title_alt,
+        authors: &bibliography::family_names(&metadata),
+        counter: Some(0),
+        confidence: None,
+        metadata: &metadata,
+    };
+    if let Some(missing) = args
+        .template()
+        .fields()
+        .into_iter()
+        .find(|field| values.get(*field).is_none())
+    {
+        log::info!("{{{missing}}} is not in the name of {}", path.display());
+        return None;
+    }
+
+    Some(Extraction::Title {
+        title: title.to_string(),
+        title_alt: title_alt.map(ToString::to_string),
+        original: title.to_string(),
+        candidates: Vec::new(),
+        metadata,
+        source: Provenance::FileName,
+        decoding: None,
+        skipped: Vec::new(),
+    })
+}
+
+/// Whether to ask the user for the title instead of using one of the `candidates`, either because
+/// every title is asked for or because the confidence in the candidates is too low.
+fn needs_review(args: &RunArgs, candidates: &[Candidate]) -> bool {
+    if args.interactive {
+        return true;
+    }
+    if !args.review_failures || args.use_candidate.is_some() {
+        return false;
+    }
+    let confidence = pdf::confidence(candidates);
+    log::info!("confidence: {confidence}");
+
+    confidence < args.min_confidence
+}
+
+/// What is loaded once to extract the titles of all documents of a run.
+struct Sources {
+    overrides: Overrides,
+    trust_policy: TrustPolicy,
+    normalize_options: normalize::Options,
+    cache: Option<Cache>,
+    /// The titles learned from earlier runs with `--learn`.
+    learned: Option<Learned>,
+    snapshots: Option<Snapshots>,
+}
+
+impl Sources {
+    fn load(args: &RunArgs) -> Self {
+        let overrides = args
+            .overrides
+            .as_ref()
+            .map(Overrides::load)
+            .transpose()
+            .unwrap_or_else(|err| exit_with_error(&tr!("error-load-overrides"), &err))
+            .unwrap_or_default();
+        let trust_policy = args
+            .trust_policy
+            .as_ref()
+            .map(trust::load)
+            .transpose()
+            .unwrap_or_else(|err| exit_with_error(&tr!("error-load-trust-policy"), &err))
+            .unwrap_or_default();
+        let normalize_options = args
+            .normalize_options()
+            .unwrap_or_else(|err| exit_with_error(&tr!("error-load-acronyms"), &err));
+        let cache = if args.cache {
+            Cache::user().or_else(|| {
+                log::warn!("{}", tr!("warn-no-cache-directory"));
+                None
+            })
+        } else {
+            None
+        };
+        let learned = if args.learn {
+            corrections()
+                .map(|corrections| corrections.load())
+                .transpose()
+                .unwrap_or_else(|err| {
+                    log::warn!("{}", tr!("warn-read-corrections", error = err.to_string()));
+                    None
+                })
+        } else {
+            None
+        };
+
+        let snapshots = args
+            .snapshot
+            .as_deref()
+            .map(Snapshots::create)
+            .transpose()
+            .unwrap_or_else(|err| exit_with_error(&tr!("error-create-snapshot"), &err));
+
+        Self {
+            overrides,
+            trust_policy,
+            normalize_options,
+            cache,
+            learned,
+            snapshots,
+        }
+    }
+}
+
+/// Log that a file given by the user could not be loaded and exit, as the run depends on it.
+fn exit_with_error(message: &str, err: &impl Display) -> ! {
+    log::error!("{message}: {err}");
+    std::process::exit(1);
+}
+
+/// The corrections in the user's state directory, warning if there is none.
+fn corrections() -> Option<Corrections> {
+    Corrections::user().or_else(|| {
+        log::warn!("{}", tr!("warn-no-corrections-directory"));
+        None
+    })
+}
+
+fn extract(
+    args: &RunArgs,
+    sources: &Sources,
+    path: &Path,
+    fallback: Option<&Metadata>,
+) -> Option<Extraction> {
+    let normalize_options = &sources.normalize_options;
+    if let Some(extraction) = override_title(sources, path) {
+        return Some(extraction);
+    }
+    // Names read with `--name-template` often match the template as well.
+    if let Some(extraction) = named_title(args, path) {
+        return Some(extraction);
+    }
+    if !args.rename_templated && is_templated(args, path) {
+        skip(path, &tr!("already-templated"));
+        return None;
+    }
+
+    if let ControlFlow::Break(extraction) = sniff_pdf(args, path) {
+        return extraction;
+    }
+
+    let extracted = parse(args, sources.cache.as_ref(), path)?;
+    if let Some(snapshots) = &sources.snapshots {
+        if let Err(err) = snapshots.add(path, &args.parse_options(), &extracted) {
+            log::warn!(
+                "{}",
+                tr!(
+                    "warn-write-snapshot",
+                    path = path.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+        }
+    }
+    let Extracted {
+        mut candidates,
+        metadata,
+        decoding,
+    } = extracted;
+    if !has_selected_type(args, path, metadata.class) {
+        return None;
+    }
+    if let Some(learned) = &sources.learned {
+        learned.apply(&metadata, &mut candidates);
+    }
+    bilingual::retain_languages(&mut candidates, &args.language);
+    let metadata = merge_metadata(args, &sources.trust_policy, path, metadata, fallback);
+    let originals: Vec<_> = candidates
+        .iter()
+        .map(|candidate| (candidate.text.clone(), candidate.parallel.clone()))
+        .collect();
+    for candidate in &mut candidates {
+        candidate.text = normalize::normalize(&candidate.text, normalize_options);
+        candidate.parallel = candidate
+            .parallel
+            .as_ref()
+            .map(|parallel| normalize::normalize(parallel, normalize_options));
+    }
+
+    for (index, candidate) in candidates.iter().enumerate() {
+        log::info!("candidate {}: {candidate}", index + 1);
+    }
+
+    // A title from a source the trust policy prefers over the layout, unless a specific
+    // candidate was asked for.
+    if let Some(title) = metadata
+        .title
+        .first()
+        .filter(|title| title.provenance != Provenance::Layout && args.use_candidate.is_none())
+    {
+        log::info!("using title from {}: {:?}", title.provenance, title.value);
+        return Some(Extraction::Title {
+            title: normalize::normalize(&title.value, normalize_options),
+            title_alt: None,
+            original: title.value.clone(),
+            candidates,
+            source: title.provenance,
+            metadata,
+            decoding,
+            skipped: Vec::new(),
+        });
+    }
+
+    if needs_review(args, &candidates) {
+        return Some(Extraction::Uncertain(candidates, metadata));
+    }
+
+    let (index, skipped) = choose_candidate(args, &candidates);
+    let Some(candidate) = candidates.get(index) else {
+        log::error!(
+            "{}",
+            tr!("error-no-candidate", path = path.display().to_string())
+        );
+        return None;
+    };
+
+    let (title, title_alt, original) = layout_title(args, candidate, &originals[index]);
+
+    Some(Extraction::Title {
+        title,
+        title_alt,
+        original,
+        candidates,
+        metadata,
+        source: Provenance::Layout,
+        decoding,
+        skipped,
+    })
+}
+
+/// The index of the candidate to take the title from, with the candidates passed over before it.
+///
+/// With `--fallback-titles`, candidates that are not plausible as a title are passed over, unless
+/// none of them is.
+fn choose_candidate(args: &RunArgs, candidates: &[Candidate]) -> (usize, Vec<Skipped>) {
+    if !args.fallback_titles {
+        return (args.candidate_index(), Vec::new());
+    }
+
+    let mut skipped = Vec::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        match candidate.implausible() {
+            Some(reason) => {
+                log::info!("passing over candidate {}, it is {reason}", index + 1);
+                skipped.push(Skipped {
+                    candidate: index + 1,
+                    reason,
+                });
+            }
+            None => return (index, skipped),
+        }
+    }
+
+    (0, Vec::new())
+}
+
+/// Continue if the document at `path` is a PDF document, or break with what is extracted from
+/// other documents.
+fn sniff_pdf(args: &RunArgs, path: &Path) -> ControlFlow<Option<Extraction>> {
+    match sniff::sniff(path) {
+        Ok(sniff::Kind::Pdf) => return ControlFlow::Continue(()),
+        Ok(sniff::Kind::Html) if has_selected_type(args, path, None) => {
+            return ControlFlow::Break(html_title(args, path));
+        }
+        Ok(sniff::Kind::Html) => {}
+        Ok(sniff::Kind::Empty) => skip(path, &tr!("file-empty")),
+        Ok(sniff::Kind::Unknown) => skip(path, &tr!("not-a-pdf")),
+        Err(err) => {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-read",
+                    path = path.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+        }
+    }
+
+    ControlFlow::Break(None)
+}
+
+/// Whether the document at `path`, recognized as `class`, is of one of the types given with
+/// `--only-types`, or any document if none are given. Skips the document otherwise.
+fn has_selected_type(args: &RunArgs, path: &Path, class: Option<pdf::DocumentClass>) -> bool {
+    if args.only_types.is_empty() || class.is_some_and(|class| args.only_types.contains(&class)) {
+        return true;
+    }
+
+    let types = args
+        .only_types
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let reason = class.map_or_else(
+        || tr!("type-not-recognized", types = types.as_str()),
+        |class| {
+            tr!(
+                "type-not-selected",
+                class = class.to_string(),
+                types = types.as_str()
+            )
+        },
+    );
+    skip(path, &reason);
+
+    false
+}
+
+/// Whether the name of the document at `path` already matches the template with more than a
+/// title, see [`template::Parsed::is_distinctive`].
+fn is_templated(args: &RunArgs, path: &Path) -> bool {
+    let Some(name) = path.file_stem() else {
+        return false;
+    };
+    let name = args.separator.restore(&name.to_string_lossy());
+
+    args.template()
+        .parse(&name)
+        .is_some_and(|parsed| parsed.is_distinctive())
+}
+
+/// The title the overrides give the document at `path`, if they give it one.
+fn override_title(sources: &Sources, path: &Path) -> Option<Extraction> {
+    match sources.overrides.get(path) {
+        Ok(Some(title)) => {
+            log::info!("using title override for {}", path.display());
+            Some(Extraction::Title {
+                title: title.to_string(),
+                title_alt: None,
+                original: title.to_string(),
+                candidates: Vec::new(),
+                metadata: Metadata::default(),
+                source: Provenance::Override,
+                decoding: None,
+                skipped: Vec::new(),
+            })
+        }
+        Ok(None) => None,
+        Err(err) => {
+            log::warn!(
+                "{}",
+                tr!(
+                    "warn-lookup-overrides",
+                    path = path.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+            None
+        }
+    }
+}
+
+/// The title of the document from the layout `candidate`, the title in another language if it
+/// has one and the `original` text of the chosen title with its parallel title, before they were
+/// normalized.
+fn layout_title(
+    args: &RunArgs,
+    candidate: &Candidate,
+    (original, original_parallel): &(String, Option<String>),
+) -> (String, Option<String>, String) {
+    Bilingual::detect(candidate).map_or_else(
+        || (candidate.text.clone(), None, original.clone()),
+        |bilingual| {
+            let (title, title_alt) = bilingual.select(args.title_lang);
+            log::info!("bilingual title {title:?} / {title_alt:?}");
+            let original = if title == candidate.text {
+                original
+            } else {
+                original_parallel.as_ref().unwrap_or(original)
+            };
+            (title, Some(title_alt), original.clone())
+        },
+    )
+}
+
+/// Parse the title candidates and metadata of the document at `path`, or get them from the cache.
+fn parse(args: &RunArgs, cache: Option<&Cache>, path: &Path) -> Option<Extracted> {
+    let options = args.parse_options();
+    if args.sandbox && fs::metadata(path).is_ok_and(|file| file.len() > sandbox::MAX_DOCUMENT_SIZE)
+    {
+        skip(path, &tr!("sandbox-too-large"));
+        return None;
+    }
+    let Some(cache) = cache else {
+        return sandbox::confine(args.sandbox, || {
+            pdf::parse_pdf(path, &options, &mut parse_callbacks(path))
+        })
+        .inspect_err(|err| {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-document",
+                    path = path.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+        })
+        .ok()
+        .map(|parsed| Extracted {
+            decoding: Some(Decoding::from(&parsed.statistics)),
+            candidates: parsed.candidates,
+            metadata: parsed.metadata,
+        });
+    };
+
+    let data = fs::read(path)
+        .inspect_err(|err| {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-read",
+                    path = path.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+        })
+        .ok()?;
+    let hash = overrides::hash(&data);
+    match cache.get(&hash, &options) {
+        Ok(Some(cached)) => {
+            log::info!("using cached candidates for {}", path.display());
+            return Some(cached);
+        }
+        Ok(None) => {}
+        Err(err) => log::warn!(
+            "{}",
+            tr!(
+                "warn-read-cache",
+                path = path.display().to_string(),
+                error = err.to_string()
+            )
+        ),
+    }
+
+    let parsed = sandbox::confine(args.sandbox, || {
+        pdf::parse_pdf_bytes(&data, &options, &mut parse_callbacks(path))
+    })
+    .inspect_err(|err| {
+        log::error!(
+            "{}",
+            tr!(
+                "error-document",
+                path = path.display().to_string(),
+                error = err.to_string()
+            )
+        );
+    })
+    .ok()?;
+    let decoding = Decoding::from(&parsed.statistics);
+    if parsed.statistics.over_budget {
+        log::debug!(
+            "not caching {}, whose candidates depend on how fast it was parsed",
+            path.display()
+        );
+    } else if let Err(err) = cache.insert(
+        &hash,
+        &options,
+        &parsed.candidates,
+        &parsed.metadata,
+        &decoding,
+    ) {
+        log::warn!(
+            "{}",
+            tr!(
+                "warn-write-cache",
+                path = path.display().to_string(),
+                error = err.to_string()
+            )
+        );
+    }
+
+    Some(Extracted {
+        candidates: parsed.candidates,
+        metadata: parsed.metadata,
+        decoding: Some(decoding),
+    })
+}
+
+/// The callbacks to parse the document at `path` with, which print its warnings.
+fn parse_callbacks(path: &Path) -> pdf::Callbacks<'_> {
+    pdf::Callbacks::default().on_warning(|warning| {
+        if let pdf::Warning::NoText { page } = warning {
+            log::warn!(
+                "{}",
+                tr!(
+                    "warn-no-text",
+                    path = path.display().to_string(),
+                    page = page + 1
+                )
+            );
+        }
+    })
+}
+
+fn html_title(args: &RunArgs, path: &Path) -> Option<Extraction> {
+    if !args.html_titles {
+        skip(path, &tr!("html-page"));
+        return None;
+    }
+
+    match sniff::html_title(path) {
+        Ok(Some(title)) => Some(Extraction::Title {
+            original: title.clone(),
+            title,
+            title_alt: None,
+            candidates: Vec::new(),
+            metadata: Metadata::default(),
+            source: Provenance::Html,
+            decoding: None,
+            skipped: Vec::new(),
+        }),
+        Ok(None) => {
+            skip(path, &tr!("html-page-without-title"));
+            None
+        }
+        Err(err) => {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-read",
+                    path = path.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+            None
+        }
+    }
+}
+
+/// Name companion documents like supplementary material after the main document they belong to.
+///
+/// The main document is looked for among the other documents in the same directory, both the ones
+/// that are being renamed and the ones that already exist.
+fn name_companions(documents: &mut [Titled]) {
+    for index in 0..documents.len() {
+        let document = &documents[index];
+        let Some(kind) = companion::kind(&document.title) else {
+            continue;
+        };
+        let siblings = companion::sibling_titles(&document.path);
+        let batch = documents
+            .iter()
+            .filter(|other| other.path.parent() == document.path.parent())
+            .map(|other| other.title.as_str());
+        let Some(main) = companion::find_main(
+            &document.title,
+            &document.candidates,
+            batch.chain(siblings.iter().map(String::as_str)),
+        ) else {
+            log::info!("no main document found for {}", document.path.display());
+            continue;
+        };
+
+        log::info!("{} belongs to {main:?}", document.path.display());
+        documents[index].title = format!("{main} - {}", kind.suffix());
+    }
+}
+
+/// Rename the `document` after its title and return where it is now.
+fn rename(args: &RunArgs, counters: &mut Counters, document: &Titled) -> Option<PathBuf> {
+    let Titled {
+        path,
+        title,
+        metadata,
+        source,
+        ..
+    } = document;
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    let counter = match args
+        .template()
+        .uses_counter()
+        .then(|| counters.get(directory))
+        .transpose()
+    {
+        Ok(counter) => counter,
+        Err(err) => {
+            counter_error(path, &err);
+            return None;
+        }
+    };
+    let name = file_name(args, document, counter);
+    let Some(target) = rename::target(path, &name, args.separator) else {
+        log::error!(
+            "{}",
+            tr!(
+                "error-invalid-file-name",
+                path = path.display().to_string(),
+                title = format!("{name:?}")
+            )
+        );
+        return None;
+    };
+
+    let renamed = match rename::rename(path, &target, &args.rename_options()) {
+        Ok(Outcome::Renamed(target)) => {
+            if !args.json() && !args.paperless {
+                println!(
+                    "{}",
+                    tr!(
+                        "renamed",
+                        from = path.display().to_string(),
+                        to = target.display().to_string()
+                    )
+                );
+            }
+            target
+        }
+        Ok(Outcome::Unchanged) => {
+            if !args.json() && !args.paperless {
+                println!("{}", tr!("unchanged", path = path.display().to_string()));
+            }
+            path.clone()
+        }
+        Ok(Outcome::Skipped(reason)) => {
+            skip(path, &reason);
+            return None;
+        }
+        Err(err) => {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-rename",
+                    path = path.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+            return None;
+        }
+    };
+
+    if args.show_changes && !args.json() && !args.paperless {
+        show_changes(&document.original, &target);
+    }
+
+    if counter.is_some() {
+        if let Err(err) = counters.advance(directory, args.dry_run) {
+            counter_error(&renamed, &err);
+        }
+    }
+
+    let record = Record {
+        schema_version: report::SCHEMA_VERSION,
+        path,
+        renamed: &renamed,
+        title: pdf::Field::new(title, *source),
+        candidates: &document.candidates,
+        metadata,
+        confidence: document.confidence(),
+        decoding: document.decoding.as_ref(),
+        skipped: &document.skipped,
+    };
+    send(args, &record);
+    write_sidecars(args, &record);
+
+    Some(renamed)
+}
+
+/// The name to give the `document`, according to the template and without extension.
+fn file_name(args: &RunArgs, document: &Titled, counter: Option<u64>) -> String {
+    let title = match args.sort_key {
+        Some(sort_key::Mode::Prefix) => sort_key::sort_key(&document.title, &args.sort_language),
+        _ => document.title.clone(),
+    };
+    let title_alt = document
+        .title_alt
+        .as_deref()
+        .map(|title_alt| args.colon.apply(title_alt));
+    let authors = bibliography::family_names(&document.metadata);
+
+    args.template().render(&template::Values {
+        title: &args.colon.apply(&title),
+        title_alt: title_alt.as_deref(),
+        authors: &authors,
+        counter,
+        confidence: document.confidence(),
+        metadata: &document.metadata,
+    })
+}
+
+/// Print which characters of the `original` title were changed to arrive at the name of `target`.
+fn show_changes(original: &str, target: &Path) {
+    let name = target
+        .file_stem()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    if let Some(changes) = changes::highlight(original, &name) {
+        println!("{}", tr!("changes", changes = changes));
+    }
+}
+
+/// Send the `record` to every output it was asked for.
+fn send(args: &RunArgs, record: &Record) {
+    for sink in args.sinks() {
+        if let Err(err) = sink.send(record) {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-output",
+                    path = record.path.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+        }
+    }
+}
+
+/// Write the files accompanying the document `renamed`, if requested.
+fn write_sidecars(args: &RunArgs, record: &Record) {
+    let Record {
+        renamed,
+        title: pdf::Field { value: title, .. },
+        metadata,
+        ..
+    } = *record;
+    if args.sort_key == Some(sort_key::Mode::Sidecar) && !args.dry_run {
+        let key = sort_key::sort_key(title, &args.sort_language);
+        if let Err(err) = sort_key::write_sidecar(renamed, &key) {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-write-sort-key",
+                    path = renamed.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+        }
+    }
+
+    if args.opf && !args.dry_run {
+        if let Err(err) = opf::write_sidecar(renamed, title, metadata) {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-write-opf",
+                    path = renamed.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+        }
+    }
+
+    if args.json_sidecar && !args.dry_run {
+        if let Err(err) = record.write_sidecar() {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-write-json-sidecar",
+                    path = renamed.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+        }
+    }
+}
+
+/// Report that the counter for the document at `path` could not be used.
+fn counter_error(path: &Path, err: &counter::Error) {
+    log::error!(
+        "{}",
+        tr!(
+            "error-counter",
+            path = path.display().to_string(),
+            error = err.to_string()
+        )
+    );
+}
+
+/// Warn that the document at `path` is skipped because of `reason`.
+fn skip(path: &Path, reason: &str) {
+    log::warn!(
+        "{}",
+        tr!(
+            "skipping",
+            path = path.display().to_string(),
+            reason = reason
+        )
+    );
+}
+
+fn split(args: &SplitArgs) {
+    let output = args.output.clone().unwrap_or_else(|| {
+        args.path
+            .parent()
+            .map_or_else(PathBuf::new, Path::to_path_buf)
+    });
+
+    match split::split(
+        &args.path,
+        &output,
+        args.level,
+        args.colon,
+        args.separator,
+        args.dry_run,
+    ) {
+        Ok(chapters) => {
+            for chapter in chapters {
+                println!("{}", chapter.display());
+            }
+        }
+        Err(err) => log::error!(
+            "{}",
+            tr!(
+                "error-split",
+                path = args.path.display().to_string(),
+                error = err.to_string()
+            )
+        ),
+    }
+}
+
+fn stats(args: &StatsArgs) {
+    let overrides = args
+        .overrides
+        .as_ref()
+        .map(Overrides::load)
+        .transpose()
+        .unwrap_or_else(|err| exit_with_error(&tr!("error-load-overrides"), &err))
+        .unwrap_or_default();
+    let options = pdf::Options {
+        page_count: args.page_count,
+        page: None,
+        default_leading: pdf::DEFAULT_LEADING,
+        low_memory: false,
+        ensemble: false,
+        password: None,
+        #[cfg(feature = "ocr")]
+        ocr: false,
+        #[cfg(feature = "ocr")]
+        rendering: pdf::Rendering::default(),
+        profile: None,
+        budgets: pdf::Budgets::default(),
+    };
+    let paths: Vec<_> = args
+        .paths
+        .iter()
+        .flat_map(|path| files::documents(path))
+        .collect();
+
+    print!(
+        "{}",
+        stats::Statistics::collect(&paths, &overrides, &options, args.ambiguity_threshold)
+    );
+}
+
+fn check_template(args: &TemplateCheckArgs) {
+    let template: Template = match args.template.parse() {
+        Ok(template) => template,
+        Err(err) => {
+            eprintln!("{}", tr!("error-template", error = err.to_string()));
+            std::process::exit(1);
+        }
+    };
+
+    let fields = template.fields();
+    if fields.is_empty() {
+        println!("{}", tr!("template-no-fields"));
+    } else {
+        let fields: Vec<_> = fields.iter().map(|field| format!("{{{field}}}")).collect();
+        println!("{}", tr!("template-fields", fields = fields.join(", ")));
+    }
+    for field in template.unguarded() {
+        println!(
+            "{}",
+            tr!("template-unguarded", field = format!("{{{field}}}"))
+        );
+    }
+
+    let Some(sample) = &args.sample else {
+        return;
+    };
+    let extractor = Extractor::new().page_count(args.page_count);
+    let extractor = match args.profile {
+        Some(profile) => extractor.profile(profile),
+        None => extractor,
+    };
+    let document = match extractor.extract_path(sample) {
+        Ok(document) => document,
+        Err(err) => {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-document",
+                    path = sample.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+            return;
+        }
+    };
+    let Some(candidate) = document.title() else {
+        log::error!(
+            "{}",
+            tr!("error-no-candidate", path = sample.display().to_string())
+        );
+        return;
+    };
 
-    let text  = pdf::parse_pdf("data/pdf/analysis_of_blood_flow_in_one.pdf", 2)
-        .expect("could not load document");
-    let text = sanitize_filename::sanitize(text);
+    let (title, title_alt) = Bilingual::detect(candidate).map_or_else(
+        || (candidate.text.clone(), None),
+        |bilingual| {
+            let (title, title_alt) = bilingual.select(None);
+            (title, Some(title_alt))
+        },
+    );
+    let authors = bibliography::family_names(&document.metadata);
+    let values = template::Values {
+        title: &title,
+        title_alt: title_alt.as_deref(),
+        authors: &authors,
+        counter: Some(1),
+        confidence: Some(document.confidence()),
+        metadata: &document.metadata,
+    };
+    for field in fields {
+        if values.get(field).is_none() {
+            println!(
+                "{}",
+                tr!(
+                    "template-missing",
+                    path = sample.display().to_string(),
+                    field = format!("{{{field}}}")
+                )
+            );
+        }
+    }
 
-    println!("{text}");
+    let name = template.render(&values);
+    let preview = rename::target(sample, &name, args.separator)
+        .and_then(|target| {
+            target
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+    println!("{}", tr!("template-preview", name = preview));
 }
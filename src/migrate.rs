@@ -0,0 +1,16 @@
+use crate::cli::MigrateArgs;
+
+/// Rename the documents named after the `--from` template after the `--to` template.
+///
+/// The fields are read from the current names like with `--name-template`, and only documents
+/// whose names lack a field of the new template are parsed.
+pub fn migrate(args: &MigrateArgs) {
+    let mut run = args.run.clone();
+    run.template = Some(args.to.clone());
+    run.name_template = Some(args.from.clone());
+    // Names after the old template often match the new one as well, e.g. `{author}` matches the
+    // year of `{year} - {author} - {title}`, so they are not skipped as already templated.
+    run.rename_templated = true;
+
+    crate::run(&run);
+}
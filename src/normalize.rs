@@ -0,0 +1,65 @@
+pub mod case;
+pub mod exponents;
+pub mod greek;
+pub mod numbering;
+pub mod symbols;
+pub mod typography;
+
+/// Options controlling how extracted titles are cleaned up before they are used as file names.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// The steps of cleaning up the typography of titles to leave out.
+    pub skip_typography: Vec<typography::Step>,
+    /// What to do with non-text symbols like math operators and emoji.
+    pub symbols: symbols::Policy,
+    /// Spell out Greek letters.
+    pub greek: bool,
+    /// Write powers of ten in E notation.
+    pub e_notation: bool,
+    /// Rewrite the numbering of parts in a series in this style.
+    pub numbering: Option<numbering::Style>,
+    /// Capitalize titles in this style.
+    pub case: Option<case::Style>,
+    /// Words whose capitalization is preserved when capitalizing titles.
+    pub dictionary: case::Dictionary,
+    /// Transliterate titles to ASCII.
+    pub ascii: bool,
+}
+
+/// Invisible characters that `ToUnicode` maps sometimes emit: soft hyphens, zero-width spaces and
+/// joiners, word joiners and byte order marks.
+const INVISIBLE: &[char] = &[
+    '\u{ad}', '\u{180e}', '\u{200b}', '\u{200c}', '\u{200d}', '\u{2060}', '\u{feff}',
+];
+
+/// Clean up an extracted `title` according to `options`.
+pub fn normalize(title: &str, options: &Options) -> String {
+    let mut title = symbols::normalize(
+        &typography::normalize(&strip_invisible(title), &options.skip_typography),
+        options.symbols,
+    );
+
+    if options.greek {
+        title = greek::transliterate(&title);
+    }
+    if options.e_notation {
+        title = exponents::e_notation(&title);
+    }
+    if let Some(style) = options.numbering {
+        title = numbering::normalize(&title, style);
+    }
+    if let Some(style) = options.case {
+        title = case::normalize(&title, style, &options.dictionary);
+    }
+    if options.ascii {
+        title = deunicode::deunicode(&title);
+    }
+
+    title
+}
+
+/// Remove invisible characters from `title`, which would otherwise lead to names that look the
+/// same as others but are not.
+pub fn strip_invisible(title: &str) -> String {
+    title.replace(INVISIBLE, "")
+}
@@ -0,0 +1,206 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use clap::ValueEnum;
+
+use super::numbering::from_roman;
+
+/// How the letters of titles are capitalized.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Style {
+    /// Capitalize every word except short function words (`The Art of Programming`).
+    Title,
+    /// Only capitalize the first word (`The art of programming`).
+    Sentence,
+    /// Don't capitalize anything (`the art of programming`).
+    Lower,
+}
+
+/// Short words that are not capitalized in title case unless they start or end the title.
+const SMALL_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "from", "in", "into", "nor", "of", "on",
+    "or", "over", "per", "the", "to", "via", "vs", "with",
+];
+
+/// Acronyms and proper nouns that are always preserved.
+const BUILTIN: &[&str] = &[
+    "ACM",
+    "AI",
+    "AIDS",
+    "API",
+    "CNN",
+    "COVID-19",
+    "CPU",
+    "CSS",
+    "CT",
+    "DNA",
+    "EU",
+    "GAN",
+    "GitHub",
+    "GPU",
+    "HIV",
+    "HTML",
+    "HTTP",
+    "IEEE",
+    "iOS",
+    "IoT",
+    "iPhone",
+    "JavaScript",
+    "JSON",
+    "LaTeX",
+    "LLM",
+    "LSTM",
+    "macOS",
+    "MIME",
+    "ML",
+    "MRI",
+    "mRNA",
+    "NASA",
+    "NLP",
+    "NoSQL",
+    "PDF",
+    "RNA",
+    "RNN",
+    "SARS-CoV-2",
+    "SQL",
+    "TeX",
+    "TypeScript",
+    "UAV",
+    "UK",
+    "URL",
+    "USA",
+    "USB",
+    "XML",
+];
+
+/// A dictionary of words whose capitalization must be preserved exactly.
+#[derive(Clone, Debug)]
+pub struct Dictionary(HashMap<String, String>);
+
+impl Default for Dictionary {
+    fn default() -> Self {
+        let mut dictionary = Self(HashMap::new());
+        dictionary.extend(BUILTIN.iter().copied());
+
+        dictionary
+    }
+}
+
+impl Dictionary {
+    /// Add words from a file containing one word per line to the dictionary.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file could not be read.
+    pub fn extend_from_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let content = fs::read_to_string(path)?;
+        self.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#')),
+        );
+
+        Ok(())
+    }
+
+    fn extend<'a>(&mut self, words: impl IntoIterator<Item = &'a str>) {
+        self.0.extend(
+            words
+                .into_iter()
+                .map(|word| (word.to_lowercase(), word.to_string())),
+        );
+    }
+
+    fn get(&self, word: &str) -> Option<&str> {
+        self.0.get(&word.to_lowercase()).map(String::as_str)
+    }
+}
+
+/// Capitalize `title` in the given `style`, preserving the words in `dictionary`.
+///
+/// Unless the whole title is written in capitals, words in capitals are assumed to be acronyms and
+/// preserved as well.
+pub fn normalize(title: &str, style: Style, dictionary: &Dictionary) -> String {
+    let preserve_capitals = title.chars().any(char::is_lowercase);
+    let words: Vec<&str> = title.split(' ').collect();
+    let first = words.iter().position(|word| !word.is_empty());
+    let last = words.iter().rposition(|word| !word.is_empty());
+
+    words
+        .iter()
+        .enumerate()
+        .map(|(index, word)| {
+            let start = word.find(char::is_alphanumeric);
+            let end = word.rfind(char::is_alphanumeric);
+            let (Some(start), Some(end)) = (start, end) else {
+                return (*word).to_string();
+            };
+            let end = end + word[end..].chars().next().map_or(1, char::len_utf8);
+            let (prefix, core, suffix) = (&word[..start], &word[start..end], &word[end..]);
+            // a word following a colon starts a subtitle
+            let starts_part = Some(index) == first
+                || index
+                    .checked_sub(1)
+                    .and_then(|previous| words.get(previous))
+                    .is_some_and(|previous| previous.ends_with(':'));
+            let capitalize = match style {
+                Style::Title => {
+                    starts_part
+                        || Some(index) == last
+                        || !SMALL_WORDS.contains(&core.to_lowercase().as_str())
+                }
+                Style::Sentence => starts_part,
+                Style::Lower => false,
+            };
+
+            format!(
+                "{prefix}{}{suffix}",
+                case_word(core, capitalize, preserve_capitals, dictionary)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn case_word(
+    word: &str,
+    capitalize: bool,
+    preserve_capitals: bool,
+    dictionary: &Dictionary,
+) -> String {
+    if let Some(preserved) = dictionary.get(word) {
+        return preserved.to_string();
+    }
+    // roman numerals like in `Part II`, restricted to small ones so words like `MIX` are cased
+    if word.len() > 1 && word.chars().all(|c| "IVX".contains(c)) && from_roman(word).is_some() {
+        return word.to_string();
+    }
+
+    word.split('-')
+        .map(|part| {
+            if preserve_capitals
+                && part.chars().filter(|c| c.is_alphabetic()).count() > 1
+                && !part.chars().any(char::is_lowercase)
+            {
+                return part.to_string();
+            }
+
+            dictionary.get(part).map_or_else(
+                || {
+                    let mut chars = part.chars();
+                    match chars.next() {
+                        Some(first) if capitalize => first
+                            .to_uppercase()
+                            .chain(chars.flat_map(char::to_lowercase))
+                            .collect(),
+                        _ => part.to_lowercase(),
+                    }
+                },
+                str::to_string,
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
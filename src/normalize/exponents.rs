@@ -0,0 +1,63 @@
+/// Write powers of ten in titles in E notation (`10^6` becomes `1e6` and `2.5 × 10^-3` becomes
+/// `2.5e-3`).
+pub fn e_notation(title: &str) -> String {
+    let mut normalized = String::with_capacity(title.len());
+    let mut rest = title;
+
+    while let Some(start) = rest.find("10^") {
+        let (before, after) = rest.split_at(start);
+        let after = &after[3..];
+        let sign = after
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '-' | '+' | '−'))
+            .map_or(0, char::len_utf8);
+        let digits = after[sign..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(after.len() - sign);
+        let is_number_start = !before.ends_with(|c: char| c.is_ascii_digit() || c == '.');
+        if digits == 0 || !is_number_start {
+            normalized.push_str(before);
+            normalized.push_str("10^");
+            rest = after;
+            continue;
+        }
+
+        let (before, mantissa) = split_mantissa(before);
+        normalized.push_str(before);
+        normalized.push_str(mantissa.unwrap_or("1"));
+        normalized.push('e');
+        if after.starts_with(['-', '−']) {
+            normalized.push('-');
+        }
+        normalized.push_str(&after[sign..sign + digits]);
+        rest = &after[sign + digits..];
+    }
+    normalized.push_str(rest);
+
+    normalized
+}
+
+/// Split a number multiplied with the power of ten (`2.5 × `) off the end of `text`.
+fn split_mantissa(text: &str) -> (&str, Option<&str>) {
+    let Some(product) = text
+        .trim_end()
+        .strip_suffix(['×', 'x', '·', '*'])
+        .map(str::trim_end)
+    else {
+        return (text, None);
+    };
+    let start = product.len()
+        - product
+            .chars()
+            .rev()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .count();
+    let mantissa = &product[start..];
+
+    if mantissa.starts_with(|c: char| c.is_ascii_digit()) {
+        (&product[..start], Some(mantissa))
+    } else {
+        (text, None)
+    }
+}
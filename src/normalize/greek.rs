@@ -0,0 +1,66 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// The names of the Greek letters, in alphabetical order.
+const LETTERS: [&str; 24] = [
+    "alpha", "beta", "gamma", "delta", "epsilon", "zeta", "eta", "theta", "iota", "kappa",
+    "lambda", "mu", "nu", "xi", "omicron", "pi", "rho", "sigma", "tau", "upsilon", "phi", "chi",
+    "psi", "omega",
+];
+
+/// The Greek letters in the order of [`LETTERS`], without final sigma.
+const LOWERCASE: &str = "αβγδεζηθικλμνξοπρστυφχψω";
+
+/// Spell out the Greek letters in `title` (`α-synuclein` becomes `alpha-synuclein`, `Ω` becomes
+/// `Omega`), including their variants used in math (`ϑ`, `𝜶`).
+///
+/// Consecutive letters are joined with hyphens (`αβ` becomes `alpha-beta`).
+pub fn transliterate(title: &str) -> String {
+    let mut transliterated = String::with_capacity(title.len());
+    let mut after_letter = false;
+
+    for c in title.chars() {
+        if let Some(name) = name(c) {
+            if after_letter {
+                transliterated.push('-');
+            }
+            transliterated.push_str(&name);
+            after_letter = true;
+        } else {
+            transliterated.push(c);
+            after_letter = false;
+        }
+    }
+
+    transliterated
+}
+
+/// The spelled out name of `c` if it is a Greek letter, capitalized for capital letters.
+fn name(c: char) -> Option<String> {
+    let c = match c {
+        // the mathematical alphanumeric symbols are bold, italic, etc. versions of the letters
+        '\u{1d6a8}'..='\u{1d7cb}' => c.nfkc().next()?,
+        'ς' => 'σ',
+        'ϐ' => 'β',
+        'ϑ' => 'θ',
+        'ϕ' => 'φ',
+        'ϖ' => 'π',
+        'ϰ' => 'κ',
+        'ϱ' => 'ρ',
+        'ϵ' => 'ε',
+        'ϴ' => 'Θ',
+        c => c,
+    };
+    let lowercase = c.to_lowercase().next()?;
+    let index = LOWERCASE.chars().position(|letter| letter == lowercase)?;
+    let name = LETTERS[index];
+
+    Some(if c.is_uppercase() {
+        let mut chars = name.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    } else {
+        name.to_string()
+    })
+}
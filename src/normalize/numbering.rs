@@ -0,0 +1,149 @@
+use clap::ValueEnum;
+
+/// How numbers in titles are written.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Style {
+    /// Write numbers with arabic digits (`Part 2`).
+    Arabic,
+    /// Write numbers as roman numerals (`Part II`).
+    Roman,
+}
+
+/// Words that are usually followed by the number of a part in a series.
+const KEYWORDS: &[&str] = &[
+    "part", "pt", "volume", "vol", "book", "chapter", "ch", "section", "episode", "issue",
+    "edition", "band", "bd", "teil", "tome", "livre", "tomo", "libro", "parte",
+];
+
+/// Numbers that are commonly spelled out after a keyword.
+const NUMBER_WORDS: &[&str] = &[
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven",
+    "twelve",
+];
+
+/// The largest number that is converted when it isn't preceded by a keyword, to avoid mangling
+/// things like years.
+const MAX_TRAILING_NUMBER: u32 = 20;
+
+/// Rewrite the numbering of `title` in the given `style`.
+///
+/// Numbers following a keyword like `Part` or `Volume` are converted, as well as a trailing number
+/// at the end of the title (`Rocky II` and `Rocky 2`).
+pub fn normalize(title: &str, style: Style) -> String {
+    let words: Vec<&str> = title.split(' ').collect();
+    let last = words.iter().rposition(|word| !word.is_empty());
+
+    words
+        .iter()
+        .enumerate()
+        .map(|(index, word)| {
+            let after_keyword = index
+                .checked_sub(1)
+                .and_then(|previous| words.get(previous))
+                .is_some_and(|previous| is_keyword(previous));
+            let trailing = Some(index) == last;
+
+            if after_keyword || trailing {
+                convert(word, style, after_keyword).unwrap_or_else(|| (*word).to_string())
+            } else {
+                (*word).to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_keyword(word: &str) -> bool {
+    let word = word.trim_end_matches('.').to_lowercase();
+
+    KEYWORDS.contains(&word.as_str())
+}
+
+/// Convert a single word, keeping surrounding punctuation.
+fn convert(word: &str, style: Style, after_keyword: bool) -> Option<String> {
+    let start = word.find(char::is_alphanumeric)?;
+    let end = word
+        .char_indices()
+        .rfind(|(_, c)| c.is_alphanumeric())
+        .map(|(index, c)| index + c.len_utf8())?;
+    let (prefix, number, suffix) = (&word[..start], &word[start..end], &word[end..]);
+
+    let (value, is_roman) = if let Ok(value) = number.parse::<u32>() {
+        (value, false)
+    } else if let Some(value) = from_roman(number) {
+        // a lone `I` at the end of a title is much more likely to be the pronoun
+        if !after_keyword && (number == "I" || number.contains(['L', 'C', 'D', 'M'])) {
+            return None;
+        }
+        (value, true)
+    } else if after_keyword {
+        let position = NUMBER_WORDS
+            .iter()
+            .position(|word| number.eq_ignore_ascii_case(word))?;
+        (u32::try_from(position).ok()? + 1, false)
+    } else {
+        return None;
+    };
+
+    if value == 0 || (!after_keyword && value > MAX_TRAILING_NUMBER) {
+        return None;
+    }
+
+    let converted = match style {
+        Style::Arabic => value.to_string(),
+        Style::Roman if is_roman => number.to_string(),
+        Style::Roman => to_roman(value)?,
+    };
+
+    Some(format!("{prefix}{converted}{suffix}"))
+}
+
+const NUMERALS: &[(u32, &str)] = &[
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+fn to_roman(mut value: u32) -> Option<String> {
+    if value == 0 || value >= 4000 {
+        return None;
+    }
+
+    let mut roman = String::new();
+    for &(amount, numeral) in NUMERALS {
+        while value >= amount {
+            roman.push_str(numeral);
+            value -= amount;
+        }
+    }
+
+    Some(roman)
+}
+
+/// Parse an uppercase roman numeral, only accepting its canonical form.
+pub(super) fn from_roman(roman: &str) -> Option<u32> {
+    if roman.is_empty() || !roman.chars().all(|c| "IVXLCDM".contains(c)) {
+        return None;
+    }
+
+    let mut rest = roman;
+    let mut value = 0;
+    for &(amount, numeral) in NUMERALS {
+        while let Some(remaining) = rest.strip_prefix(numeral) {
+            value += amount;
+            rest = remaining;
+        }
+    }
+
+    (rest.is_empty() && to_roman(value).as_deref() == Some(roman)).then_some(value)
+}
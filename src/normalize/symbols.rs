@@ -0,0 +1,109 @@
+use clap::ValueEnum;
+use unicode_properties::{GeneralCategoryGroup, UnicodeGeneralCategory};
+
+/// What to do with non-text symbols like math operators and emoji in titles.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Policy {
+    /// Leave symbols as they are.
+    #[default]
+    Keep,
+    /// Remove symbols.
+    Strip,
+    /// Replace symbols with words (`∇` becomes `nabla`, `≥` becomes `geq`) and remove the ones
+    /// that have no name.
+    Transliterate,
+}
+
+/// Names of common math symbols.
+const MATH_SYMBOLS: &[(char, &str)] = &[
+    ('∇', "nabla"),
+    ('∂', "partial"),
+    ('∑', "sum"),
+    ('∏', "product"),
+    ('∫', "integral"),
+    ('√', "sqrt"),
+    ('∞', "infinity"),
+    ('≥', "geq"),
+    ('≤', "leq"),
+    ('≠', "neq"),
+    ('≈', "approx"),
+    ('≡', "equiv"),
+    ('∝', "propto"),
+    ('±', "pm"),
+    ('×', "times"),
+    ('÷', "div"),
+    ('·', "cdot"),
+    ('∘', "circ"),
+    ('∈', "in"),
+    ('∉', "notin"),
+    ('⊂', "subset"),
+    ('⊆', "subseteq"),
+    ('∪', "union"),
+    ('∩', "intersection"),
+    ('∅', "emptyset"),
+    ('∀', "forall"),
+    ('∃', "exists"),
+    ('¬', "not"),
+    ('∧', "and"),
+    ('∨', "or"),
+    ('→', "to"),
+    ('⇒', "implies"),
+    ('⇔', "iff"),
+    ('°', "deg"),
+];
+
+/// Apply the symbol `policy` to `title`.
+pub fn normalize(title: &str, policy: Policy) -> String {
+    if policy == Policy::Keep {
+        return title.to_string();
+    }
+
+    let mut normalized = String::with_capacity(title.len());
+    for c in title.chars() {
+        if is_emoji_component(c) {
+            continue;
+        }
+        if !is_symbol(c) {
+            normalized.push(c);
+            continue;
+        }
+
+        let name = match policy {
+            Policy::Transliterate => name(c),
+            _ => None,
+        };
+        if let Some(name) = name {
+            if normalized.ends_with(|c: char| !c.is_whitespace()) {
+                normalized.push(' ');
+            }
+            normalized.push_str(&name);
+        }
+        normalized.push(' ');
+    }
+
+    normalized.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether `c` is a non-ASCII symbol. ASCII symbols like `+` or `=` are considered text.
+fn is_symbol(c: char) -> bool {
+    !c.is_ascii() && c.general_category_group() == GeneralCategoryGroup::Symbol
+}
+
+/// Invisible characters that glue emoji sequences together or select their presentation.
+const fn is_emoji_component(c: char) -> bool {
+    matches!(c, '\u{200d}' | '\u{fe0e}' | '\u{fe0f}')
+}
+
+fn name(c: char) -> Option<String> {
+    MATH_SYMBOLS
+        .iter()
+        .find(|(symbol, _)| *symbol == c)
+        .map(|(_, name)| (*name).to_string())
+        .or_else(|| {
+            // deunicode knows names for emoji, but only crude ASCII approximations for most other
+            // symbols, which we'd rather drop
+            let name = deunicode::deunicode_char(c)?;
+            (name.len() > 1 && name.chars().all(|c| c.is_ascii_alphabetic() || c == ' '))
+                .then(|| name.trim().to_string())
+        })
+}
@@ -0,0 +1,95 @@
+use clap::ValueEnum;
+use unicode_normalization::UnicodeNormalization;
+
+/// A step of cleaning up the typography of extracted text.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// Expand typographic ligatures (`ﬁ` becomes `fi`).
+    Ligatures,
+    /// Compose letters with diacritics given as combining sequences (Unicode NFC).
+    Unicode,
+    /// Collapse runs of whitespace into a single space and trim the ends.
+    Whitespace,
+    /// Join words hyphenated at the end of a line (`analy- sis` becomes `analysis`).
+    Hyphenation,
+}
+
+/// The ligatures of the alphabetic presentation forms block and what they stand for.
+const LIGATURES: &[(char, &str)] = &[
+    ('\u{fb00}', "ff"),
+    ('\u{fb01}', "fi"),
+    ('\u{fb02}', "fl"),
+    ('\u{fb03}', "ffi"),
+    ('\u{fb04}', "ffl"),
+    ('\u{fb05}', "st"),
+    ('\u{fb06}', "st"),
+];
+
+/// Words following a hyphen that show it is the hyphen of a shortened compound (`pre- and
+/// post-processing`) instead of one at the end of a line.
+const CONJUNCTIONS: &[&str] = &["and", "or", "to", "und", "oder", "bis", "et", "ou"];
+
+/// Clean up the typography of `title` in all steps but the ones to `skip`, in the order of
+/// [`Step`].
+pub fn normalize(title: &str, skip: &[Step]) -> String {
+    let mut title = title.to_string();
+
+    if !skip.contains(&Step::Ligatures) {
+        title = expand_ligatures(&title);
+    }
+    if !skip.contains(&Step::Unicode) {
+        title = title.nfc().collect();
+    }
+    if !skip.contains(&Step::Whitespace) {
+        title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+    if !skip.contains(&Step::Hyphenation) {
+        title = merge_hyphenation(&title);
+    }
+
+    title
+}
+
+/// Expand the typographic ligatures in `title`.
+fn expand_ligatures(title: &str) -> String {
+    title
+        .chars()
+        .fold(String::with_capacity(title.len()), |mut expanded, c| {
+            match LIGATURES.iter().find(|(ligature, _)| *ligature == c) {
+                Some((_, letters)) => expanded.push_str(letters),
+                None => expanded.push(c),
+            }
+            expanded
+        })
+}
+
+/// Join the words of `title` that were hyphenated at the end of a line and joined with a space.
+///
+/// Only a lowercase letter followed by a hyphen and a word starting with a lowercase letter is
+/// joined. The hyphen is kept if the first part already is a compound (`state-of-the- art`
+/// becomes `state-of-the-art`).
+fn merge_hyphenation(title: &str) -> String {
+    let mut merged = String::with_capacity(title.len());
+
+    for word in title.split(' ') {
+        let previous = merged.rsplit(' ').next().unwrap_or_default();
+        let hyphenated = previous
+            .strip_suffix('-')
+            .filter(|stem| stem.chars().next_back().is_some_and(char::is_lowercase))
+            .is_some_and(|_| {
+                word.chars().next().is_some_and(char::is_lowercase)
+                    && !CONJUNCTIONS.contains(&word.trim_end_matches(|c: char| !c.is_alphabetic()))
+            });
+
+        if hyphenated {
+            if !previous[..previous.len() - 1].contains('-') {
+                merged.pop();
+            }
+        } else if !merged.is_empty() {
+            merged.push(' ');
+        }
+        merged.push_str(word);
+    }
+
+    merged
+}
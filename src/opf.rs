@@ -0,0 +1,67 @@
+use std::{fmt::Write as _, fs, io, path::Path};
+
+use nomenclate::pdf::Metadata;
+
+use crate::bibliography::{self, Name};
+
+/// Escape the characters that can't appear in XML text and attributes.
+fn escape(text: &str) -> String {
+    text.chars().fold(String::new(), |mut escaped, c| {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+        escaped
+    })
+}
+
+/// The OPF package describing a document, in the format Calibre reads and writes.
+fn opf(title: &str, metadata: &Metadata) -> String {
+    let mut opf = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n",
+        "<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"2.0\">\n",
+        "  <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\" ",
+        "xmlns:opf=\"http://www.idpf.org/2007/opf\">\n",
+    ));
+
+    let _ = writeln!(opf, "    <dc:title>{}</dc:title>", escape(title));
+    for author in bibliography::authors(metadata) {
+        let name = Name::parse(author);
+        let file_as = name.given.map_or_else(
+            || name.family.to_string(),
+            |given| format!("{}, {given}", name.family),
+        );
+        let _ = writeln!(
+            opf,
+            "    <dc:creator opf:role=\"aut\" opf:file-as=\"{}\">{}</dc:creator>",
+            escape(&file_as),
+            escape(author)
+        );
+    }
+    if let Some(year) = metadata.year.first() {
+        let _ = writeln!(opf, "    <dc:date>{}</dc:date>", year.value);
+    }
+    if let Some(doi) = metadata.doi.first() {
+        let _ = writeln!(
+            opf,
+            "    <dc:identifier opf:scheme=\"DOI\">{}</dc:identifier>",
+            escape(&doi.value)
+        );
+    }
+    opf.push_str("  </metadata>\n</package>\n");
+
+    opf
+}
+
+/// Write the title and metadata of the document at `path` to an OPF file with the same name next to
+/// it, which Calibre reads when the document is added to a library.
+///
+/// # Errors
+///
+/// This function will return an error if the sidecar file could not be written.
+pub fn write_sidecar(path: &Path, title: &str, metadata: &Metadata) -> io::Result<()> {
+    fs::write(path.with_extension("opf"), opf(title, metadata))
+}
@@ -0,0 +1,135 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("could not read {path}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not parse overrides file {path}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct OverridesFile {
+    #[serde(default)]
+    hashes: HashMap<String, String>,
+    #[serde(default)]
+    paths: HashMap<PathBuf, String>,
+}
+
+/// Hand-written titles for documents the extraction gets wrong.
+///
+/// Titles are looked up by the SHA-256 hash of the file content first, so they survive the file
+/// being renamed, and by path second. An overrides file looks like this:
+///
+/// ```toml
+/// [hashes]
+/// "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08" = "A Better Title"
+///
+/// [paths]
+/// "papers/scan.pdf" = "Another Title"
+/// ```
+///
+/// Relative paths are resolved against the directory containing the overrides file.
+#[derive(Default)]
+pub struct Overrides {
+    hashes: HashMap<String, String>,
+    paths: HashMap<PathBuf, String>,
+}
+
+impl Overrides {
+    /// Load overrides from a TOML file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file could not be read or parsed.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|source| Error::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let file: OverridesFile = toml::from_str(&content).map_err(|source| Error::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+
+        Ok(Self {
+            hashes: file
+                .hashes
+                .into_iter()
+                .map(|(hash, title)| (hash.to_lowercase(), title))
+                .collect(),
+            paths: file
+                .paths
+                .into_iter()
+                .map(|(path, title)| (normalize(&base.join(path)), title))
+                .collect(),
+        })
+    }
+
+    /// The number of titles looked up by hash and by path.
+    pub fn len(&self) -> (usize, usize) {
+        (self.hashes.len(), self.paths.len())
+    }
+
+    /// Get the title override for the document at `path`, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the document had to be hashed but could not be read.
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Result<Option<&str>, Error> {
+        let path = path.as_ref();
+
+        if !self.hashes.is_empty() {
+            let hash = hash_file(path)?;
+            if let Some(title) = self.hashes.get(&hash) {
+                log::debug!("found override for hash {hash}");
+                return Ok(Some(title));
+            }
+        }
+
+        Ok(self.paths.get(&normalize(path)).map(String::as_str))
+    }
+}
+
+/// Compute the hex encoded SHA-256 hash of a file's content.
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be read.
+pub fn hash_file<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    let path = path.as_ref();
+    let content = fs::read(path).map_err(|source| Error::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    Ok(hash(&content))
+}
+
+/// Compute the hex encoded SHA-256 hash of a document's content.
+pub fn hash(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
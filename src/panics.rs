@@ -0,0 +1,79 @@
+use std::{
+    any::Any,
+    backtrace::Backtrace,
+    cell::{Cell, RefCell},
+    panic::{self, AssertUnwindSafe},
+    sync::Once,
+};
+
+/// A panic caught while processing a document.
+pub struct Panic {
+    pub message: String,
+    /// Where in the code it happened, like `src/pdf/font.rs:12:5`.
+    pub location: Option<String>,
+    pub backtrace: String,
+}
+
+thread_local! {
+    /// Whether panics on this thread are caught by [`catch`].
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    /// The last panic caught on this thread, with what only the panic hook knows about it.
+    static CAUGHT: RefCell<Option<Panic>> = const { RefCell::new(None) };
+}
+
+static HOOK: Once = Once::new();
+
+/// Call `f` and return the panic it ended in instead of unwinding further, so an unforeseen panic,
+/// e.g. in a dependency, only fails the document being processed.
+///
+/// Caught panics are not printed by the default panic hook, their message, location and backtrace
+/// are returned instead.
+pub fn catch<T>(f: impl FnOnce() -> T) -> Result<T, Panic> {
+    HOOK.call_once(install_hook);
+
+    let catching = CATCHING.replace(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.set(catching);
+
+    result.map_err(|payload| {
+        CAUGHT.take().unwrap_or_else(|| Panic {
+            message: message(payload.as_ref()),
+            location: None,
+            backtrace: String::new(),
+        })
+    })
+}
+
+/// Go on unwinding with a `panic` that was caught on another thread, so [`catch`] returns it as if
+/// it happened on this one.
+pub fn resume(panic: Panic) -> ! {
+    let message = panic.message.clone();
+    CAUGHT.set(Some(panic));
+    panic::resume_unwind(Box::new(message))
+}
+
+/// Record the panics [`catch`] catches along with their backtrace and leave the others to the
+/// default panic hook.
+fn install_hook() {
+    let default = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if CATCHING.get() {
+            CAUGHT.set(Some(Panic {
+                message: message(info.payload()),
+                location: info.location().map(ToString::to_string),
+                backtrace: Backtrace::force_capture().to_string(),
+            }));
+        } else {
+            default(info);
+        }
+    }));
+}
+
+/// The message a panic was started with.
+fn message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panicked without a message".to_string())
+}
@@ -0,0 +1,174 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ureq::unversioned::multipart::Form;
+
+use crate::{
+    bibliography::{self, Entry},
+    i18n::tr,
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("could not read {path}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("request to paperless-ngx failed")]
+    Request(#[from] ureq::Error),
+}
+
+/// The fields of a renamed document that paperless-ngx expects when it is consumed.
+#[derive(Serialize)]
+pub struct Document<'a> {
+    pub path: &'a Path,
+    pub title: &'a str,
+    /// The date the document was created, as `YYYY-MM-DD`.
+    pub created: Option<&'a str>,
+    /// The first author of the document.
+    pub correspondent: Option<&'a str>,
+    pub tags: &'a [String],
+}
+
+impl<'a> Document<'a> {
+    pub fn new(entry: &'a Entry, tags: &'a [String]) -> Self {
+        Self {
+            path: &entry.path,
+            title: &entry.title,
+            created: entry
+                .metadata
+                .created
+                .first()
+                .map(|created| created.value.as_str()),
+            correspondent: bibliography::authors(&entry.metadata).first().copied(),
+            tags,
+        }
+    }
+
+    /// Print the fields as a line of JSON.
+    pub fn print(&self) {
+        match serde_json::to_string(self) {
+            Ok(json) => println!("{json}"),
+            Err(err) => log::error!(
+                "{}",
+                tr!(
+                    "error-serialize",
+                    path = self.path.display().to_string(),
+                    error = err.to_string()
+                )
+            ),
+        }
+    }
+}
+
+/// A page of objects returned by the REST API of paperless-ngx.
+#[derive(Deserialize)]
+struct Page {
+    results: Vec<Object>,
+}
+
+#[derive(Deserialize)]
+struct Object {
+    id: u64,
+}
+
+/// A client for the REST API of a paperless-ngx instance.
+pub struct Client {
+    url: String,
+    token: String,
+    /// The ids of correspondents and tags by their kind and name, or `None` if there is none.
+    ids: RefCell<HashMap<(&'static str, String), Option<u64>>>,
+}
+
+impl Client {
+    pub fn new(url: &str, token: String) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            token,
+            ids: RefCell::default(),
+        }
+    }
+
+    /// Upload `document` to be consumed by paperless-ngx.
+    ///
+    /// Correspondents and tags are assigned by name and left out if paperless-ngx does not know
+    /// them.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the document could not be read or a request failed.
+    pub fn upload(&self, document: &Document) -> Result<(), Error> {
+        let correspondent = document
+            .correspondent
+            .map(|name| self.id("correspondents", name))
+            .transpose()?
+            .flatten()
+            .map(|id| id.to_string());
+        let tags = document
+            .tags
+            .iter()
+            .map(|name| self.id("tags", name))
+            .filter_map(Result::transpose)
+            .map(|id| id.map(|id| id.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut form = Form::new()
+            .file("document", document.path)
+            .map_err(|source| Error::Read {
+                path: document.path.to_path_buf(),
+                source,
+            })?
+            .text("title", document.title);
+        if let Some(created) = document.created {
+            form = form.text("created", created);
+        }
+        if let Some(correspondent) = &correspondent {
+            form = form.text("correspondent", correspondent);
+        }
+        for tag in &tags {
+            form = form.text("tags", tag);
+        }
+
+        ureq::post(format!("{}/api/documents/post_document/", self.url))
+            .header("Authorization", self.authorization())
+            .send(form)?;
+
+        Ok(())
+    }
+
+    /// Look up the id of the object of `kind` (e.g. `tags`) called `name`, ignoring case.
+    fn id(&self, kind: &'static str, name: &str) -> Result<Option<u64>, Error> {
+        let key = (kind, name.to_string());
+        if let Some(id) = self.ids.borrow().get(&key) {
+            return Ok(*id);
+        }
+
+        let page: Page = ureq::get(format!("{}/api/{kind}/", self.url))
+            .header("Authorization", self.authorization())
+            .query("name__iexact", name)
+            .call()?
+            .body_mut()
+            .read_json()?;
+        let id = page.results.first().map(|object| object.id);
+        if id.is_none() {
+            log::warn!(
+                "{}",
+                tr!("paperless-unknown", name = format!("{name:?}"), kind = kind)
+            );
+        }
+        self.ids.borrow_mut().insert(key, id);
+
+        Ok(id)
+    }
+
+    fn authorization(&self) -> String {
+        format!("Token {}", self.token)
+    }
+}
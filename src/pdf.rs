@@ -1,61 +1,215 @@
-use std::{fmt::Display, path::Path};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    io::Read,
+    ops::RangeInclusive,
+    path::Path,
+    sync::Arc,
+};
 
-use approx::{abs_diff_eq, abs_diff_ne};
+use approx::{abs_diff_eq, relative_eq};
+use backend::{Adjusted, Backend, Operation, Page};
+use bumpalo::Bump;
+pub use callbacks::{Callbacks, Warning};
 use error::Error;
-use font::{FontCache, FontInfo};
+pub use font::Coverage;
+use font::FontInfo;
+use geometry::{snap, Transform};
+use graphics::Graphics;
+use identifiers::Identifiers;
+pub use metadata::{Field, Metadata, Preference, Provenance, TrustPolicy};
 use pdf::{
-    content::{Op, TextDrawAdjusted},
-    file::FileOptions,
-    object::{PageRc, Resolve},
-    primitive::PdfString,
+    any::AnySync,
+    content::Point,
+    file::{Cache, File, Log},
+    object::PageRc,
+    PdfError,
 };
+use serde::{Deserialize, Serialize};
+pub use session::{BorrowedScanSession, ScanSession};
 
+mod backend;
+mod callbacks;
+mod class;
+pub use class::DocumentClass;
+mod datasheet;
 pub mod error;
 mod font;
+mod geometry;
+mod graphics;
+mod identifiers;
+mod legal;
+mod memory;
+mod metadata;
+mod music;
+#[cfg(feature = "ocr")]
+mod ocr;
+mod plausibility;
+mod profile;
+mod receipt;
+#[cfg(feature = "ocr")]
+pub use ocr::{Language, Region, Rendering};
+pub use profile::Profile;
+mod score;
+mod session;
+mod stage;
+use stage::Allowance;
+pub use stage::{Budget, Budgets, Stage, Timings};
+mod statement;
+mod stream;
+mod xmp;
 
-struct PositionedText {
-    text: String,
+/// Text drawn on a page, borrowed from the content stream where possible and otherwise allocated
+/// in the page's arena.
+#[derive(Clone)]
+struct PositionedText<'a> {
+    text: Cow<'a, str>,
     font_size: f32,
+    /// The distance of the start of the text from the left of the displayed page.
+    x: f32,
+    /// The distance of the text from the top of the displayed page.
     y: f32,
+    /// The distance to the next line of text in the same block, as set when the text was drawn.
+    leading: f32,
+    /// How far the text is raised above its baseline by the text rise, lowered if negative.
+    raised: f32,
+    /// The font size on the displayed page, after scaling the text.
+    scaled_font_size: f32,
+    /// Whether the text is set as a subscript or superscript of a line in the title.
+    script: Option<Script>,
+    /// Whether the text is set diagonally across the page like a watermark, instead of horizontally
+    /// or vertically.
+    slanted: bool,
+}
+
+/// Text set smaller than and raised above or lowered below the line it is in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Script {
+    Sub,
+    Super,
 }
 
-impl PositionedText {
-    fn from_text(text: &PdfString, state: &TextState) -> Self {
+impl<'a> PositionedText<'a> {
+    fn new(text: Cow<'a, str>, state: &TextState, graphics: &Graphics) -> Self {
+        let origin = graphics.to_page(state.origin());
+        let y = graphics.depth(origin);
+        let next_line = graphics.to_page(state.matrix.apply(Point {
+            x: 0.,
+            y: -state.effective_leading(),
+        }));
+        let risen = graphics.to_page(state.matrix.apply(Point {
+            x: 0.,
+            y: state.rise,
+        }));
+        let unit = graphics.to_page(state.matrix.apply(Point { x: 0., y: 1. }));
+        let (up_x, up_y) = ((unit.x - origin.x).abs(), (unit.y - origin.y).abs());
+
+        // Everything that lines and blocks are grouped by is snapped to the same grid, so the
+        // grouping does not depend on the platform.
+        let y = snap(y);
         Self {
-            text: state.font.decode(text).expect("could not parse pdf string"),
+            text,
             font_size: state.font_size,
-            y: state.y,
+            x: snap(graphics.indent(origin)),
+            y,
+            leading: snap(graphics.depth(next_line)) - y,
+            raised: y - snap(graphics.depth(risen)),
+            scaled_font_size: snap(state.font_size * (unit.x - origin.x).hypot(unit.y - origin.y)),
+            script: None,
+            slanted: up_x.min(up_y) > up_x.max(up_y) * SLANT,
         }
     }
-    fn from_text_array(array: &[TextDrawAdjusted], state: &TextState) -> Self {
-        Self {
-            text: array
-                .iter()
-                .filter_map(|elem| match elem {
-                    TextDrawAdjusted::Text(text) => {
-                        Some(state.font.decode(text).expect("could not parse pdf string"))
-                    }
-                    TextDrawAdjusted::Spacing(spacing) => {
-                        if *spacing < -100. {
-                            Some(String::from(" "))
+
+    /// # Errors
+    ///
+    /// This function will return an error if the text could not be decoded with the current font.
+    fn from_text(
+        text: &'a [u8],
+        state: &TextState,
+        graphics: &Graphics,
+        statistics: &mut Statistics,
+        backend: &dyn Backend,
+        arena: &'a Bump,
+    ) -> Result<Self, Error> {
+        let text = match statistics.decode(backend, &state.font, text)? {
+            Cow::Borrowed(text) => text,
+            Cow::Owned(text) => arena.alloc_str(&text),
+        };
+
+        Ok(Self::new(Cow::Borrowed(text), state, graphics))
+    }
+
+    /// # Errors
+    ///
+    /// This function will return an error if a piece of the text could not be decoded with the
+    /// current font.
+    fn from_text_array(
+        array: &'a [Adjusted],
+        state: &TextState,
+        graphics: &Graphics,
+        statistics: &mut Statistics,
+        backend: &dyn Backend,
+        arena: &'a Bump,
+    ) -> Result<Self, Error> {
+        let mut text = bumpalo::collections::String::new_in(arena);
+        for elem in array {
+            match elem {
+                Adjusted::Text(fragment) => {
+                    text.push_str(&statistics.decode(backend, &state.font, fragment)?);
+                }
+                Adjusted::Spacing(spacing) => {
+                    // The adjustment is in thousandths of an em and moves the next glyph to the
+                    // right if negative. Character spacing widens (or narrows) every gap.
+                    let gap = -spacing / 1000.
+                        + if state.font_size > 0. {
+                            state.char_spacing / state.font_size
                         } else {
-                            None
-                        }
+                            0.
+                        };
+                    if gap > 0.1 {
+                        text.push(' ');
                     }
-                })
-                .collect::<String>(),
-            font_size: state.font_size,
-            y: state.y,
+                }
+            }
+        }
+
+        Ok(Self::new(
+            Cow::Borrowed(text.into_bump_str()),
+            state,
+            graphics,
+        ))
+    }
+
+    /// Guess where the text ends, from the number of its glyphs.
+    #[allow(clippy::cast_precision_loss)]
+    fn end(&self) -> f32 {
+        (self.text.trim().chars().count() as f32 * self.scaled_font_size)
+            .mul_add(GLYPH_WIDTH, self.x)
+    }
+
+    fn into_owned(self) -> PositionedText<'static> {
+        PositionedText {
+            text: Cow::Owned(self.text.into_owned()),
+            font_size: self.font_size,
+            x: self.x,
+            y: self.y,
+            leading: self.leading,
+            raised: self.raised,
+            scaled_font_size: self.scaled_font_size,
+            script: self.script,
+            slanted: self.slanted,
         }
     }
 }
 
-impl Display for PositionedText {
+impl Display for PositionedText<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "text at y = {} with font size {}: {:?}",
-            self.y, self.font_size, self.text
+            "text {} below the top with font size {} and leading {}: {:?}",
+            self.y, self.font_size, self.leading, self.text
         )
     }
 }
@@ -65,144 +219,1131 @@ pub struct TextState {
     pub font: FontInfo,
     pub font_size: f32,
     pub leading: f32,
+    /// The leading as a multiple of the font size that is used if no leading is set.
+    pub default_leading: f32,
+    /// Extra space between glyphs in unscaled text space units, set by `Tc` and `"`.
+    pub char_spacing: f32,
+    /// How far text is moved up from the baseline in unscaled text space units, set by `Ts`.
+    pub rise: f32,
+    /// The text matrix, set by `Tm` and moved by `Td`, `TD` and `T*`.
+    pub matrix: Transform,
+    /// The text matrix at the start of the current line.
+    pub line_matrix: Transform,
+}
+
+impl TextState {
+    /// Move to the start of the next line, offset from the start of the current line.
+    ///
+    /// The start of the line is [snapped](snap), so lines moved to one after another, e.g. with
+    /// `T*`, do not drift apart differently depending on how the rounding errors add up.
+    fn translate(&mut self, tx: f32, ty: f32) {
+        self.line_matrix = Transform::translation(tx, ty)
+            .then(&self.line_matrix)
+            .snapped();
+        self.matrix = self.line_matrix;
+        log::debug!("translate by ({tx}, {ty})");
+    }
+
+    /// The leading that is used to move to the next line.
+    fn effective_leading(&self) -> f32 {
+        if abs_diff_eq!(self.leading, 0.) {
+            snap(self.font_size * self.default_leading)
+        } else {
+            self.leading
+        }
+    }
+
+    /// The position of the next glyph in text space.
+    const fn origin(&self) -> Point {
+        self.matrix.apply(Point { x: 0., y: 0. })
+    }
+}
+
+/// How much the font sizes of text in the same block may differ, relative to the larger one. This
+/// allows for rounding in documents that scale their fonts.
+const FONT_SIZE_TOLERANCE: f32 = 0.02;
+
+/// How far apart lines may be, as a multiple of their leading, to still belong to the same block.
+const BLOCK_GAP: f32 = 1.5;
+
+/// How much smaller than the title the text below it may be to still be considered a parallel
+/// title, relative to the size of the title.
+const PARALLEL_FONT_SIZE_TOLERANCE: f32 = 0.25;
+
+/// The range of font sizes of subscripts and superscripts, relative to the size of their line.
+const SCRIPT_FONT_SIZES: RangeInclusive<f32> = 0.4..=0.9;
+
+/// The average width of a glyph, relative to the font size.
+const GLYPH_WIDTH: f32 = 0.6;
+
+/// How wide a gap between two pieces of text has to be to separate words, relative to the font
+/// size.
+const GAP: f32 = 0.15;
+
+/// How much wider than the width of a glyph the gap to the next glyph drawn on its own may be,
+/// relative to the font size, for both to be pieces of the same line. Glyphs further apart are
+/// kept apart, e.g. in another column.
+const MAX_GLYPH_GAP: f32 = 1.;
+
+/// How far subscripts and superscripts have to be moved from the baseline of their line, and how
+/// far at most, relative to the size of the line.
+const SCRIPT_OFFSETS: RangeInclusive<f32> = 0.05..=0.6;
+
+/// How far text can lean sideways (as a fraction of its height) to still be considered horizontal
+/// or vertical and not diagonal, which is enough for slanted fake italics.
+const SLANT: f32 = 0.5;
+
+/// How far an image may be above or below a block of text to hold the rest of its title, relative
+/// to the font size of the block.
+const IMAGE_GAP: f32 = 1.5;
+
+/// The smallest width and height of images that might hold part of a title, in points. Smaller
+/// ones are logos and icons.
+const MIN_IMAGE_SIZE: (f32, f32) = (72., 12.);
+
+/// The share of the page an image may cover at most to hold part of a title. Larger ones are
+/// backgrounds or scanned pages.
+const MAX_IMAGE_AREA: f32 = 0.8;
+
+/// The default leading of typical body text, as a multiple of the font size.
+pub const DEFAULT_LEADING: f32 = 1.2;
+
+/// Options controlling which parts of a document are parsed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Options {
+    /// The number of pages to parse, starting from the first one.
+    pub page_count: usize,
+    /// If set, only this page (zero-based) is parsed and `page_count` is ignored.
+    pub page: Option<usize>,
+    /// The leading as a multiple of the font size that is used if a page moves to the next line
+    /// without setting a leading.
+    pub default_leading: f32,
+    /// Map documents instead of reading them, don't keep decoded streams around and skip huge
+    /// content streams. Images are never decoded, regardless of this option.
+    #[serde(default)]
+    pub low_memory: bool,
+    /// Parse documents with both backends, the `pdf` crate and lopdf, and take the candidates they
+    /// agree on, or else those of the backend more confident in its title. This helps with
+    /// malformed documents the backends read differently, but takes about twice as long.
+    #[serde(default)]
+    pub ensemble: bool,
+    /// The password to decrypt encrypted documents with if the empty password does not work. It
+    /// is never serialized.
+    #[serde(skip)]
+    pub password: Option<String>,
+    /// Recognize the text of pages that have none, like those of scanned documents, with
+    /// `pdftoppm` and `tesseract`.
+    #[cfg(feature = "ocr")]
+    #[serde(default)]
+    pub ocr: bool,
+    /// How pages are rendered to recognize their text.
+    #[cfg(feature = "ocr")]
+    #[serde(default)]
+    pub rendering: Rendering,
+    /// The kind of documents to tune the heuristics for.
+    #[serde(default)]
+    pub profile: Option<Profile>,
+    /// How much time and how many operations the stages of finding the title may take.
+    #[serde(default)]
+    pub budgets: Budgets,
+}
+
+/// A piece of text that could be the title of a document.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Candidate {
+    pub text: String,
+    /// The zero-based index of the page the text was found on.
+    pub page: usize,
+    /// The distance of the text from the top of the page.
     pub y: f32,
+    /// How far down the page the text is, from 0 at the top to 1 at the bottom, if the size of
+    /// the page is known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<f32>,
+    pub font_size: f32,
+    /// How likely the text is the title, between 0 and 1, see [`Candidate::rank`].
+    #[serde(default)]
+    pub score: f32,
+    /// The next block of text below the title in a similar font size, which may be the title in
+    /// another language.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parallel: Option<String>,
+    /// The pieces of text the candidate was put together from, in the order they were drawn.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<Span>,
+    /// Whether the text was recognized in the rendered page because the page has no text of its
+    /// own or its text layer is implausible. The position and font size of recognized text are
+    /// estimates.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub recognized: bool,
+    /// How plausible the text layer of the page and the text recognized in its rendering were, if
+    /// the text layer looked unreliable and both were compared. The text of the more plausible one
+    /// was used, as told by [`Candidate::recognized`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calibration: Option<Calibration>,
+    /// Whether part of the text was recognized in an image right above or below the rest of it,
+    /// for titles set partly as an image and partly as text.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub assembled: bool,
+    /// Whether the candidate is set in one or two centered lines on a page with little other
+    /// text, like the title page or half-title page of a book.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub title_page: bool,
+}
+
+/// The plausibility of the two sources of the text of a page, between 0 and 1, see
+/// [`Candidate::calibration`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Calibration {
+    pub layout: f32,
+    pub ocr: f32,
+}
+
+/// A piece of text as drawn on a page by a single text operation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Span {
+    pub text: String,
+    /// The distance of the start of the text from the left of the page.
+    pub x: f32,
+    /// The distance of the text from the top of the page.
+    pub y: f32,
+    pub font_size: f32,
+}
+
+impl From<&PositionedText<'_>> for Span {
+    fn from(text: &PositionedText) -> Self {
+        Self {
+            text: text.text.to_string(),
+            x: text.x,
+            y: text.y,
+            font_size: text.font_size,
+        }
+    }
+}
+
+/// Why a candidate is not plausible as the title of a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Implausible {
+    TooShort,
+    TooLong,
+    /// Text like `Untitled` or `Microsoft Word - draft.docx` that programs put where the title
+    /// belongs.
+    Placeholder,
+}
+
+impl Display for Implausible {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::TooShort => "too short",
+            Self::TooLong => "too long",
+            Self::Placeholder => "a placeholder",
+        })
+    }
+}
+
+impl Candidate {
+    /// How many characters a title has at least and at most.
+    const TITLE_LENGTH: RangeInclusive<usize> = 4..=300;
+
+    /// Whether the text is neither too short nor too long to be a title.
+    #[must_use]
+    pub fn has_plausible_length(&self) -> bool {
+        Self::TITLE_LENGTH.contains(&self.text.trim().chars().count())
+    }
+
+    /// Why the text is not plausible as a title, if it is not: it is too short or too long, or a
+    /// placeholder instead of a title.
+    #[must_use]
+    pub fn implausible(&self) -> Option<Implausible> {
+        let length = self.text.trim().chars().count();
+        if length < *Self::TITLE_LENGTH.start() {
+            Some(Implausible::TooShort)
+        } else if length > *Self::TITLE_LENGTH.end() {
+            Some(Implausible::TooLong)
+        } else if metadata::is_junk_title(&self.text) {
+            Some(Implausible::Placeholder)
+        } else {
+            None
+        }
+    }
+
+    /// Compare two candidates by how likely they are the title, the more likely one first.
+    ///
+    /// The candidate with the higher score wins, which combines a large font size relative to the
+    /// other candidates with being high up on the first page and having a plausible length. If the
+    /// scores are equal, text in a larger font wins, then the candidate on the earlier page, then
+    /// the one higher up on the page, then the one with a plausible length and finally the longer
+    /// one. Font sizes within [`FONT_SIZE_TOLERANCE`] of each other count as equal, as they do
+    /// when scoring.
+    #[must_use]
+    pub fn rank(&self, other: &Self) -> Ordering {
+        other
+            .score
+            .total_cmp(&self.score)
+            .then(
+                if relative_eq!(
+                    self.font_size,
+                    other.font_size,
+                    max_relative = FONT_SIZE_TOLERANCE
+                ) {
+                    Ordering::Equal
+                } else {
+                    other.font_size.total_cmp(&self.font_size)
+                },
+            )
+            .then(self.page.cmp(&other.page))
+            .then(self.y.total_cmp(&other.y))
+            .then(
+                other
+                    .has_plausible_length()
+                    .cmp(&self.has_plausible_length()),
+            )
+            .then(other.text.len().cmp(&self.text.len()))
+    }
+}
+
+impl Display for Candidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} on page {} with font size {} (score {:.2})",
+            self.text,
+            self.page + 1,
+            self.font_size,
+            self.score
+        )
+    }
+}
+
+/// Statistics about how well the text of a document could be decoded and how long finding its
+/// title took.
+#[derive(Clone, Debug, Default)]
+pub struct Statistics {
+    /// The names of fonts whose encoding is missing or unsupported.
+    pub failed_fonts: BTreeSet<String>,
+    pub coverage: Coverage,
+    /// How many glyphs could be mapped in each font with a name, which adds up to `coverage`
+    /// unless text was set in an unnamed font.
+    pub fonts: BTreeMap<String, Coverage>,
+    /// How long each stage of finding the title took.
+    pub timings: Timings,
+    /// Whether a stage ran out of time, so the candidates depend on how fast the document was
+    /// parsed and a run with more time may find others.
+    pub over_budget: bool,
+}
+
+impl Statistics {
+    /// Decode `text` set in `font`, counting its glyphs for the document and the font.
+    fn decode<'a>(
+        &mut self,
+        backend: &dyn Backend,
+        font: &FontInfo,
+        text: &'a [u8],
+    ) -> Result<Cow<'a, str>, Error> {
+        let mut coverage = Coverage::default();
+        let text = backend.decode(font, text, &mut coverage)?;
+        self.coverage += coverage;
+        if let Some(name) = font.name() {
+            *self.fonts.entry(name.to_string()).or_default() += coverage;
+        }
+
+        Ok(text)
+    }
+}
+
+/// The result of parsing a document.
+pub struct Parsed {
+    /// The title candidates, ordered by [`Candidate::rank`].
+    pub candidates: Vec<Candidate>,
+    /// The title of the first candidate and the metadata stored in the document.
+    pub metadata: Metadata,
+    pub statistics: Statistics,
 }
 
-/// Load a PDF document and parse the first `page_count` pages.
+/// Load a PDF document and collect title candidates from the pages selected by `options`.
+///
+/// Every page contributes the topmost block of text in each of its few largest font sizes as
+/// candidates, leaving out running headers and footers, page numbers, watermarks and drop caps.
+/// The candidates are scored and ordered by [`Candidate::rank`], so the runners-up follow the most
+/// likely title.
 ///
-/// If the document has less than `page_count` pages, all pages are parsed.
+/// If the document has less pages than requested, all pages are parsed.
 ///
 /// If a page could not be parsed properly, it is skipped and a warning is shown to the user.
 ///
+/// `callbacks` are called while the document is parsed to report progress, warnings and
+/// candidates as they are found.
+///
 /// # Errors
 ///
 /// This function will return an error if the document could not be loaded.
-pub fn parse_pdf<P: AsRef<Path>>(path: P, page_count: usize) -> Result<String, Error> {
-    let path = path.as_ref().to_path_buf();
-    let file = FileOptions::cached()
-        .open(path.clone())
-        .map_err(|err| Error::Load { path, source: err })?;
-    let resolver = file.resolver();
-    let mut max_font_size = 0.;
-    let mut text = String::new();
-
-    for (page_number, page) in
-        file.pages()
-            .take(page_count)
-            .enumerate()
-            .filter_map(|(page_number, page)| {
-                page.inspect_err(|err| log::warn!("skipping page {page_number}: {err}"))
-                    .map(|page| (page_number, page))
-                    .ok()
-            })
-    {
-        if let Ok((page_text, font_size)) = largest_text_elements(&page, &resolver)
-            .inspect_err(|err| log::error!("could not parse page {page_number}: {err}"))
-        {
-            if font_size > max_font_size {
-                text = page_text
-                    .into_iter()
-                    .map(|text| text.text)
-                    .collect::<Vec<_>>()
-                    .join(" ");
-                max_font_size = font_size;
-            }
-        }
-    }
+pub fn parse_pdf<P: AsRef<Path>>(
+    path: P,
+    options: &Options,
+    callbacks: &mut Callbacks,
+) -> Result<Parsed, Error> {
+    Ok(ScanSession::open(path, options)?.finish(callbacks))
+}
 
-    Ok(text)
+/// Parse a PDF document that is already loaded into memory.
+///
+/// See [`parse_pdf`] for how the document is parsed.
+///
+/// # Errors
+///
+/// This function will return an error if `data` is not a valid PDF document.
+pub fn parse_pdf_bytes(
+    data: &[u8],
+    options: &Options,
+    callbacks: &mut Callbacks,
+) -> Result<Parsed, Error> {
+    Ok(ScanSession::load(data, options)?.finish(callbacks))
 }
 
-fn largest_text_elements(
-    page: &PageRc,
-    resolver: &impl Resolve,
-) -> Result<(Vec<PositionedText>, f32), Error> {
-    let font_cache = FontCache::from_page(page, resolver);
-    let mut state = TextState::default();
-    let mut max_font_size = 0.;
-    let mut positioned_text = Vec::new();
+/// Parse a PDF document read from `reader`.
+///
+/// The whole document is read into memory first. See [`parse_pdf`] for how the document is
+/// parsed.
+///
+/// # Errors
+///
+/// This function will return an error if reading fails or the data is not a valid PDF document.
+pub fn parse_pdf_reader<R: Read>(
+    mut reader: R,
+    options: &Options,
+    callbacks: &mut Callbacks,
+) -> Result<Parsed, Error> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).map_err(Error::Read)?;
+
+    parse_pdf_bytes(&data, options, callbacks)
+}
+
+/// Parse a PDF document the caller already loaded with the `pdf` crate, instead of loading it
+/// again.
+///
+/// See [`parse_pdf`] for how the document is parsed and [`BorrowedScanSession::with_file`] for what
+/// is different about documents loaded by the caller.
+pub fn parse_pdf_file<B, OC, SC, L>(
+    file: &File<B, OC, SC, L>,
+    options: &Options,
+    callbacks: &mut Callbacks,
+) -> Parsed
+where
+    B: pdf::backend::Backend + Sync,
+    OC: Cache<Result<AnySync, Arc<PdfError>>> + Sync,
+    SC: Cache<Result<Arc<[u8]>, Arc<PdfError>>> + Sync,
+    L: Log + Sync,
+{
+    BorrowedScanSession::with_file(file, options).finish(callbacks)
+}
+
+/// Parse the `pages` of a PDF document the caller already loaded with the `pdf` crate, see
+/// [`BorrowedScanSession::with_pages`].
+pub fn parse_pdf_pages<B, OC, SC, L>(
+    file: &File<B, OC, SC, L>,
+    pages: Vec<PageRc>,
+    options: &Options,
+    callbacks: &mut Callbacks,
+) -> Parsed
+where
+    B: pdf::backend::Backend + Sync,
+    OC: Cache<Result<AnySync, Arc<PdfError>>> + Sync,
+    SC: Cache<Result<Arc<[u8]>, Arc<PdfError>>> + Sync,
+    L: Log + Sync,
+{
+    BorrowedScanSession::with_pages(file, pages, options).finish(callbacks)
+}
+
+/// Estimate how confident we are that the first of the ranked `candidates` is the title.
+///
+/// The confidence is between 0 and 1. It is high if the first candidate scores clearly higher than
+/// the runner-up and has a plausible length for a title.
+#[must_use]
+pub fn confidence(candidates: &[Candidate]) -> f32 {
+    let Some(first) = candidates.first() else {
+        return 0.;
+    };
+    let margin = margin(candidates).unwrap_or(1.);
+    let plausibility = if first.has_plausible_length() {
+        1.
+    } else {
+        0.5
+    };
+
+    2.5f32.mul_add(margin, 0.5).min(1.) * plausibility
+}
+
+/// How much lower (between 0 and 1) the runner-up of the ranked `candidates` scores than the first
+/// candidate, or `None` if there is no runner-up.
+///
+/// Candidates with a small margin are nearly tied, so the title might as well be the runner-up.
+#[must_use]
+pub fn margin(candidates: &[Candidate]) -> Option<f32> {
+    let [first, second, ..] = candidates else {
+        return None;
+    };
+
+    Some((first.score - second.score).clamp(0., 1.))
+}
+
+/// Score all `candidates` relative to each other with the heuristics of `profile` and order them
+/// by [`Candidate::rank`].
+pub fn rank(candidates: &mut [Candidate], profile: Option<Profile>) {
+    let max_font_size = candidates
+        .iter()
+        .map(|candidate| candidate.font_size)
+        .fold(0., f32::max);
+    for candidate in candidates.iter_mut() {
+        candidate.score = score::score(candidate, max_font_size, profile);
+    }
+    candidates.sort_by(Candidate::rank);
+}
+
+/// Collect the blocks of text on `page` of a document read with `backend` that might be its title,
+/// one for each of the largest font sizes, largest first.
+fn title_blocks(
+    page: Page,
+    backend: &dyn Backend,
+    options: &Options,
+    statistics: &mut Statistics,
+    identifiers: &mut Identifiers,
+) -> Result<PageText, Error> {
+    let mut state = TextState {
+        default_leading: options.default_leading,
+        ..TextState::default()
+    };
+    let Page {
+        mut graphics,
+        fonts: font_cache,
+        operations,
+    } = page;
+
+    // The text is borrowed from the operations or allocated in an arena that is dropped with the
+    // page, and only copied if it ends up in the title.
+    let arena = Bump::new();
+    let mut positioned_text = bumpalo::collections::Vec::new_in(&arena);
+    let mut glyph = None;
+    let mut pictures = Vec::new();
+    let mut allowance = Allowance::decode(&options.budgets, &statistics.timings);
+    for operation in &operations {
+        if graphics.apply(operation) {
+            continue;
+        }
+        if matches!(operation, Operation::Text(_) | Operation::AdjustedText(_)) && !allowance.draw()
+        {
+            statistics.over_budget |= allowance.out_of_time;
+            break;
+        }
 
-    for operation in page
-        .contents
-        .as_ref()
-        .ok_or(Error::NoContent)?
-        .operations(resolver)?
-    {
         match operation {
-            Op::BeginText => {
+            // The leading is part of the text state and persists across text objects.
+            Operation::BeginText => {
                 log::debug!("reset text state");
                 state.font_size = 0.;
-                state.leading = 0.;
-                state.y = 0.;
+                state.matrix = Transform::IDENTITY;
+                state.line_matrix = Transform::IDENTITY;
             }
-            Op::Leading { leading: amount } => {
+            Operation::Leading(amount) => {
                 log::debug!("leading: {amount}");
-                state.leading = amount;
+                state.leading = *amount;
             }
-            Op::GraphicsState { ref name } => {
-                if let Some((font, size)) =
-                    font_cache.get_font_from_graphic_state(name, page, resolver)
-                {
+            Operation::GraphicsState(name) => {
+                if let Some((font, size)) = font_cache.get_font_from_graphic_state(name) {
                     log::debug!("graphics state font {name} ({size})");
                     state.font = font;
                     state.font_size = size;
-
-                    if size > max_font_size {
-                        max_font_size = size;
-                    }
                 }
             }
-            Op::TextFont { ref name, size } => {
+            Operation::Font { name, size } => {
                 log::debug!("font {name} ({size})");
                 state.font = font_cache.get_font(name);
-                state.font_size = size;
-
-                if size > max_font_size {
-                    max_font_size = size;
-                }
+                state.font_size = *size;
+            }
+            Operation::MoveText { x, y } => state.translate(*x, *y),
+            Operation::TextMatrix(matrix) => {
+                log::debug!("set text matrix {matrix:?}");
+                state.matrix = *matrix;
+                state.line_matrix = state.matrix;
             }
-            // `Td`, `TD`
-            Op::MoveTextPosition { translation } => {
-                translate_text(&mut state, translation.y);
+            Operation::CharSpacing(char_space) => {
+                log::debug!("character spacing: {char_space}");
+                state.char_spacing = *char_space;
             }
-            // `Tm`
-            Op::SetTextMatrix { matrix } => {
-                state.y = matrix.f;
-                log::debug!("set y = {}", state.y);
+            Operation::TextRise(rise) => {
+                log::debug!("text rise: {rise}");
+                state.rise = *rise;
             }
-            // `T*`
-            Op::TextNewline => {
-                let dy = -state.leading;
-                translate_text(&mut state, dy);
+            // Only changes the width of space characters, which are kept as text.
+            Operation::WordSpacing(word_space) => log::debug!("word spacing: {word_space}"),
+            Operation::NextLine => {
+                state.translate(0., -state.effective_leading());
             }
-            // `Tj`
-            Op::TextDraw { text } => {
-                let text = PositionedText::from_text(&text, &state);
-                log::debug!("write {text}");
-                positioned_text.push(text);
+            Operation::Text(text) => {
+                let origin = graphics.to_page(state.origin());
+                let text = PositionedText::from_text(
+                    text, &state, &graphics, statistics, backend, &arena,
+                )?;
+                if is_visible(&text, origin, &graphics) {
+                    coalesce(&mut positioned_text, text, &mut glyph);
+                } else {
+                    // Glyphs on either side of hidden text are not next to each other.
+                    glyph = None;
+                }
             }
-            Op::TextDrawAdjusted { array } => {
-                let text = PositionedText::from_text_array(&array, &state);
-                log::debug!("write {text}");
-                positioned_text.push(PositionedText::from_text_array(&array, &state));
+            Operation::AdjustedText(array) => {
+                let origin = graphics.to_page(state.origin());
+                let text = PositionedText::from_text_array(
+                    array, &state, &graphics, statistics, backend, &arena,
+                )?;
+                if is_visible(&text, origin, &graphics) {
+                    coalesce(&mut positioned_text, text, &mut glyph);
+                } else {
+                    // Glyphs on either side of hidden text are not next to each other.
+                    glyph = None;
+                }
             }
-            operation => log::trace!("skipping operation {operation:?}"),
+            Operation::Image => pictures.extend(Picture::drawn(&graphics)),
+            // Already applied to the graphics state.
+            _ => {}
         }
     }
 
-    log::info!("max font size: {max_font_size}");
-    Ok((
-        positioned_text
-            .into_iter()
-            .filter(|text| abs_diff_eq!(text.font_size, max_font_size))
-            .collect(),
-        max_font_size,
+    Ok(PageText::new(
+        &positioned_text,
+        &pictures,
+        graphics.size(),
+        identifiers,
     ))
 }
 
-fn translate_text(state: &mut TextState, dy: f32) {
-    if abs_diff_ne!(dy, 0.) {
-        state.y += dy;
-        log::debug!("translate y by {dy}, y = {}", state.y);
+/// The topmost block of text in each of the largest font sizes that might be the title, leaving
+/// out text that can't be part of it.
+///
+/// The `size` of the page as it is displayed is used to leave out running headers and footers and
+/// to find out whether the page is a title page, see [`Candidate::title_page`].
+fn blocks(positioned_text: &[PositionedText], size: Option<(f32, f32)>) -> Vec<TitleBlock> {
+    let page_height = size.map(|(_, height)| height);
+    let text = score::filter(positioned_text, page_height);
+    let sparse = score::is_sparse(&text);
+
+    score::tiers(&text)
+        .into_iter()
+        .map(|font_size| {
+            let block = block(&text, font_size);
+            TitleBlock {
+                page_height,
+                title_page: sparse
+                    && size.is_some_and(|(width, _)| score::is_centered(&block.text, width)),
+                ..block
+            }
+        })
+        .collect()
+}
+
+/// What was found in the text of a page.
+struct PageText {
+    /// The blocks that might be the title.
+    blocks: Vec<TitleBlock>,
+    /// Whether a references section starts on the page. Nothing after it is the title, so the
+    /// blocks below its heading are left out and the pages after it don't need to be parsed.
+    references: bool,
+}
+
+impl PageText {
+    /// Find the title blocks in the text of a page of `size` with `pictures` on it, and scan it
+    /// for `identifiers`.
+    fn new(
+        text: &[PositionedText],
+        pictures: &[Picture],
+        size: Option<(f32, f32)>,
+        identifiers: &mut Identifiers,
+    ) -> Self {
+        identifiers.scan(
+            &text
+                .iter()
+                .map(|text| text.text.as_ref())
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+
+        if identifiers.composer.is_none() {
+            identifiers.composer =
+                size.and_then(|(width, height)| music::find_composer(text, width, height));
+        }
+
+        let references = score::references_start(text);
+
+        Self {
+            blocks: blocks(text, size)
+                .into_iter()
+                .filter(|block| {
+                    references.is_none_or(|start| block.text.iter().any(|text| text.y < start))
+                })
+                .map(|block| block.with_adjacent(pictures))
+                .collect(),
+            references: references.is_some(),
+        }
+    }
+}
+
+/// Text of a page that might be its title.
+struct TitleBlock {
+    text: Vec<PositionedText<'static>>,
+    font_size: f32,
+    /// The height of the displayed page, if it is known.
+    page_height: Option<f32>,
+    /// The next block below the title in a similar font size, see [`Candidate::parallel`].
+    parallel: Option<String>,
+    /// See [`Candidate::recognized`].
+    recognized: bool,
+    /// See [`Candidate::calibration`].
+    calibration: Option<Calibration>,
+    /// The image right above or below the block, which might hold the rest of the title.
+    #[cfg_attr(not(feature = "ocr"), allow(dead_code))]
+    image: Option<Adjacent>,
+    /// See [`Candidate::assembled`].
+    assembled: bool,
+    /// See [`Candidate::title_page`].
+    title_page: bool,
+}
+
+/// Where an image is drawn on a page, in points from the top left corner of the page as it is
+/// displayed.
+#[derive(Clone, Copy, Debug)]
+struct Picture {
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+}
+
+impl Picture {
+    /// Where the image drawn into the unit square of user space of `graphics` ends up, if it is
+    /// visible and might hold part of a title.
+    fn drawn(graphics: &Graphics) -> Option<Self> {
+        let corners =
+            [(0., 0.), (1., 0.), (0., 1.), (1., 1.)].map(|(x, y)| graphics.to_page(Point { x, y }));
+        let center = Point {
+            x: f32::midpoint(corners[0].x, corners[3].x),
+            y: f32::midpoint(corners[0].y, corners[3].y),
+        };
+        if graphics.hides_everything() || !graphics.is_on_page(center) {
+            return None;
+        }
+        let indents = corners.map(|corner| graphics.indent(corner));
+        let depths = corners.map(|corner| graphics.depth(corner));
+        let picture = Self {
+            left: indents.into_iter().fold(f32::INFINITY, f32::min),
+            top: depths.into_iter().fold(f32::INFINITY, f32::min),
+            right: indents.into_iter().fold(f32::NEG_INFINITY, f32::max),
+            bottom: depths.into_iter().fold(f32::NEG_INFINITY, f32::max),
+        };
+        let (width, height) = (picture.right - picture.left, picture.bottom - picture.top);
+        let covered = graphics
+            .size()
+            .map(|(page_width, page_height)| width * height / (page_width * page_height).max(1.));
+        let fits = width >= MIN_IMAGE_SIZE.0
+            && height >= MIN_IMAGE_SIZE.1
+            && covered.is_none_or(|covered| covered <= MAX_IMAGE_AREA);
+
+        fits.then_some(picture)
+    }
+
+    /// The smallest area covering both `self` and `other`.
+    #[cfg(feature = "ocr")]
+    const fn union(self, other: Self) -> Self {
+        Self {
+            left: self.left.min(other.left),
+            top: self.top.min(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+
+    /// Whether the line of recognized `text` starts within the image, allowing for half its font
+    /// size of slack as recognized positions are only approximate.
+    #[cfg(feature = "ocr")]
+    fn contains(&self, text: &PositionedText) -> bool {
+        let slack = text.font_size / 2.;
+        (self.left - slack..self.right + slack).contains(&text.x)
+            && (self.top - slack..self.bottom + slack).contains(&text.y)
+    }
+}
+
+/// An image right above or below a block of text.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(not(feature = "ocr"), allow(dead_code))]
+struct Adjacent {
+    picture: Picture,
+    /// Whether the image is above the block, and so holds the beginning of the title.
+    above: bool,
+}
+
+impl TitleBlock {
+    /// Find the image closest to the block among `pictures` that is at most [`IMAGE_GAP`] above or
+    /// below it and overlaps it horizontally.
+    fn with_adjacent(self, pictures: &[Picture]) -> Self {
+        let top = self
+            .text
+            .iter()
+            .map(|text| text.y - text.scaled_font_size)
+            .fold(f32::INFINITY, f32::min);
+        // Leaving room for descenders.
+        let bottom = self
+            .text
+            .iter()
+            .map(|text| text.scaled_font_size.mul_add(0.25, text.y))
+            .fold(f32::NEG_INFINITY, f32::max);
+        let left = self
+            .text
+            .iter()
+            .map(|text| text.x)
+            .fold(f32::INFINITY, f32::min);
+        let right = self
+            .text
+            .iter()
+            .map(PositionedText::end)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let max_gap = self.font_size * IMAGE_GAP;
+
+        let image = pictures
+            .iter()
+            .filter(|picture| picture.left < right && picture.right > left)
+            .filter_map(|picture| {
+                let (gap, above) = if picture.bottom <= top {
+                    (top - picture.bottom, true)
+                } else if picture.top >= bottom {
+                    (picture.top - bottom, false)
+                } else {
+                    return None;
+                };
+                (gap <= max_gap).then_some((gap, above, *picture))
+            })
+            .min_by(|(a, ..), (b, ..)| a.total_cmp(b))
+            .map(|(_, above, picture)| Adjacent { picture, above });
+
+        Self { image, ..self }
+    }
+}
+
+/// Whether `text` is set in `font_size`.
+fn is_size(text: &PositionedText, font_size: f32) -> bool {
+    relative_eq!(
+        text.font_size,
+        font_size,
+        max_relative = FONT_SIZE_TOLERANCE
+    )
+}
+
+/// Keep only the topmost block of text set in `font_size`.
+///
+/// Lines belong to the same block if they are at most [`BLOCK_GAP`] times the leading apart. Text
+/// in the same font size further down the page is not part of the title, but the next block in a
+/// similar but not larger size is kept as a possible parallel title.
+fn block(positioned_text: &[PositionedText], font_size: f32) -> TitleBlock {
+    log::info!("font size: {font_size}");
+
+    let in_size = |text: &PositionedText| is_size(text, font_size);
+    let mut block: Vec<_> = positioned_text
+        .iter()
+        .filter(|text| in_size(text))
+        .collect();
+    let bottom = block_bottom(&block);
+    block.retain(|text| {
+        let inside = text.y <= bottom;
+        if !inside {
+            log::debug!("skip {text} outside of the title block");
+        }
+        inside
+    });
+
+    // Keep the text in the order it was drawn, so subscripts and superscripts stay where they are.
+    let text = positioned_text
+        .iter()
+        .filter_map(|text| {
+            if in_size(text) {
+                return (text.y <= bottom).then(|| text.clone().into_owned());
+            }
+
+            let script = script(text, &block)?;
+            log::debug!("{script:?}script {text}");
+            Some(
+                PositionedText {
+                    script: Some(script),
+                    ..text.clone()
+                }
+                .into_owned(),
+            )
+        })
+        .collect();
+
+    let mut parallel: Vec<_> = positioned_text
+        .iter()
+        .filter(|text| {
+            text.y > bottom
+                && text.font_size >= font_size * (1. - PARALLEL_FONT_SIZE_TOLERANCE)
+                && (text.font_size <= font_size || in_size(text))
+        })
+        .collect();
+    let parallel_bottom = block_bottom(&parallel);
+    parallel.retain(|text| text.y <= parallel_bottom);
+
+    TitleBlock {
+        text,
+        font_size,
+        page_height: None,
+        recognized: false,
+        calibration: None,
+        image: None,
+        assembled: false,
+        title_page: false,
+        parallel: (!parallel.is_empty()).then(|| {
+            parallel
+                .iter()
+                .map(|text| text.text.as_ref())
+                .collect::<Vec<_>>()
+                .join(" ")
+        }),
+    }
+}
+
+/// Whether `text` is a subscript or superscript in one of the `lines`, judging by its size and how
+/// far it is moved from their baselines.
+fn script(text: &PositionedText, lines: &[&PositionedText]) -> Option<Script> {
+    let baseline = text.y - text.raised;
+    let line = lines
+        .iter()
+        .min_by(|a, b| (a.y - baseline).abs().total_cmp(&(b.y - baseline).abs()))?;
+    if line.scaled_font_size <= 0.
+        || !SCRIPT_FONT_SIZES.contains(&(text.scaled_font_size / line.scaled_font_size))
+    {
+        return None;
+    }
+
+    let offset = (line.y - baseline) / line.scaled_font_size;
+    if SCRIPT_OFFSETS.contains(&offset) {
+        Some(Script::Super)
+    } else if SCRIPT_OFFSETS.contains(&-offset) {
+        Some(Script::Sub)
+    } else {
+        None
+    }
+}
+
+/// Join the text of a title, gluing subscripts and superscripts to the text they belong to (`H`,
+/// `2`, `O` becomes `H2O`). Numeric superscripts are marked with a caret (`10^6`), superscripts at
+/// the end of the title or made of symbols are footnote marks and dropped.
+fn flatten(text: &[PositionedText]) -> String {
+    let last = text.iter().rposition(|text| text.script.is_none());
+    let mut flattened = String::new();
+    let mut previous: Option<&PositionedText> = None;
+
+    for (index, text) in text.iter().enumerate() {
+        let Some(script) = text.script else {
+            // Text drawn right after a script without moving further continues the word.
+            let glued = previous.is_some_and(|previous| {
+                previous.script.is_some()
+                    && text.x < GAP.mul_add(text.scaled_font_size, previous.end())
+            });
+            if !flattened.is_empty() && !glued {
+                flattened.push(' ');
+            }
+            flattened.push_str(&text.text);
+            previous = Some(text);
+            continue;
+        };
+
+        let script_text = text.text.trim();
+        if script == Script::Super
+            && (last.is_none_or(|last| index > last)
+                || !script_text
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || is_sign(c)))
+        {
+            log::debug!("drop footnote mark {script_text:?}");
+            continue;
+        }
+        if script == Script::Super
+            && script_text.starts_with(|c: char| c.is_ascii_digit() || is_sign(c))
+        {
+            flattened.push('^');
+        }
+        flattened.push_str(script_text);
+        previous = Some(text);
+    }
+
+    flattened
+}
+
+/// Whether `c` is a plus or minus sign of a number.
+const fn is_sign(c: char) -> bool {
+    matches!(c, '-' | '+' | '−')
+}
+
+/// Find the bottom of the topmost block of `text`, which is infinitely far down if there is none.
+fn block_bottom(text: &[&PositionedText]) -> f32 {
+    let mut lines: Vec<_> = text.iter().map(|text| (text.y, text.leading)).collect();
+    lines.sort_by(|a, b| a.0.total_cmp(&b.0));
+    lines
+        .windows(2)
+        .find(|lines| lines[1].0 - lines[0].0 > lines[0].1.abs() * BLOCK_GAP)
+        .map(|lines| lines[0].0)
+        .or_else(|| lines.last().map(|line| line.0))
+        .unwrap_or(f32::INFINITY)
+}
+
+/// Add `text` to the `drawn` text of a page, or append it to the last piece if both are glyphs
+/// drawn one by one on the same line. Some documents draw every glyph with an operation of its
+/// own, which would otherwise leave thousands of pieces that are joined with spaces between every
+/// glyph. `glyph` is where the last glyph drawn on its own starts and what it is.
+fn coalesce<'a>(
+    drawn: &mut bumpalo::collections::Vec<'_, PositionedText<'a>>,
+    text: PositionedText<'a>,
+    glyph: &mut Option<(f32, char)>,
+) {
+    let mut chars = text.text.chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        *glyph = None;
+        drawn.push(text);
+        return;
+    };
+
+    if let (Some(previous), Some((x, previous_glyph))) = (drawn.last_mut(), *glyph) {
+        let width = glyph_width(previous_glyph);
+        let gap = (text.x - x) / text.scaled_font_size - width;
+        if same_line(previous, &text) && gap > -width && gap <= MAX_GLYPH_GAP {
+            let previous_text = previous.text.to_mut();
+            if gap > GAP && !c.is_whitespace() && !previous_text.ends_with(char::is_whitespace) {
+                previous_text.push(' ');
+            }
+            previous_text.push(c);
+            *glyph = Some((text.x, c));
+            return;
+        }
+    }
+
+    *glyph = Some((text.x, c));
+    drawn.push(text);
+}
+
+/// Whether `a` and `b` are set on the same line in the same size.
+fn same_line(a: &PositionedText, b: &PositionedText) -> bool {
+    !a.slanted
+        && !b.slanted
+        && relative_eq!(a.y, b.y)
+        && relative_eq!(a.raised, b.raised)
+        && relative_eq!(a.font_size, b.font_size)
+        && relative_eq!(a.scaled_font_size, b.scaled_font_size)
+}
+
+/// Roughly how wide the glyph of `c` is in common fonts, relative to the font size. Only used to
+/// tell gaps between words apart from gaps between glyphs.
+fn glyph_width(c: char) -> f32 {
+    if "fijlrtI.,:;'!|()[]".contains(c) {
+        0.3
+    } else if "mwMW@%".contains(c) {
+        0.9
+    } else if c.is_uppercase() {
+        0.7
+    } else {
+        0.5
+    }
+}
+
+/// Whether `text` starting at `origin` in page space can be seen, logging why if it can't.
+fn is_visible(text: &PositionedText, origin: Point, graphics: &Graphics) -> bool {
+    if graphics.hides_everything() {
+        log::debug!("skip clipped {text}");
+        false
+    } else if !graphics.is_on_page(origin) {
+        log::debug!("skip off-page {text}");
+        false
+    } else {
+        log::debug!("write {text}");
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(text: &str, page: usize, y: f32, font_size: f32) -> Candidate {
+        Candidate {
+            text: text.to_string(),
+            page,
+            y,
+            position: None,
+            font_size,
+            score: 0.5,
+            parallel: None,
+            spans: Vec::new(),
+            recognized: false,
+            calibration: None,
+            assembled: false,
+            title_page: false,
+        }
+    }
+
+    #[test]
+    fn higher_score_ranks_first() {
+        let mut better = candidate("Title", 1, 500., 10.);
+        better.score = 0.8;
+        let worse = candidate("Other Title", 0, 100., 20.);
+
+        assert_eq!(better.rank(&worse), Ordering::Less);
+        assert_eq!(worse.rank(&better), Ordering::Greater);
+    }
+
+    #[test]
+    fn larger_font_breaks_score_ties() {
+        let larger = candidate("Title", 1, 500., 20.);
+        let smaller = candidate("Other Title", 0, 100., 10.);
+
+        assert_eq!(larger.rank(&smaller), Ordering::Less);
+    }
+
+    #[test]
+    fn font_sizes_within_the_tolerance_tie() {
+        // Sizes 1.5% apart are still the same, 5% apart they are not.
+        let within = candidate("Title", 1, 100., 10.15);
+        let outside = candidate("Title", 1, 100., 10.5);
+        let earlier = candidate("Title", 0, 100., 10.);
+
+        // Within the tolerance the earlier page wins despite the smaller font.
+        assert_eq!(earlier.rank(&within), Ordering::Less);
+        // Outside of it the larger font wins.
+        assert_eq!(outside.rank(&earlier), Ordering::Less);
+    }
+
+    #[test]
+    fn earlier_page_breaks_font_size_ties() {
+        let first = candidate("Title", 0, 500., 10.);
+        let second = candidate("Title", 1, 100., 10.);
+
+        assert_eq!(first.rank(&second), Ordering::Less);
+    }
+
+    #[test]
+    fn higher_position_breaks_page_ties() {
+        let top = candidate("Title", 0, 100., 10.);
+        let bottom = candidate("Title", 0, 500., 10.);
+
+        assert_eq!(top.rank(&bottom), Ordering::Less);
+    }
+
+    #[test]
+    fn plausible_then_longer_text_breaks_position_ties() {
+        let short = candidate("Ti", 0, 100., 10.);
+        let plausible = candidate("Title", 0, 100., 10.);
+        let longer = candidate("A Longer Title", 0, 100., 10.);
+        let too_long = candidate(&"Title ".repeat(100), 0, 100., 10.);
+
+        assert_eq!(plausible.rank(&short), Ordering::Less);
+        assert_eq!(plausible.rank(&too_long), Ordering::Less);
+        assert_eq!(longer.rank(&plausible), Ordering::Less);
+        assert_eq!(plausible.rank(&plausible.clone()), Ordering::Equal);
     }
 }
@@ -0,0 +1,124 @@
+use std::borrow::Cow;
+
+use pdf::content::Point;
+
+pub use self::{lopdf_rs::Lopdf, pdf_rs::PdfRs};
+use super::{
+    error::Error,
+    font::{Coverage, FontCache, FontInfo},
+    geometry::Transform,
+    graphics::Graphics,
+    metadata::Info,
+    stage::Timings,
+};
+
+mod lopdf_rs;
+mod pdf_rs;
+
+/// A parser that documents can be read with.
+///
+/// Pages are handed out as a stream of the operations that matter for finding the title: Those
+/// that change the graphics or text state and those that draw text. Every backend decodes text
+/// with the fonts of its own pages.
+pub trait Backend {
+    /// The number of pages of the document.
+    fn page_count(&self) -> u32;
+
+    /// Parse the page with the zero-based index `index`. Content streams with more than
+    /// `max_length` encoded bytes are skipped. How long loading the fonts and parsing the
+    /// operations took is added to `timings`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the page or its content could not be parsed.
+    fn page(
+        &self,
+        index: u32,
+        max_length: Option<usize>,
+        timings: &mut Timings,
+    ) -> Result<Page, Error>;
+
+    /// Decode `text` set in `font`, counting the glyphs that could not be mapped in `coverage`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the text could not be decoded with the font.
+    fn decode<'t>(
+        &self,
+        font: &FontInfo,
+        text: &'t [u8],
+        coverage: &mut Coverage,
+    ) -> Result<Cow<'t, str>, Error>;
+
+    /// The XMP packet in the metadata stream of the document, if it has one that can be read.
+    fn xmp(&self) -> Option<String>;
+
+    /// The document information dictionary, if the document has one.
+    fn info(&self) -> Option<Info>;
+}
+
+/// A parsed page.
+pub struct Page {
+    /// The graphics state at the start of the content of the page.
+    pub graphics: Graphics,
+    pub fonts: FontCache,
+    pub operations: Vec<Operation>,
+}
+
+/// An operation of a content stream, see [`Backend`].
+#[derive(Clone, Debug)]
+pub enum Operation {
+    /// `q`
+    Save,
+    /// `Q`
+    Restore,
+    /// `cm`
+    Transform(Transform),
+    /// `m`, `l` and the curves, which are bounded by their control points.
+    Path(Vec<Point>),
+    /// `re`
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+    /// `W`, `W*`
+    Clip,
+    /// Any operation that ends a path, like `n`, `S` or `f`.
+    Paint,
+    /// `Do` with an image, and inline images, which fill the unit square of user space.
+    Image,
+    /// `BT`
+    BeginText,
+    /// `TL`
+    Leading(f32),
+    /// `gs`
+    GraphicsState(String),
+    /// `Tf`
+    Font { name: String, size: f32 },
+    /// `Td`, and `TD` after setting the leading
+    MoveText { x: f32, y: f32 },
+    /// `Tm`
+    TextMatrix(Transform),
+    /// `Tc`
+    CharSpacing(f32),
+    /// `Ts`
+    TextRise(f32),
+    /// `Tw`
+    WordSpacing(f32),
+    /// `T*`
+    NextLine,
+    /// `Tj`, and `'` and `"` after moving to the next line
+    Text(Vec<u8>),
+    /// `TJ`
+    AdjustedText(Vec<Adjusted>),
+}
+
+/// A piece of text drawn with `TJ`.
+#[derive(Clone, Debug)]
+pub enum Adjusted {
+    Text(Vec<u8>),
+    /// How far the next glyph is moved to the left, in thousandths of an em.
+    Spacing(f32),
+}
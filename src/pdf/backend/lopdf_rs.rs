@@ -0,0 +1,564 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+use lopdf::{content::Content, Dictionary, Document, LoadOptions, Object, ObjectId};
+use pdf::{
+    content::{Matrix, Point},
+    encoding::BaseEncoding,
+    object::NoResolve,
+    primitive::Name,
+};
+
+use super::{Adjusted, Backend, Operation, Page};
+use crate::pdf::{
+    error::Error,
+    font::{Coverage, FontCache, FontInfo},
+    geometry::BoundingBox,
+    graphics::Graphics,
+    metadata::Info,
+    stage::{Stage, Timings},
+    stream,
+};
+
+/// How many parents of a page are searched for inherited attributes, which keeps cycles in broken
+/// page trees from hanging.
+const MAX_PAGE_TREE_DEPTH: usize = 32;
+
+/// The secondary backend, which reads documents with lopdf.
+///
+/// It is more lenient with broken cross-reference tables and object streams than the `pdf` crate,
+/// but always reads the whole document and parses every `ToUnicode` map of a page, even in
+/// low-memory mode. CID fonts without a `ToUnicode` map are not decoded through their embedded font
+/// program.
+pub struct Lopdf {
+    document: Document,
+    pages: Vec<ObjectId>,
+}
+
+impl Lopdf {
+    /// Load a document from `data`, decrypting it with `password` if the empty one doesn't work.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the document could not be loaded or decrypted.
+    pub fn load(data: &[u8], password: Option<&str>) -> Result<Self, Error> {
+        let document = match (Document::load_mem(data), password) {
+            (Err(_), Some(password)) => {
+                Document::load_mem_with_options(data, LoadOptions::with_password(password))
+            }
+            (result, _) => result,
+        }?;
+        let pages = document.get_pages().into_values().collect();
+
+        Ok(Self { document, pages })
+    }
+
+    /// Look up `key` in the dictionary of the page `id`, or in one of its parents if the page
+    /// inherits it.
+    fn inherited(&self, mut id: ObjectId, key: &[u8]) -> Option<&Object> {
+        for _ in 0..MAX_PAGE_TREE_DEPTH {
+            let dictionary = self.document.get_dictionary(id).ok()?;
+            if let Ok(value) = dictionary.get_deref(key, &self.document) {
+                return Some(value);
+            }
+            id = dictionary
+                .get(b"Parent")
+                .and_then(Object::as_reference)
+                .ok()?;
+        }
+
+        None
+    }
+
+    fn graphics(&self, id: ObjectId) -> Graphics {
+        let rectangle = |key: &[u8]| {
+            let array = self.inherited(id, key)?.as_array().ok()?;
+            let [x1, y1, x2, y2] = array.as_slice() else {
+                return None;
+            };
+            Some(BoundingBox::new(
+                x1.as_float().ok()?,
+                y1.as_float().ok()?,
+                x2.as_float().ok()?,
+                y2.as_float().ok()?,
+            ))
+        };
+        // Only the part of the media box inside the crop box is visible.
+        let visible = match (rectangle(b"MediaBox"), rectangle(b"CropBox")) {
+            (Some(media_box), Some(crop_box)) => Some(media_box.intersection(&crop_box)),
+            (media_box, _) => media_box,
+        };
+        let rotation = self
+            .inherited(id, b"Rotate")
+            .and_then(|rotate| rotate.as_i64().ok())
+            .and_then(|rotate| i32::try_from(rotate).ok())
+            .unwrap_or(0);
+
+        Graphics::new(visible, rotation)
+    }
+
+    /// The resource dictionaries of the page `id` and its parents.
+    fn resources(&self, id: ObjectId) -> Vec<&Dictionary> {
+        let Ok((resources, parents)) = self.document.get_page_resources(id) else {
+            return Vec::new();
+        };
+
+        resources
+            .into_iter()
+            .chain(
+                parents
+                    .into_iter()
+                    .filter_map(|id| self.document.get_dictionary(id).ok()),
+            )
+            .collect()
+    }
+
+    /// The names of the images among the external objects of the page `id`.
+    fn images(&self, id: ObjectId) -> HashSet<Vec<u8>> {
+        self.resources(id)
+            .into_iter()
+            .filter_map(|resources| self.dictionary(resources, b"XObject"))
+            .flat_map(Dictionary::iter)
+            .filter(|(_, xobject)| {
+                self.resolve(xobject)
+                    .and_then(|xobject| xobject.as_stream().ok())
+                    .and_then(|xobject| xobject.dict.get(b"Subtype").ok())
+                    .and_then(|subtype| subtype.as_name().ok())
+                    .is_some_and(|subtype| subtype == b"Image")
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Load the fonts of the resources of the page `id` and its parents.
+    fn fonts(&self, id: ObjectId) -> FontCache {
+        let mut font_cache = FontCache::default();
+        let resources = self.resources(id);
+
+        let mut fonts = HashMap::new();
+        for (name, font) in resources
+            .iter()
+            .rev()
+            .filter_map(|resources| self.dictionary(resources, b"Font"))
+            .flat_map(Dictionary::iter)
+        {
+            if let Some(font) = self.resolve(font).and_then(|font| font.as_dict().ok()) {
+                fonts.insert(String::from_utf8_lossy(name).into_owned(), font);
+            }
+        }
+        for (name, font) in fonts {
+            match self.font(font) {
+                Ok(font_info) if font_info.name().is_none() => {
+                    let font_info = font_info.named(&name);
+                    font_cache.insert(name, font_info);
+                }
+                Ok(font_info) => font_cache.insert(name, font_info),
+                Err(err) => {
+                    log::info!("Unable to add font: {err}");
+                    font_cache.failed.insert(base_font(font).unwrap_or(name));
+                }
+            }
+        }
+
+        for (name, state) in resources
+            .iter()
+            .rev()
+            .filter_map(|resources| self.dictionary(resources, b"ExtGState"))
+            .flat_map(Dictionary::iter)
+        {
+            let Some(font) = self
+                .resolve(state)
+                .and_then(|state| state.as_dict().ok())
+                .and_then(|state| state.get(b"Font").ok())
+                .and_then(|font| font.as_array().ok())
+            else {
+                continue;
+            };
+            let [font, size] = font.as_slice() else {
+                continue;
+            };
+            let font = self
+                .resolve(font)
+                .and_then(|font| font.as_dict().ok())
+                .and_then(|font| self.font(font).ok())
+                .unwrap_or_default();
+            font_cache.insert_graphics_state(
+                String::from_utf8_lossy(name).into_owned(),
+                font,
+                size.as_float().unwrap_or_default(),
+            );
+        }
+
+        font_cache
+    }
+
+    /// The decoder for the font dictionary `font`, named after its base font.
+    fn font(&self, font: &Dictionary) -> Result<FontInfo, Error> {
+        let decoder = self.decoder(font)?;
+
+        Ok(match base_font(font) {
+            Some(name) => decoder.named(&name),
+            None => decoder,
+        })
+    }
+
+    fn decoder(&self, font: &Dictionary) -> Result<FontInfo, Error> {
+        let cid = font
+            .get(b"Subtype")
+            .and_then(Object::as_name)
+            .is_ok_and(|subtype| subtype == b"Type0");
+        let to_unicode = font
+            .get_deref(b"ToUnicode", &self.document)
+            .and_then(Object::as_stream)
+            .and_then(lopdf::Stream::get_plain_content)
+            .ok();
+        if to_unicode.is_some() || cid {
+            return Ok(FontInfo::from_to_unicode(to_unicode.as_deref(), cid));
+        }
+
+        let missing = || Error::MissingEncoding(Name::from(base_font(font).unwrap_or_default()));
+        let encoding = font
+            .get_deref(b"Encoding", &self.document)
+            .map_err(|_| missing())?;
+        match encoding {
+            Object::Name(name) => FontInfo::from_encoding(&base_encoding(name), HashMap::new()),
+            Object::Dictionary(encoding) => {
+                let base = encoding
+                    .get(b"BaseEncoding")
+                    .and_then(Object::as_name)
+                    .map_or(BaseEncoding::None, base_encoding);
+                FontInfo::from_encoding(&base, self.differences(encoding))
+            }
+            _ => Err(missing()),
+        }
+    }
+
+    /// The glyph names of the codes in the `Differences` array of an encoding dictionary, which
+    /// lists a code followed by the names of the glyphs from that code on.
+    fn differences(&self, encoding: &Dictionary) -> HashMap<u32, String> {
+        let mut differences = HashMap::new();
+        let Some(array) = encoding
+            .get_deref(b"Differences", &self.document)
+            .ok()
+            .and_then(|differences| differences.as_array().ok())
+        else {
+            return differences;
+        };
+
+        let mut code = 0;
+        for element in array {
+            match element {
+                Object::Integer(start) => code = u32::try_from(*start).unwrap_or_default(),
+                Object::Name(name) => {
+                    differences.insert(code, String::from_utf8_lossy(name).into_owned());
+                    code += 1;
+                }
+                _ => {}
+            }
+        }
+
+        differences
+    }
+
+    fn resolve<'a>(&'a self, object: &'a Object) -> Option<&'a Object> {
+        self.document
+            .dereference(object)
+            .ok()
+            .map(|(_, object)| object)
+    }
+
+    fn dictionary<'a>(&'a self, dictionary: &'a Dictionary, key: &[u8]) -> Option<&'a Dictionary> {
+        dictionary
+            .get_deref(key, &self.document)
+            .and_then(Object::as_dict)
+            .ok()
+    }
+
+    /// The decoded and concatenated content streams of the page `id`, see [`stream::concatenate`].
+    fn content(&self, id: ObjectId, max_length: Option<usize>) -> Result<Vec<u8>, Error> {
+        let parts: Vec<_> = self
+            .document
+            .get_page_contents(id)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, id)| {
+                let stream = self.document.get_object(id).and_then(Object::as_stream);
+                let part = stream.and_then(|stream| {
+                    if max_length.is_some_and(|max_length| stream.content.len() > max_length) {
+                        log::warn!(
+                            "skipping content stream {} with {} bytes",
+                            index + 1,
+                            stream.content.len()
+                        );
+                        return Ok(None);
+                    }
+                    stream.get_plain_content().map(Some)
+                });
+                part.inspect_err(|err| log::warn!("skipping content stream {}: {err}", index + 1))
+                    .ok()
+                    .flatten()
+            })
+            .collect();
+        if parts.is_empty() {
+            return Err(Error::NoContent);
+        }
+
+        Ok(stream::concatenate(&parts))
+    }
+}
+
+impl Backend for Lopdf {
+    fn page_count(&self) -> u32 {
+        u32::try_from(self.pages.len()).unwrap_or(u32::MAX)
+    }
+
+    fn page(
+        &self,
+        index: u32,
+        max_length: Option<usize>,
+        timings: &mut Timings,
+    ) -> Result<Page, Error> {
+        let id = *self
+            .pages
+            .get(index as usize)
+            .ok_or(Error::Lopdf(lopdf::Error::PageNumberNotFound(index + 1)))?;
+        let operations = timings.time(Stage::Operations, || {
+            let content = Content::decode(&self.content(id, max_length)?)?;
+            let images = self.images(id);
+            Ok::<_, Error>(
+                content
+                    .operations
+                    .iter()
+                    .flat_map(|operation| operations(operation, &images))
+                    .collect(),
+            )
+        })?;
+
+        Ok(Page {
+            graphics: self.graphics(id),
+            fonts: timings.time(Stage::Fonts, || self.fonts(id)),
+            operations,
+        })
+    }
+
+    fn decode<'t>(
+        &self,
+        font: &FontInfo,
+        text: &'t [u8],
+        coverage: &mut Coverage,
+    ) -> Result<Cow<'t, str>, Error> {
+        // The decoders of this backend are built right away, so nothing needs to be resolved.
+        font.decode(text, coverage, &NoResolve)
+    }
+
+    fn xmp(&self) -> Option<String> {
+        let data = self
+            .document
+            .catalog()
+            .ok()?
+            .get_deref(b"Metadata", &self.document)
+            .and_then(Object::as_stream)
+            .and_then(lopdf::Stream::get_plain_content)
+            .ok()?;
+
+        Some(String::from_utf8_lossy(&data).into_owned())
+    }
+
+    fn info(&self) -> Option<Info> {
+        let info = self
+            .document
+            .trailer
+            .get_deref(b"Info", &self.document)
+            .and_then(Object::as_dict)
+            .ok()?;
+        let text = |key: &[u8]| {
+            info.get_deref(key, &self.document)
+                .and_then(lopdf::decode_text_string)
+                .ok()
+        };
+
+        Some(Info {
+            title: text(b"Title"),
+            author: text(b"Author"),
+            subject: text(b"Subject"),
+            keywords: text(b"Keywords"),
+            creator: text(b"Creator"),
+            producer: text(b"Producer"),
+            creation_date: info
+                .get_deref(b"CreationDate", &self.document)
+                .and_then(Object::as_str)
+                .ok()
+                .and_then(date),
+        })
+    }
+}
+
+fn base_font(font: &Dictionary) -> Option<String> {
+    font.get(b"BaseFont")
+        .and_then(Object::as_name)
+        .ok()
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+}
+
+fn base_encoding(name: &[u8]) -> BaseEncoding {
+    match name {
+        b"StandardEncoding" => BaseEncoding::StandardEncoding,
+        b"SymbolEncoding" => BaseEncoding::SymbolEncoding,
+        b"MacRomanEncoding" => BaseEncoding::MacRomanEncoding,
+        b"WinAnsiEncoding" => BaseEncoding::WinAnsiEncoding,
+        b"MacExpertEncoding" => BaseEncoding::MacExpertEncoding,
+        b"Identity-H" => BaseEncoding::IdentityH,
+        other => BaseEncoding::Other(String::from_utf8_lossy(other).into_owned()),
+    }
+}
+
+/// The year, month and day of a date like `D:20240131120000Z`, with a missing month or day set to
+/// 0.
+fn date(date: &[u8]) -> Option<(u16, u8, u8)> {
+    let date = std::str::from_utf8(date).ok()?;
+    let date = date.strip_prefix("D:").unwrap_or(date);
+    let part = |range: std::ops::Range<usize>| date.get(range)?.parse().ok();
+
+    Some((
+        part(0..4)?,
+        part(4..6)
+            .and_then(|month| u8::try_from(month).ok())
+            .unwrap_or_default(),
+        part(6..8)
+            .and_then(|day| u8::try_from(day).ok())
+            .unwrap_or_default(),
+    ))
+}
+
+/// The image drawn by the `Do` `operation`, if it draws one of the `images` and not a form.
+fn image(operation: &lopdf::content::Operation, images: &HashSet<Vec<u8>>) -> Option<Operation> {
+    operation
+        .operands
+        .first()
+        .and_then(|name| name.as_name().ok())
+        .filter(|name| images.contains(*name))
+        .map(|_| Operation::Image)
+}
+
+/// The operations of this backend an operation of a content stream stands for. `images` are the
+/// names of the images that can be drawn with `Do`.
+fn operations(operation: &lopdf::content::Operation, images: &HashSet<Vec<u8>>) -> Vec<Operation> {
+    let numbers: Vec<f32> = operation
+        .operands
+        .iter()
+        .filter_map(|operand| operand.as_float().ok())
+        .collect();
+    let points = || {
+        numbers
+            .chunks_exact(2)
+            .map(|point| Point {
+                x: point[0],
+                y: point[1],
+            })
+            .collect()
+    };
+    let matrix = || {
+        let matrix: [f32; 6] = numbers.as_slice().try_into().ok()?;
+        Some(
+            Matrix {
+                a: matrix[0],
+                b: matrix[1],
+                c: matrix[2],
+                d: matrix[3],
+                e: matrix[4],
+                f: matrix[5],
+            }
+            .into(),
+        )
+    };
+    let number = || numbers.first().copied();
+    let name = || {
+        operation
+            .operands
+            .iter()
+            .find_map(|operand| operand.as_name().ok())
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+    };
+    let text = || {
+        operation
+            .operands
+            .iter()
+            .find_map(|operand| operand.as_str().ok())
+            .map(<[u8]>::to_vec)
+    };
+
+    let operations = match operation.operator.as_str() {
+        "q" => vec![Some(Operation::Save)],
+        "Q" => vec![Some(Operation::Restore)],
+        "cm" => vec![matrix().map(Operation::Transform)],
+        "m" | "l" | "c" | "v" | "y" => vec![Some(Operation::Path(points()))],
+        "re" => vec![match *numbers.as_slice() {
+            [x, y, width, height] => Some(Operation::Rect {
+                x,
+                y,
+                width,
+                height,
+            }),
+            _ => None,
+        }],
+        "W" | "W*" => vec![Some(Operation::Clip)],
+        "n" | "S" | "s" | "f" | "F" | "f*" | "B" | "B*" | "b" | "b*" => {
+            vec![Some(Operation::Paint)]
+        }
+        "Do" => vec![image(operation, images)],
+        "BI" => vec![Some(Operation::Image)],
+        "BT" => vec![Some(Operation::BeginText)],
+        "TL" => vec![number().map(Operation::Leading)],
+        "gs" => vec![name().map(Operation::GraphicsState)],
+        "Tf" => vec![name()
+            .zip(numbers.last().copied())
+            .map(|(name, size)| Operation::Font { name, size })],
+        "Td" | "TD" => match *numbers.as_slice() {
+            [x, y] => {
+                let leading = (operation.operator == "TD").then_some(Operation::Leading(-y));
+                vec![leading, Some(Operation::MoveText { x, y })]
+            }
+            _ => Vec::new(),
+        },
+        "Tm" => vec![matrix().map(Operation::TextMatrix)],
+        "Tc" => vec![number().map(Operation::CharSpacing)],
+        "Ts" => vec![number().map(Operation::TextRise)],
+        "Tw" => vec![number().map(Operation::WordSpacing)],
+        "T*" => vec![Some(Operation::NextLine)],
+        "Tj" => vec![text().map(Operation::Text)],
+        "'" => vec![Some(Operation::NextLine), text().map(Operation::Text)],
+        "\"" => match *numbers.as_slice() {
+            [word_spacing, char_spacing] => vec![
+                Some(Operation::WordSpacing(word_spacing)),
+                Some(Operation::CharSpacing(char_spacing)),
+                Some(Operation::NextLine),
+                text().map(Operation::Text),
+            ],
+            _ => Vec::new(),
+        },
+        "TJ" => vec![operation.operands.first().and_then(adjusted_text)],
+        operator => {
+            log::trace!("skipping operation {operator}");
+            Vec::new()
+        }
+    };
+
+    operations.into_iter().flatten().collect()
+}
+
+/// The operation for the array of a `TJ` operation.
+fn adjusted_text(array: &Object) -> Option<Operation> {
+    Some(Operation::AdjustedText(
+        array
+            .as_array()
+            .ok()?
+            .iter()
+            .filter_map(|element| match element {
+                Object::String(text, _) => Some(Adjusted::Text(text.clone())),
+                element => element.as_float().ok().map(Adjusted::Spacing),
+            })
+            .collect(),
+    ))
+}
@@ -0,0 +1,290 @@
+use std::{borrow::Cow, collections::HashSet, sync::Arc, time::Instant};
+
+use pdf::{
+    any::AnySync,
+    content::{Op, TextDrawAdjusted},
+    file::{Cache, File, Log, Trailer},
+    object::{Catalog, Page as PdfPage, PageRc, Resolve, XObject},
+    primitive::PdfString,
+    PdfError,
+};
+
+use super::{Adjusted, Backend, Operation, Page};
+use crate::pdf::{
+    error::Error,
+    font::{CmapCache, Coverage, FontCache, FontInfo},
+    geometry::BoundingBox,
+    graphics::Graphics,
+    memory::DocumentFile,
+    metadata::Info,
+    stage::{Stage, Timings},
+    stream,
+};
+
+/// A document loaded with the `pdf` crate, either by nomenclate or by the caller with caches of
+/// their own choosing.
+pub trait PdfFile {
+    fn resolver(&self) -> impl Resolve + '_;
+    fn num_pages(&self) -> u32;
+    fn get_page(&self, index: u32) -> pdf::error::Result<PageRc>;
+    fn get_root(&self) -> &Catalog;
+    fn trailer(&self) -> &Trailer;
+}
+
+impl<B, OC, SC, L> PdfFile for File<B, OC, SC, L>
+where
+    B: pdf::backend::Backend,
+    OC: Cache<Result<AnySync, Arc<PdfError>>>,
+    SC: Cache<Result<Arc<[u8]>, Arc<PdfError>>>,
+    L: Log,
+{
+    fn resolver(&self) -> impl Resolve + '_ {
+        Self::resolver(self)
+    }
+
+    fn num_pages(&self) -> u32 {
+        Self::num_pages(self)
+    }
+
+    fn get_page(&self, index: u32) -> pdf::error::Result<PageRc> {
+        Self::get_page(self, index)
+    }
+
+    fn get_root(&self) -> &Catalog {
+        Self::get_root(self)
+    }
+
+    fn trailer(&self) -> &Trailer {
+        &self.trailer
+    }
+}
+
+impl<F: PdfFile> PdfFile for &F {
+    fn resolver(&self) -> impl Resolve + '_ {
+        F::resolver(self)
+    }
+
+    fn num_pages(&self) -> u32 {
+        F::num_pages(self)
+    }
+
+    fn get_page(&self, index: u32) -> pdf::error::Result<PageRc> {
+        F::get_page(self, index)
+    }
+
+    fn get_root(&self) -> &Catalog {
+        F::get_root(self)
+    }
+
+    fn trailer(&self) -> &Trailer {
+        F::trailer(self)
+    }
+}
+
+/// The primary backend, which reads documents with the `pdf` crate.
+pub struct PdfRs<F> {
+    file: F,
+    /// The pages to parse instead of all pages of the document, if only some were given.
+    pages: Option<Vec<PageRc>>,
+    cmaps: Arc<CmapCache>,
+}
+
+impl<B: pdf::backend::Backend> PdfRs<DocumentFile<B>> {
+    pub fn new(file: DocumentFile<B>) -> Self {
+        Self {
+            file,
+            pages: None,
+            cmaps: Arc::default(),
+        }
+    }
+}
+
+impl<'a, F: PdfFile> PdfRs<&'a F> {
+    /// Read the document `file` the caller already loaded, or only its `pages` if given.
+    pub fn borrowed(file: &'a F, pages: Option<Vec<PageRc>>) -> Self {
+        Self {
+            file,
+            pages,
+            cmaps: Arc::default(),
+        }
+    }
+}
+
+impl<F: PdfFile> PdfRs<F> {
+    fn get_page(&self, index: u32) -> Result<PageRc, Error> {
+        match &self.pages {
+            Some(pages) => pages
+                .get(index as usize)
+                .cloned()
+                .ok_or(PdfError::PageOutOfBounds {
+                    page_nr: index,
+                    max: u32::try_from(pages.len()).unwrap_or(u32::MAX),
+                })
+                .map_err(Error::from),
+            None => Ok(self.file.get_page(index)?),
+        }
+    }
+}
+
+impl<F: PdfFile + Sync> Backend for PdfRs<F> {
+    fn page_count(&self) -> u32 {
+        self.pages.as_ref().map_or_else(
+            || self.file.num_pages(),
+            |pages| u32::try_from(pages.len()).unwrap_or(u32::MAX),
+        )
+    }
+
+    fn page(
+        &self,
+        index: u32,
+        max_length: Option<usize>,
+        timings: &mut Timings,
+    ) -> Result<Page, Error> {
+        let resolver = self.file.resolver();
+        let page = self.get_page(index)?;
+        let fonts = timings.time(Stage::Fonts, || {
+            FontCache::from_page(&page, &resolver, &self.cmaps)
+        });
+        let content = page.contents.as_ref().ok_or(Error::NoContent)?;
+        let start = Instant::now();
+        let images: HashSet<_> = page
+            .resources()
+            .map(|resources| {
+                resources
+                    .xobjects
+                    .iter()
+                    .filter(|(_, xobject)| {
+                        resolver
+                            .get(**xobject)
+                            .is_ok_and(|xobject| matches!(*xobject, XObject::Image(_)))
+                    })
+                    .map(|(name, _)| name.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let operations = stream::operations(content, &resolver, max_length)?
+            .into_iter()
+            .filter_map(|operation| match operation {
+                Op::XObject { name } => images.contains(&name).then_some(Operation::Image),
+                Op::InlineImage { .. } => Some(Operation::Image),
+                operation => self::operation(operation),
+            })
+            .collect::<Vec<_>>();
+        timings.add(Stage::Operations, start.elapsed());
+
+        timings.time(Stage::Fonts, || {
+            let (mut names, mut states) = (HashSet::new(), HashSet::new());
+            for operation in &operations {
+                match operation {
+                    Operation::Font { name, .. } => names.insert(name.as_str()),
+                    Operation::GraphicsState(name) => states.insert(name.as_str()),
+                    _ => false,
+                };
+            }
+            fonts.prepare(names, states, || self.file.resolver());
+        });
+
+        Ok(Page {
+            graphics: graphics(&page),
+            fonts,
+            operations,
+        })
+    }
+
+    fn decode<'t>(
+        &self,
+        font: &FontInfo,
+        text: &'t [u8],
+        coverage: &mut Coverage,
+    ) -> Result<Cow<'t, str>, Error> {
+        font.decode(text, coverage, &self.file.resolver())
+    }
+
+    fn xmp(&self) -> Option<String> {
+        let resolver = self.file.resolver();
+        let stream = resolver.get(self.file.get_root().metadata?).ok()?;
+        let data = (*stream).data(&resolver).ok()?;
+
+        Some(String::from_utf8_lossy(&data).into_owned())
+    }
+
+    fn info(&self) -> Option<Info> {
+        let info = self.file.trailer().info_dict.as_ref()?;
+        let text = |text: &Option<PdfString>| text.as_ref().map(PdfString::to_string_lossy);
+
+        Some(Info {
+            title: text(&info.title),
+            author: text(&info.author),
+            subject: text(&info.subject),
+            keywords: text(&info.keywords),
+            creator: text(&info.creator),
+            producer: text(&info.producer),
+            creation_date: info
+                .creation_date
+                .as_ref()
+                .map(|date| (date.year, date.month, date.day)),
+        })
+    }
+}
+
+fn graphics(page: &PdfPage) -> Graphics {
+    // Only the part of the media box inside the crop box is visible.
+    let visible = match (page.media_box(), page.crop_box()) {
+        (Ok(media_box), Ok(crop_box)) => {
+            Some(BoundingBox::from(media_box).intersection(&BoundingBox::from(crop_box)))
+        }
+        (Ok(media_box), Err(_)) => Some(BoundingBox::from(media_box)),
+        (Err(_), _) => None,
+    };
+
+    Graphics::new(visible, page.rotate)
+}
+
+fn operation(operation: Op) -> Option<Operation> {
+    Some(match operation {
+        Op::Save => Operation::Save,
+        Op::Restore => Operation::Restore,
+        Op::Transform { matrix } => Operation::Transform(matrix.into()),
+        Op::MoveTo { p } | Op::LineTo { p } => Operation::Path(vec![p]),
+        Op::CurveTo { c1, c2, p } => Operation::Path(vec![c1, c2, p]),
+        Op::Rect { rect } => Operation::Rect {
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+        },
+        Op::Clip { .. } => Operation::Clip,
+        Op::EndPath | Op::Stroke | Op::Fill { .. } | Op::FillAndStroke { .. } => Operation::Paint,
+        Op::BeginText => Operation::BeginText,
+        Op::Leading { leading } => Operation::Leading(leading),
+        Op::GraphicsState { name } => Operation::GraphicsState(name.as_str().to_string()),
+        Op::TextFont { name, size } => Operation::Font {
+            name: name.as_str().to_string(),
+            size,
+        },
+        // The pdf crate emits the leading set by `TD` as a separate operation.
+        Op::MoveTextPosition { translation } => Operation::MoveText {
+            x: translation.x,
+            y: translation.y,
+        },
+        Op::SetTextMatrix { matrix } => Operation::TextMatrix(matrix.into()),
+        Op::CharSpacing { char_space } => Operation::CharSpacing(char_space),
+        Op::TextRise { rise } => Operation::TextRise(rise),
+        Op::WordSpacing { word_space } => Operation::WordSpacing(word_space),
+        Op::TextNewline => Operation::NextLine,
+        Op::TextDraw { text } => Operation::Text(text.data.to_vec()),
+        Op::TextDrawAdjusted { array } => Operation::AdjustedText(
+            array
+                .into_iter()
+                .map(|element| match element {
+                    TextDrawAdjusted::Text(text) => Adjusted::Text(text.data.to_vec()),
+                    TextDrawAdjusted::Spacing(spacing) => Adjusted::Spacing(spacing),
+                })
+                .collect(),
+        ),
+        operation => {
+            log::trace!("skipping operation {operation:?}");
+            return None;
+        }
+    })
+}
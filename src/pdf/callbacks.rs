@@ -0,0 +1,97 @@
+use std::fmt::Debug;
+
+use super::{error::Error, Candidate, Stage};
+
+/// A problem that did not stop a document from being parsed.
+#[derive(Debug)]
+pub enum Warning<'a> {
+    /// The page with this zero-based index could not be parsed and was skipped.
+    SkippedPage { page: usize, error: &'a Error },
+    /// The text of this font could not be decoded. Text set in it is missing from the candidates.
+    FailedFont { page: usize, name: &'a str },
+    /// This many codes on the page could not be mapped to text and were replaced with U+FFFD.
+    UnmappedGlyphs { page: usize, count: usize },
+    /// The page with this zero-based index has no text, e.g. because it is scanned, and none could
+    /// be recognized on it.
+    NoText { page: usize },
+    /// This stage took longer than its budget, so this many pages left were skipped.
+    OverBudget { stage: Stage, skipped: usize },
+    /// The text of the page with this zero-based index, which has none of its own, could not be
+    /// recognized.
+    #[cfg(feature = "ocr")]
+    FailedOcr {
+        page: usize,
+        error: &'a std::io::Error,
+    },
+}
+
+type PageDone<'a> = Box<dyn FnMut(usize, usize) + 'a>;
+type OnWarning<'a> = Box<dyn FnMut(&Warning) + 'a>;
+type OnCandidate<'a> = Box<dyn FnMut(&Candidate) + 'a>;
+
+/// Hooks that are called while a document is parsed, e.g. to show progress for large documents.
+///
+/// ```
+/// # use nomenclate::pdf::Callbacks;
+/// let callbacks = Callbacks::default()
+///     .on_page_done(|done, total| println!("{done}/{total}"))
+///     .on_candidate(|candidate| println!("found {candidate}"));
+/// ```
+#[derive(Default)]
+pub struct Callbacks<'a> {
+    page_done: Option<PageDone<'a>>,
+    warning: Option<OnWarning<'a>>,
+    candidate: Option<OnCandidate<'a>>,
+}
+
+impl<'a> Callbacks<'a> {
+    /// Called after each page with the number of pages parsed so far and the number of pages
+    /// that will be parsed in total.
+    #[must_use]
+    pub fn on_page_done(mut self, callback: impl FnMut(usize, usize) + 'a) -> Self {
+        self.page_done = Some(Box::new(callback));
+        self
+    }
+
+    /// Called for every problem that does not stop the document from being parsed.
+    #[must_use]
+    pub fn on_warning(mut self, callback: impl FnMut(&Warning) + 'a) -> Self {
+        self.warning = Some(Box::new(callback));
+        self
+    }
+
+    /// Called for every candidate as soon as it is found, before the candidates are ranked.
+    #[must_use]
+    pub fn on_candidate(mut self, callback: impl FnMut(&Candidate) + 'a) -> Self {
+        self.candidate = Some(Box::new(callback));
+        self
+    }
+
+    pub(super) fn page_done(&mut self, done: usize, total: usize) {
+        if let Some(callback) = &mut self.page_done {
+            callback(done, total);
+        }
+    }
+
+    pub(super) fn warning(&mut self, warning: &Warning) {
+        if let Some(callback) = &mut self.warning {
+            callback(warning);
+        }
+    }
+
+    pub(super) fn candidate(&mut self, candidate: &Candidate) {
+        if let Some(callback) = &mut self.candidate {
+            callback(candidate);
+        }
+    }
+}
+
+impl Debug for Callbacks<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Callbacks")
+            .field("page_done", &self.page_done.is_some())
+            .field("warning", &self.warning.is_some())
+            .field("candidate", &self.candidate.is_some())
+            .finish()
+    }
+}
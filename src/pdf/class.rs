@@ -0,0 +1,146 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::{datasheet, identifiers::Identifiers, Candidate, Profile};
+
+/// Documents with at least this many pages that are not recognized as anything else are books.
+const BOOK_PAGES: u32 = 80;
+
+/// Receipts have at most this many pages.
+const RECEIPT_PAGES: u32 = 2;
+
+/// The kind of a document, as recognized in its text and layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DocumentClass {
+    /// Scientific papers, with a DOI or arXiv identifier or a references section.
+    Paper,
+    Book,
+    Magazine,
+    Standard,
+    Legal,
+    Statement,
+    Datasheet,
+    Receipt,
+    Music,
+    /// Documents without text of their own that are not recognized as anything else, like
+    /// scanned pages.
+    Scan,
+}
+
+impl DocumentClass {
+    pub const ALL: [Self; 10] = [
+        Self::Paper,
+        Self::Book,
+        Self::Magazine,
+        Self::Standard,
+        Self::Legal,
+        Self::Statement,
+        Self::Datasheet,
+        Self::Receipt,
+        Self::Music,
+        Self::Scan,
+    ];
+}
+
+impl From<Profile> for DocumentClass {
+    fn from(profile: Profile) -> Self {
+        match profile {
+            Profile::Book => Self::Book,
+            Profile::Magazine => Self::Magazine,
+            Profile::Standard => Self::Standard,
+            Profile::Legal => Self::Legal,
+            Profile::Statement => Self::Statement,
+            Profile::Datasheet => Self::Datasheet,
+            Profile::Receipt => Self::Receipt,
+            Profile::Music => Self::Music,
+        }
+    }
+}
+
+impl Display for DocumentClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Paper => "paper",
+            Self::Book => "book",
+            Self::Magazine => "magazine",
+            Self::Standard => "standard",
+            Self::Legal => "legal",
+            Self::Statement => "statement",
+            Self::Datasheet => "datasheet",
+            Self::Receipt => "receipt",
+            Self::Music => "music",
+            Self::Scan => "scan",
+        })
+    }
+}
+
+impl FromStr for DocumentClass {
+    type Err = String;
+
+    fn from_str(class: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|known| known.to_string() == class)
+            .ok_or_else(|| {
+                format!(
+                    "unknown document type {class:?}, expected one of {}",
+                    Self::ALL.map(|known| known.to_string()).join(", ")
+                )
+            })
+    }
+}
+
+/// What a document is recognized as by.
+pub(super) struct Signs<'a> {
+    /// The profile the document was parsed with, which is taken as the user saying what it is.
+    pub profile: Option<Profile>,
+    pub candidates: &'a [Candidate],
+    pub identifiers: &'a Identifiers,
+    /// Whether a references section was found.
+    pub references: bool,
+    /// How many pages the whole document has.
+    pub page_count: u32,
+    /// Whether the document has no text of its own, only text recognized in its rendered pages if
+    /// any.
+    pub scanned: bool,
+}
+
+/// Recognize what kind of document the one with the `signs` is, if it is any of the known ones.
+///
+/// Court filings and standards are recognized by their identifiers, papers by a DOI, an arXiv
+/// identifier or a references section, statements, datasheets, receipts and sheet music by what
+/// their profiles look for, and books by their length.
+pub(super) fn classify(signs: &Signs) -> Option<DocumentClass> {
+    if let Some(profile) = signs.profile {
+        return Some(profile.into());
+    }
+
+    let identifiers = signs.identifiers;
+    let class = if identifiers.case_number.is_some() {
+        DocumentClass::Legal
+    } else if identifiers.standard_id.is_some() {
+        DocumentClass::Standard
+    } else if identifiers.doi.is_some() || identifiers.arxiv.is_some() || signs.references {
+        DocumentClass::Paper
+    } else if identifiers.statement {
+        DocumentClass::Statement
+    } else if identifiers.manufacturer.is_some()
+        && datasheet::part_number(signs.candidates).is_some()
+    {
+        DocumentClass::Datasheet
+    } else if identifiers.total.is_some() && signs.page_count <= RECEIPT_PAGES {
+        DocumentClass::Receipt
+    } else if identifiers.composer.is_some() {
+        DocumentClass::Music
+    } else if signs.page_count >= BOOK_PAGES {
+        DocumentClass::Book
+    } else if signs.scanned {
+        DocumentClass::Scan
+    } else {
+        return None;
+    };
+
+    Some(class)
+}
@@ -0,0 +1,90 @@
+use super::{identifiers::is_whole_word, Candidate};
+
+/// Makers of electronic components, as they are named in their datasheets, with the other ways
+/// they are written.
+const MANUFACTURERS: &[(&str, &[&str])] = &[
+    ("Texas Instruments", &["texas instruments"]),
+    ("STMicroelectronics", &["stmicroelectronics"]),
+    ("Analog Devices", &["analog devices"]),
+    ("Microchip", &["microchip technology", "microchip"]),
+    ("NXP", &["nxp semiconductors", "nxp"]),
+    ("Infineon", &["infineon technologies", "infineon"]),
+    ("onsemi", &["on semiconductor", "onsemi"]),
+    ("Maxim Integrated", &["maxim integrated"]),
+    ("Renesas", &["renesas electronics", "renesas"]),
+    ("Vishay", &["vishay"]),
+    ("Nexperia", &["nexperia"]),
+    ("ROHM", &["rohm semiconductor", "rohm"]),
+    ("Toshiba", &["toshiba"]),
+    ("Murata", &["murata"]),
+    ("TDK", &["tdk"]),
+    ("Bosch", &["bosch sensortec", "bosch"]),
+    ("Espressif", &["espressif systems", "espressif"]),
+    ("Nordic Semiconductor", &["nordic semiconductor"]),
+    ("Silicon Labs", &["silicon labs", "silicon laboratories"]),
+    ("Diodes Incorporated", &["diodes incorporated"]),
+    ("Littelfuse", &["littelfuse"]),
+    ("Allegro MicroSystems", &["allegro microsystems"]),
+    ("Cypress", &["cypress semiconductor"]),
+    ("Atmel", &["atmel"]),
+    ("Fairchild", &["fairchild semiconductor", "fairchild"]),
+    ("Linear Technology", &["linear technology"]),
+    ("Broadcom", &["broadcom"]),
+    ("Lattice", &["lattice semiconductor"]),
+];
+
+/// The range of lengths of part numbers.
+const PART_NUMBER_LENGTH: std::ops::RangeInclusive<usize> = 4..=24;
+
+/// Find the maker of the component described in `text` from the first of the [`MANUFACTURERS`]
+/// named in it, e.g. the text of the first page of a datasheet.
+pub fn find_manufacturer(text: &str) -> Option<String> {
+    let lowercase = text.to_lowercase();
+
+    MANUFACTURERS
+        .iter()
+        .filter_map(|(name, spellings)| {
+            spellings
+                .iter()
+                .filter_map(|spelling| {
+                    lowercase
+                        .match_indices(spelling)
+                        .find(|(start, _)| is_whole_word(&lowercase, *start, spelling.len()))
+                })
+                .map(|(start, _)| start)
+                .min()
+                .map(|start| (start, *name))
+        })
+        .min_by_key(|(start, _)| *start)
+        .map(|(_, name)| name.to_string())
+}
+
+/// The number of the part a datasheet describes, from the first of the ranked `candidates` with a
+/// word that looks like one, like `LM7805`, `STM32F103C8` or `ATmega328P`.
+pub fn part_number(candidates: &[Candidate]) -> Option<&str> {
+    candidates.iter().find_map(|candidate| {
+        candidate
+            .text
+            .split_whitespace()
+            .map(|word| word.trim_matches(|c: char| c.is_ascii_punctuation()))
+            .find(|word| is_part_number(word))
+    })
+}
+
+/// Whether `word` looks like the number of a part: at least two letters and two digits with a few
+/// dashes, slashes or dots, starting with a capital or a digit and not all lowercase. Quantities
+/// like `3.3V` have too few letters.
+fn is_part_number(word: &str) -> bool {
+    let letters = word.chars().filter(char::is_ascii_alphabetic).count();
+    let lowercase = word.chars().filter(char::is_ascii_lowercase).count();
+    let digits = word.chars().filter(char::is_ascii_digit).count();
+
+    PART_NUMBER_LENGTH.contains(&word.len())
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '/' | '.'))
+        && word.starts_with(|c: char| c.is_ascii_uppercase() || c.is_ascii_digit())
+        && letters >= 2
+        && digits >= 2
+        && lowercase < letters
+}
@@ -11,6 +11,18 @@ pub enum Error {
         #[source]
         source: PdfError,
     },
+    #[error("could not load document from memory")]
+    LoadData {
+        #[source]
+        source: PdfError,
+    },
+    #[error("document is encrypted and the password is missing or wrong")]
+    Encrypted {
+        #[source]
+        source: PdfError,
+    },
+    #[error("could not read document")]
+    Read(#[source] std::io::Error),
     #[error("page has no content")]
     NoContent,
     #[error("unsupported encoding: {0:?}")]
@@ -19,4 +31,6 @@ pub enum Error {
     MissingEncoding(Name),
     #[error("an error occurred when parsing the pdf: {0}")]
     Pdf(#[from] PdfError),
+    #[error("an error occurred when parsing the pdf with lopdf: {0}")]
+    Lopdf(#[from] lopdf::Error),
 }
@@ -1,124 +1,770 @@
-use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex, OnceLock, PoisonError},
+};
 
+use chardetng::EncodingDetector;
 use pdf::{
     encoding::BaseEncoding,
-    font::{self, Font, ToUnicodeMap},
-    object::{Page, RcRef, Resolve},
-    primitive::{Name, PdfString},
+    font::{CidToGidMap, Font, FontData, ToUnicodeMap},
+    object::{MaybeRef, Page, PlainRef, Resolve},
+    primitive::Name,
     PdfError,
 };
 use pdf_encoding::DifferenceForwardMap;
+use rayon::prelude::*;
 
 use super::error::Error;
 
+/// The character that stands in for codes that could not be mapped to text.
+const REPLACEMENT: &str = "\u{fffd}";
+
 #[derive(Clone, Default)]
 enum Decoder {
-    Map(DifferenceForwardMap),
-    Cmap(ToUnicodeMap),
+    Map {
+        map: DifferenceForwardMap,
+        /// Whether printable ASCII bytes map to themselves, so ASCII text can be used as is.
+        ascii: bool,
+    },
+    /// A `ToUnicode` map, or a map built from the font program embedded for a CID font, together
+    /// with the byte sequences that are codes in the font.
+    Cmap {
+        map: ToUnicodeMap,
+        code_space: CodeSpace,
+    },
     #[default]
     None,
 }
 
-impl Decoder {
-    fn from_font(font: &Font, resolver: &impl Resolve) -> Result<Self, Error> {
-        if let Some(Ok(to_unicode)) = font.to_unicode(resolver) {
-            Ok(Self::Cmap(to_unicode))
-        } else if let Some(encoding) = font.encoding() {
-            Ok(Self::Map(DifferenceForwardMap::new(
-                match &encoding.base {
-                    BaseEncoding::StandardEncoding => Some(&pdf_encoding::STANDARD),
-                    BaseEncoding::SymbolEncoding => Some(&pdf_encoding::SYMBOL),
-                    BaseEncoding::WinAnsiEncoding => Some(&pdf_encoding::WINANSI),
-                    BaseEncoding::MacRomanEncoding => Some(&pdf_encoding::MACROMAN),
-                    BaseEncoding::None => None,
-                    other => {
-                        return Err(Error::UnsupportedEncoding(other.clone()));
+/// The ranges of byte sequences that are codes in a font, e.g. `<00> <FF>` for fonts with single
+/// byte codes or `<0000> <FFFF>` for most CID fonts.
+///
+/// Codes can have different lengths, so text is split into codes by finding the range that each
+/// next sequence of bytes falls into.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct CodeSpace(Vec<(Vec<u8>, Vec<u8>)>);
+
+impl CodeSpace {
+    fn bytes(length: usize) -> Self {
+        Self(vec![(vec![0; length], vec![0xff; length])])
+    }
+
+    /// The code space of `font` if its `ToUnicode` map doesn't declare one: two bytes for CID
+    /// fonts, which mostly use the `Identity-H` encoding, and single bytes otherwise.
+    fn of(font: &Font) -> Self {
+        Self::of_cid(font.is_cid())
+    }
+
+    fn of_cid(cid: bool) -> Self {
+        Self::bytes(if cid { 2 } else { 1 })
+    }
+
+    /// Read the `begincodespacerange` sections of a character map.
+    fn parse(cmap: &[u8]) -> Option<Self> {
+        let cmap = String::from_utf8_lossy(cmap);
+        let mut ranges = Vec::new();
+
+        for section in cmap.split("begincodespacerange").skip(1) {
+            let section = section
+                .split("endcodespacerange")
+                .next()
+                .unwrap_or_default();
+            let bounds: Vec<Vec<u8>> = section
+                .split('<')
+                .skip(1)
+                .filter_map(|bound| hex(bound.split('>').next()?))
+                .collect();
+            ranges.extend(
+                bounds
+                    .chunks_exact(2)
+                    .filter(|range| range[0].len() == range[1].len() && !range[0].is_empty())
+                    .map(|range| (range[0].clone(), range[1].clone())),
+            );
+        }
+
+        (!ranges.is_empty()).then_some(Self(ranges))
+    }
+
+    /// Split `data` into codes.
+    ///
+    /// Bytes that don't start a code in any range are taken as a code of the shortest length, as
+    /// PDF readers do.
+    fn split<'s, 'd: 's>(&'s self, mut data: &'d [u8]) -> impl Iterator<Item = &'d [u8]> + 's {
+        let shortest = self.0.iter().map(|(low, _)| low.len()).min().unwrap_or(1);
+
+        std::iter::from_fn(move || {
+            if data.is_empty() {
+                return None;
+            }
+            let length = self
+                .0
+                .iter()
+                .find(|(low, high)| {
+                    data.len() >= low.len()
+                        && data
+                            .iter()
+                            .zip(low.iter().zip(high))
+                            .all(|(byte, (low, high))| (low..=high).contains(&byte))
+                })
+                .map_or(shortest, |(low, _)| low.len())
+                .min(data.len());
+            let (code, rest) = data.split_at(length);
+            data = rest;
+
+            Some(code)
+        })
+    }
+}
+
+/// Parse hexadecimal digits like `00FF`, ignoring whitespace.
+fn hex(digits: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = digits
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).ok()?;
+            // An odd last digit is followed by an implicit 0.
+            u8::from_str_radix(&format!("{pair:0<2}"), 16).ok()
+        })
+        .collect()
+}
+
+/// Read the `bfchar` and `bfrange` sections of a `ToUnicode` map.
+///
+/// Used for documents that are not read with the `pdf` crate, whose parser is not public.
+fn parse_to_unicode(cmap: &[u8]) -> ToUnicodeMap {
+    let cmap = String::from_utf8_lossy(cmap);
+    let mut map = ToUnicodeMap::new();
+    let mut insert = |code: Option<u16>, text: &[u8]| {
+        if let (Some(code), Ok(text)) = (code, utf16be_to_string(text)) {
+            map.insert(code, text.into());
+        }
+    };
+
+    for section in sections(&cmap, "beginbfchar", "endbfchar") {
+        for pair in tokens(section).chunks_exact(2) {
+            if let [Token::Hex(code), Token::Hex(text)] = pair {
+                insert(code_key(code), text);
+            }
+        }
+    }
+    for section in sections(&cmap, "beginbfrange", "endbfrange") {
+        for range in tokens(section).chunks_exact(3) {
+            let [Token::Hex(low), Token::Hex(high), destination] = range else {
+                continue;
+            };
+            let (Some(low), Some(high)) = (code_key(low), code_key(high)) else {
+                continue;
+            };
+            match destination {
+                // The last byte of the text is incremented for every code in the range.
+                Token::Hex(text) => {
+                    let mut text = text.clone();
+                    for code in low..=high {
+                        insert(Some(code), &text);
+                        match text.last_mut() {
+                            Some(last) if *last < 0xff => *last += 1,
+                            _ => break,
+                        }
+                    }
+                }
+                Token::Array(texts) => {
+                    for (code, text) in (low..=high).zip(texts) {
+                        insert(Some(code), text);
                     }
-                },
-                encoding
-                    .differences
-                    .iter()
-                    .map(|(k, v)| (*k, v.to_string()))
-                    .collect(),
-            )))
+                }
+            }
+        }
+    }
+
+    map
+}
+
+/// The parts of `cmap` between `begin` and `end`.
+fn sections<'a>(cmap: &'a str, begin: &'a str, end: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    cmap.split(begin)
+        .skip(1)
+        .map(move |section| section.split(end).next().unwrap_or_default())
+}
+
+/// A hexadecimal string or an array of them in a section of a character map.
+enum Token {
+    Hex(Vec<u8>),
+    Array(Vec<Vec<u8>>),
+}
+
+fn tokens(section: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut array: Option<Vec<Vec<u8>>> = None;
+    let mut rest = section;
+
+    while let Some(start) = rest.find(['<', '[', ']']) {
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix('[') {
+            array = Some(Vec::new());
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix(']') {
+            tokens.extend(array.take().map(Token::Array));
+            rest = after;
         } else {
-            Err(Error::MissingEncoding(
+            let end = rest.find('>').unwrap_or(rest.len());
+            let data = hex(&rest[1..end]).unwrap_or_default();
+            match &mut array {
+                Some(array) => array.push(data),
+                None => tokens.push(Token::Hex(data)),
+            }
+            rest = rest.get(end + 1..).unwrap_or_default();
+        }
+    }
+
+    tokens
+}
+
+/// The key of a code in a `ToUnicode` map, which only has room for codes of up to two bytes.
+fn code_key(code: &[u8]) -> Option<u16> {
+    match *code {
+        [byte] => Some(byte.into()),
+        [high, low] => Some(u16::from_be_bytes([high, low])),
+        _ => None,
+    }
+}
+
+/// Decoders for fonts that use one of the standard encodings without differences, shared by all
+/// documents. These are mostly the base-14 fonts, which appear in almost every document.
+static STANDARD_DECODERS: [OnceLock<Arc<Decoder>>; 5] = [const { OnceLock::new() }; 5];
+
+/// Decoders of the `ToUnicode` maps of a document by their object, or `None` if the map could not
+/// be parsed. CID fonts without a `ToUnicode` map are decoded through their embedded font program
+/// and stored by the object of the font.
+///
+/// Most documents use the same fonts on every page, so every map only needs to be parsed once.
+#[derive(Default)]
+pub struct CmapCache(Mutex<HashMap<PlainRef, Option<Arc<Decoder>>>>);
+
+impl CmapCache {
+    fn get_or_parse(&self, font: &MaybeRef<Font>, resolver: &impl Resolve) -> Option<Arc<Decoder>> {
+        let parse = || {
+            Decoder::from_to_unicode(font, resolver)
+                .or_else(|| Decoder::from_font_program(font, resolver))
+                .map(Arc::new)
+        };
+        let Some(key) = font
+            .to_unicode
+            .as_ref()
+            .map(|to_unicode| to_unicode.get_ref().get_inner())
+            .or_else(|| Some(font.as_ref()?.get_inner()))
+        else {
+            return parse();
+        };
+        if let Some(decoder) = self.lock().get(&key) {
+            return decoder.clone();
+        }
+
+        let decoder = parse();
+        self.lock().insert(key, decoder.clone());
+
+        decoder
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PlainRef, Option<Arc<Decoder>>>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Decoder {
+    /// The decoder for the `ToUnicode` map of `font`, if it has one that can be parsed.
+    fn from_to_unicode(font: &Font, resolver: &impl Resolve) -> Option<Self> {
+        let data = (**font.to_unicode.as_ref()?).data(resolver).ok()?;
+        let map = font.to_unicode(resolver)?.ok()?;
+
+        Some(Self::Cmap {
+            map,
+            code_space: CodeSpace::parse(&data).unwrap_or_else(|| CodeSpace::of(font)),
+        })
+    }
+
+    /// The decoder for a CID font without a `ToUnicode` map, which maps codes to glyphs through the
+    /// `CIDToGIDMap` of its descendant font and glyphs to text through the character map of its
+    /// embedded TrueType or OpenType font program.
+    fn from_font_program(font: &Font, resolver: &impl Resolve) -> Option<Self> {
+        let FontData::Type0(type0) = &font.data else {
+            return None;
+        };
+        let descendant = type0.descendant_fonts.first()?;
+        let (FontData::CIDFontType0(cid_font) | FontData::CIDFontType2(cid_font)) =
+            &descendant.data
+        else {
+            return None;
+        };
+        let descriptor = &cid_font.font_descriptor;
+        let program = match (&descriptor.font_file2, &descriptor.font_file3) {
+            (Some(file), _) => (**file).data(resolver),
+            (None, Some(file)) => (**file).data(resolver),
+            (None, None) => return None,
+        }
+        .ok()?;
+        let face = ttf_parser::Face::parse(&program, 0).ok()?;
+
+        let mut glyphs = HashMap::new();
+        for subtable in face
+            .tables()
+            .cmap?
+            .subtables
+            .into_iter()
+            .filter(ttf_parser::cmap::Subtable::is_unicode)
+        {
+            subtable.codepoints(|codepoint| {
+                if let (Some(c), Some(glyph)) =
+                    (char::from_u32(codepoint), subtable.glyph_index(codepoint))
+                {
+                    glyphs.entry(glyph.0).or_insert(c);
+                }
+            });
+        }
+        let map = match &cid_font.cid_to_gid_map {
+            Some(CidToGidMap::Table(table)) => {
+                ToUnicodeMap::create(table.iter().enumerate().filter_map(|(cid, glyph)| {
+                    Some((
+                        u16::try_from(cid).ok()?,
+                        glyphs.get(glyph)?.to_string().into(),
+                    ))
+                }))
+            }
+            Some(CidToGidMap::Identity) | None => ToUnicodeMap::create(
+                glyphs
+                    .into_iter()
+                    .map(|(glyph, c)| (glyph, c.to_string().into())),
+            ),
+        };
+        log::debug!(
+            "mapped {} glyphs of CID font {:?} through its font program",
+            map.len(),
+            descriptor.font_name.as_str()
+        );
+
+        (!map.is_empty()).then(|| Self::Cmap {
+            map,
+            code_space: CodeSpace::bytes(2),
+        })
+    }
+
+    /// The decoder for the encoding of `font`, ignoring its `ToUnicode` map.
+    fn from_encoding(font: &Font) -> Result<Arc<Self>, Error> {
+        let encoding = font.encoding().ok_or_else(|| {
+            Error::MissingEncoding(
                 font.name
                     .clone()
                     .unwrap_or_else(|| Name::from("MISSING_NAME")),
+            )
+        })?;
+
+        Self::from_base(
+            &encoding.base,
+            encoding
+                .differences
+                .iter()
+                .map(|(k, v)| (*k, v.to_string()))
+                .collect(),
+        )
+    }
+
+    /// The decoder for the `base` encoding with the glyph names of some codes replaced by
+    /// `differences`.
+    fn from_base(
+        base: &BaseEncoding,
+        differences: HashMap<u32, String>,
+    ) -> Result<Arc<Self>, Error> {
+        let (index, table) = match base {
+            BaseEncoding::StandardEncoding => (0, Some(&pdf_encoding::STANDARD)),
+            BaseEncoding::SymbolEncoding => (1, Some(&pdf_encoding::SYMBOL)),
+            BaseEncoding::WinAnsiEncoding => (2, Some(&pdf_encoding::WINANSI)),
+            BaseEncoding::MacRomanEncoding => (3, Some(&pdf_encoding::MACROMAN)),
+            BaseEncoding::None => (4, None),
+            other => {
+                return Err(Error::UnsupportedEncoding(other.clone()));
+            }
+        };
+        let standard = differences.is_empty();
+        let decoder = || {
+            let map = DifferenceForwardMap::new(table, differences);
+            let ascii = (0x20..0x7f)
+                .all(|byte| map.get(byte).is_some_and(|text| text.as_bytes() == [byte]));
+
+            Self::Map { map, ascii }
+        };
+
+        if standard {
+            Ok(Arc::clone(
+                STANDARD_DECODERS[index].get_or_init(|| Arc::new(decoder())),
             ))
+        } else {
+            Ok(Arc::new(decoder()))
+        }
+    }
+}
+
+/// How many glyphs could be mapped to text when decoding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Coverage {
+    pub glyphs: usize,
+    pub unmapped: usize,
+}
+
+impl Coverage {
+    const fn count<T>(&mut self, mapped: Option<T>) -> Option<T> {
+        self.glyphs += 1;
+        if mapped.is_none() {
+            self.unmapped += 1;
+        }
+
+        mapped
+    }
+
+    /// The share of glyphs that could be mapped, in percent. Without any glyphs, all of them were.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn percent(&self) -> f64 {
+        if self.glyphs == 0 {
+            100.
+        } else {
+            100. * (self.glyphs - self.unmapped) as f64 / self.glyphs as f64
         }
     }
 }
 
+impl std::ops::AddAssign for Coverage {
+    fn add_assign(&mut self, other: Self) {
+        self.glyphs += other.glyphs;
+        self.unmapped += other.unmapped;
+    }
+}
+
+/// A font's decoder. Cloning is cheap and the decoder can be shared between threads.
 #[derive(Default, Clone)]
-pub struct FontInfo(Decoder);
+pub struct FontInfo {
+    decoder: Arc<LazyDecoder>,
+    /// The name of the font, like `Helvetica-Bold`, if it has one.
+    name: Option<Arc<str>>,
+}
+
+/// A decoder that parses the font's `ToUnicode` map (or the font program of a CID font without
+/// one) when the first text in the font is decoded, since many fonts in a page's resources are
+/// never used.
+#[derive(Default)]
+struct LazyDecoder {
+    decoder: OnceLock<Arc<Decoder>>,
+    /// The font whose `ToUnicode` map is parsed on first use.
+    font: Option<(MaybeRef<Font>, Arc<CmapCache>)>,
+    /// The decoder used if the `ToUnicode` map can't be parsed.
+    fallback: Arc<Decoder>,
+}
+
+impl LazyDecoder {
+    fn get(&self, resolver: &impl Resolve) -> &Decoder {
+        self.decoder.get_or_init(|| {
+            self.font
+                .as_ref()
+                .and_then(|(font, cmaps)| {
+                    let decoder = cmaps.get_or_parse(font, resolver);
+                    if decoder.is_none() {
+                        log::info!("Unable to parse ToUnicode map, using the font encoding");
+                    }
+                    decoder
+                })
+                .unwrap_or_else(|| Arc::clone(&self.fallback))
+        })
+    }
+}
 
 impl FontInfo {
-    pub fn decode(&self, text: &PdfString) -> Result<String, Error> {
-        let data = &text.data;
+    /// A font whose `ToUnicode` map is parsed when it is first used, falling back to `fallback`.
+    fn with_to_unicode(
+        font: MaybeRef<Font>,
+        cmaps: &Arc<CmapCache>,
+        fallback: Arc<Decoder>,
+    ) -> Self {
+        Self {
+            decoder: Arc::new(LazyDecoder {
+                decoder: OnceLock::new(),
+                font: Some((font, Arc::clone(cmaps))),
+                fallback,
+            }),
+            name: None,
+        }
+    }
 
-        match &self.0 {
-            Decoder::Map(map) => Ok(data
-                .iter()
-                .filter_map(|&b| map.get(b))
-                .cloned()
-                .collect::<String>()),
-            Decoder::Cmap(ref cmap) => {
+    fn with_decoder(decoder: Arc<Decoder>) -> Self {
+        Self {
+            decoder: Arc::new(LazyDecoder {
+                decoder: OnceLock::from(decoder),
+                ..LazyDecoder::default()
+            }),
+            name: None,
+        }
+    }
+
+    /// Parse the `ToUnicode` map of the font now instead of when its first text is decoded.
+    pub fn prepare(&self, resolver: &impl Resolve) {
+        self.decoder.get(resolver);
+    }
+
+    /// This font, named `name`.
+    #[must_use]
+    pub fn named(self, name: &str) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..self
+        }
+    }
+
+    /// The name of the font, if it has one.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// A simple font that uses the `base` encoding with the glyph names of some codes replaced by
+    /// `differences`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the base encoding is not supported.
+    pub fn from_encoding(
+        base: &BaseEncoding,
+        differences: HashMap<u32, String>,
+    ) -> Result<Self, Error> {
+        Decoder::from_base(base, differences).map(Self::with_decoder)
+    }
+
+    /// A font decoded with the `ToUnicode` map `cmap`. Text in a CID font with a map that can't be
+    /// read is split into two-byte codes that are all unmapped.
+    pub fn from_to_unicode(cmap: Option<&[u8]>, cid: bool) -> Self {
+        let code_space = cmap
+            .and_then(CodeSpace::parse)
+            .unwrap_or_else(|| CodeSpace::of_cid(cid));
+        let map = cmap.map_or_else(ToUnicodeMap::new, parse_to_unicode);
+
+        Self::with_decoder(Arc::new(Decoder::Cmap { map, code_space }))
+    }
+
+    /// Decode `text` set in this font, counting the glyphs that could not be mapped in `coverage`.
+    ///
+    /// Text that is already readable as is, like ASCII text in most simple fonts, is borrowed. If
+    /// the encoding of the font leaves nothing of the text but replacement characters, the raw
+    /// bytes are decoded instead if more of them can be read, so the text does not vanish.
+    pub fn decode<'a>(
+        &self,
+        data: &'a [u8],
+        coverage: &mut Coverage,
+        resolver: &impl Resolve,
+    ) -> Result<Cow<'a, str>, Error> {
+        let text = match self.decoder.get(resolver) {
+            Decoder::Map { map, ascii } => {
+                if *ascii && data.iter().all(|byte| (0x20..0x7f).contains(byte)) {
+                    if let Ok(text) = std::str::from_utf8(data) {
+                        coverage.glyphs += text.len();
+                        return Ok(Cow::Borrowed(text));
+                    }
+                }
+
+                data.iter()
+                    .map(|&b| {
+                        coverage
+                            .count(map.get(b))
+                            .map_or(REPLACEMENT, String::as_str)
+                    })
+                    .collect()
+            }
+            Decoder::Cmap { map, code_space } => {
+                let mut lookups = RecentLookups::new(map);
+                let mut text = String::with_capacity(data.len());
                 // TODO: check for BOMs other than UTF-16BE
-                if data.starts_with(&[0xfe, 0xff]) {
-                    let mut text = String::new();
-                    data.chunks_exact(2)
-                        .map(|chunk| {
-                            u16::from_be_bytes(chunk.try_into().unwrap_or_else(|_| {
-                                unreachable!(
-                                    "This cannot fail because we use exact chunks of length 2."
-                                )
-                            }))
-                        })
-                        .filter_map(|code| cmap.get(code))
+                if let Some(data) = data.strip_prefix(&[0xfe, 0xff]) {
+                    utf16be_units(data)
+                        .into_iter()
+                        .map(|code| lookups.get(code))
+                        .chain(odd_byte(data).then_some(None))
+                        .map(|mapped| coverage.count(mapped).unwrap_or(REPLACEMENT))
                         .for_each(|mapped| text.push_str(mapped));
-                    Ok(text)
                 } else {
-                    Ok(data
-                        .iter()
-                        .filter_map(|&b| cmap.get(b.into()))
-                        .collect::<String>())
+                    code_space
+                        .split(data)
+                        .map(|code| {
+                            coverage
+                                .count(code_key(code).and_then(|code| lookups.get(code)))
+                                .unwrap_or(REPLACEMENT)
+                        })
+                        .for_each(|mapped| text.push_str(mapped));
                 }
+                text
             }
             Decoder::None => {
-                // TODO: check for BOMs other than UTF-16BE
-                if data.starts_with(&[0xfe, 0xff]) {
-                    let mut text = String::new();
-                    font::utf16be_to_char(&data[2..]).try_for_each(|result| {
-                        result.map_or(Err(Error::Pdf(PdfError::Utf16Decode)), |c| {
-                            text.push(c);
-                            Ok(())
-                        })
-                    })?;
-                    Ok(text)
-                } else if let Ok(text) = std::str::from_utf8(data) {
-                    Ok(text.to_string())
-                } else {
-                    Err(Error::Pdf(PdfError::Utf16Decode))
-                }
+                let text = decode_raw(data)?;
+                coverage.glyphs += text.chars().count();
+                coverage.unmapped += text.matches(REPLACEMENT).count();
+                return Ok(text);
             }
+        };
+
+        if !unreadable(&text) || data.is_empty() {
+            return Ok(Cow::Owned(text));
+        }
+        match decode_raw(data) {
+            Ok(raw) if readable(&raw) > readable(&text) => {
+                log::info!(
+                    "the encoding of font {} leaves nothing readable of {text:?}, using the raw \
+                     bytes {raw:?} instead",
+                    self.name().unwrap_or("without a name")
+                );
+                Ok(Cow::Owned(
+                    raw.chars().filter(|c| !c.is_control()).collect(),
+                ))
+            }
+            _ => Ok(Cow::Owned(text)),
         }
     }
 }
 
-pub struct FontCache(HashMap<Name, FontInfo>);
+/// Decode `data` without a font encoding: as UTF-16BE if it starts with a byte order mark, as
+/// UTF-8 if it is valid UTF-8 and in the legacy encoding it is most likely in otherwise.
+fn decode_raw(data: &[u8]) -> Result<Cow<'_, str>, Error> {
+    // TODO: check for BOMs other than UTF-16BE
+    data.strip_prefix(&[0xfe, 0xff]).map_or_else(
+        || {
+            Ok(std::str::from_utf8(data)
+                .map_or_else(|_| Cow::Owned(decode_detected(data)), Cow::Borrowed))
+        },
+        |data| utf16be_to_string(data).map(Cow::Owned),
+    )
+}
+
+/// Whether nothing of `text` could be decoded, which is the case if it is empty or consists of
+/// replacement characters and whitespace only. Symbols and punctuation are decoded text, e.g. the
+/// bullets of symbol fonts.
+fn unreadable(text: &str) -> bool {
+    text.chars()
+        .all(|c| c == char::REPLACEMENT_CHARACTER || c.is_whitespace())
+}
+
+/// How many letters and digits there are in `text`, which replacement characters, spaces and
+/// control characters are not.
+fn readable(text: &str) -> usize {
+    text.chars().filter(|c| c.is_alphanumeric()).count()
+}
+
+/// Decode `data`, which is neither UTF-8 nor UTF-16, in the legacy encoding it is most likely in.
+///
+/// Old tools write text in fonts without an encoding in the code page of the system they ran on,
+/// mostly Windows-1252 or Latin-1, which is detected from the bytes. Bytes that are invalid in the
+/// detected encoding are replaced with U+FFFD.
+fn decode_detected(data: &[u8]) -> String {
+    let mut detector = EncodingDetector::new();
+    detector.feed(data, true);
+    let encoding = detector.guess(None, false);
+    log::debug!(
+        "decoding text in a font without an encoding as {}",
+        encoding.name()
+    );
+
+    encoding.decode_without_bom_handling(data).0.into_owned()
+}
+
+/// Convert UTF-16BE bytes to code units in one go, which the compiler vectorizes. A trailing byte
+/// that does not make up a whole code unit is left out, see [`odd_byte`].
+fn utf16be_units(data: &[u8]) -> Vec<u16> {
+    data.chunks_exact(2)
+        .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]))
+        .collect()
+}
+
+/// Whether UTF-16BE `data` ends in a byte that is not part of a whole code unit, which is decoded
+/// as [`REPLACEMENT`] instead of being dropped silently.
+const fn odd_byte(data: &[u8]) -> bool {
+    data.len() % 2 == 1
+}
+
+/// Decode UTF-16BE text, skipping the conversion of every character for text that is all ASCII.
+fn utf16be_to_string(data: &[u8]) -> Result<String, Error> {
+    let units = utf16be_units(data);
+
+    let mut text = if units.iter().all(|&unit| unit < 0x80) {
+        let ascii = units.iter().map(|unit| unit.to_be_bytes()[1]).collect();
+        String::from_utf8(ascii).map_err(|_| Error::Pdf(PdfError::Utf16Decode))?
+    } else {
+        String::from_utf16(&units).map_err(|_| Error::Pdf(PdfError::Utf16Decode))?
+    };
+    if odd_byte(data) {
+        text.push_str(REPLACEMENT);
+    }
+
+    Ok(text)
+}
+
+/// How many lookups [`RecentLookups`] remembers.
+const RECENT_LOOKUPS: usize = 16;
+
+/// A small least-recently-used cache in front of a `ToUnicode` map.
+///
+/// Titles consist of few distinct glyphs, so most lookups hit the cache, which is cheaper than
+/// hashing the code.
+struct RecentLookups<'a> {
+    map: &'a ToUnicodeMap,
+    /// The most recently used lookup comes first.
+    entries: [(u16, Option<&'a str>); RECENT_LOOKUPS],
+    len: usize,
+}
+
+impl<'a> RecentLookups<'a> {
+    const fn new(map: &'a ToUnicodeMap) -> Self {
+        Self {
+            map,
+            entries: [(0, None); RECENT_LOOKUPS],
+            len: 0,
+        }
+    }
+
+    fn get(&mut self, code: u16) -> Option<&'a str> {
+        if let Some(index) = self.entries[..self.len]
+            .iter()
+            .position(|&(cached, _)| cached == code)
+        {
+            self.entries[..=index].rotate_right(1);
+            return self.entries[0].1;
+        }
+
+        let mapped = self.map.get(code);
+        self.len = (self.len + 1).min(RECENT_LOOKUPS);
+        self.entries[..self.len].rotate_right(1);
+        self.entries[0] = (code, mapped);
+
+        mapped
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct FontCache {
+    fonts: HashMap<String, FontInfo>,
+    /// The fonts and font sizes set by the graphics states of the page.
+    graphics_states: HashMap<String, (FontInfo, f32)>,
+    /// The names of fonts that could not be added because their encoding is missing or unsupported.
+    pub failed: BTreeSet<String>,
+}
 
 impl FontCache {
-    pub fn from_page(page: &Page, resolver: &impl Resolve) -> Self {
-        let mut font_cache = Self(HashMap::new());
+    /// Load the fonts used on `page`. Their `ToUnicode` maps are parsed on first use, or all at
+    /// once with [`Self::prepare`], and only once per document, see [`CmapCache`].
+    pub fn from_page(page: &Page, resolver: &impl Resolve, cmaps: &Arc<CmapCache>) -> Self {
+        let mut font_cache = Self::default();
 
         if let Ok(resources) = page.resources() {
             for (name, font) in &resources.fonts {
-                if let Some(font) = font.as_ref() {
-                    if let Ok(font) = resolver.get(font) {
-                        font_cache.add_font(name, &font, resolver);
-                    }
+                if let Ok(font) = font.load(resolver) {
+                    font_cache.add_font(name, font, cmaps);
                 }
             }
 
@@ -128,46 +774,99 @@ impl FontCache {
                 .filter_map(|state| state.font)
             {
                 if let Ok(font) = resolver.get(font) {
-                    if let Some(name) = &font.name {
-                        font_cache.add_font(name, &font, resolver);
+                    if let Some(name) = font.name.clone() {
+                        font_cache.add_font(&name, font.into(), cmaps);
                     }
                 }
             }
+
+            for (name, state) in &resources.graphics_states {
+                if let Some((font, font_size)) = state.font {
+                    let font = resolver
+                        .get(font)
+                        .ok()
+                        .and_then(|font| Some(font_cache.get_font(font.name.as_ref()?)))
+                        .unwrap_or_default();
+                    font_cache.insert_graphics_state(name.as_str().to_string(), font, font_size);
+                }
+            }
         }
 
         font_cache
     }
 
-    pub fn get_font(&self, name: &Name) -> FontInfo {
-        self.0.get(name).cloned().unwrap_or_else(FontInfo::default)
+    /// Parse the `ToUnicode` maps of the fonts named `names` and of the fonts set by the graphics
+    /// states named `states` in parallel, since parsing them can take a while. The maps of fonts
+    /// that are not used are never parsed.
+    ///
+    /// Every thread needs its own resolver, which tracks the objects it is resolving.
+    pub fn prepare<'n, R: Resolve>(
+        &self,
+        names: impl IntoIterator<Item = &'n str>,
+        states: impl IntoIterator<Item = &'n str>,
+        resolver: impl Fn() -> R + Sync,
+    ) {
+        let fonts: Vec<_> = names
+            .into_iter()
+            .filter_map(|name| self.fonts.get(name))
+            .chain(
+                states
+                    .into_iter()
+                    .filter_map(|name| self.graphics_states.get(name).map(|(font, _)| font)),
+            )
+            .collect();
+
+        fonts.par_iter().for_each(|font| font.prepare(&resolver()));
     }
 
-    pub fn get_font_from_graphic_state(
-        &self,
-        name: &Name,
-        page: &Page,
-        resolver: &impl Resolve,
-    ) -> Option<(FontInfo, f32)> {
-        page.resources()
-            .ok()
-            .and_then(|resources| resources.graphics_states.get(name))
-            .and_then(|state| state.font)
-            .map(|(font, font_size)| {
-                (
-                    resolver
-                        .get(font)
-                        .ok()
-                        .and_then(|font| Some(self.get_font(font.name.as_ref()?)))
-                        .unwrap_or_else(FontInfo::default),
-                    font_size,
-                )
-            })
-    }
-
-    fn add_font(&mut self, name: &Name, font: &RcRef<Font>, resolver: &impl Resolve) {
-        let _ = Decoder::from_font(font, resolver)
-            .inspect_err(|err| log::warn!("Unable to add font: {err}"))
-            .map(FontInfo)
-            .map(|font_info| self.0.insert(name.clone(), font_info));
+    pub fn get_font(&self, name: &str) -> FontInfo {
+        self.fonts
+            .get(name)
+            .cloned()
+            .unwrap_or_else(FontInfo::default)
+    }
+
+    /// The font and font size set by the graphics state `name`, if it sets a font.
+    pub fn get_font_from_graphic_state(&self, name: &str) -> Option<(FontInfo, f32)> {
+        self.graphics_states.get(name).cloned()
+    }
+
+    pub fn insert(&mut self, name: String, font: FontInfo) {
+        self.fonts.insert(name, font);
+    }
+
+    pub fn insert_graphics_state(&mut self, name: String, font: FontInfo, font_size: f32) {
+        self.graphics_states.insert(name, (font, font_size));
+    }
+
+    fn add_font(&mut self, name: &Name, font: MaybeRef<Font>, cmaps: &Arc<CmapCache>) {
+        let decoder = Decoder::from_encoding(&font);
+        let font_name = font.name.as_ref().unwrap_or(name).as_str().to_string();
+
+        if font.to_unicode.is_some() || font.is_cid() {
+            // CID fonts have multi-byte codes that only their own maps can make sense of.
+            let fallback = if font.is_cid() {
+                Arc::new(Decoder::Cmap {
+                    map: ToUnicodeMap::new(),
+                    code_space: CodeSpace::of(&font),
+                })
+            } else {
+                decoder.unwrap_or_default()
+            };
+            let font_info = FontInfo::with_to_unicode(font, cmaps, fallback).named(&font_name);
+            self.insert(name.as_str().to_string(), font_info);
+            return;
+        }
+
+        match decoder {
+            Ok(decoder) => self.insert(
+                name.as_str().to_string(),
+                FontInfo::with_decoder(decoder).named(&font_name),
+            ),
+            Err(err) => {
+                log::info!("Unable to add font: {err}");
+                self.failed.insert(font_name);
+            }
+        }
     }
 }
@@ -0,0 +1,156 @@
+use pdf::{
+    content::{Matrix, Point},
+    object::Rectangle,
+};
+
+/// How many steps per point positions on a page are snapped to by [`snap`].
+const RESOLUTION: f32 = 1024.;
+
+/// Snap `value`, a distance in points, to a grid of [`RESOLUTION`] steps per point.
+///
+/// The last bits of computed positions depend on how the arithmetic was done, e.g. whether
+/// multiplications and additions were fused or how `hypot` is implemented on a platform. Snapped
+/// positions are the same everywhere, apart from values right between two steps, and sums and
+/// differences of them are exact on pages of any usual size, so text is grouped into lines and
+/// blocks the same on every platform and optimization level.
+pub fn snap(value: f32) -> f32 {
+    (value * RESOLUTION).round() / RESOLUTION
+}
+
+/// An affine transformation as used by PDF content streams.
+#[derive(Clone, Copy, Debug)]
+pub struct Transform {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+    f: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl From<Matrix> for Transform {
+    fn from(Matrix { a, b, c, d, e, f }: Matrix) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        a: 1.,
+        b: 0.,
+        c: 0.,
+        d: 1.,
+        e: 0.,
+        f: 0.,
+    };
+
+    pub const fn translation(tx: f32, ty: f32) -> Self {
+        Self {
+            e: tx,
+            f: ty,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// The transformation that applies `self` first and `other` second.
+    pub const fn then(&self, other: &Self) -> Self {
+        Self {
+            a: self.a.mul_add(other.a, self.b * other.c),
+            b: self.a.mul_add(other.b, self.b * other.d),
+            c: self.c.mul_add(other.a, self.d * other.c),
+            d: self.c.mul_add(other.b, self.d * other.d),
+            e: self.e.mul_add(other.a, self.f.mul_add(other.c, other.e)),
+            f: self.e.mul_add(other.b, self.f.mul_add(other.d, other.f)),
+        }
+    }
+
+    /// The transformation with its translation [snapped](snap) to the grid of positions.
+    pub fn snapped(self) -> Self {
+        Self {
+            e: snap(self.e),
+            f: snap(self.f),
+            ..self
+        }
+    }
+
+    pub const fn apply(&self, Point { x, y }: Point) -> Point {
+        Point {
+            x: self.a.mul_add(x, self.c.mul_add(y, self.e)),
+            y: self.b.mul_add(x, self.d.mul_add(y, self.f)),
+        }
+    }
+}
+
+/// An axis-aligned rectangle in page space.
+#[derive(Clone, Copy, Debug)]
+pub struct BoundingBox {
+    pub left: f32,
+    pub bottom: f32,
+    pub right: f32,
+    pub top: f32,
+}
+
+impl From<Rectangle> for BoundingBox {
+    fn from(rectangle: Rectangle) -> Self {
+        Self::new(
+            rectangle.left,
+            rectangle.bottom,
+            rectangle.right,
+            rectangle.top,
+        )
+    }
+}
+
+impl BoundingBox {
+    /// The bounding box with the opposite corners `(x1, y1)` and `(x2, y2)`.
+    pub const fn new(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        Self {
+            left: x1.min(x2),
+            bottom: y1.min(y2),
+            right: x1.max(x2),
+            top: y1.max(y2),
+        }
+    }
+
+    pub const fn point(Point { x, y }: Point) -> Self {
+        Self {
+            left: x,
+            bottom: y,
+            right: x,
+            top: y,
+        }
+    }
+
+    /// Grow the bounding box to contain `point`.
+    pub const fn include(&mut self, Point { x, y }: Point) {
+        self.left = self.left.min(x);
+        self.bottom = self.bottom.min(y);
+        self.right = self.right.max(x);
+        self.top = self.top.max(y);
+    }
+
+    /// The overlap of both bounding boxes, which is empty if they don't overlap.
+    pub const fn intersection(&self, other: &Self) -> Self {
+        Self {
+            left: self.left.max(other.left),
+            bottom: self.bottom.max(other.bottom),
+            right: self.right.min(other.right),
+            top: self.top.min(other.top),
+        }
+    }
+
+    pub fn contains(&self, Point { x, y }: Point) -> bool {
+        (self.left..=self.right).contains(&x) && (self.bottom..=self.top).contains(&y)
+    }
+
+    /// Whether the bounding box has no area.
+    pub const fn is_empty(&self) -> bool {
+        self.right - self.left <= f32::EPSILON || self.top - self.bottom <= f32::EPSILON
+    }
+}
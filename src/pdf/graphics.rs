@@ -0,0 +1,171 @@
+use pdf::content::Point;
+
+use super::{
+    backend::Operation,
+    geometry::{BoundingBox, Transform},
+};
+
+/// The parts of the graphics state that are saved and restored with `q` and `Q`.
+#[derive(Clone, Copy, Default)]
+struct GraphicsState {
+    /// The current transformation matrix, from user space to page space.
+    ctm: Transform,
+    /// The bounding box of the clipping path in page space, if there is one.
+    clip: Option<BoundingBox>,
+}
+
+/// Tracks the graphics state of a page's content stream.
+pub struct Graphics {
+    state: GraphicsState,
+    saved: Vec<GraphicsState>,
+    /// The bounding box of the path under construction, in page space.
+    path: Option<BoundingBox>,
+    /// Whether the path under construction is used for clipping once it is painted.
+    clip_path: bool,
+    page: Option<BoundingBox>,
+    /// How many degrees the page is rotated clockwise when displayed.
+    rotation: i32,
+}
+
+impl Graphics {
+    /// The graphics state at the start of a page whose `visible` part is known, and that is rotated
+    /// by `rotation` degrees clockwise when displayed.
+    pub const fn new(visible: Option<BoundingBox>, rotation: i32) -> Self {
+        Self {
+            state: GraphicsState {
+                ctm: Transform::IDENTITY,
+                clip: None,
+            },
+            saved: Vec::new(),
+            path: None,
+            clip_path: false,
+            page: visible,
+            rotation: rotation.rem_euclid(360),
+        }
+    }
+
+    /// Update the graphics state with `operation`.
+    ///
+    /// Returns whether the operation only concerns the graphics state.
+    pub fn apply(&mut self, operation: &Operation) -> bool {
+        match *operation {
+            // `q`
+            Operation::Save => self.saved.push(self.state),
+            // `Q`
+            Operation::Restore => {
+                if let Some(saved) = self.saved.pop() {
+                    self.state = saved;
+                }
+            }
+            // `cm`
+            Operation::Transform(matrix) => {
+                self.state.ctm = matrix.then(&self.state.ctm);
+            }
+            Operation::Path(ref points) => self.extend_path(points),
+            // `re`
+            Operation::Rect {
+                x,
+                y,
+                width,
+                height,
+            } => self.extend_path(&[(0., 0.), (width, 0.), (0., height), (width, height)].map(
+                |(dx, dy)| Point {
+                    x: x + dx,
+                    y: y + dy,
+                },
+            )),
+            // `W`, `W*`
+            Operation::Clip => self.clip_path = true,
+            Operation::Paint => {
+                if self.clip_path {
+                    let bounds = self
+                        .path
+                        .unwrap_or(BoundingBox::point(Point { x: 0., y: 0. }));
+                    let clip = self
+                        .state
+                        .clip
+                        .map_or(bounds, |clip| clip.intersection(&bounds));
+                    log::debug!("clip to {clip:?}");
+                    self.state.clip = Some(clip);
+                }
+                self.path = None;
+                self.clip_path = false;
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Transform `point` from user space to page space.
+    pub const fn to_page(&self, point: Point) -> Point {
+        self.state.ctm.apply(point)
+    }
+
+    /// Whether `point` in page space lies on the visible part of the page.
+    pub fn is_on_page(&self, point: Point) -> bool {
+        self.page.is_none_or(|page| page.contains(point))
+    }
+
+    /// The width and height of the page as it is displayed, after cropping and rotating it.
+    pub fn size(&self) -> Option<(f32, f32)> {
+        self.page.map(|page| {
+            let (width, height) = (page.right - page.left, page.top - page.bottom);
+            match self.rotation {
+                90 | 270 => (height, width),
+                _ => (width, height),
+            }
+        })
+    }
+
+    /// How far `point` in page space is from the top edge of the page as it is displayed, after
+    /// cropping and rotating it.
+    pub fn depth(&self, point: Point) -> f32 {
+        let Some(page) = self.page else {
+            return -point.y;
+        };
+
+        match self.rotation {
+            90 => point.x - page.left,
+            180 => point.y - page.bottom,
+            270 => page.right - point.x,
+            _ => page.top - point.y,
+        }
+    }
+
+    /// How far `point` in page space is from the left edge of the page as it is displayed, after
+    /// cropping and rotating it.
+    pub fn indent(&self, point: Point) -> f32 {
+        let Some(page) = self.page else {
+            return point.x;
+        };
+
+        match self.rotation {
+            90 => point.y - page.bottom,
+            180 => page.right - point.x,
+            270 => page.top - point.y,
+            _ => point.x - page.left,
+        }
+    }
+
+    /// Whether anything drawn is clipped away completely, either because the clipping path has no
+    /// area or because it lies outside of the page.
+    pub fn hides_everything(&self) -> bool {
+        self.state.clip.is_some_and(|clip| {
+            clip.is_empty()
+                || self
+                    .page
+                    .is_some_and(|page| clip.intersection(&page).is_empty())
+        })
+    }
+
+    fn extend_path(&mut self, points: &[Point]) {
+        for &point in points {
+            let point = self.state.ctm.apply(point);
+            match &mut self.path {
+                Some(path) => path.include(point),
+                None => self.path = Some(BoundingBox::point(point)),
+            }
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use super::{datasheet, legal, receipt, statement};
+
+/// The identifiers of a document found in its text.
+#[derive(Clone, Debug, Default)]
+pub struct Identifiers {
+    pub doi: Option<String>,
+    pub arxiv: Option<String>,
+    /// The date of the issue of a periodical, see [`find_issue_date`].
+    pub issue_date: Option<String>,
+    /// The identifier of a standard, see [`find_standard_id`].
+    pub standard_id: Option<String>,
+    /// The number of the case of a court filing, see [`legal::find_case_number`].
+    pub case_number: Option<String>,
+    /// The date a court filing was filed, see [`legal::find_filing_date`].
+    pub filed: Option<String>,
+    /// The type of a court filing, see [`legal::find_document_type`].
+    pub document_type: Option<String>,
+    /// Whether the document is a statement of an account or a bill, see
+    /// [`statement::is_statement`].
+    pub statement: bool,
+    /// The period a statement covers, see [`statement::find_period`].
+    pub period: Option<String>,
+    /// The last digits of the number of the account of a statement, see
+    /// [`statement::find_account_suffix`].
+    pub account_suffix: Option<String>,
+    /// The maker of the component a datasheet describes, see [`datasheet::find_manufacturer`].
+    pub manufacturer: Option<String>,
+    /// The total of a receipt, see [`receipt::find_total`].
+    pub total: Option<receipt::Total>,
+    /// The first date written in numbers, see [`receipt::find_date`].
+    pub date: Option<String>,
+    /// The composer named at the top of sheet music, see
+    /// [`music::find_composer`](super::music::find_composer).
+    pub composer: Option<String>,
+}
+
+impl Identifiers {
+    /// Look for the identifiers not found yet in `text`, e.g. the text of a page.
+    pub fn scan(&mut self, text: &str) {
+        if self.doi.is_none() {
+            self.doi = find_doi(text);
+        }
+        if self.arxiv.is_none() {
+            self.arxiv = find_arxiv(text);
+        }
+        if self.issue_date.is_none() {
+            self.issue_date = find_issue_date(text);
+        }
+        if self.standard_id.is_none() {
+            self.standard_id = find_standard_id(text);
+        }
+        if self.case_number.is_none() {
+            self.case_number = legal::find_case_number(text);
+        }
+        if self.filed.is_none() {
+            self.filed = legal::find_filing_date(text);
+        }
+        if self.document_type.is_none() {
+            self.document_type = legal::find_document_type(text);
+        }
+        self.statement = self.statement || statement::is_statement(text);
+        if self.period.is_none() {
+            self.period = statement::find_period(text);
+        }
+        if self.account_suffix.is_none() {
+            self.account_suffix = statement::find_account_suffix(text);
+        }
+        if self.manufacturer.is_none() {
+            self.manufacturer = datasheet::find_manufacturer(text);
+        }
+        if self.total.is_none() {
+            self.total = receipt::find_total(text);
+        }
+        if self.date.is_none() {
+            self.date = receipt::find_date(text);
+        }
+    }
+}
+
+/// Find the first DOI (`10.<registrant>/<suffix>`) in `text`.
+pub fn find_doi(text: &str) -> Option<String> {
+    text.match_indices("10.").find_map(|(start, _)| {
+        let doi = &text[start..];
+        let registrant = doi[3..].find('/')?;
+        let suffix = &doi[3 + registrant + 1..];
+        let suffix_length = suffix
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '<' | '>'))
+            .unwrap_or(suffix.len());
+
+        ((4..=9).contains(&registrant)
+            && doi[3..3 + registrant]
+                .bytes()
+                .all(|byte| byte.is_ascii_digit())
+            && suffix_length > 0)
+            .then(|| {
+                doi[..3 + registrant + 1 + suffix_length]
+                    .trim_end_matches(['.', ',', ';', ')'])
+                    .to_string()
+            })
+    })
+}
+
+/// Find the first arXiv identifier in `text`, like `arXiv:1706.03762v5` or
+/// `arxiv.org/abs/hep-th/9901001`, without its version.
+pub fn find_arxiv(text: &str) -> Option<String> {
+    // Lowercasing ASCII keeps the byte offsets of `text`.
+    let lowercase = text.to_ascii_lowercase();
+
+    ["arxiv:", "arxiv.org/abs/", "arxiv.org/pdf/"]
+        .into_iter()
+        .flat_map(|prefix| {
+            lowercase
+                .match_indices(prefix)
+                .map(move |(start, _)| start + prefix.len())
+        })
+        .find_map(|start| {
+            let id = text[start..].trim_start();
+            new_arxiv_id(id).or_else(|| old_arxiv_id(id))
+        })
+}
+
+/// The identifier at the start of `text` if it is one like `1706.03762`, used since 2007.
+fn new_arxiv_id(text: &str) -> Option<String> {
+    let digits = |text: &str| text.bytes().take_while(u8::is_ascii_digit).count();
+    let year_month = digits(text);
+    let number = text.get(year_month + 1..).map_or(0, digits);
+
+    (year_month == 4 && text[4..].starts_with('.') && (4..=5).contains(&number))
+        .then(|| text[..5 + number].to_string())
+}
+
+/// The identifier at the start of `text` if it is one like `hep-th/9901001` or `math.GT/0309136`,
+/// used before 2007.
+fn old_arxiv_id(text: &str) -> Option<String> {
+    let (archive, rest) = text.split_once('/')?;
+    let number = rest.bytes().take_while(u8::is_ascii_digit).count();
+    let is_archive = !archive.is_empty()
+        && archive.starts_with(|c: char| c.is_ascii_lowercase())
+        && archive
+            .chars()
+            .all(|c| c.is_ascii_alphabetic() || matches!(c, '-' | '.'));
+
+    (is_archive && number == 7).then(|| format!("{archive}/{}", &rest[..number]))
+}
+
+/// The names of the months in English, German and French and their abbreviations, lowercase and
+/// without accents, in the order of the months.
+const MONTHS: [&[&str]; 12] = [
+    &["january", "jan", "januar", "janvier"],
+    &["february", "feb", "februar", "fevrier"],
+    &["march", "mar", "marz", "maerz", "mars"],
+    &["april", "apr", "avril"],
+    &["may", "mai"],
+    &["june", "jun", "juni", "juin"],
+    &["july", "jul", "juli", "juillet"],
+    &["august", "aug", "aout"],
+    &["september", "sep", "sept", "septembre"],
+    &["october", "oct", "oktober", "okt", "octobre"],
+    &["november", "nov", "novembre"],
+    &["december", "dec", "dezember", "dez", "decembre"],
+];
+
+/// Words in front of the number of an issue, lowercase and without accents.
+const ISSUE_MARKERS: [&str; 7] = ["no", "nr", "n", "issue", "vol", "heft", "ausgabe"];
+
+/// Find the first date of an issue of a periodical in `text`, written with the name of its month
+/// like `March 2024`, `16. März 2024`, `March 16, 2024` or `March/April 2024`, as `YYYY-MM` or
+/// `YYYY-MM-DD` if the day is given.
+pub fn find_issue_date(text: &str) -> Option<String> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| deunicode::deunicode(word).to_lowercase())
+        .collect();
+    // Days may be written as ordinals, like `16th`.
+    let day = |word: &str| {
+        let digits = word.trim_end_matches(char::is_alphabetic);
+        digits
+            .parse::<u8>()
+            .ok()
+            .filter(|day| (1..=31).contains(day) && digits.len() <= 2)
+    };
+    let year = |word: &str| {
+        word.parse::<u16>()
+            .ok()
+            .filter(|year| word.len() == 4 && (1800..=2100).contains(year))
+    };
+
+    words.iter().enumerate().find_map(|(index, word)| {
+        let month = month(word)?;
+        let mut rest = &words[index + 1..];
+        // The first month of issues that span several, like `March/April`.
+        if rest.first().is_some_and(|next| month_after(next, month)) {
+            rest = &rest[1..];
+        }
+        let (day, year) = match rest {
+            [first, second, ..] if day(first).is_some() && year(second).is_some() => {
+                (day(first), year(second)?)
+            }
+            [first, ..] => (
+                index
+                    .checked_sub(1)
+                    .filter(|before| {
+                        // Not the number of the issue, like `No. 12 December 2023`.
+                        before
+                            .checked_sub(1)
+                            .is_none_or(|marker| !ISSUE_MARKERS.contains(&words[marker].as_str()))
+                    })
+                    .and_then(|before| day(&words[before])),
+                year(first)?,
+            ),
+            [] => return None,
+        };
+
+        Some(day.map_or_else(
+            || format!("{year:04}-{month:02}"),
+            |day| format!("{year:04}-{month:02}-{day:02}"),
+        ))
+    })
+}
+
+/// The date written as `MM/DD/YYYY`, `MM/DD/YY`, `DD.MM.YYYY` or `YYYY-MM-DD` in `word`, as
+/// `YYYY-MM-DD`.
+pub fn numeric_date(word: &str) -> Option<String> {
+    let word = word.trim_matches(|c: char| !c.is_ascii_digit());
+    let (year, month, day) = if let Some((year, rest)) = word.split_once('-') {
+        let (month, day) = rest.split_once('-')?;
+        (year, month, day)
+    } else if let Some((day, rest)) = word.split_once('.') {
+        let (month, year) = rest.split_once('.')?;
+        (year, month, day)
+    } else {
+        let (month, rest) = word.split_once('/')?;
+        let (day, year) = rest.split_once('/')?;
+        (year, month, day)
+    };
+    let year: u16 = match (year.len(), year.parse().ok()?) {
+        (2, year) => 2000 + year,
+        (4, year) => year,
+        _ => return None,
+    };
+    let month: u8 = month
+        .parse()
+        .ok()
+        .filter(|month| (1..=12).contains(month))?;
+    let day: u8 = day.parse().ok().filter(|day| (1..=31).contains(day))?;
+
+    Some(format!("{year:04}-{month:02}-{day:02}"))
+}
+
+/// Whether the text at `start` and `length` bytes long in `text` is a whole word.
+pub fn is_whole_word(text: &str, start: usize, length: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[start + length..].chars().next();
+
+    before.is_none_or(|c| !c.is_alphanumeric()) && after.is_none_or(|c| !c.is_alphanumeric())
+}
+
+/// The number of the month named `word`, from 1 for January.
+fn month(word: &str) -> Option<usize> {
+    MONTHS
+        .iter()
+        .position(|names| names.contains(&word))
+        .map(|index| index + 1)
+}
+
+/// Whether `word` names a month after `month` in the same year.
+fn month_after(word: &str, month: usize) -> bool {
+    self::month(word).is_some_and(|next| next > month)
+}
+
+/// The bodies publishing standards, as they are written in front of the number of a standard.
+const STANDARDS_BODIES: [&str; 14] = [
+    "ISO", "IEC", "ISO/IEC", "RFC", "DIN", "EN", "BS", "ANSI", "IEEE", "ASTM", "ETSI", "NF",
+    "ÖNORM", "SN",
+];
+
+/// Find the first identifier of a standard in `text`, like `ISO 9001:2015`, `RFC 9114`,
+/// `DIN EN 1090-2` or `IEEE Std 802.11-2020`.
+pub fn find_standard_id(text: &str) -> Option<String> {
+    let words: Vec<_> = text.split_whitespace().collect();
+
+    (0..words.len()).find_map(|start| {
+        let bodies = words[start..]
+            .iter()
+            .take_while(|word| STANDARDS_BODIES.contains(word))
+            .count();
+        if bodies == 0 || (start > 0 && STANDARDS_BODIES.contains(&words[start - 1])) {
+            return None;
+        }
+        let mut rest = &words[start + bodies..];
+        if rest
+            .first()
+            .is_some_and(|word| matches!(*word, "Std" | "Std."))
+        {
+            rest = &rest[1..];
+        }
+        let number = standard_number(rest.first()?)?;
+
+        Some(format!(
+            "{} {number}",
+            words[start..start + bodies].join(" ")
+        ))
+    })
+}
+
+/// The number of a standard at the start of `word`, like `9001:2015`, `1090-2` or `D638-14`,
+/// without the punctuation after it.
+fn standard_number(word: &str) -> Option<&str> {
+    let number = word
+        .trim_end_matches(|c: char| c.is_ascii_punctuation())
+        .trim_start_matches(|c: char| c.is_ascii_uppercase());
+    let prefix = word.len()
+        - word
+            .trim_start_matches(|c: char| c.is_ascii_uppercase())
+            .len();
+    let digits = number.chars().filter(char::is_ascii_digit).count();
+
+    (prefix <= 1
+        && number.starts_with(|c: char| c.is_ascii_digit())
+        && digits >= 2
+        && number
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '-' | ':' | '.' | '/')))
+    .then(|| &word[..prefix + number.len()])
+}
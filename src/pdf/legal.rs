@@ -0,0 +1,165 @@
+use super::identifiers::{find_issue_date, is_whole_word, numeric_date};
+
+/// The labels in front of the number of a case in the caption of court filings, lowercase.
+const CASE_LABELS: &[&str] = &[
+    "case no",
+    "case number",
+    "civil action no.",
+    "criminal no.",
+    "docket no.",
+    "index no.",
+    "cause no.",
+    "aktenzeichen",
+    "az.",
+];
+
+/// The words in front of the date a document was filed, lowercase.
+const FILING_LABELS: &[&str] = &["filed", "dated", "entered", "eingereicht"];
+
+/// How many characters after a label of the filing date are searched for the date.
+const FILING_DATE_DISTANCE: usize = 40;
+
+/// The words court filings are named with, as they are set in their captions.
+const DOCUMENT_TYPES: &[&str] = &[
+    "COMPLAINT",
+    "ANSWER",
+    "MOTION",
+    "ORDER",
+    "MEMORANDUM",
+    "BRIEF",
+    "AFFIDAVIT",
+    "DECLARATION",
+    "STIPULATION",
+    "NOTICE",
+    "SUBPOENA",
+    "PETITION",
+    "JUDGMENT",
+    "OPINION",
+    "REPLY",
+    "RESPONSE",
+    "OBJECTION",
+    "SUMMONS",
+    "KLAGE",
+    "URTEIL",
+    "BESCHLUSS",
+];
+
+/// The most words a document type is made of, like `MOTION TO DISMISS FOR LACK OF JURISDICTION`.
+const MAX_DOCUMENT_TYPE_WORDS: usize = 8;
+
+/// Short words that stay lowercase in document types unless they come first.
+const SMALL_WORDS: &[&str] = &["a", "an", "and", "for", "in", "of", "on", "or", "the", "to"];
+
+/// Find the number of a case after one of the [`CASE_LABELS`] written as a whole word in `text`,
+/// like `1:23-cv-04567` in `Case No. 1:23-cv-04567` or `3 O 123/21` in `Az.: 3 O 123/21`.
+pub fn find_case_number(text: &str) -> Option<String> {
+    // Lowercasing ASCII keeps the byte offsets of `text`.
+    let lowercase = text.to_ascii_lowercase();
+
+    CASE_LABELS
+        .iter()
+        .flat_map(|label| {
+            lowercase
+                .match_indices(label)
+                .filter(|(start, _)| is_whole_word(&lowercase, *start, label.len()))
+                .map(move |(start, _)| start + label.len())
+        })
+        .min()
+        .and_then(|start| {
+            let parts: Vec<_> = text[start..]
+                .trim_start_matches([':', '.', '#', ' '])
+                .split_whitespace()
+                .map(|part| part.trim_end_matches([',', ';', '.']))
+                .take_while(|part| is_case_number_part(part))
+                .take(4)
+                .collect();
+
+            parts
+                .iter()
+                .any(|part| part.contains(|c: char| c.is_ascii_digit()))
+                .then(|| parts.join(" "))
+        })
+}
+
+/// Whether `part` is a piece of a case number, either made of letters, digits and separators with
+/// at least one digit, or a short uppercase register like the `O` of `3 O 123/21`.
+fn is_case_number_part(part: &str) -> bool {
+    let allowed = !part.is_empty()
+        && part
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | ':' | '.' | '/'));
+    let register = part.len() <= 2 && part.chars().all(|c| c.is_ascii_uppercase());
+
+    allowed && (part.contains(|c: char| c.is_ascii_digit()) || register)
+}
+
+/// Find the date a document was filed in `text`, written shortly after one of the
+/// [`FILING_LABELS`] as a whole word, like `Filed 03/15/24` or `Dated: March 15, 2024`, as
+/// `YYYY-MM-DD`.
+pub fn find_filing_date(text: &str) -> Option<String> {
+    let lowercase = text.to_ascii_lowercase();
+
+    FILING_LABELS
+        .iter()
+        .flat_map(|label| {
+            lowercase
+                .match_indices(label)
+                .filter(|(start, _)| is_whole_word(&lowercase, *start, label.len()))
+                .map(move |(start, _)| start + label.len())
+        })
+        .filter_map(|start| {
+            let after = &text[start..];
+            let end = after
+                .char_indices()
+                .nth(FILING_DATE_DISTANCE)
+                .map_or(after.len(), |(end, _)| end);
+            let after = &after[..end];
+            after
+                .split_whitespace()
+                .find_map(numeric_date)
+                .or_else(|| find_issue_date(after).filter(|date| date.len() == 10))
+                .map(|date| (start, date))
+        })
+        .min_by_key(|(start, _)| *start)
+        .map(|(_, date)| date)
+}
+
+/// Find the type of a court filing in `text`, like `Motion to Dismiss` for
+/// `DEFENDANT'S MOTION TO DISMISS`, from the first of the [`DOCUMENT_TYPES`] set in capitals and
+/// the capitalized words after it.
+pub fn find_document_type(text: &str) -> Option<String> {
+    let words: Vec<_> = text
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| c.is_ascii_punctuation()))
+        .collect();
+    let start = words
+        .iter()
+        .position(|word| DOCUMENT_TYPES.contains(word))?;
+    let words: Vec<_> = words[start..]
+        .iter()
+        .take_while(|word| {
+            !word.is_empty()
+                && word.chars().any(char::is_alphabetic)
+                && !word.chars().any(char::is_lowercase)
+        })
+        .take(MAX_DOCUMENT_TYPE_WORDS)
+        .enumerate()
+        .map(|(index, word)| {
+            let word = word.to_lowercase();
+            if index > 0 && SMALL_WORDS.contains(&word.as_str()) {
+                return word;
+            }
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+        .collect();
+    // Document types don't end in a small word, like `Motion to` before a line break.
+    let end = words
+        .iter()
+        .rposition(|word| !SMALL_WORDS.contains(&word.as_str()))?;
+
+    Some(words[..=end].join(" "))
+}
@@ -0,0 +1,107 @@
+use std::{fs, io, ops::Deref, path::Path, sync::Arc};
+
+use memmap2::Mmap;
+use pdf::{
+    backend::Backend,
+    file::{Cache, File, FileOptions, NoLog, ObjectCache, SyncCache},
+    object::PlainRef,
+    PdfError,
+};
+
+/// Content streams with more encoded bytes are skipped in low-memory mode.
+pub const MAX_LOW_MEMORY_STREAM_LENGTH: usize = 4 * 1024 * 1024;
+
+/// The content of a document file, either read into memory or mapped from disk.
+pub enum Data {
+    Read(Vec<u8>),
+    Mapped(Mmap),
+}
+
+impl Data {
+    /// Read the file at `path`, or map it in low-memory mode so only the parts that are parsed are
+    /// loaded.
+    pub fn open(path: &Path, low_memory: bool) -> io::Result<Self> {
+        if low_memory {
+            let file = fs::File::open(path)?;
+            // SAFETY: The file is only read. If it is modified while it is mapped, parsing fails
+            // or produces garbage, which is no worse than a corrupt document.
+            Ok(Self::Mapped(unsafe { Mmap::map(&file)? }))
+        } else {
+            fs::read(path).map(Self::Read)
+        }
+    }
+}
+
+impl Deref for Data {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Read(data) => data,
+            Self::Mapped(data) => data,
+        }
+    }
+}
+
+type Stream = Result<Arc<[u8]>, Arc<PdfError>>;
+
+/// A cache of decoded streams, which caches nothing in low-memory mode.
+pub struct StreamCache(Option<pdf::file::StreamCache>);
+
+impl StreamCache {
+    fn new(low_memory: bool) -> Self {
+        Self((!low_memory).then(SyncCache::new))
+    }
+}
+
+impl Cache<Stream> for StreamCache {
+    fn get_or_compute(&self, key: PlainRef, compute: impl FnOnce() -> Stream) -> Stream {
+        match &self.0 {
+            Some(cache) => cache.get_or_compute(key, compute),
+            None => compute(),
+        }
+    }
+
+    fn clear(&self) {
+        if let Some(cache) = &self.0 {
+            Cache::<Stream>::clear(cache);
+        }
+    }
+}
+
+pub type DocumentFile<B> = File<B, ObjectCache, StreamCache, NoLog>;
+
+/// Load a document from `backend`, without keeping decoded streams around in low-memory mode.
+///
+/// Encrypted documents are decrypted with `password`, which is empty for most of them.
+pub fn load<B: Backend>(
+    backend: B,
+    low_memory: bool,
+    password: &[u8],
+) -> Result<DocumentFile<B>, PdfError> {
+    FileOptions::cached()
+        .password(password)
+        .cache(SyncCache::new(), StreamCache::new(low_memory))
+        .load(backend)
+}
+
+/// Load a document with `load`, first with the empty password and then with `password` if the
+/// document is encrypted with another one.
+pub fn load_decrypted<B: Backend>(
+    load: impl Fn(&[u8]) -> Result<DocumentFile<B>, PdfError>,
+    password: Option<&str>,
+) -> Result<DocumentFile<B>, PdfError> {
+    match (load(b""), password) {
+        (Err(err), Some(password)) if is_encrypted(&err) => load(password.as_bytes()),
+        (result, _) => result,
+    }
+}
+
+/// Whether loading a document failed because it could not be decrypted with the password given.
+pub fn is_encrypted(err: &PdfError) -> bool {
+    match err {
+        PdfError::InvalidPassword => true,
+        PdfError::Try { source, .. } => is_encrypted(source),
+        _ => false,
+    }
+}
@@ -0,0 +1,378 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    identifiers::{find_doi, Identifiers},
+    xmp, DocumentClass,
+};
+
+/// Where a piece of metadata came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Provenance {
+    /// Set in an overrides file.
+    Override,
+    /// Extracted from the layout of the document's text.
+    Layout,
+    /// Typed in by the user.
+    User,
+    /// Taken from an HTML page saved as a PDF document.
+    Html,
+    /// Taken from the document information dictionary.
+    InfoDict,
+    /// Taken from the XMP metadata stream of the document.
+    Xmp,
+    /// Looked up on Crossref by the DOI of the document.
+    Crossref,
+    /// Looked up on arXiv by the arXiv identifier of the document.
+    Arxiv,
+    /// Recognized in the rendered pages of a document without text, like a scanned one, or with a
+    /// text layer that is less plausible.
+    Ocr,
+    /// Taken from the email the document was attached to.
+    Email,
+    /// Read from the name the document had before, which was given by another template or
+    /// program.
+    FileName,
+}
+
+impl Display for Provenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Override => "override",
+            Self::Layout => "layout",
+            Self::User => "user",
+            Self::Html => "html",
+            Self::InfoDict => "info-dict",
+            Self::Xmp => "xmp",
+            Self::Crossref => "crossref",
+            Self::Arxiv => "arxiv",
+            Self::Ocr => "ocr",
+            Self::Email => "email",
+            Self::FileName => "file-name",
+        })
+    }
+}
+
+/// A value of a metadata field together with where it came from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Field<T> {
+    pub value: T,
+    pub provenance: Provenance,
+}
+
+impl<T> Field<T> {
+    pub const fn new(value: T, provenance: Provenance) -> Self {
+        Self { value, provenance }
+    }
+}
+
+/// The fields of the document information dictionary that are used, as read by any backend.
+#[derive(Clone, Debug, Default)]
+pub struct Info {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+    pub creator: Option<String>,
+    pub producer: Option<String>,
+    /// The year, month and day the document was created. Dates without a month or day have them
+    /// set to 0.
+    pub creation_date: Option<(u16, u8, u8)>,
+}
+
+/// The metadata of a document.
+///
+/// Every field lists the values found in all sources, the most trustworthy one first. Sources
+/// without a value for a field are left out.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metadata {
+    pub title: Vec<Field<String>>,
+    pub author: Vec<Field<String>>,
+    pub year: Vec<Field<u16>>,
+    /// The date the document was created, as `YYYY-MM-DD`.
+    pub created: Vec<Field<String>>,
+    pub doi: Vec<Field<String>>,
+    /// The arXiv identifier, like `1706.03762`.
+    pub arxiv: Vec<Field<String>>,
+    /// The name of the periodical the document is an issue of, from its masthead.
+    pub publication: Vec<Field<String>>,
+    /// The date of the issue, as `YYYY-MM` or `YYYY-MM-DD`.
+    pub issue_date: Vec<Field<String>>,
+    /// The identifier of the standard the document is, like `ISO 9001:2015`.
+    pub standard_id: Vec<Field<String>>,
+    /// The number of the case a court filing belongs to, like `1:23-cv-04567`.
+    pub case_number: Vec<Field<String>>,
+    /// The date a court filing was filed, as `YYYY-MM-DD`.
+    pub filed: Vec<Field<String>>,
+    /// The type of a court filing, like `Motion to Dismiss`.
+    pub document_type: Vec<Field<String>>,
+    /// The bank, utility or other institution that sent a statement.
+    pub institution: Vec<Field<String>>,
+    /// The period a statement covers, as `YYYY-MM` or `YYYY-MM-DD - YYYY-MM-DD`.
+    pub period: Vec<Field<String>>,
+    /// The last four digits of the number of the account of a statement. The rest of the number is
+    /// never kept.
+    pub account_suffix: Vec<Field<String>>,
+    /// The maker of the component a datasheet describes.
+    pub manufacturer: Vec<Field<String>>,
+    /// The number of the part a datasheet describes, like `LM7805`.
+    pub part_number: Vec<Field<String>>,
+    /// The shop or business that issued a receipt.
+    pub vendor: Vec<Field<String>>,
+    /// The date of the purchase on a receipt, as `YYYY-MM-DD`.
+    pub purchased: Vec<Field<String>>,
+    /// The total of a receipt with a decimal point, like `23.45`.
+    pub amount: Vec<Field<String>>,
+    /// The code of the currency of the total of a receipt, like `EUR`.
+    pub currency: Vec<Field<String>>,
+    /// The composer of a piece of sheet music.
+    pub composer: Vec<Field<String>>,
+    /// The title of a piece of sheet music.
+    pub piece: Vec<Field<String>>,
+    /// The application the document was made with, like `Microsoft® Word 2016`.
+    pub creator: Vec<Field<String>>,
+    /// The application that converted the document to PDF, like `pdfTeX-1.40.25`.
+    pub producer: Vec<Field<String>>,
+    /// What kind of document it is, if it was recognized as one of the known kinds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class: Option<DocumentClass>,
+}
+
+impl Metadata {
+    /// Add the fields set in the document information dictionary.
+    pub(super) fn add_info_dict(&mut self, info: &Info) {
+        let text = |text: &Option<String>| {
+            text.as_ref()
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty())
+        };
+        let field = |value| Field::new(value, Provenance::InfoDict);
+
+        self.title.extend(
+            text(&info.title)
+                .filter(|title| !is_junk_title(title))
+                .map(field),
+        );
+        self.author.extend(
+            text(&info.author)
+                .filter(|author| !is_junk_author(author))
+                .map(field),
+        );
+        self.year.extend(
+            info.creation_date
+                .map(|(year, _, _)| Field::new(year, Provenance::InfoDict)),
+        );
+        self.created.extend(
+            info.creation_date
+                .map(|(year, month, day)| {
+                    format!("{year:04}-{:02}-{:02}", month.max(1), day.max(1))
+                })
+                .map(field),
+        );
+        self.doi.extend(
+            [&info.subject, &info.keywords, &info.title]
+                .into_iter()
+                .find_map(|value| find_doi(&text(value)?))
+                .map(field),
+        );
+        self.creator.extend(text(&info.creator).map(field));
+        self.producer.extend(text(&info.producer).map(field));
+    }
+}
+
+impl Metadata {
+    /// Add the values of every field of `other` after the values of this metadata.
+    pub fn append(&mut self, other: &Self) {
+        self.title.extend_from_slice(&other.title);
+        self.author.extend_from_slice(&other.author);
+        self.year.extend_from_slice(&other.year);
+        self.created.extend_from_slice(&other.created);
+        self.doi.extend_from_slice(&other.doi);
+        self.arxiv.extend_from_slice(&other.arxiv);
+        self.publication.extend_from_slice(&other.publication);
+        self.issue_date.extend_from_slice(&other.issue_date);
+        self.standard_id.extend_from_slice(&other.standard_id);
+        self.case_number.extend_from_slice(&other.case_number);
+        self.filed.extend_from_slice(&other.filed);
+        self.document_type.extend_from_slice(&other.document_type);
+        self.institution.extend_from_slice(&other.institution);
+        self.period.extend_from_slice(&other.period);
+        self.account_suffix.extend_from_slice(&other.account_suffix);
+        self.manufacturer.extend_from_slice(&other.manufacturer);
+        self.part_number.extend_from_slice(&other.part_number);
+        self.vendor.extend_from_slice(&other.vendor);
+        self.purchased.extend_from_slice(&other.purchased);
+        self.amount.extend_from_slice(&other.amount);
+        self.currency.extend_from_slice(&other.currency);
+        self.composer.extend_from_slice(&other.composer);
+        self.piece.extend_from_slice(&other.piece);
+        self.creator.extend_from_slice(&other.creator);
+        self.producer.extend_from_slice(&other.producer);
+        self.class = self.class.or(other.class);
+    }
+
+    /// Add the identifiers found in the text of the document.
+    pub(super) fn add_identifiers(&mut self, identifiers: Identifiers) {
+        let field = |value| Field::new(value, Provenance::Layout);
+
+        self.doi.extend(identifiers.doi.map(field));
+        self.arxiv.extend(identifiers.arxiv.map(field));
+    }
+
+    /// Add the fields set in an XMP packet, the XML of the metadata stream of the document.
+    pub(super) fn add_xmp(&mut self, xml: &str) {
+        let field = |value| Field::new(value, Provenance::Xmp);
+
+        self.title.extend(
+            xmp::property(xml, "dc:title")
+                .into_iter()
+                .find(|title| !is_junk_title(title))
+                .map(field),
+        );
+        let authors: Vec<_> = xmp::property(xml, "dc:creator")
+            .into_iter()
+            .filter(|author| !is_junk_author(author))
+            .collect();
+        if !authors.is_empty() {
+            self.author.push(field(authors.join("; ")));
+        }
+        // Dates are written as `YYYY`, `YYYY-MM`, `YYYY-MM-DD` or with a time after that.
+        if let Some(date) = xmp::property(xml, "xmp:CreateDate").first() {
+            if let Some(year) = date.get(..4).and_then(|year| year.parse().ok()) {
+                self.year.push(Field::new(year, Provenance::Xmp));
+                let month = date.get(5..7).unwrap_or("01");
+                let day = date.get(8..10).unwrap_or("01");
+                self.created.push(field(format!("{year:04}-{month}-{day}")));
+            }
+        }
+        self.doi.extend(
+            ["prism:doi", "dc:identifier", "dc:description", "dc:subject"]
+                .into_iter()
+                .flat_map(|name| xmp::property(xml, name))
+                .find_map(|value| find_doi(&value))
+                .map(field),
+        );
+        self.creator.extend(
+            xmp::property(xml, "xmp:CreatorTool")
+                .into_iter()
+                .next()
+                .map(field),
+        );
+        self.producer.extend(
+            xmp::property(xml, "pdf:Producer")
+                .into_iter()
+                .next()
+                .map(field),
+        );
+    }
+}
+
+/// Whether a title from the metadata of a document was most likely left there by the program that
+/// made it instead of being the title, like `Microsoft Word - draft.docx` or `Untitled`.
+pub(super) fn is_junk_title(title: &str) -> bool {
+    const PLACEHOLDERS: [&str; 8] = [
+        "untitled",
+        "unbenannt",
+        "no title",
+        "title",
+        "document",
+        "presentation",
+        "powerpoint presentation",
+        "slide 1",
+    ];
+    const EXTENSIONS: [&str; 12] = [
+        ".doc", ".docx", ".odt", ".rtf", ".txt", ".tex", ".dvi", ".ps", ".pdf", ".indd", ".ppt",
+        ".pptx",
+    ];
+
+    let title = title.trim().to_lowercase();
+    let numbered = title
+        .strip_prefix("document")
+        .is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()));
+
+    PLACEHOLDERS.contains(&title.as_str())
+        || numbered
+        || title.starts_with("microsoft ")
+        || EXTENSIONS
+            .iter()
+            .any(|extension| title.ends_with(extension))
+        || !title.chars().any(char::is_alphabetic)
+}
+
+/// Whether an author from the metadata of a document is most likely the account of whoever made
+/// the document instead of its author, like `Administrator`.
+fn is_junk_author(author: &str) -> bool {
+    const ACCOUNTS: [&str; 8] = [
+        "admin",
+        "administrator",
+        "user",
+        "owner",
+        "author",
+        "unknown",
+        "default",
+        "standard",
+    ];
+
+    let author = author.trim().to_lowercase();
+    ACCOUNTS.contains(&author.as_str()) || !author.chars().any(char::is_alphabetic)
+}
+
+/// The sources to trust for a metadata field, the most trusted first.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Preference {
+    pub prefer: Vec<Provenance>,
+}
+
+impl Preference {
+    /// Order `values` by how much their source is trusted. Values from sources that are not
+    /// listed keep their order after the listed ones.
+    fn apply<T>(&self, values: &mut [Field<T>]) {
+        values.sort_by_key(|field| {
+            self.prefer
+                .iter()
+                .position(|provenance| *provenance == field.provenance)
+                .unwrap_or(self.prefer.len())
+        });
+    }
+}
+
+/// Which sources to trust for each metadata field, e.g. the layout for the title but the
+/// document information dictionary for the year:
+///
+/// ```toml
+/// [title]
+/// prefer = ["layout", "info-dict"]
+///
+/// [year]
+/// prefer = ["info-dict"]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrustPolicy {
+    pub title: Preference,
+    pub author: Preference,
+    pub year: Preference,
+    pub created: Preference,
+    pub doi: Preference,
+    pub arxiv: Preference,
+}
+
+impl TrustPolicy {
+    /// Merge the values found in all sources by ordering every field of `metadata` by this
+    /// policy, so the first value of a field is the one to use.
+    #[must_use]
+    pub fn merge(&self, mut metadata: Metadata) -> Metadata {
+        self.title.apply(&mut metadata.title);
+        self.author.apply(&mut metadata.author);
+        self.year.apply(&mut metadata.year);
+        self.created.apply(&mut metadata.created);
+        self.doi.apply(&mut metadata.doi);
+        self.arxiv.apply(&mut metadata.arxiv);
+
+        metadata
+    }
+}
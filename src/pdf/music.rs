@@ -0,0 +1,124 @@
+use super::PositionedText;
+
+/// The labels in front of the name of a composer, lowercase.
+const COMPOSER_LABELS: &[&str] = &[
+    "music by",
+    "composed by",
+    "composer:",
+    "music:",
+    "musik:",
+    "musik von",
+    "komponiert von",
+    "musique de",
+];
+
+/// The labels of other people named at the top of sheet music, lowercase. Lyricists are often set
+/// right-aligned like composers.
+const OTHER_LABELS: &[&str] = &[
+    "words",
+    "lyrics",
+    "text",
+    "arr",
+    "arranged",
+    "transcribed",
+    "edited",
+    "bearbeitung",
+];
+
+/// How far down the page the name of the composer is looked for, as a fraction of its height.
+const COMPOSER_AREA: f32 = 0.3;
+
+/// How far the end of a line may be from the right edge of the page to count as right-aligned, as
+/// a fraction of its width.
+const RIGHT_TOLERANCE: f32 = 0.2;
+
+/// How far apart two pieces of text may be vertically to be on the same line, as a fraction of
+/// their font size.
+const LINE_TOLERANCE: f32 = 0.2;
+
+/// The most words the name of a composer is made of.
+const MAX_COMPOSER_WORDS: usize = 6;
+
+/// Find the composer named at the top of a page of sheet music `width` wide and `height` high,
+/// either after one of the [`COMPOSER_LABELS`] like `Music by Irving Berlin` or in the topmost line
+/// set right-aligned below the title, which is how most editions credit them. Years of birth and
+/// death like `(1685-1750)` are left out.
+pub fn find_composer(text: &[PositionedText], width: f32, height: f32) -> Option<String> {
+    if width <= 0. || height <= 0. {
+        return None;
+    }
+    let mut text: Vec<_> = text
+        .iter()
+        .filter(|text| !text.slanted && text.y <= height * COMPOSER_AREA)
+        .collect();
+    text.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    let lines: Vec<_> = text
+        .chunk_by(|a, b| (a.y - b.y).abs() <= a.scaled_font_size * LINE_TOLERANCE)
+        .map(|line| {
+            let joined = line
+                .iter()
+                .map(|text| text.text.trim())
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let start = line.iter().map(|text| text.x).fold(f32::INFINITY, f32::min);
+            let end = line
+                .iter()
+                .map(|text| text.end())
+                .fold(f32::NEG_INFINITY, f32::max);
+            (joined, start, end)
+        })
+        .collect();
+
+    let labelled = lines.iter().find_map(|(line, _, _)| {
+        // Lowercasing ASCII keeps the byte offsets of `line`.
+        let lowercase = line.to_ascii_lowercase();
+        COMPOSER_LABELS.iter().find_map(|label| {
+            let start = lowercase.find(label)? + label.len();
+            name(&line[start..])
+        })
+    });
+
+    labelled.or_else(|| {
+        lines
+            .iter()
+            .filter(|(_, start, end)| *start > width / 2. && *end >= width * (1. - RIGHT_TOLERANCE))
+            .filter(|(line, _, _)| {
+                let lowercase = line.to_lowercase();
+                !OTHER_LABELS
+                    .iter()
+                    .any(|label| lowercase.starts_with(label))
+            })
+            .find_map(|(line, _, _)| name(line))
+    })
+}
+
+/// The name of a person in `text` without years in parentheses or after a comma and the
+/// punctuation around it, if it looks like one: a few words with letters and no other digits.
+fn name(text: &str) -> Option<String> {
+    let mut name = String::new();
+    let mut depth = 0_usize;
+    for c in text.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => name.push(c),
+            _ => {}
+        }
+    }
+    // Years after a comma, like `Ludwig van Beethoven, 1770-1827`.
+    if name.contains(|c: char| c.is_ascii_digit()) {
+        name.truncate(name.find(',').unwrap_or(name.len()));
+    }
+    let name = name
+        .trim_matches(|c: char| !c.is_alphanumeric() && c != '.')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let words = name.split_whitespace().count();
+
+    ((1..=MAX_COMPOSER_WORDS).contains(&words)
+        && name.chars().any(char::is_alphabetic)
+        && !name.chars().any(|c| c.is_ascii_digit()))
+    .then_some(name)
+}
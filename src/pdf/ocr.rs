@@ -0,0 +1,555 @@
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    io::{self, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    str::FromStr,
+    sync::OnceLock,
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::PositionedText;
+
+mod raster;
+
+/// The resolution of page coordinates, which are in points.
+const POINTS_PER_INCH: f32 = 72.;
+
+/// Words that tesseract is less confident about (in percent) are left out, which drops most of the
+/// noise it finds in pictures.
+const MIN_WORD_CONFIDENCE: f32 = 30.;
+
+/// How much the heights of recognized lines may differ to be considered the same font size,
+/// relative to the smaller one. The height of a line depends on whether it has ascenders and
+/// descenders, so it only roughly follows the font size.
+const LINE_HEIGHT_TOLERANCE: f32 = 0.25;
+
+/// How pages are rendered to recognize their text.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rendering {
+    /// The resolution pages are rendered at, in dots per inch. Higher resolutions recognize small
+    /// text better but take longer.
+    pub dpi: u16,
+    /// The part of the page that is rendered.
+    pub region: Region,
+    /// Only recognize the text of the first this many pages, counting from the first page that is
+    /// parsed.
+    pub max_pages: Option<usize>,
+    /// The languages the text is recognized in.
+    pub language: Language,
+    /// Whether to straighten, enlarge and binarize rendered pages before recognizing their text,
+    /// if they need it.
+    pub preprocess: bool,
+}
+
+impl Default for Rendering {
+    fn default() -> Self {
+        Self {
+            dpi: 300,
+            region: Region::FULL,
+            max_pages: None,
+            language: Language::Default,
+            preprocess: true,
+        }
+    }
+}
+
+/// The languages tesseract recognizes text in.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Language {
+    /// Whatever tesseract uses if no language is given, which is English.
+    #[default]
+    Default,
+    /// Detect the script of every rendered page and use the installed languages written in it.
+    Auto,
+    /// The names of tesseract's language data joined with `+`, like `deu+eng`.
+    List(String),
+}
+
+impl Language {
+    /// The `-l` argument for tesseract to recognize the text of `image` in.
+    fn argument(&self, image: &[u8]) -> Option<String> {
+        match self {
+            Self::Default => None,
+            Self::List(languages) => Some(languages.clone()),
+            Self::Auto => {
+                let script = detect_script(image)
+                    .map_err(|err| log::warn!("could not detect the script of the page: {err}"))
+                    .ok()??;
+                let languages = languages_of_script(&script);
+                log::debug!("detected script {script}, recognizing {languages:?}");
+                languages
+            }
+        }
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Default => f.write_str("default"),
+            Self::Auto => f.write_str("auto"),
+            Self::List(languages) => f.write_str(languages),
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    /// Parse `default`, `auto` or languages joined with `+`, like `deu+eng` or `script/Latin`.
+    fn from_str(language: &str) -> Result<Self, Self::Err> {
+        match language {
+            "default" => Ok(Self::Default),
+            "auto" => Ok(Self::Auto),
+            _ if language.split('+').all(|name| {
+                !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '/'))
+            }) =>
+            {
+                Ok(Self::List(language.to_string()))
+            }
+            _ => Err(format!(
+                "expected `default`, `auto` or languages like `deu+eng`, not {language:?}"
+            )),
+        }
+    }
+}
+
+impl From<Language> for String {
+    fn from(language: Language) -> Self {
+        language.to_string()
+    }
+}
+
+impl TryFrom<String> for Language {
+    type Error = String;
+
+    fn try_from(language: String) -> Result<Self, Self::Error> {
+        language.parse()
+    }
+}
+
+/// A part of a page as it is displayed, in fractions of its width and height from the top left
+/// corner.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Region {
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Region {
+    pub const FULL: Self = Self {
+        left: 0.,
+        top: 0.,
+        width: 1.,
+        height: 1.,
+    };
+
+    /// The upper third of the page, where the title of a cover or title page usually is.
+    pub const TOP: Self = Self {
+        height: 1. / 3.,
+        ..Self::FULL
+    };
+
+    /// The region in pixels of a page `size` points wide and high rendered at `dpi`, as `(x, y,
+    /// width, height)`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn pixels(&self, (width, height): (f32, f32), dpi: u16) -> [u32; 4] {
+        let scale = f32::from(dpi) / POINTS_PER_INCH;
+        [
+            self.left * width,
+            self.top * height,
+            self.width * width,
+            self.height * height,
+        ]
+        .map(|length| (length * scale).round().max(0.) as u32)
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if *self == Self::FULL {
+            f.write_str("full")
+        } else if *self == Self::TOP {
+            f.write_str("top")
+        } else {
+            write!(
+                f,
+                "{},{},{},{}",
+                self.left, self.top, self.width, self.height
+            )
+        }
+    }
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    /// Parse `full`, `top` or the left and top edge, width and height of a region like
+    /// `0,0,1,0.5`, in fractions of the page.
+    fn from_str(region: &str) -> Result<Self, Self::Err> {
+        match region {
+            "full" => return Ok(Self::FULL),
+            "top" => return Ok(Self::TOP),
+            _ => {}
+        }
+
+        let fractions: Vec<f32> = region
+            .split(',')
+            .map(|fraction| fraction.trim().parse::<f32>())
+            .collect::<Result<_, _>>()
+            .map_err(|err| format!("{region:?} is not a list of fractions: {err}"))?;
+        let [left, top, width, height] = fractions[..] else {
+            return Err(format!(
+                "expected `full`, `top` or LEFT,TOP,WIDTH,HEIGHT, not {region:?}"
+            ));
+        };
+        if [left, top, width, height]
+            .iter()
+            .any(|fraction| !(0. ..=1.).contains(fraction))
+            || width <= 0.
+            || height <= 0.
+            || left + width > 1.
+            || top + height > 1.
+        {
+            return Err(format!("{region:?} is not a region inside the page"));
+        }
+
+        Ok(Self {
+            left,
+            top,
+            width,
+            height,
+        })
+    }
+}
+
+/// Where to read a document from to render its pages.
+pub enum Source {
+    Path(PathBuf),
+    Data(Vec<u8>),
+}
+
+/// The text recognized on a rendered page.
+pub struct Page {
+    pub text: Vec<PositionedText<'static>>,
+    /// The height of the page, computed from the size of the rendered image.
+    pub height: f32,
+}
+
+/// Render the page with the zero-based index `page` of `document` with `pdftoppm` and recognize
+/// its lines of text with `tesseract`.
+///
+/// Only the region of the page selected by `rendering` is rendered if the `size` of the displayed
+/// page in points is known, otherwise the whole page is. The font size of each line is estimated
+/// from its height, and the position from its bounding box, so they are only approximations of what
+/// the text layer of the page would contain.
+///
+/// # Errors
+///
+/// This function will return an error if `pdftoppm` or `tesseract` could not be run or failed.
+pub fn recognize(
+    document: &Source,
+    page: usize,
+    size: Option<(f32, f32)>,
+    rendering: &Rendering,
+    default_leading: f32,
+) -> io::Result<Page> {
+    let number = (page + 1).to_string();
+    let mut render = Command::new("pdftoppm");
+    render.args(["-gray", "-singlefile"]).args([
+        "-r",
+        &rendering.dpi.to_string(),
+        "-f",
+        &number,
+        "-l",
+        &number,
+    ]);
+    let crop = size
+        .filter(|_| rendering.region != Region::FULL)
+        .map(|size| rendering.region.pixels(size, rendering.dpi));
+    if let Some(crop) = crop {
+        log::debug!("rendering {crop:?} of page {number}");
+        for (option, pixels) in ["-x", "-y", "-W", "-H"].into_iter().zip(crop) {
+            render.args([option, &pixels.to_string()]);
+        }
+    }
+    let image = match document {
+        Source::Path(path) => run(render.arg(path), None)?,
+        Source::Data(data) => run(render.arg("-"), Some(data))?,
+    };
+    let (image, scale) = if rendering.preprocess {
+        preprocess(image)
+    } else {
+        (image, 1)
+    };
+    let mut tesseract = Command::new("tesseract");
+    tesseract.args(["stdin", "stdout"]);
+    if let Some(language) = rendering.language.argument(&image) {
+        tesseract.args(["-l", &language]);
+    }
+    let tsv = run(tesseract.arg("tsv"), Some(&image))?;
+
+    let [x, y, ..] = crop.unwrap_or_default();
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    let mut page = parse_tsv(
+        &String::from_utf8_lossy(&tsv),
+        f32::from(rendering.dpi) * scale as f32,
+        (x * scale as u32, y * scale as u32),
+        default_leading,
+    );
+    // The rendered image only covers the region.
+    if let Some((_, height)) = size.filter(|_| crop.is_some()) {
+        page.height = height;
+    }
+
+    Ok(page)
+}
+
+/// Preprocess the PGM `image` rendered by `pdftoppm`, returning the image to recognize and how
+/// many times it was enlarged.
+fn preprocess(image: Vec<u8>) -> (Vec<u8>, usize) {
+    let Some(mut raster) = raster::Image::from_pgm(&image) else {
+        log::warn!("could not read the rendered page to preprocess it");
+        return (image, 1);
+    };
+    let preprocessing = raster::preprocess(&mut raster);
+    log::debug!("preprocessed the rendered page: {preprocessing}");
+    if preprocessing == raster::Preprocessing::default() {
+        (image, 1)
+    } else {
+        (raster.to_pgm(), preprocessing.scale)
+    }
+}
+
+/// Detect the script the text of `image` is written in with tesseract's orientation and script
+/// detection, like `Latin` or `Cyrillic`. Pages with too little text to tell have none.
+fn detect_script(image: &[u8]) -> io::Result<Option<String>> {
+    let output = run(
+        Command::new("tesseract").args(["stdin", "stdout", "--psm", "0"]),
+        Some(image),
+    )?;
+
+    Ok(String::from_utf8_lossy(&output)
+        .lines()
+        .find_map(|line| line.strip_prefix("Script:"))
+        .map(|script| script.trim().to_string())
+        .filter(|script| !script.is_empty()))
+}
+
+/// The installed languages written in `script`, joined with `+`. If none of them are installed, the
+/// model for the whole script is used if it is.
+fn languages_of_script(script: &str) -> Option<String> {
+    // The languages most documents in each script are written in, by the name of their data.
+    const LANGUAGES: [(&str, &[&str]); 12] = [
+        ("Latin", &["eng", "deu", "fra", "spa", "ita", "por", "nld"]),
+        ("Cyrillic", &["rus", "ukr", "bul", "srp"]),
+        ("Greek", &["ell"]),
+        ("Arabic", &["ara", "fas", "urd"]),
+        ("Hebrew", &["heb"]),
+        ("Devanagari", &["hin", "mar", "nep"]),
+        ("Han", &["chi_sim", "chi_tra"]),
+        ("Japanese", &["jpn"]),
+        ("Katakana", &["jpn"]),
+        ("Hiragana", &["jpn"]),
+        ("Korean", &["kor"]),
+        ("Thai", &["tha"]),
+    ];
+
+    let installed = installed_languages();
+    let languages: Vec<_> = LANGUAGES
+        .iter()
+        .find(|(name, _)| *name == script)
+        .map(|(_, languages)| *languages)
+        .unwrap_or_default()
+        .iter()
+        .filter(|language| installed.iter().any(|installed| installed == *language))
+        .copied()
+        .collect();
+
+    if languages.is_empty() {
+        let model = format!("script/{script}");
+        installed.contains(&model).then_some(model)
+    } else {
+        Some(languages.join("+"))
+    }
+}
+
+/// The languages tesseract has data for, which are only listed once.
+fn installed_languages() -> &'static [String] {
+    static INSTALLED: OnceLock<Vec<String>> = OnceLock::new();
+
+    INSTALLED.get_or_init(|| {
+        match run(Command::new("tesseract").arg("--list-langs"), None) {
+            // The first line says where the data is.
+            Ok(output) => String::from_utf8_lossy(&output)
+                .lines()
+                .skip(1)
+                .map(|language| language.trim().to_string())
+                .filter(|language| !language.is_empty())
+                .collect(),
+            Err(err) => {
+                log::warn!("could not list the languages of tesseract: {err}");
+                Vec::new()
+            }
+        }
+    })
+}
+
+/// Run `command` with `input` on its standard input and return its standard output.
+fn run(command: &mut Command, input: Option<&[u8]>) -> io::Result<Vec<u8>> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    log::debug!("running {program}");
+    let mut child = command
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| io::Error::new(err.kind(), format!("could not run {program}: {err}")))?;
+
+    // Writing the input while the output is read keeps both pipes from filling up.
+    let output = thread::scope(|scope| {
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            scope.spawn(move || stdin.write_all(input));
+        }
+        child.wait_with_output()
+    })?;
+
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(io::Error::other(format!(
+            "{program} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// A word recognized by tesseract, with its bounding box in pixels.
+struct Word<'a> {
+    /// The block, paragraph and line the word is in.
+    line: (&'a str, &'a str, &'a str),
+    left: f32,
+    top: f32,
+    bottom: f32,
+    text: &'a str,
+}
+
+/// Put the words in the TSV output of tesseract for a page with `dpi` pixels per inch together into
+/// lines of text. The image was cropped to start at `offset` pixels from the top left of the page.
+#[allow(clippy::cast_precision_loss)]
+fn parse_tsv(tsv: &str, dpi: f32, offset: (u32, u32), default_leading: f32) -> Page {
+    let scale = POINTS_PER_INCH / dpi;
+    let (offset_x, offset_y) = (offset.0 as f32, offset.1 as f32);
+    let mut height = 0.;
+    let mut words = Vec::new();
+
+    // level, page_num, block_num, par_num, line_num, word_num, left, top, width, height, conf, text
+    for row in tsv.lines().skip(1) {
+        let columns: Vec<_> = row.split('\t').collect();
+        let [level, _, block, paragraph, line, _, left, top, _, word_height, confidence, text] =
+            columns[..]
+        else {
+            continue;
+        };
+        let number = |column: &str| column.trim().parse::<f32>().ok();
+        let (Some(left), Some(top), Some(word_height)) =
+            (number(left), number(top), number(word_height))
+        else {
+            continue;
+        };
+
+        match level {
+            // The page itself, whose box is the whole image.
+            "1" => height = word_height * scale,
+            "5" if !text.trim().is_empty()
+                && number(confidence)
+                    .is_some_and(|confidence| confidence >= MIN_WORD_CONFIDENCE) =>
+            {
+                words.push(Word {
+                    line: (block, paragraph, line),
+                    left: left + offset_x,
+                    top: top + offset_y,
+                    bottom: top + offset_y + word_height,
+                    text: text.trim(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let mut text: Vec<_> = words
+        .chunk_by(|a, b| a.line == b.line)
+        .map(|line| {
+            let left = line
+                .iter()
+                .map(|word| word.left)
+                .fold(f32::INFINITY, f32::min);
+            let top = line
+                .iter()
+                .map(|word| word.top)
+                .fold(f32::INFINITY, f32::min);
+            let bottom = line.iter().map(|word| word.bottom).fold(0., f32::max);
+            let font_size = (bottom - top) * scale;
+            let text = line
+                .iter()
+                .map(|word| word.text)
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            PositionedText {
+                text: Cow::Owned(text),
+                font_size,
+                x: left * scale,
+                // Roughly where the baseline is, above the descenders.
+                y: font_size.mul_add(-0.2, bottom * scale),
+                leading: font_size * default_leading,
+                raised: 0.,
+                scaled_font_size: font_size,
+                script: None,
+                slanted: false,
+            }
+        })
+        .collect();
+    unify_font_sizes(&mut text);
+
+    Page { text, height }
+}
+
+/// Give lines of similar heights the same font size, so the lines of a title end up in the same
+/// block even if only some of them have descenders.
+fn unify_font_sizes(text: &mut [PositionedText]) {
+    let mut sizes: Vec<_> = text.iter().map(|text| text.font_size).collect();
+    sizes.sort_by(f32::total_cmp);
+    let groups: Vec<_> = sizes
+        .chunk_by(|a, b| *b <= *a * (1. + LINE_HEIGHT_TOLERANCE))
+        .map(|group| (group[0], group[group.len() - 1], group[group.len() / 2]))
+        .collect();
+
+    for text in text {
+        if let Some((_, _, median)) = groups
+            .iter()
+            .find(|(smallest, largest, _)| (*smallest..=*largest).contains(&text.font_size))
+        {
+            // Rounded, so rounding errors don't split the group again.
+            let size = (median * 2.).round() / 2.;
+            text.leading *= size / text.font_size.max(f32::EPSILON);
+            text.font_size = size;
+            text.scaled_font_size = size;
+        }
+    }
+}
@@ -0,0 +1,361 @@
+use std::fmt::Display;
+
+/// Contrast below which, as the difference between the darkest and lightest percent of the
+/// pixels, a page is binarized.
+const MIN_CONTRAST: u8 = 96;
+
+/// The largest skew that is looked for, in degrees.
+const MAX_SKEW: f32 = 5.;
+
+/// The steps in which the skew is looked for, in degrees.
+const SKEW_STEP: f32 = 0.2;
+
+/// Pages skewed less than this many degrees are left as they are.
+const MIN_SKEW: f32 = 0.4;
+
+/// The height of a line of text in pixels below which tesseract starts to miss characters.
+const MIN_LINE_HEIGHT: usize = 24;
+
+/// How many times a page is enlarged at most to make its text large enough.
+const MAX_SCALE: usize = 4;
+
+/// The number of pixels a page is not enlarged beyond.
+const MAX_PIXELS: usize = 40_000_000;
+
+/// A grayscale image with one byte per pixel, from black to white.
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Read a binary PGM image, as written by `pdftoppm -gray`.
+    pub fn from_pgm(data: &[u8]) -> Option<Self> {
+        let mut rest = data.strip_prefix(b"P5")?;
+        let mut header = [0_usize; 3];
+        for value in &mut header {
+            loop {
+                rest = rest.trim_ascii_start();
+                // Comments run until the end of the line.
+                if rest.first() != Some(&b'#') {
+                    break;
+                }
+                let end = rest.iter().position(|&byte| byte == b'\n')?;
+                rest = &rest[end..];
+            }
+            let end = rest
+                .iter()
+                .position(|byte| !byte.is_ascii_digit())
+                .unwrap_or(rest.len());
+            *value = std::str::from_utf8(&rest[..end]).ok()?.parse().ok()?;
+            rest = &rest[end..];
+        }
+        let [width, height, max] = header;
+        // A single whitespace character separates the header from the pixels.
+        let pixels = rest.get(1..width.checked_mul(height)? + 1)?;
+        if max != 255 {
+            return None;
+        }
+
+        Some(Self {
+            width,
+            height,
+            pixels: pixels.to_vec(),
+        })
+    }
+
+    /// Write the image as a binary PGM image.
+    pub fn to_pgm(&self) -> Vec<u8> {
+        let mut data = format!("P5\n{} {}\n255\n", self.width, self.height).into_bytes();
+        data.extend_from_slice(&self.pixels);
+        data
+    }
+
+    fn histogram(&self) -> [usize; 256] {
+        let mut histogram = [0; 256];
+        for &pixel in &self.pixels {
+            histogram[usize::from(pixel)] += 1;
+        }
+        histogram
+    }
+
+    /// The pixel at `(x, y)`, interpolated between the surrounding ones. Pixels outside the image
+    /// are white.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn sample(&self, x: f32, y: f32) -> u8 {
+        let pixel = |x: f32, y: f32| {
+            if x < 0. || y < 0. || x >= self.width as f32 || y >= self.height as f32 {
+                255.
+            } else {
+                f32::from(self.pixels[y as usize * self.width + x as usize])
+            }
+        };
+        let (left, top) = (x.floor(), y.floor());
+        let (right, bottom) = (x - left, y - top);
+        let upper = pixel(left, top).mul_add(1. - right, pixel(left + 1., top) * right);
+        let lower = pixel(left, top + 1.).mul_add(1. - right, pixel(left + 1., top + 1.) * right);
+
+        upper.mul_add(1. - bottom, lower * bottom).round() as u8
+    }
+
+    /// Rotate the image by `degrees` clockwise around its center.
+    #[allow(clippy::cast_precision_loss)]
+    fn rotate(&mut self, degrees: f32) {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let (center_x, center_y) = (self.width as f32 / 2., self.height as f32 / 2.);
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for y in 0..self.height {
+            let dy = y as f32 - center_y;
+            for x in 0..self.width {
+                let dx = x as f32 - center_x;
+                pixels.push(self.sample(
+                    dx.mul_add(cos, -dy * sin) + center_x,
+                    dx.mul_add(sin, dy * cos) + center_y,
+                ));
+            }
+        }
+        self.pixels = pixels;
+    }
+
+    /// Enlarge the image `scale` times.
+    #[allow(clippy::cast_precision_loss)]
+    fn scale(&mut self, scale: usize) {
+        let (width, height) = (self.width * scale, self.height * scale);
+        let factor = 1. / scale as f32;
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(self.sample(
+                    (x as f32 + 0.5).mul_add(factor, -0.5).max(0.),
+                    (y as f32 + 0.5).mul_add(factor, -0.5).max(0.),
+                ));
+            }
+        }
+        *self = Self {
+            width,
+            height,
+            pixels,
+        };
+    }
+
+    /// Make the pixels darker than `threshold` black and all others white.
+    fn binarize(&mut self, threshold: u8) {
+        for pixel in &mut self.pixels {
+            *pixel = if *pixel < threshold { 0 } else { 255 };
+        }
+    }
+
+    /// The coordinates of a sample of the pixels darker than `threshold`.
+    fn ink(&self, threshold: u8) -> Vec<(usize, usize)> {
+        // Enough pixels to find the direction of the lines, without taking too long on large pages.
+        const SAMPLES: usize = 200_000;
+
+        let ink = self
+            .pixels
+            .iter()
+            .filter(|&&pixel| pixel < threshold)
+            .count();
+        let step = (ink / SAMPLES).max(1);
+        self.pixels
+            .iter()
+            .enumerate()
+            .filter(|(_, &pixel)| pixel < threshold)
+            .step_by(step)
+            .map(|(index, _)| (index % self.width, index / self.width))
+            .collect()
+    }
+
+    /// The angle in degrees the lines of text are rotated clockwise by, found by shearing the
+    /// pixels darker than `threshold` until their rows are the most uneven, which is when they
+    /// line up with the lines of text.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss
+    )]
+    fn skew(&self, threshold: u8) -> f32 {
+        let ink = self.ink(threshold);
+        if ink.is_empty() {
+            return 0.;
+        }
+        let score = |degrees: f32| {
+            let slope = degrees.to_radians().tan();
+            let offset = (self.width as f32 * slope.abs()).ceil() as usize;
+            let mut rows = vec![0_usize; self.height + 2 * offset + 1];
+            for &(x, y) in &ink {
+                let row = (x as f32).mul_add(-slope, y as f32).round() as isize + offset as isize;
+                if let Some(row) = usize::try_from(row).ok().and_then(|row| rows.get_mut(row)) {
+                    *row += 1;
+                }
+            }
+            rows.iter().map(|count| count * count).sum::<usize>()
+        };
+
+        let steps = (MAX_SKEW / SKEW_STEP).round() as i32;
+        (-steps..=steps)
+            .map(|step| step as f32 * SKEW_STEP)
+            .map(|degrees| (degrees, score(degrees)))
+            // Prefer the smaller of equally good angles.
+            .max_by_key(|(degrees, score)| (*score, std::cmp::Reverse(degrees.abs().to_bits())))
+            .map_or(0., |(degrees, _)| degrees)
+    }
+
+    /// The median height of the lines of text, as runs of rows with pixels darker than
+    /// `threshold`.
+    fn line_height(&self, threshold: u8) -> Option<usize> {
+        // Rows with fewer dark pixels are noise between the lines.
+        let min_ink = (self.width / 200).max(2);
+        let mut heights = Vec::new();
+        let mut height = 0;
+        for row in self.pixels.chunks(self.width) {
+            if row.iter().filter(|&&pixel| pixel < threshold).count() >= min_ink {
+                height += 1;
+            } else {
+                // Shorter runs are specks and rules.
+                if height >= 3 {
+                    heights.push(height);
+                }
+                height = 0;
+            }
+        }
+        heights.sort_unstable();
+
+        heights.get(heights.len() / 2).copied()
+    }
+}
+
+/// What was done to a page before recognizing its text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Preprocessing {
+    /// The angle in degrees the page was rotated counterclockwise by to straighten its lines.
+    pub deskewed: f32,
+    /// Whether the page had too little contrast and was made black and white.
+    pub binarized: bool,
+    /// How many times the page was enlarged to make its text large enough.
+    pub scale: usize,
+}
+
+impl Default for Preprocessing {
+    /// Nothing.
+    fn default() -> Self {
+        Self {
+            deskewed: 0.,
+            binarized: false,
+            scale: 1,
+        }
+    }
+}
+
+impl Display for Preprocessing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "deskewed by {}°, {}binarized, scaled {}×",
+            self.deskewed,
+            if self.binarized { "" } else { "not " },
+            self.scale
+        )
+    }
+}
+
+/// Straighten the lines of `image`, enlarge it if its text is small and make it black and white if
+/// it has little contrast, like photographed pages often do.
+pub fn preprocess(image: &mut Image) -> Preprocessing {
+    let histogram = image.histogram();
+    let threshold = otsu(&histogram);
+    let (dark, light) = percentiles(&histogram, image.pixels.len());
+    let binarized = light.saturating_sub(dark) < MIN_CONTRAST;
+
+    let skew = image.skew(threshold);
+    let deskewed = if skew.abs() >= MIN_SKEW {
+        image.rotate(skew);
+        skew
+    } else {
+        0.
+    };
+
+    let scale = image
+        .line_height(threshold)
+        .filter(|height| *height < MIN_LINE_HEIGHT)
+        .map_or(1, |height| MIN_LINE_HEIGHT.div_ceil(height.max(1)))
+        .min(MAX_SCALE)
+        .min((MAX_PIXELS / image.pixels.len().max(1)).isqrt())
+        .max(1);
+    if scale > 1 {
+        image.scale(scale);
+    }
+
+    if binarized {
+        image.binarize(threshold);
+    }
+
+    Preprocessing {
+        deskewed,
+        binarized,
+        scale,
+    }
+}
+
+/// The threshold that separates the pixels of `histogram` into dark and light ones with the least
+/// variance within each, by Otsu's method.
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn otsu(histogram: &[usize; 256]) -> u8 {
+    let total = histogram.iter().sum::<usize>() as f64;
+    let sum = histogram
+        .iter()
+        .enumerate()
+        .map(|(value, count)| (value * count) as f64)
+        .sum::<f64>();
+    let (mut dark_count, mut dark_sum) = (0., 0.);
+    let (mut best, mut best_variance) = (128, 0.);
+
+    for (value, &count) in histogram.iter().enumerate() {
+        dark_count += count as f64;
+        dark_sum += (value * count) as f64;
+        let light_count = total - dark_count;
+        if dark_count == 0. || light_count == 0. {
+            continue;
+        }
+        let difference = dark_sum / dark_count - (sum - dark_sum) / light_count;
+        let variance = dark_count * light_count * difference * difference;
+        if variance > best_variance {
+            best_variance = variance;
+            // Pixels up to and including `value` are dark.
+            best = value + 1;
+        }
+    }
+
+    best.min(255) as u8
+}
+
+/// The values of the darkest and lightest percent of the `total` pixels of `histogram`.
+#[allow(clippy::cast_possible_truncation)]
+fn percentiles(histogram: &[usize; 256], total: usize) -> (u8, u8) {
+    let percent = total / 100;
+    let mut count = 0;
+    let dark = histogram
+        .iter()
+        .position(|value| {
+            count += value;
+            count > percent
+        })
+        .unwrap_or(0);
+    count = 0;
+    let light = 255
+        - histogram
+            .iter()
+            .rev()
+            .position(|value| {
+                count += value;
+                count > percent
+            })
+            .unwrap_or(0);
+
+    (dark as u8, light as u8)
+}
@@ -0,0 +1,95 @@
+/// Short words that are common in the languages of most documents, which are plausible even though
+/// they don't look like words to the other rules, or are too short for them.
+const COMMON_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "by", "for", "in", "is", "of", "on", "or", "the", "to", "with",
+    "der", "die", "das", "und", "von", "zu", "mit", "für", "le", "la", "les", "de", "des", "du",
+    "et", "en", "el", "los", "las", "y", "del", "il", "di", "e",
+];
+
+/// Digits that recognition and broken encodings commonly put in place of letters, like `0` for `o`
+/// and `1` for `l`.
+const CONFUSED_DIGITS: &[char] = &['0', '1', '5', '8'];
+
+/// How plausible `text` is as text someone wrote, between 0 for none of its words to 1 for all of
+/// them.
+///
+/// A word is plausible if it is common, or if it looks like a word: Latin words need a vowel,
+/// mustn't have long runs of consonants and only change from lowercase to uppercase once, like
+/// `McDonald`. Words with characters that could not be decoded, unusual symbols or digits between
+/// lowercase letters, like `c0mpany`, are implausible, as those are what broken text layers and
+/// misrecognized pages are made of. Numbers and single characters are not counted, so text with
+/// no other words has no plausibility.
+pub fn plausibility(text: &str) -> Option<f32> {
+    let (plausible, total) = text
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| c.is_ascii_punctuation() && c != '|'))
+        .filter(|word| {
+            (word.chars().count() > 1 && word.chars().any(char::is_alphabetic)) || is_common(word)
+        })
+        .fold((0, 0), |(plausible, total), word| {
+            (plausible + usize::from(is_plausible(word)), total + 1)
+        });
+
+    #[allow(clippy::cast_precision_loss)]
+    (total > 0).then(|| plausible as f32 / total as f32)
+}
+
+fn is_common(word: &str) -> bool {
+    COMMON_WORDS.contains(&word.to_lowercase().as_str())
+}
+
+fn is_plausible(word: &str) -> bool {
+    if is_common(word) {
+        return true;
+    }
+    if word.chars().any(|c| {
+        c == '\u{fffd}'
+            || c.is_control()
+            || ('\u{e000}'..='\u{f8ff}').contains(&c)
+            || matches!(c, '|' | '¦' | '~' | '^' | '`' | '\\')
+    }) {
+        return false;
+    }
+
+    let chars: Vec<_> = word.chars().collect();
+    let confused = chars.windows(3).any(|window| {
+        window[0].is_lowercase() && CONFUSED_DIGITS.contains(&window[1]) && window[2].is_lowercase()
+    }) || chars
+        .windows(2)
+        .next()
+        .is_some_and(|start| CONFUSED_DIGITS.contains(&start[0]) && start[1].is_lowercase());
+    if confused {
+        return false;
+    }
+
+    // Only Latin words can be judged by their vowels, other scripts are taken as they are.
+    let latin: Vec<_> = chars
+        .iter()
+        .filter(|c| c.is_alphabetic())
+        .map(|&c| deunicode::deunicode_char(c).unwrap_or(""))
+        .collect();
+    if latin.is_empty()
+        || !latin
+            .iter()
+            .all(|letter| letter.len() == 1 && letter.chars().all(|c| c.is_ascii_alphabetic()))
+    {
+        return true;
+    }
+    let letters: String = latin.concat();
+    // All-caps abbreviations like `HTTP` have no vowels.
+    let abbreviation = letters.len() <= 5 && letters.chars().all(|c| c.is_ascii_uppercase());
+    let is_vowel = |c: char| "aeiouy".contains(c.to_ascii_lowercase());
+    let has_vowel = letters.chars().any(is_vowel);
+    let longest_consonants = letters
+        .split(is_vowel)
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let case_changes = letters
+        .as_bytes()
+        .windows(2)
+        .filter(|pair| pair[0].is_ascii_lowercase() && pair[1].is_ascii_uppercase())
+        .count();
+
+    (has_vowel || abbreviation) && longest_consonants <= 6 && case_changes <= 1
+}
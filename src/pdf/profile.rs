@@ -0,0 +1,111 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Heuristics tuned for a kind of document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Profile {
+    /// Books, whose title is on a sparse title or half-title page in the front matter. Titles on
+    /// these pages are preferred over larger text on dense pages like the copyright page or the
+    /// first page of a chapter.
+    Book,
+    /// Magazines and newspapers, whose cover is dominated by the masthead with the name of the
+    /// publication. Issues are named after the publication and the date of the issue, which are
+    /// also kept separately in [`Metadata::publication`](super::Metadata::publication) and
+    /// [`Metadata::issue_date`](super::Metadata::issue_date).
+    Magazine,
+    /// Standards like those of ISO, DIN or the IETF, whose identifier (`ISO 9001:2015`) is kept in
+    /// [`Metadata::standard_id`](super::Metadata::standard_id) to name them by.
+    Standard,
+    /// Court filings, whose caption holds the number of the case, the date they were filed and
+    /// the type of document, kept in [`Metadata::case_number`](super::Metadata::case_number),
+    /// [`Metadata::filed`](super::Metadata::filed) and
+    /// [`Metadata::document_type`](super::Metadata::document_type). The type of document is used as
+    /// the title instead of the name of the court above it.
+    Legal,
+    /// Statements of bank accounts and bills of utilities, which are named after the institution
+    /// that sent them and the period they cover. These and the last digits of the account, but
+    /// never the whole number, are kept in [`Metadata::institution`](super::Metadata::institution),
+    /// [`Metadata::period`](super::Metadata::period) and
+    /// [`Metadata::account_suffix`](super::Metadata::account_suffix).
+    Statement,
+    /// Datasheets of electronic components, which are named after the maker and the number of the
+    /// part, like `Texas Instruments LM7805 datasheet`. Both are kept in
+    /// [`Metadata::manufacturer`](super::Metadata::manufacturer) and
+    /// [`Metadata::part_number`](super::Metadata::part_number).
+    Datasheet,
+    /// Receipts, usually scanned and read with OCR, whose vendor at the top is the title. The date
+    /// of the purchase and the total with its currency are kept in
+    /// [`Metadata::purchased`](super::Metadata::purchased),
+    /// [`Metadata::amount`](super::Metadata::amount) and
+    /// [`Metadata::currency`](super::Metadata::currency).
+    Receipt,
+    /// Sheet music, whose title is the piece and whose composer is usually set right-aligned below
+    /// it, kept in [`Metadata::piece`](super::Metadata::piece) and
+    /// [`Metadata::composer`](super::Metadata::composer).
+    Music,
+}
+
+impl Profile {
+    pub const ALL: [Self; 8] = [
+        Self::Book,
+        Self::Magazine,
+        Self::Standard,
+        Self::Legal,
+        Self::Statement,
+        Self::Datasheet,
+        Self::Receipt,
+        Self::Music,
+    ];
+
+    /// How many pages to search for the title if no page count is given.
+    #[must_use]
+    pub const fn page_count(self) -> usize {
+        match self {
+            // The title page comes after the cover, the half-title page and maybe a frontispiece.
+            Self::Book => 6,
+            // The masthead and the date of the issue are on the cover, the caption of court
+            // filings, the summary of statements, the header of datasheets and the title of sheet
+            // music on their first page, and receipts are a single page.
+            Self::Magazine
+            | Self::Legal
+            | Self::Statement
+            | Self::Datasheet
+            | Self::Receipt
+            | Self::Music => 1,
+            Self::Standard => 2,
+        }
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Book => "book",
+            Self::Magazine => "magazine",
+            Self::Standard => "standard",
+            Self::Legal => "legal",
+            Self::Statement => "statement",
+            Self::Datasheet => "datasheet",
+            Self::Receipt => "receipt",
+            Self::Music => "music",
+        })
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(profile: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|known| known.to_string() == profile)
+            .ok_or_else(|| {
+                format!(
+                    "unknown profile {profile:?}, expected one of {}",
+                    Self::ALL.map(|known| known.to_string()).join(", ")
+                )
+            })
+    }
+}
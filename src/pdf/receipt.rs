@@ -0,0 +1,128 @@
+use super::identifiers::numeric_date;
+
+/// The labels in front of the total of a receipt, lowercase.
+const TOTAL_LABELS: &[&str] = &[
+    "total",
+    "grand total",
+    "amount due",
+    "gesamt",
+    "gesamtbetrag",
+    "summe",
+    "zu zahlen",
+    "montant",
+];
+
+/// How many characters after a label of the total are searched for the amount.
+const TOTAL_DISTANCE: usize = 30;
+
+/// The symbols of currencies and their codes.
+const CURRENCY_SYMBOLS: &[(&str, &str)] = &[("€", "EUR"), ("$", "USD"), ("£", "GBP"), ("¥", "JPY")];
+
+/// The codes of common currencies.
+const CURRENCY_CODES: &[&str] = &[
+    "EUR", "USD", "GBP", "CHF", "JPY", "CAD", "AUD", "SEK", "NOK", "DKK", "PLN", "CZK",
+];
+
+/// The total of a receipt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Total {
+    /// The amount with a decimal point and without separators of thousands, like `1234.50`.
+    pub amount: String,
+    /// The code of the currency written next to the amount, like `EUR`.
+    pub currency: Option<String>,
+}
+
+/// Find the total of a receipt in `text`, the largest amount after one of the [`TOTAL_LABELS`].
+pub fn find_total(text: &str) -> Option<Total> {
+    // Lowercasing ASCII keeps the byte offsets of `text`.
+    let lowercase = text.to_ascii_lowercase();
+
+    TOTAL_LABELS
+        .iter()
+        .flat_map(|label| {
+            let lowercase = &lowercase;
+            lowercase
+                .match_indices(label)
+                // Not the end of another word, like `subtotal`.
+                .filter(|(start, _)| {
+                    lowercase[..*start]
+                        .chars()
+                        .next_back()
+                        .is_none_or(|c| !c.is_alphabetic())
+                })
+                .map(move |(start, _)| start + label.len())
+        })
+        .filter_map(|start| {
+            let after = &text[start..];
+            let end = after
+                .char_indices()
+                .nth(TOTAL_DISTANCE)
+                .map_or(after.len(), |(end, _)| end);
+            let after = &after[..end];
+            let amount = after.split_whitespace().find_map(amount)?;
+            Some(Total {
+                currency: find_currency(after),
+                amount,
+            })
+        })
+        .max_by(|a, b| {
+            let value = |total: &Total| total.amount.parse::<f64>().unwrap_or(0.);
+            value(a).total_cmp(&value(b))
+        })
+}
+
+/// The amount of money written in `word` with cents, like `23.45`, `1,234.50`, `1.234,50` or
+/// `€12,00`, with a decimal point and without separators of thousands. Numbers without cents are
+/// more likely counts or dates.
+fn amount(word: &str) -> Option<String> {
+    let number = word.trim_matches(|c: char| !c.is_ascii_digit());
+    if number.is_empty()
+        || !number
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | '\''))
+    {
+        return None;
+    }
+    // The decimal separator is the last one, with exactly two digits after it.
+    let separator = number
+        .rfind(['.', ','])
+        .filter(|separator| number.len() - separator - 1 == 2)?;
+    let (whole, cents) = (&number[..separator], &number[separator + 1..]);
+    let whole: String = whole.chars().filter(char::is_ascii_digit).collect();
+
+    (!whole.is_empty()).then(|| format!("{whole}.{cents}"))
+}
+
+/// Find the code of the first currency written in `text` as a code like `EUR` or a symbol like
+/// `€`.
+pub fn find_currency(text: &str) -> Option<String> {
+    let codes = CURRENCY_CODES.iter().filter_map(|code| {
+        text.match_indices(code)
+            // Codes may be written right next to the amount, like `12.00EUR`.
+            .find(|(start, _)| {
+                text[..*start]
+                    .chars()
+                    .next_back()
+                    .is_none_or(|c| !c.is_alphabetic())
+                    && text[start + code.len()..]
+                        .chars()
+                        .next()
+                        .is_none_or(|c| !c.is_alphabetic())
+            })
+            .map(|(start, _)| (start, *code))
+    });
+    let symbols = CURRENCY_SYMBOLS
+        .iter()
+        .filter_map(|(symbol, code)| text.find(symbol).map(|start| (start, *code)));
+
+    codes
+        .chain(symbols)
+        .min_by_key(|(start, _)| *start)
+        .map(|(_, code)| code.to_string())
+}
+
+/// Find the first date written in numbers in `text`, like `15.03.2024` or `03/15/2024`, as
+/// `YYYY-MM-DD`.
+pub fn find_date(text: &str) -> Option<String> {
+    text.split_whitespace().find_map(numeric_date)
+}
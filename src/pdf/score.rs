@@ -0,0 +1,372 @@
+use approx::relative_eq;
+
+use super::{Candidate, PositionedText, Profile, BLOCK_GAP, FONT_SIZE_TOLERANCE};
+
+/// How many of the largest font sizes on a page are considered for its title.
+pub const TIERS: usize = 3;
+
+/// How close to the top or bottom edge of a page (as a fraction of its height) running headers and
+/// footers are set.
+const MARGIN: f32 = 0.05;
+
+/// How close to the top or bottom edge of a page page numbers are set, which may be a bit further
+/// away from the edges than running headers.
+const PAGE_NUMBER_MARGIN: f32 = 0.1;
+
+/// How far two pieces of text can be apart vertically (relative to their font size) to be on the
+/// same line.
+const LINE_TOLERANCE: f32 = 0.2;
+
+/// The words captions of figures and tables start with, before their number.
+const CAPTION_LABELS: &[&str] = &[
+    "figure",
+    "fig.",
+    "fig",
+    "table",
+    "tab.",
+    "abbildung",
+    "abb.",
+    "tabelle",
+    "chart",
+    "diagram",
+    "exhibit",
+    "plate",
+    "scheme",
+    "listing",
+    "algorithm",
+];
+
+/// The headings of sections that list the works cited in a document.
+const REFERENCES_HEADINGS: &[&str] = &[
+    "references",
+    "bibliography",
+    "works cited",
+    "literature cited",
+    "literature",
+    "literatur",
+    "literaturverzeichnis",
+    "quellenverzeichnis",
+    "bibliographie",
+    "références",
+    "références bibliographiques",
+    "referencias",
+    "bibliografía",
+    "bibliografia",
+];
+
+/// How many lines below the heading of a references section have to look like references.
+const MIN_REFERENCES: usize = 2;
+
+/// How many letters and digits a page may have at most to be a title page.
+const TITLE_PAGE_MAX_CHARACTERS: usize = 400;
+
+/// How many lines a title on a title page has at most.
+const TITLE_PAGE_MAX_LINES: usize = 2;
+
+/// How far the center of a line may be from the center of the page to be centered, relative to the
+/// width of the page.
+const CENTER_TOLERANCE: f32 = 0.1;
+
+/// How much the score of a candidate on a title page depends on being on one with the book
+/// profile. The rest of the score is scaled down to make room.
+const TITLE_PAGE_WEIGHT: f32 = 0.4;
+
+/// How much the score of a candidate depends on its font size relative to the largest candidate.
+const SIZE_WEIGHT: f32 = 0.6;
+/// How much the score depends on how high up on the first page a candidate is.
+const POSITION_WEIGHT: f32 = 0.15;
+/// How much the score depends on whether a candidate has a plausible length for a title.
+const LENGTH_WEIGHT: f32 = 0.15;
+/// How much the score depends on whether a candidate is on the first page.
+const FIRST_PAGE_WEIGHT: f32 = 0.1;
+
+/// Group consecutive pieces of `text` drawn at the same height in the same font size into lines.
+fn lines<'t, 'a>(text: &'t [PositionedText<'a>]) -> Vec<&'t [PositionedText<'a>]> {
+    text.chunk_by(|a, b| {
+        relative_eq!(a.font_size, b.font_size, max_relative = FONT_SIZE_TOLERANCE)
+            && (a.y - b.y).abs() <= a.scaled_font_size * LINE_TOLERANCE
+    })
+    .collect()
+}
+
+/// Leave out the lines of `text` that can't be part of the title: running headers, footers and
+/// page numbers close to the top or bottom edge of the page, captions of figures and tables, and
+/// slanted text like watermarks.
+pub fn filter<'a>(
+    text: &[PositionedText<'a>],
+    page_height: Option<f32>,
+) -> Vec<PositionedText<'a>> {
+    let in_margin = |y: f32, margin: f32| {
+        page_height.is_some_and(|height| y < height * margin || y > height * (1. - margin))
+    };
+    // The last line of the caption that the lines below it in the same font size continue.
+    let mut caption: Option<&PositionedText> = None;
+
+    lines(text)
+        .into_iter()
+        .filter(|line| {
+            let joined = line
+                .iter()
+                .map(|text| text.text.as_ref())
+                .collect::<String>();
+            let continues_caption = caption.is_some_and(|caption| {
+                let gap = line[0].y - caption.y;
+                relative_eq!(
+                    line[0].font_size,
+                    caption.font_size,
+                    max_relative = FONT_SIZE_TOLERANCE
+                ) && gap > 0.
+                    && gap <= caption.leading.max(caption.scaled_font_size) * BLOCK_GAP
+            });
+            caption = None;
+            let reason = if in_margin(line[0].y, MARGIN) {
+                "running header or footer"
+            } else if in_margin(line[0].y, PAGE_NUMBER_MARGIN) && is_page_number(&joined) {
+                "page number"
+            } else if continues_caption || is_caption(&joined) {
+                caption = Some(&line[0]);
+                "caption"
+            } else {
+                return true;
+            };
+            log::debug!("skip {reason} {joined:?}");
+            false
+        })
+        .flatten()
+        .filter(|text| {
+            if text.slanted {
+                log::debug!("skip slanted {text}");
+            }
+            !text.slanted
+        })
+        .cloned()
+        .collect()
+}
+
+/// The font sizes of `text` that might be the size of the title, largest first.
+///
+/// Sizes only used for single characters, like drop caps and decorations, are left out.
+pub fn tiers(text: &[PositionedText]) -> Vec<f32> {
+    let mut sizes: Vec<f32> = text.iter().map(|text| text.font_size).collect();
+    sizes.sort_by(|a, b| b.total_cmp(a));
+    sizes.dedup_by(|a, b| relative_eq!(*a, *b, max_relative = FONT_SIZE_TOLERANCE));
+
+    sizes
+        .into_iter()
+        .filter(|size| {
+            let characters: usize = text
+                .iter()
+                .filter(|text| {
+                    relative_eq!(text.font_size, *size, max_relative = FONT_SIZE_TOLERANCE)
+                })
+                .map(|text| text.text.chars().filter(|c| c.is_alphanumeric()).count())
+                .sum();
+            if characters <= 1 {
+                log::debug!("skip font size {size} with a single character");
+            }
+            characters > 1
+        })
+        .take(TIERS)
+        .collect()
+}
+
+/// Whether `line` is a page number like `12`, `xii`, `- 12 -`, `Page 12`, `12 / 30` or
+/// `12 of 30`.
+fn is_page_number(line: &str) -> bool {
+    let line = line.trim().trim_matches(['-', '–', '—', ' ']);
+    let line = ["page", "seite", "p."]
+        .iter()
+        .find_map(|prefix| {
+            line.get(..prefix.len())
+                .filter(|start| start.eq_ignore_ascii_case(prefix))
+                .map(|_| line[prefix.len()..].trim_start())
+        })
+        .unwrap_or(line);
+    let (page, total) = line
+        .split_once(['/', '|'])
+        .or_else(|| line.split_once(" of "))
+        .unwrap_or((line, "1"));
+
+    is_number(page) && is_number(total)
+}
+
+/// Whether `line` starts a caption like `Figure 1: …`, `Fig. 2.3 …`, `Table IV. …` or
+/// `Abbildung S1 – …`, with a label followed by a number.
+fn is_caption(line: &str) -> bool {
+    let line = line.trim_start();
+    let Some(rest) = CAPTION_LABELS.iter().find_map(|label| {
+        line.get(..label.len())
+            .filter(|start| start.eq_ignore_ascii_case(label))
+            .map(|_| &line[label.len()..])
+    }) else {
+        return false;
+    };
+    // The label is a word of its own, unlike in `Tableau` or `Figures of Speech`.
+    if rest.starts_with(char::is_alphabetic) {
+        return false;
+    }
+
+    let rest = rest.trim_start();
+    let end = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '.' || c == '-'))
+        .unwrap_or(rest.len());
+    let number = rest[..end].trim_end_matches(['.', '-']);
+    let numbered = number.chars().any(|c| c.is_ascii_digit())
+        && number.chars().filter(|c| c.is_alphabetic()).count() <= 1;
+    let roman = number.len() <= 5 && number.chars().all(|c| "IVXLC".contains(c));
+
+    !number.is_empty() && (numbered || roman)
+}
+
+/// Where a references section starts in `text`, if it does: At a line that is only the heading of
+/// one, like `References` or `7. Bibliography`, with lines below it that look like references, so
+/// the heading is not an entry of a table of contents.
+pub fn references_start(text: &[PositionedText]) -> Option<f32> {
+    let lines: Vec<_> = lines(text)
+        .into_iter()
+        .map(|line| {
+            (
+                line[0].y,
+                line.iter()
+                    .map(|text| text.text.as_ref())
+                    .collect::<String>(),
+            )
+        })
+        .collect();
+
+    lines
+        .iter()
+        .find(|(y, heading)| {
+            is_references_heading(heading)
+                && lines
+                    .iter()
+                    .filter(|(below, line)| below > y && is_reference(line))
+                    .count()
+                    >= MIN_REFERENCES
+        })
+        .map(|(y, _)| *y)
+}
+
+/// Whether `line` is the heading of a references section, possibly numbered.
+fn is_references_heading(line: &str) -> bool {
+    let heading = line
+        .trim()
+        .trim_start_matches(|c: char| c.is_ascii_digit() || "IVX.".contains(c))
+        .trim()
+        .trim_end_matches(':')
+        .to_lowercase();
+
+    REFERENCES_HEADINGS.contains(&heading.as_str())
+}
+
+/// Whether `line` looks like the start of a reference, numbered like `[12]` or with a year.
+fn is_reference(line: &str) -> bool {
+    let line = line.trim_start();
+    let numbered = line
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .is_some_and(|(label, _)| !label.is_empty() && label.len() <= 12);
+    let year = line
+        .as_bytes()
+        .windows(4)
+        .enumerate()
+        .any(|(start, digits)| {
+            digits.iter().all(u8::is_ascii_digit)
+                && matches!(&digits[..2], b"15" | b"16" | b"17" | b"18" | b"19" | b"20")
+                && !line.as_bytes()[..start]
+                    .last()
+                    .is_some_and(u8::is_ascii_digit)
+                && !line
+                    .as_bytes()
+                    .get(start + 4)
+                    .is_some_and(u8::is_ascii_digit)
+        });
+
+    numbered || year
+}
+
+/// Whether the `text` of a page is sparse enough for a title page.
+pub fn is_sparse(text: &[PositionedText]) -> bool {
+    text.iter()
+        .flat_map(|text| text.text.chars())
+        .filter(|c| c.is_alphanumeric())
+        .count()
+        <= TITLE_PAGE_MAX_CHARACTERS
+}
+
+/// Whether the `text` of a block is set in one or two lines centered on a page that is `width`
+/// wide.
+pub fn is_centered(text: &[PositionedText], width: f32) -> bool {
+    let lines: Vec<_> = text
+        .chunk_by(|a, b| (a.y - b.y).abs() <= a.scaled_font_size * LINE_TOLERANCE)
+        .collect();
+
+    (1..=TITLE_PAGE_MAX_LINES).contains(&lines.len())
+        && width > 0.
+        && lines.iter().all(|line| {
+            let start = line.iter().map(|text| text.x).fold(f32::INFINITY, f32::min);
+            let end = line
+                .iter()
+                .map(PositionedText::end)
+                .fold(f32::NEG_INFINITY, f32::max);
+            (f32::midpoint(start, end) - width / 2.).abs() <= width * CENTER_TOLERANCE
+        })
+}
+
+/// Whether `text` is a number in arabic or lowercase roman numerals.
+fn is_number(text: &str) -> bool {
+    let text = text.trim();
+
+    !text.is_empty()
+        && (text.chars().all(|c| c.is_ascii_digit())
+            || text.len() <= 4 && text.chars().all(|c| "ivxlcdm".contains(c)))
+}
+
+/// How likely `candidate` is the title, between 0 and 1, if the largest candidate of the document
+/// is set in `max_font_size`.
+///
+/// The score combines the font size with how high up on the first page the candidate is and
+/// whether it has a plausible length for a title. With the book profile, candidates on title pages
+/// score higher.
+pub fn score(candidate: &Candidate, max_font_size: f32, profile: Option<Profile>) -> f32 {
+    let size = if max_font_size > 0. {
+        (candidate.font_size / max_font_size).min(1.)
+    } else {
+        0.
+    };
+    let (position, first_page) = if candidate.page == 0 {
+        (candidate.position.map_or(0.5, |position| 1. - position), 1.)
+    } else {
+        (0., 0.)
+    };
+
+    let score = SIZE_WEIGHT.mul_add(
+        size,
+        POSITION_WEIGHT.mul_add(
+            position.clamp(0., 1.),
+            LENGTH_WEIGHT.mul_add(
+                plausibility(&candidate.text),
+                FIRST_PAGE_WEIGHT * first_page,
+            ),
+        ),
+    );
+
+    match profile {
+        Some(Profile::Book) => (1. - TITLE_PAGE_WEIGHT).mul_add(
+            score,
+            TITLE_PAGE_WEIGHT * f32::from(u8::from(candidate.title_page)),
+        ),
+        _ => score,
+    }
+}
+
+/// How plausible the length of `text` is for a title, between 0 and 1.
+#[allow(clippy::cast_precision_loss)]
+fn plausibility(text: &str) -> f32 {
+    match text.trim().chars().count() {
+        0..=1 => 0.,
+        2..=3 => 0.5,
+        4..=300 => 1.,
+        length => 300. / length as f32,
+    }
+}
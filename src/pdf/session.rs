@@ -0,0 +1,1162 @@
+use std::{
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use approx::relative_eq;
+use pdf::{
+    any::AnySync,
+    backend::Backend as Storage,
+    file::{Cache, File, Log},
+    object::PageRc,
+    PdfError,
+};
+
+use super::{
+    backend::{Backend, Lopdf, PdfRs},
+    class::{self, Signs},
+    datasheet,
+    error::Error,
+    flatten,
+    font::Coverage,
+    identifiers::Identifiers,
+    memory::{self, Data, DocumentFile},
+    plausibility::plausibility,
+    rank,
+    stage::Stage,
+    statement, title_blocks, Calibration, Callbacks, Candidate, Field, Metadata, Options, PageText,
+    Parsed, Profile, Provenance, Span, Statistics, TitleBlock, Warning, FONT_SIZE_TOLERANCE,
+};
+#[cfg(feature = "ocr")]
+use super::{blocks, ocr, Adjacent, Picture, PositionedText};
+
+/// Text layers less plausible than this are compared with the text recognized in the rendered page,
+/// see [`plausibility`].
+const WEAK_TEXT_LAYER: f32 = 0.6;
+
+/// How close to the bottom of its page a candidate has to end, and how close to the top of the next
+/// page a candidate has to start to continue it, as a share of the height of the page.
+const CONTINUATION_MARGIN: f32 = 0.2;
+
+/// How much more plausible recognized text has to be than the text layer to be used instead. The
+/// text layer is preferred otherwise, as the positions and font sizes of its text are exact.
+const CALIBRATION_MARGIN: f32 = 0.1;
+
+/// A document opened with one of the backends.
+enum Document<'a, B: Storage + Sync> {
+    Pdf(PdfRs<DocumentFile<B>>),
+    Lopdf(Lopdf),
+    /// A document the caller loaded with the `pdf` crate.
+    Borrowed(Box<dyn Backend + 'a>),
+}
+
+impl<B: Storage + Sync> Document<'_, B> {
+    fn backend(&self) -> &dyn Backend {
+        match self {
+            Self::Pdf(document) => document,
+            Self::Lopdf(document) => document,
+            Self::Borrowed(document) => document.as_ref(),
+        }
+    }
+}
+
+/// The secondary backend, which pages are parsed with if the primary one fails on them.
+enum Fallback<B> {
+    /// Not needed so far. The document is loaded from here when it is.
+    Unloaded(Origin<B>),
+    Loaded(Box<Lopdf>),
+    /// The document is already parsed with the secondary backend or could not be loaded with it.
+    Unavailable,
+}
+
+/// Where a document was loaded from.
+enum Origin<B> {
+    Path(PathBuf),
+    Storage(B),
+}
+
+impl<B: Storage> Origin<B> {
+    /// Load the document with the secondary backend.
+    fn load(&self, options: &Options) -> Result<Lopdf, Error> {
+        let password = options.password.as_deref();
+        match self {
+            Self::Path(path) => Lopdf::load(&fs::read(path).map_err(Error::Read)?, password),
+            Self::Storage(storage) => Lopdf::load(storage.read(..)?, password),
+        }
+    }
+}
+
+/// A scan of a document that can be advanced a few pages at a time.
+///
+/// This allows showing an early guess at the title of a large document and refining it while the
+/// rest of the pages are parsed. A scan is cancelled by dropping the session.
+///
+/// Documents are read with the `pdf` crate. Documents it can't load, and pages it can't parse, are
+/// read with lopdf instead. See [`BorrowedScanSession`] for documents the caller already loaded.
+pub struct ScanSession<B: Storage + Sync = Data>(Scan<'static, B>);
+
+/// A [`ScanSession`] of a document the caller already loaded with the `pdf` crate, which it
+/// borrows instead of loading the document again.
+pub struct BorrowedScanSession<'a>(Scan<'a, Data>);
+
+/// The state of a [`ScanSession`] or [`BorrowedScanSession`].
+struct Scan<'a, B: Storage + Sync> {
+    document: Document<'a, B>,
+    fallback: Fallback<B>,
+    /// The pages that have not been parsed yet.
+    pages: Range<u32>,
+    total: usize,
+    options: Options,
+    /// The candidates found so far, ordered by [`Candidate::rank`].
+    candidates: Vec<Candidate>,
+    statistics: Statistics,
+    /// The identifiers found in the text of the pages parsed so far.
+    identifiers: Identifiers,
+    /// Whether a references section was found on the pages parsed so far.
+    references: bool,
+    /// Where to render pages from to recognize their text, if they have none.
+    #[cfg(feature = "ocr")]
+    source: Option<ocr::Source>,
+}
+
+impl ScanSession {
+    /// Open the document at `path` and prepare to scan the pages selected by `options`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the document could not be loaded.
+    pub fn open<P: AsRef<Path>>(path: P, options: &Options) -> Result<Self, Error> {
+        Scan::open(path.as_ref(), options).map(Self)
+    }
+}
+
+impl<B: Storage + Sync + Clone> ScanSession<B> {
+    /// Load a document from `backend`, e.g. a byte slice, and prepare to scan the pages selected by
+    /// `options`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data is not a valid PDF document or could not be
+    /// decrypted.
+    pub fn load(backend: B, options: &Options) -> Result<Self, Error> {
+        Scan::load(backend, options).map(Self)
+    }
+}
+
+impl<B: Storage + Sync> ScanSession<B> {
+    /// Parse at most `page_count` of the remaining pages. Once a stage took longer than its
+    /// [budget](super::Budget), the remaining pages are skipped.
+    ///
+    /// Returns whether all selected pages have been parsed.
+    pub fn scan(&mut self, page_count: usize, callbacks: &mut Callbacks) -> bool {
+        self.0.scan(page_count, callbacks)
+    }
+
+    /// Whether all selected pages have been parsed.
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.0.is_done()
+    }
+
+    /// The best candidate among the pages parsed so far.
+    #[must_use]
+    pub fn best_candidate(&self) -> Option<&Candidate> {
+        self.0.best_candidate()
+    }
+
+    /// Parse the remaining pages and return the ranked candidates.
+    ///
+    /// With [`Options::ensemble`], the document is then parsed again with the secondary backend,
+    /// see [`agree`].
+    #[must_use]
+    pub fn finish(self, callbacks: &mut Callbacks) -> Parsed {
+        self.0.finish(callbacks)
+    }
+}
+
+impl<'a> BorrowedScanSession<'a> {
+    /// Prepare to scan the pages selected by `options` of a document the caller already loaded
+    /// with the `pdf` crate, instead of loading it again.
+    ///
+    /// Pages that can't be parsed are skipped, as the document is not loaded with lopdf, and no
+    /// text is recognized with [`Options::ocr`], as the pages can't be rendered.
+    pub fn with_file<B, OC, SC, L>(file: &'a File<B, OC, SC, L>, options: &Options) -> Self
+    where
+        B: Storage + Sync + 'a,
+        OC: Cache<Result<AnySync, Arc<PdfError>>> + Sync + 'a,
+        SC: Cache<Result<Arc<[u8]>, Arc<PdfError>>> + Sync + 'a,
+        L: Log + Sync + 'a,
+    {
+        let document = Document::Borrowed(Box::new(PdfRs::borrowed(file, None)));
+        Self(Scan::new(document, Fallback::Unavailable, options))
+    }
+
+    /// Like [`Self::with_file`], but only scan the `pages` of `file`, e.g. the ones the caller
+    /// already has at hand. Page indices in `options` and in the candidates are indices into
+    /// `pages`.
+    pub fn with_pages<B, OC, SC, L>(
+        file: &'a File<B, OC, SC, L>,
+        pages: Vec<PageRc>,
+        options: &Options,
+    ) -> Self
+    where
+        B: Storage + Sync + 'a,
+        OC: Cache<Result<AnySync, Arc<PdfError>>> + Sync + 'a,
+        SC: Cache<Result<Arc<[u8]>, Arc<PdfError>>> + Sync + 'a,
+        L: Log + Sync + 'a,
+    {
+        let document = Document::Borrowed(Box::new(PdfRs::borrowed(file, Some(pages))));
+        Self(Scan::new(document, Fallback::Unavailable, options))
+    }
+
+    /// See [`ScanSession::scan`].
+    pub fn scan(&mut self, page_count: usize, callbacks: &mut Callbacks) -> bool {
+        self.0.scan(page_count, callbacks)
+    }
+
+    /// See [`ScanSession::is_done`].
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.0.is_done()
+    }
+
+    /// See [`ScanSession::best_candidate`].
+    #[must_use]
+    pub fn best_candidate(&self) -> Option<&Candidate> {
+        self.0.best_candidate()
+    }
+
+    /// See [`ScanSession::finish`].
+    #[must_use]
+    pub fn finish(self, callbacks: &mut Callbacks) -> Parsed {
+        self.0.finish(callbacks)
+    }
+}
+
+impl Scan<'_, Data> {
+    fn open(path: &Path, options: &Options) -> Result<Self, Error> {
+        let start = Instant::now();
+        let load = |password: &[u8]| {
+            Data::open(path, options.low_memory)
+                .map_err(PdfError::from)
+                .and_then(|data| memory::load(data, options.low_memory, password))
+        };
+        let origin = Origin::Path(path.to_path_buf());
+        let (document, fallback) = match memory::load_decrypted(load, options.password.as_deref()) {
+            Ok(file) => (Document::Pdf(PdfRs::new(file)), Fallback::Unloaded(origin)),
+            Err(source) => {
+                let error = if memory::is_encrypted(&source) {
+                    Error::Encrypted { source }
+                } else {
+                    Error::Load {
+                        path: path.to_path_buf(),
+                        source,
+                    }
+                };
+                (
+                    Self::fall_back(&origin, options, error)?,
+                    Fallback::Unavailable,
+                )
+            }
+        };
+
+        let mut session = Self::new(document, fallback, options);
+        session.statistics.timings.add(Stage::Load, start.elapsed());
+        #[cfg(feature = "ocr")]
+        if options.ocr {
+            session.source = Some(ocr::Source::Path(path.to_path_buf()));
+        }
+
+        Ok(session)
+    }
+}
+
+impl<B: Storage + Sync + Clone> Scan<'_, B> {
+    fn load(backend: B, options: &Options) -> Result<Self, Error> {
+        let start = Instant::now();
+        #[cfg(feature = "ocr")]
+        let source = options
+            .ocr
+            .then(|| backend.read(..).ok())
+            .flatten()
+            .map(|data| ocr::Source::Data(data.to_vec()));
+        // A clone of the backend is kept to load the document with the secondary backend if
+        // needed, which is cheap for byte slices.
+        let origin = Origin::Storage(backend.clone());
+        let file = match &options.password {
+            Some(password) => {
+                memory::load(backend.clone(), options.low_memory, b"").or_else(|err| {
+                    if memory::is_encrypted(&err) {
+                        memory::load(backend, options.low_memory, password.as_bytes())
+                    } else {
+                        Err(err)
+                    }
+                })
+            }
+            None => memory::load(backend, options.low_memory, b""),
+        }
+        .map_err(|source| {
+            if memory::is_encrypted(&source) {
+                Error::Encrypted { source }
+            } else {
+                Error::LoadData { source }
+            }
+        });
+        let (document, fallback) = match file {
+            Ok(file) => (Document::Pdf(PdfRs::new(file)), Fallback::Unloaded(origin)),
+            Err(error) => (
+                Self::fall_back(&origin, options, error)?,
+                Fallback::Unavailable,
+            ),
+        };
+
+        let mut session = Self::new(document, fallback, options);
+        session.statistics.timings.add(Stage::Load, start.elapsed());
+        #[cfg(feature = "ocr")]
+        {
+            session.source = source;
+        }
+
+        Ok(session)
+    }
+}
+
+impl<'a, B: Storage + Sync> Scan<'a, B> {
+    /// Load the document from `origin` with the secondary backend because the primary one failed
+    /// with `error`, which is returned if the secondary one fails as well.
+    fn fall_back(
+        origin: &Origin<B>,
+        options: &Options,
+        error: Error,
+    ) -> Result<Document<'a, B>, Error> {
+        match origin.load(options) {
+            Ok(document) => {
+                log::info!("loaded the document with lopdf after: {error}");
+                Ok(Document::Lopdf(document))
+            }
+            Err(err) => {
+                log::debug!("could not load the document with lopdf either: {err}");
+                Err(error)
+            }
+        }
+    }
+
+    fn new(document: Document<'a, B>, fallback: Fallback<B>, options: &Options) -> Self {
+        let (skip, take) = options
+            .page
+            .map_or((0, options.page_count), |page| (page, 1));
+        let start = u32::try_from(skip).unwrap_or(u32::MAX);
+        let end = start
+            .saturating_add(u32::try_from(take).unwrap_or(u32::MAX))
+            .min(document.backend().page_count());
+        let pages = start..end.max(start);
+
+        Self {
+            total: pages.len(),
+            options: options.clone(),
+            document,
+            fallback,
+            pages,
+            candidates: Vec::new(),
+            statistics: Statistics::default(),
+            identifiers: Identifiers::default(),
+            references: false,
+            #[cfg(feature = "ocr")]
+            source: None,
+        }
+    }
+
+    fn scan(&mut self, page_count: usize, callbacks: &mut Callbacks) -> bool {
+        for _ in 0..page_count {
+            if let Some(stage) = self
+                .options
+                .budgets
+                .exceeded(&self.statistics.timings)
+                .filter(|_| !self.pages.is_empty())
+            {
+                let skipped = self.pages.len();
+                log::warn!("the {stage} stage ran out of time, skipping the {skipped} pages left");
+                callbacks.warning(&Warning::OverBudget { stage, skipped });
+                self.statistics.over_budget = true;
+                self.pages.start = self.pages.end;
+            }
+            let Some(page_number) = self.pages.next() else {
+                break;
+            };
+            let page_index = page_number as usize;
+            let Coverage { glyphs, unmapped } = self.statistics.coverage;
+            let result = self.parse_page(page_number, callbacks);
+
+            let count = self.statistics.coverage.unmapped - unmapped;
+            if count > 0 {
+                log::debug!(
+                    "{count} glyphs on page {} could not be mapped",
+                    page_index + 1
+                );
+                callbacks.warning(&Warning::UnmappedGlyphs {
+                    page: page_index,
+                    count,
+                });
+            }
+
+            self.references |= result.as_ref().is_ok_and(|page| page.references);
+            if result.as_ref().is_ok_and(|page| page.references) && !self.pages.is_empty() {
+                log::info!(
+                    "the references start on page {}, skipping the pages after it",
+                    page_index + 1
+                );
+                self.pages.start = self.pages.end;
+            }
+
+            let result = result.map(|ParsedPage { blocks, size, .. }| {
+                if self.statistics.coverage.glyphs > glyphs {
+                    let blocks = self.calibrate(page_index, blocks, size, callbacks);
+                    return self.assemble(page_index, blocks, size, callbacks);
+                }
+                self.recognize(page_index, size, callbacks)
+                    .unwrap_or_else(|| {
+                        log::info!("page {} has no text", page_index + 1);
+                        callbacks.warning(&Warning::NoText { page: page_index });
+                        blocks
+                    })
+            });
+
+            match result {
+                Ok(mut blocks) => {
+                    blocks.retain(|block| !block.text.is_empty());
+                    let largest = largest_font_size(blocks.iter().map(|block| block.font_size));
+                    for block in blocks {
+                        self.add_candidate(page_index, block, largest);
+                    }
+                    // Scores are relative to the other candidates found so far.
+                    let profile = self.options.profile;
+                    self.statistics
+                        .timings
+                        .time(Stage::Score, || rank(&mut self.candidates, profile));
+                    for candidate in &self.candidates {
+                        if candidate.page == page_index {
+                            callbacks.candidate(candidate);
+                        }
+                    }
+                }
+                Err(error) => {
+                    log::warn!("skipping page {}: {error}", page_index + 1);
+                    callbacks.warning(&Warning::SkippedPage {
+                        page: page_index,
+                        error: &error,
+                    });
+                }
+            }
+
+            callbacks.page_done(self.total - self.pages.len(), self.total);
+        }
+
+        self.is_done()
+    }
+
+    /// Add the `block` found on the page with the zero-based index `page_index` to the candidates,
+    /// or to the candidate it continues from the page before.
+    ///
+    /// Only text in the `largest` font size on its page continues the candidate in the largest font
+    /// size on the page before, so body text running across pages is not joined.
+    fn add_candidate(&mut self, page_index: usize, block: TitleBlock, largest: f32) {
+        let y = block
+            .text
+            .iter()
+            .map(|text| text.y)
+            .fold(f32::INFINITY, f32::min);
+        let candidate = Candidate {
+            text: flatten(&block.text),
+            page: page_index,
+            y,
+            position: block
+                .page_height
+                .filter(|height| *height > 0.)
+                .map(|height| y / height),
+            font_size: block.font_size,
+            score: 0.,
+            parallel: block.parallel,
+            spans: block.text.iter().map(Span::from).collect(),
+            recognized: block.recognized,
+            calibration: block.calibration,
+            assembled: block.assembled,
+            title_page: block.title_page,
+        };
+
+        let previous_largest = page_index.checked_sub(1).map(|previous_page| {
+            largest_font_size(
+                self.candidates
+                    .iter()
+                    .filter(|previous| previous.page == previous_page)
+                    .map(|previous| previous.font_size),
+            )
+        });
+        let in_largest_font = |font_size: f32, largest: f32| {
+            relative_eq!(font_size, largest, max_relative = FONT_SIZE_TOLERANCE)
+        };
+
+        match self
+            .candidates
+            .iter_mut()
+            .filter(|_| in_largest_font(candidate.font_size, largest))
+            .filter(|previous| {
+                previous_largest.is_some_and(|largest| in_largest_font(previous.font_size, largest))
+            })
+            .find(|previous| continues(&candidate, previous))
+        {
+            Some(previous) => {
+                log::info!(
+                    "{:?} at the top of page {} continues {:?}",
+                    candidate.text,
+                    page_index + 1,
+                    previous.text
+                );
+                previous.text = format!("{} {}", previous.text, candidate.text);
+                previous.spans.extend(candidate.spans);
+            }
+            None => self.candidates.push(candidate),
+        }
+    }
+
+    /// Collect the blocks of text on the page with the zero-based index `page_number` that might be
+    /// its title, together with the size of the page, see [`parse_page`].
+    ///
+    /// If the page can't be parsed, it is parsed again with the secondary backend.
+    fn parse_page(
+        &mut self,
+        page_number: u32,
+        callbacks: &mut Callbacks,
+    ) -> Result<ParsedPage, Error> {
+        let coverage = self.statistics.coverage;
+        let fonts = self.statistics.fonts.clone();
+        let error = match parse_page(
+            self.document.backend(),
+            page_number,
+            &self.options,
+            &mut self.statistics,
+            &mut self.identifiers,
+            callbacks,
+        ) {
+            // Pages without content have nothing the secondary backend could find.
+            Err(error) if !matches!(error, Error::NoContent) => error,
+            result => return result,
+        };
+
+        if let Fallback::Unloaded(origin) = &self.fallback {
+            self.fallback = match origin.load(&self.options) {
+                Ok(document) => Fallback::Loaded(Box::new(document)),
+                Err(err) => {
+                    log::debug!("could not load the document with lopdf: {err}");
+                    Fallback::Unavailable
+                }
+            };
+        }
+        let Fallback::Loaded(fallback) = &self.fallback else {
+            return Err(error);
+        };
+        log::info!("parsing page {} with lopdf after: {error}", page_number + 1);
+        // Glyphs decoded before the primary backend failed are not counted twice.
+        self.statistics.coverage = coverage;
+        self.statistics.fonts = fonts;
+        parse_page(
+            fallback.as_ref(),
+            page_number,
+            &self.options,
+            &mut self.statistics,
+            &mut self.identifiers,
+            callbacks,
+        )
+        .map_err(|_| error)
+    }
+
+    /// Keep the `blocks` parsed from the text layer of the page with the zero-based index `page`
+    /// and displayed `size`, unless the text layer is implausible and the text recognized in the
+    /// rendered page is more plausible.
+    #[cfg_attr(not(feature = "ocr"), allow(clippy::needless_pass_by_ref_mut))]
+    fn calibrate(
+        &mut self,
+        page: usize,
+        blocks: Vec<TitleBlock>,
+        size: Option<(f32, f32)>,
+        callbacks: &mut Callbacks,
+    ) -> Vec<TitleBlock> {
+        let text = |blocks: &[TitleBlock]| {
+            blocks
+                .iter()
+                .flat_map(|block| &block.text)
+                .map(|text| text.text.as_ref())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let Some(layout) = plausibility(&text(&blocks)).filter(|layout| *layout < WEAK_TEXT_LAYER)
+        else {
+            return blocks;
+        };
+        log::debug!(
+            "the text layer of page {} is only {layout:.2} plausible",
+            page + 1
+        );
+        let Some(recognized) = self.recognize(page, size, callbacks) else {
+            return blocks;
+        };
+        let ocr = plausibility(&text(&recognized)).unwrap_or(0.);
+        let calibration = Some(Calibration { layout, ocr });
+
+        let blocks = if ocr > layout + CALIBRATION_MARGIN {
+            log::info!(
+                "using the text recognized on page {}, which is {ocr:.2} plausible",
+                page + 1
+            );
+            recognized
+        } else {
+            log::info!(
+                "keeping the text layer of page {}, the recognized text is only {ocr:.2} plausible",
+                page + 1
+            );
+            blocks
+        };
+        blocks
+            .into_iter()
+            .map(|block| TitleBlock {
+                calibration,
+                ..block
+            })
+            .collect()
+    }
+
+    /// Complete the title `blocks` of the page with the zero-based index `page` and displayed
+    /// `size` with the text recognized in the image right above or below them, if that was asked
+    /// for.
+    ///
+    /// The image above a block holds the title and the block its subtitle, or the other way around
+    /// for images below it, so they are joined with a colon. Recognized text that is not plausible
+    /// is left out.
+    #[cfg(feature = "ocr")]
+    fn assemble(
+        &self,
+        page: usize,
+        blocks: Vec<TitleBlock>,
+        size: Option<(f32, f32)>,
+        callbacks: &mut Callbacks,
+    ) -> Vec<TitleBlock> {
+        let (Some(source), Some((width, height))) = (self.source.as_ref(), size) else {
+            return blocks;
+        };
+        let adjacent = |block: &TitleBlock| block.image.filter(|_| !block.recognized);
+        // The text of all images is recognized at once and then split up between them.
+        let Some(pictures) = blocks
+            .iter()
+            .filter_map(adjacent)
+            .map(|adjacent| adjacent.picture)
+            .reduce(Picture::union)
+        else {
+            return blocks;
+        };
+
+        let left = (pictures.left / width).clamp(0., 1.);
+        let top = (pictures.top / height).clamp(0., 1.);
+        let rendering = ocr::Rendering {
+            region: ocr::Region {
+                left,
+                top,
+                width: (pictures.right / width).clamp(0., 1.) - left,
+                height: (pictures.bottom / height).clamp(0., 1.) - top,
+            },
+            ..self.options.rendering.clone()
+        };
+        log::info!(
+            "recognizing the text of the images next to title blocks on page {}",
+            page + 1
+        );
+        let recognized =
+            match ocr::recognize(source, page, size, &rendering, self.options.default_leading) {
+                Ok(recognized) => recognized.text,
+                Err(error) => {
+                    log::warn!("could not recognize the text of page {}: {error}", page + 1);
+                    callbacks.warning(&Warning::FailedOcr {
+                        page,
+                        error: &error,
+                    });
+                    return blocks;
+                }
+            };
+
+        blocks
+            .into_iter()
+            .map(|block| {
+                let Some(Adjacent { picture, above }) = adjacent(&block) else {
+                    return block;
+                };
+                let recognized: Vec<_> = recognized
+                    .iter()
+                    .filter(|text| picture.contains(text))
+                    .cloned()
+                    .collect();
+                let text = recognized
+                    .iter()
+                    .map(|text| text.text.as_ref())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if plausibility(&text).is_none_or(|plausibility| plausibility < WEAK_TEXT_LAYER) {
+                    log::debug!("leaving out the implausible text {text:?} of the image");
+                    return block;
+                }
+
+                // The recognized lines become part of the block.
+                let recognized = recognized.into_iter().map(|text| PositionedText {
+                    font_size: block.font_size,
+                    ..text
+                });
+                let (mut title, subtitle): (Vec<_>, Vec<_>) = if above {
+                    (recognized.collect(), block.text)
+                } else {
+                    (block.text, recognized.collect())
+                };
+                if let Some(last) = title.last_mut() {
+                    if !last
+                        .text
+                        .trim_end()
+                        .ends_with(|c: char| c.is_ascii_punctuation())
+                    {
+                        last.text.to_mut().push(':');
+                    }
+                }
+                title.extend(subtitle);
+
+                TitleBlock {
+                    text: title,
+                    assembled: true,
+                    ..block
+                }
+            })
+            .collect()
+    }
+
+    #[cfg(not(feature = "ocr"))]
+    #[allow(clippy::unused_self)]
+    const fn assemble(
+        &self,
+        _page: usize,
+        blocks: Vec<TitleBlock>,
+        _size: Option<(f32, f32)>,
+        _callbacks: &Callbacks,
+    ) -> Vec<TitleBlock> {
+        blocks
+    }
+
+    /// Recognize the text of the page with the zero-based index `page` and displayed `size`, if
+    /// that was asked for.
+    #[cfg(feature = "ocr")]
+    fn recognize(
+        &mut self,
+        page: usize,
+        size: Option<(f32, f32)>,
+        callbacks: &mut Callbacks,
+    ) -> Option<Vec<TitleBlock>> {
+        let source = self.source.as_ref()?;
+        let rendering = &self.options.rendering;
+        let first = self.options.page.unwrap_or(0);
+        if rendering
+            .max_pages
+            .is_some_and(|max_pages| page - first >= max_pages)
+        {
+            log::debug!("not recognizing the text of page {}", page + 1);
+            return None;
+        }
+        log::info!("recognizing the text of page {}", page + 1);
+
+        match ocr::recognize(source, page, size, rendering, self.options.default_leading) {
+            Ok(recognized) => {
+                self.identifiers.scan(
+                    &recognized
+                        .text
+                        .iter()
+                        .map(|text| text.text.as_ref())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                );
+                let size = (size.map_or(0., |(width, _)| width), recognized.height);
+                let blocks: Vec<_> = blocks(&recognized.text, Some(size))
+                    .into_iter()
+                    .map(|block| TitleBlock {
+                        recognized: true,
+                        ..block
+                    })
+                    .collect();
+
+                (!blocks.is_empty()).then_some(blocks)
+            }
+            Err(error) => {
+                log::warn!("could not recognize the text of page {}: {error}", page + 1);
+                callbacks.warning(&Warning::FailedOcr {
+                    page,
+                    error: &error,
+                });
+                None
+            }
+        }
+    }
+
+    #[cfg(not(feature = "ocr"))]
+    #[allow(clippy::unused_self)]
+    const fn recognize(
+        &self,
+        _page: usize,
+        _size: Option<(f32, f32)>,
+        _callbacks: &Callbacks,
+    ) -> Option<Vec<TitleBlock>> {
+        None
+    }
+
+    fn is_done(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    fn best_candidate(&self) -> Option<&Candidate> {
+        self.candidates.first()
+    }
+
+    /// The text of the best candidate as a field of the metadata.
+    fn best_field(&self) -> Option<Field<String>> {
+        self.candidates
+            .first()
+            .map(|candidate| Field::new(candidate.text.clone(), provenance(candidate)))
+    }
+
+    /// The metadata the profile extracts, and the first candidate renamed after it for the kinds
+    /// of documents named after more than their title.
+    fn profile_metadata(&mut self) -> Metadata {
+        let mut metadata = Metadata::default();
+        // Issues of periodicals are named after their masthead and the date of the issue.
+        if self.options.profile == Some(Profile::Magazine) {
+            if let Some(masthead) = self.candidates.first_mut() {
+                metadata
+                    .publication
+                    .push(Field::new(masthead.text.clone(), provenance(masthead)));
+                if let Some(date) = self.identifiers.issue_date.take() {
+                    log::info!("issue of {:?} from {date}", masthead.text);
+                    masthead.text = format!("{} {date}", masthead.text);
+                    metadata
+                        .issue_date
+                        .push(Field::new(date, Provenance::Layout));
+                }
+            }
+        }
+        if self.options.profile == Some(Profile::Standard) {
+            metadata.standard_id.extend(
+                self.identifiers
+                    .standard_id
+                    .take()
+                    .map(|id| Field::new(id, Provenance::Layout)),
+            );
+        }
+        // Court filings are named after the type of document in their caption.
+        if self.options.profile == Some(Profile::Legal) {
+            let field = |value| Field::new(value, Provenance::Layout);
+            metadata
+                .case_number
+                .extend(self.identifiers.case_number.take().map(field));
+            metadata
+                .filed
+                .extend(self.identifiers.filed.take().map(field));
+            if let Some(document_type) = self.identifiers.document_type.take() {
+                if let Some(caption) = self.candidates.first_mut() {
+                    log::info!("{document_type:?} instead of {:?}", caption.text);
+                    caption.text.clone_from(&document_type);
+                }
+                metadata.document_type.push(field(document_type));
+            }
+        }
+        // Statements are named after the institution that sent them and the period they cover.
+        if self.options.profile == Some(Profile::Statement) && self.identifiers.statement {
+            let field = |value| Field::new(value, Provenance::Layout);
+            let period = self.identifiers.period.take();
+            if let Some(institution) = statement::institution(&self.candidates).map(str::to_string)
+            {
+                if let Some(first) = self.candidates.first_mut() {
+                    first.text = period.as_ref().map_or_else(
+                        || institution.clone(),
+                        |period| format!("{institution} {period}"),
+                    );
+                    log::info!("statement named {:?}", first.text);
+                }
+                metadata.institution.push(field(institution));
+            }
+            metadata.period.extend(period.map(field));
+            metadata
+                .account_suffix
+                .extend(self.identifiers.account_suffix.take().map(field));
+        }
+        // Datasheets are named after the maker and the number of the part.
+        if self.options.profile == Some(Profile::Datasheet) {
+            let field = |value| Field::new(value, Provenance::Layout);
+            let manufacturer = self.identifiers.manufacturer.take();
+            if let Some(part) = datasheet::part_number(&self.candidates).map(str::to_string) {
+                if let Some(first) = self.candidates.first_mut() {
+                    first.text = manufacturer.as_ref().map_or_else(
+                        || format!("{part} datasheet"),
+                        |manufacturer| format!("{manufacturer} {part} datasheet"),
+                    );
+                    log::info!("datasheet named {:?}", first.text);
+                }
+                metadata.part_number.push(field(part));
+            }
+            metadata.manufacturer.extend(manufacturer.map(field));
+        }
+        // Receipts are named after their vendor at the top, with the total and its currency. They
+        // are usually scanned, so the date and total come from where the vendor's name came from.
+        if self.options.profile == Some(Profile::Receipt) {
+            let source = self
+                .candidates
+                .first()
+                .map_or(Provenance::Layout, provenance);
+            let field = |value| Field::new(value, source);
+            metadata.vendor.extend(self.best_field());
+            metadata
+                .purchased
+                .extend(self.identifiers.date.take().map(field));
+            if let Some(total) = self.identifiers.total.take() {
+                metadata.amount.push(field(total.amount));
+                metadata.currency.extend(total.currency.map(field));
+            }
+        }
+        // Sheet music is named after the composer and the piece.
+        if self.options.profile == Some(Profile::Music) {
+            metadata.piece.extend(self.best_field());
+            metadata.composer.extend(
+                self.identifiers
+                    .composer
+                    .take()
+                    .map(|composer| Field::new(composer, Provenance::Layout)),
+            );
+        }
+
+        metadata
+    }
+
+    fn finish(mut self, callbacks: &mut Callbacks) -> Parsed {
+        self.scan(usize::MAX, callbacks);
+
+        let secondary = if self.options.ensemble {
+            self.secondary()
+        } else {
+            None
+        };
+        let parsed = self.collect();
+        let Some(mut secondary) = secondary else {
+            return parsed;
+        };
+        // Warnings and candidates were already reported for the primary backend.
+        let mut callbacks = Callbacks::default();
+        secondary.scan(usize::MAX, &mut callbacks);
+        agree(parsed, secondary.collect())
+    }
+
+    /// A session scanning the same pages with the secondary backend, unless the document is
+    /// already read with it or can't be.
+    fn secondary(&mut self) -> Option<Self> {
+        if !matches!(self.document, Document::Pdf(_)) {
+            return None;
+        }
+        let document = match std::mem::replace(&mut self.fallback, Fallback::Unavailable) {
+            Fallback::Unloaded(origin) => {
+                let start = Instant::now();
+                let document = origin
+                    .load(&self.options)
+                    .inspect_err(|err| {
+                        log::debug!("could not load the document with lopdf: {err}");
+                    })
+                    .ok()?;
+                self.statistics.timings.add(Stage::Load, start.elapsed());
+                document
+            }
+            Fallback::Loaded(document) => *document,
+            Fallback::Unavailable => return None,
+        };
+        let options = Options {
+            ensemble: false,
+            ..self.options.clone()
+        };
+
+        Some(Self::new(
+            Document::Lopdf(document),
+            Fallback::Unavailable,
+            &options,
+        ))
+    }
+
+    /// Collect the metadata of the document after its pages have been parsed.
+    fn collect(mut self) -> Parsed {
+        let start = Instant::now();
+        let budget = self.options.budgets.get(Stage::Cleanup).time;
+        // The metadata stored in the document is left out once the budget is used up.
+        let in_budget = || budget.is_none_or(|budget| start.elapsed() <= budget);
+        let mut metadata = self.profile_metadata();
+        metadata.title.extend(self.best_field());
+        let backend = self.document.backend();
+        if let Some(xml) = backend.xmp().filter(|_| in_budget()) {
+            metadata.add_xmp(&xml);
+        }
+        if let Some(info) = backend.info().filter(|_| in_budget()) {
+            metadata.add_info_dict(&info);
+        }
+        metadata.class = class::classify(&Signs {
+            profile: self.options.profile,
+            candidates: &self.candidates,
+            identifiers: &self.identifiers,
+            references: self.references,
+            page_count: backend.page_count(),
+            scanned: self.statistics.coverage.glyphs == 0,
+        });
+        // Identifiers in the text might also belong to cited works, so they are trusted least.
+        metadata.add_identifiers(self.identifiers);
+        self.statistics.timings.add(Stage::Cleanup, start.elapsed());
+
+        Parsed {
+            candidates: self.candidates,
+            metadata,
+            statistics: self.statistics,
+        }
+    }
+}
+
+/// The result of the `primary` backend if both backends agree on the title, or else the result
+/// of the backend more confident in its title, see [`super::confidence`]. How long both took is
+/// added up.
+fn agree(primary: Parsed, secondary: Parsed) -> Parsed {
+    let title = |parsed: &Parsed| {
+        parsed.candidates.first().map(|candidate| {
+            candidate
+                .text
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+    };
+    let (mut chosen, other) = if title(&primary) == title(&secondary) {
+        log::debug!("both backends agree on the title");
+        (primary, secondary)
+    } else {
+        let primary_confidence = super::confidence(&primary.candidates);
+        let secondary_confidence = super::confidence(&secondary.candidates);
+        log::info!(
+            "the backends disagree on the title: {:?} ({primary_confidence:.2}) with the pdf crate, \
+             {:?} ({secondary_confidence:.2}) with lopdf",
+            primary.candidates.first().map(|candidate| &candidate.text),
+            secondary.candidates.first().map(|candidate| &candidate.text),
+        );
+        if secondary_confidence > primary_confidence {
+            (secondary, primary)
+        } else {
+            (primary, secondary)
+        }
+    };
+    for (stage, elapsed) in other.statistics.timings.iter() {
+        chosen.statistics.timings.add(stage, elapsed);
+    }
+
+    chosen
+}
+
+/// Where the text of `candidate` comes from.
+const fn provenance(candidate: &Candidate) -> Provenance {
+    if candidate.recognized {
+        Provenance::Ocr
+    } else {
+        Provenance::Layout
+    }
+}
+
+/// The largest of `font_sizes`, or 0 if there are none.
+fn largest_font_size(font_sizes: impl Iterator<Item = f32>) -> f32 {
+    font_sizes.fold(0., f32::max)
+}
+
+/// Whether `candidate` starts at the top of its page in the same font size `previous` ends in at
+/// the bottom of the page before, like a long title that continues on the next page.
+fn continues(candidate: &Candidate, previous: &Candidate) -> bool {
+    let starts_at_top = candidate
+        .position
+        .is_some_and(|position| position < CONTINUATION_MARGIN);
+    // The height of the page the previous candidate is on, from where its top is.
+    let height = previous
+        .position
+        .filter(|position| *position > 0.)
+        .map(|position| previous.y / position);
+    let ends_at_bottom = height.is_some_and(|height| {
+        previous
+            .spans
+            .iter()
+            .map(|span| span.y)
+            .fold(previous.y, f32::max)
+            > (1. - CONTINUATION_MARGIN) * height
+    });
+
+    candidate.page == previous.page + 1
+        && !candidate.recognized
+        && !previous.recognized
+        && relative_eq!(candidate.font_size, previous.font_size, max_relative = 0.01)
+        && starts_at_top
+        && ends_at_bottom
+}
+
+/// What was found on a page.
+struct ParsedPage {
+    /// The blocks of text that might be the title.
+    blocks: Vec<TitleBlock>,
+    /// The width and height of the page as it is displayed, if they are known.
+    size: Option<(f32, f32)>,
+    /// Whether a references section starts on the page, see [`PageText::references`].
+    references: bool,
+}
+
+/// Collect the blocks of text on the page with the zero-based index `page_number` of a document
+/// read with `backend` that might be its title.
+fn parse_page(
+    backend: &dyn Backend,
+    page_number: u32,
+    options: &Options,
+    statistics: &mut Statistics,
+    identifiers: &mut Identifiers,
+    callbacks: &mut Callbacks,
+) -> Result<ParsedPage, Error> {
+    let max_length = options
+        .low_memory
+        .then_some(memory::MAX_LOW_MEMORY_STREAM_LENGTH);
+    let mut page = backend.page(page_number, max_length, &mut statistics.timings)?;
+    if let Some(max_operations) = options.budgets.get(Stage::Operations).operations {
+        if page.operations.len() > max_operations {
+            log::info!(
+                "leaving out {} operations of page {} over the budget",
+                page.operations.len() - max_operations,
+                page_number + 1
+            );
+            page.operations.truncate(max_operations);
+        }
+    }
+    for name in &page.fonts.failed {
+        callbacks.warning(&Warning::FailedFont {
+            page: page_number as usize,
+            name,
+        });
+    }
+    statistics
+        .failed_fonts
+        .extend(page.fonts.failed.iter().cloned());
+
+    let size = page.graphics.size();
+
+    let start = Instant::now();
+    let result = title_blocks(page, backend, options, statistics, identifiers);
+    statistics.timings.add(Stage::Decode, start.elapsed());
+
+    result.map(|PageText { blocks, references }| ParsedPage {
+        blocks,
+        size,
+        references,
+    })
+}
@@ -0,0 +1,185 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The stages finding the title of a document goes through, in order. Every page goes through the
+/// stages from [`Stage::Fonts`] to [`Stage::Score`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Stage {
+    /// Loading and decrypting the document.
+    Load,
+    /// Loading the fonts of a page.
+    Fonts,
+    /// Parsing the content stream of a page into operations.
+    Operations,
+    /// Decoding the text drawn by the operations and grouping it into blocks.
+    Decode,
+    /// Scoring and ranking the candidates.
+    Score,
+    /// Collecting the metadata of the document once its pages are parsed.
+    Cleanup,
+}
+
+impl Stage {
+    pub const ALL: [Self; 6] = [
+        Self::Load,
+        Self::Fonts,
+        Self::Operations,
+        Self::Decode,
+        Self::Score,
+        Self::Cleanup,
+    ];
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Load => "load",
+            Self::Fonts => "fonts",
+            Self::Operations => "operations",
+            Self::Decode => "decode",
+            Self::Score => "score",
+            Self::Cleanup => "cleanup",
+        })
+    }
+}
+
+impl FromStr for Stage {
+    type Err = String;
+
+    fn from_str(stage: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|known| known.to_string() == stage)
+            .ok_or_else(|| {
+                format!(
+                    "unknown stage {stage:?}, expected one of {}",
+                    Self::ALL.map(|known| known.to_string()).join(", ")
+                )
+            })
+    }
+}
+
+/// How much a stage may take of a document.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Budget {
+    /// How long the stage may take for the whole document. Once a stage took longer, the pages
+    /// left are skipped and the title is chosen from the candidates found so far. Decoding also
+    /// stops in the middle of a page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<Duration>,
+    /// How many operations the stage may work through on every page, which is only limited for
+    /// [`Stage::Operations`], where the operations after these are left out, and
+    /// [`Stage::Decode`], where it counts the operations drawing text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operations: Option<usize>,
+}
+
+/// The budgets of the stages that are limited.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Budgets(BTreeMap<Stage, Budget>);
+
+impl Budgets {
+    /// Limit how long `stage` may take for a document.
+    pub fn set_time(&mut self, stage: Stage, time: Duration) {
+        self.0.entry(stage).or_default().time = Some(time);
+    }
+
+    /// Limit how many operations `stage` may work through on every page.
+    pub fn set_operations(&mut self, stage: Stage, operations: usize) {
+        self.0.entry(stage).or_default().operations = Some(operations);
+    }
+
+    #[must_use]
+    pub fn get(&self, stage: Stage) -> Budget {
+        self.0.get(&stage).copied().unwrap_or_default()
+    }
+
+    /// The first stage that took longer than its budget according to `timings`.
+    #[must_use]
+    pub fn exceeded(&self, timings: &Timings) -> Option<Stage> {
+        self.0.iter().find_map(|(stage, budget)| {
+            budget
+                .time
+                .filter(|time| timings.get(*stage) > *time)
+                .map(|_| *stage)
+        })
+    }
+}
+
+/// What is left of the budget of [`Stage::Decode`] for the text of a page.
+pub(super) struct Allowance {
+    /// When decoding runs out of time.
+    deadline: Option<Instant>,
+    /// How many more operations drawing text may be decoded.
+    operations: Option<usize>,
+    /// Whether decoding ran out of time, rather than out of operations.
+    pub(super) out_of_time: bool,
+}
+
+impl Allowance {
+    /// The allowance for decoding a page, after decoding took as long as in `timings` so far.
+    pub(super) fn decode(budgets: &Budgets, timings: &Timings) -> Self {
+        let Budget { time, operations } = budgets.get(Stage::Decode);
+        Self {
+            deadline: time
+                .map(|time| Instant::now() + time.saturating_sub(timings.get(Stage::Decode))),
+            operations,
+            out_of_time: false,
+        }
+    }
+
+    /// Take another operation drawing text from the allowance. Returns whether there was enough
+    /// left for it.
+    pub(super) fn draw(&mut self) -> bool {
+        self.out_of_time = self
+            .deadline
+            .is_some_and(|deadline| Instant::now() > deadline);
+        let within = self.operations != Some(0) && !self.out_of_time;
+        if within {
+            self.operations = self.operations.map(|operations| operations - 1);
+        } else {
+            log::info!("the text left on the page is over the budget for decoding");
+        }
+
+        within
+    }
+}
+
+/// How long each stage took for a document.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Timings(BTreeMap<Stage, Duration>);
+
+impl Timings {
+    /// How long `stage` took.
+    #[must_use]
+    pub fn get(&self, stage: Stage) -> Duration {
+        self.0.get(&stage).copied().unwrap_or_default()
+    }
+
+    /// The stages that ran and how long they took, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (Stage, Duration)> + '_ {
+        self.0.iter().map(|(stage, elapsed)| (*stage, *elapsed))
+    }
+
+    /// Add `elapsed` to the time `stage` took.
+    pub(super) fn add(&mut self, stage: Stage, elapsed: Duration) {
+        *self.0.entry(stage).or_default() += elapsed;
+    }
+
+    /// Run `f` as part of `stage`.
+    pub(super) fn time<T>(&mut self, stage: Stage, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.add(stage, start.elapsed());
+
+        result
+    }
+}
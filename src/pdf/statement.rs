@@ -0,0 +1,182 @@
+use super::{
+    identifiers::{find_issue_date, numeric_date},
+    Candidate,
+};
+
+/// Phrases that only statements of accounts and bills have, lowercase.
+const STATEMENT_PHRASES: &[&str] = &[
+    "statement period",
+    "account statement",
+    "statement of account",
+    "account summary",
+    "billing period",
+    "billing date",
+    "kontoauszug",
+    "abrechnungszeitraum",
+    "rechnungszeitraum",
+    "relevé de compte",
+];
+
+/// The labels in front of the period a statement covers, lowercase.
+const PERIOD_LABELS: &[&str] = &[
+    "statement period",
+    "billing period",
+    "for the period",
+    "period",
+    "abrechnungszeitraum",
+    "rechnungszeitraum",
+    "zeitraum",
+    "période",
+];
+
+/// The labels in front of the number of an account or card, lowercase.
+const ACCOUNT_LABELS: &[&str] = &[
+    "account number",
+    "account no",
+    "account ending in",
+    "card ending in",
+    "acct",
+    "kontonummer",
+    "konto-nr",
+    "iban",
+];
+
+/// How many characters after a label are searched for the period or the account number.
+const LABEL_DISTANCE: usize = 60;
+
+/// How many digits at the end of an account number are kept. The rest is never stored, so it
+/// can't end up in file names.
+const ACCOUNT_SUFFIX_DIGITS: usize = 4;
+
+/// Words in the names of banks, utilities and other institutions sending statements, lowercase.
+const INSTITUTION_WORDS: &[&str] = &[
+    "bank",
+    "banque",
+    "sparkasse",
+    "volksbank",
+    "raiffeisen",
+    "credit",
+    "savings",
+    "energy",
+    "electric",
+    "power",
+    "gas",
+    "water",
+    "utilities",
+    "telecom",
+    "stadtwerke",
+    "versicherung",
+    "insurance",
+];
+
+/// The most words the name of an institution is made of.
+const MAX_INSTITUTION_WORDS: usize = 6;
+
+/// Whether `text` is part of a statement of an account or a bill.
+pub fn is_statement(text: &str) -> bool {
+    let lowercase = text.to_lowercase();
+    STATEMENT_PHRASES
+        .iter()
+        .any(|phrase| lowercase.contains(phrase))
+}
+
+/// Find the period a statement covers in `text`, written after one of the [`PERIOD_LABELS`] like
+/// `Statement period: 03/01/2024 - 03/31/2024`, or else the first month named like `March 2024`.
+///
+/// Periods within a month are written as `YYYY-MM`, others as `YYYY-MM-DD - YYYY-MM-DD`.
+pub fn find_period(text: &str) -> Option<String> {
+    // Lowercasing ASCII keeps the byte offsets of `text`.
+    let lowercase = text.to_ascii_lowercase();
+
+    let labelled = PERIOD_LABELS
+        .iter()
+        .flat_map(|label| {
+            lowercase
+                .match_indices(label)
+                .map(move |(start, _)| start + label.len())
+        })
+        .filter_map(|start| {
+            let dates: Vec<_> = after(text, start)
+                .split_whitespace()
+                .filter_map(numeric_date)
+                .take(2)
+                .collect();
+            match &dates[..] {
+                [from, to] if from[..7] == to[..7] => Some((start, from[..7].to_string())),
+                [from, to] => Some((start, format!("{from} - {to}"))),
+                [from] => Some((start, from[..7].to_string())),
+                [] => find_issue_date(after(text, start)).map(|month| (start, month[..7].into())),
+                _ => None,
+            }
+        })
+        .min_by_key(|(start, _)| *start)
+        .map(|(_, period)| period);
+
+    labelled.or_else(|| find_issue_date(text).map(|month| month[..7].to_string()))
+}
+
+/// Find the last [`ACCOUNT_SUFFIX_DIGITS`] digits of the number of an account or card in `text`,
+/// written after one of the [`ACCOUNT_LABELS`] in full (`DE89 3704 0044 0532 0130 00`) or masked
+/// (`****1234`).
+pub fn find_account_suffix(text: &str) -> Option<String> {
+    let lowercase = text.to_ascii_lowercase();
+
+    ACCOUNT_LABELS
+        .iter()
+        .flat_map(|label| {
+            lowercase
+                .match_indices(label)
+                .map(move |(start, _)| start + label.len())
+        })
+        .filter_map(|start| {
+            let digits: String = after(text, start)
+                .trim_start_matches(|c: char| !c.is_alphanumeric() && c != '*')
+                .split_whitespace()
+                .take_while(|part| {
+                    part.chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '*' | '•' | '-'))
+                        && part
+                            .chars()
+                            .any(|c| c.is_ascii_digit() || matches!(c, '*' | '•'))
+                })
+                .flat_map(str::chars)
+                .filter(char::is_ascii_digit)
+                .collect();
+            let suffix = digits.get(digits.len().checked_sub(ACCOUNT_SUFFIX_DIGITS)?..)?;
+            Some((start, suffix.to_string()))
+        })
+        .min_by_key(|(start, _)| *start)
+        .map(|(_, suffix)| suffix)
+}
+
+/// The name of the institution that sent a statement, from the first of the ranked `candidates`
+/// on the first page that is short and has one of the [`INSTITUTION_WORDS`] in it.
+pub fn institution(candidates: &[Candidate]) -> Option<&str> {
+    let first_page = candidates.iter().map(|candidate| candidate.page).min()?;
+
+    candidates
+        .iter()
+        .filter(|candidate| candidate.page == first_page)
+        .map(|candidate| candidate.text.trim())
+        .find(|text| {
+            let words: Vec<_> = text
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .collect();
+            words.len() <= MAX_INSTITUTION_WORDS
+                && words
+                    .iter()
+                    .any(|word| INSTITUTION_WORDS.contains(&word.to_lowercase().as_str()))
+        })
+}
+
+/// The text at most [`LABEL_DISTANCE`] characters after the byte offset `start` of `text`.
+fn after(text: &str, start: usize) -> &str {
+    let after = &text[start..];
+    let end = after
+        .char_indices()
+        .nth(LABEL_DISTANCE)
+        .map_or(after.len(), |(end, _)| end);
+
+    &after[..end]
+}
@@ -0,0 +1,211 @@
+use pdf::{
+    content::{parse_ops, Content, Op},
+    object::Resolve,
+};
+
+use super::error::Error;
+
+/// All operators that can appear in a content stream.
+const OPERATORS: &[&[u8]] = &[
+    b"b", b"B", b"b*", b"B*", b"BDC", b"BI", b"BMC", b"BT", b"BX", b"c", b"cm", b"CS", b"cs", b"d",
+    b"d0", b"d1", b"Do", b"DP", b"EI", b"EMC", b"ET", b"EX", b"f", b"F", b"f*", b"G", b"g", b"gs",
+    b"h", b"i", b"ID", b"j", b"J", b"K", b"k", b"l", b"m", b"M", b"MP", b"n", b"q", b"Q", b"re",
+    b"RG", b"rg", b"ri", b"s", b"S", b"SC", b"sc", b"SCN", b"scn", b"sh", b"T*", b"Tc", b"Td",
+    b"TD", b"Tf", b"Tj", b"TJ", b"TL", b"Tm", b"Tr", b"Ts", b"Tw", b"Tz", b"v", b"w", b"W", b"W*",
+    b"y", b"'", b"\"",
+];
+
+/// How a content stream ends, which decides how it is joined with the next one.
+enum End<'a> {
+    /// Inside a string, which continues in the next stream.
+    String,
+    /// Inside a comment, which would swallow the start of the next stream.
+    Comment,
+    /// After these regular characters, which are empty if the stream ends with a delimiter.
+    Token(&'a [u8]),
+}
+
+const fn is_delimiter(byte: u8) -> bool {
+    matches!(
+        byte,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+const fn is_regular(byte: u8) -> bool {
+    !byte.is_ascii_whitespace() && byte != 0 && !is_delimiter(byte)
+}
+
+fn end(data: &[u8]) -> End<'_> {
+    let mut depth = 0usize;
+    let mut escaped = false;
+    let mut hex = false;
+    let mut comment = false;
+    let mut token_start = 0;
+
+    for (index, &byte) in data.iter().enumerate() {
+        if comment {
+            comment = byte != b'\r' && byte != b'\n';
+        } else if depth > 0 {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'(' => depth += 1,
+                b')' => depth -= 1,
+                _ => {}
+            }
+        } else if hex {
+            hex = byte != b'>';
+        } else {
+            match byte {
+                b'(' => depth = 1,
+                b'%' => comment = true,
+                // `<<` starts a dictionary instead of a hex string.
+                b'<' => {
+                    hex = data.get(index + 1) != Some(&b'<')
+                        && data.get(index.wrapping_sub(1)) != Some(&b'<');
+                }
+                _ => {}
+            }
+        }
+
+        if !is_regular(byte) {
+            token_start = index + 1;
+        }
+    }
+
+    if depth > 0 || hex {
+        End::String
+    } else if comment {
+        End::Comment
+    } else {
+        End::Token(&data[token_start..])
+    }
+}
+
+/// Concatenate content streams.
+///
+/// Streams should only be split between tokens, but some documents split them in the middle of a
+/// token or string, so streams are joined directly. Only if a stream ends with a complete operator
+/// that would run into the next stream, or with a comment, a line break is inserted.
+pub fn concatenate(parts: &[impl AsRef<[u8]>]) -> Vec<u8> {
+    let mut data = Vec::new();
+
+    for part in parts {
+        let part = part.as_ref();
+        let separate = match end(&data) {
+            End::String => false,
+            End::Comment => true,
+            End::Token(token) => {
+                OPERATORS.contains(&token) && part.first().copied().is_some_and(is_regular)
+            }
+        };
+        if separate {
+            data.push(b'\n');
+        }
+        data.extend_from_slice(part);
+    }
+
+    data
+}
+
+/// Parse the operations of a page's content streams.
+///
+/// The streams are concatenated before parsing, see [`concatenate`]. Streams longer than
+/// `max_length` encoded bytes are skipped. Streams that can't be decoded are skipped. If the
+/// concatenated streams can't be parsed, every stream is parsed on its own and the operations of
+/// the ones that can be parsed are kept.
+pub fn operations(
+    content: &Content,
+    resolver: &impl Resolve,
+    max_length: Option<usize>,
+) -> Result<Vec<Op>, Error> {
+    let parts: Vec<_> = content
+        .parts
+        .iter()
+        .enumerate()
+        .filter(|(index, part)| {
+            let too_long = max_length.is_some_and(|max_length| part.len() > max_length);
+            if too_long {
+                log::warn!(
+                    "skipping content stream {} with {} bytes",
+                    index + 1,
+                    part.len()
+                );
+            }
+            !too_long
+        })
+        .filter_map(|(index, part)| {
+            part.data(resolver)
+                .inspect_err(|err| log::warn!("skipping content stream {}: {err}", index + 1))
+                .ok()
+        })
+        .collect();
+    if parts.is_empty() {
+        return Err(Error::NoContent);
+    }
+
+    let data = concatenate(&parts);
+    match parse_ops(&data, resolver) {
+        Ok(operations) => Ok(operations),
+        Err(err) if parts.len() > 1 => {
+            log::warn!("could not parse content streams together, parsing them one by one: {err}");
+            Ok(parts
+                .iter()
+                .enumerate()
+                .filter_map(|(index, part)| {
+                    parse_ops(part, resolver)
+                        .inspect_err(|err| {
+                            log::warn!("skipping content stream {}: {err}", index + 1);
+                        })
+                        .ok()
+                })
+                .flatten()
+                .collect())
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pdf::object::NoResolve;
+
+    use super::*;
+
+    fn texts(parts: &[&[u8]]) -> Vec<String> {
+        parse_ops(&concatenate(parts), &NoResolve)
+            .unwrap()
+            .into_iter()
+            .filter_map(|operation| match operation {
+                Op::TextDraw { text } => Some(text.to_string_lossy()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn operators_split_across_streams_are_joined() {
+        let parts: &[&[u8]] = &[b"BT /F1 12 Tf 10 20 Td (Title) T", b"j ET"];
+
+        assert_eq!(texts(parts), ["Title"]);
+    }
+
+    #[test]
+    fn strings_split_across_streams_are_joined() {
+        let parts: &[&[u8]] = &[b"BT /F1 12 Tf (Ti", b"tle) Tj ET"];
+
+        assert_eq!(texts(parts), ["Title"]);
+    }
+
+    #[test]
+    fn complete_operators_are_separated_from_the_next_stream() {
+        let parts: &[&[u8]] = &[b"BT /F1 12 Tf (Title) Tj", b"ET BT (Subtitle) Tj ET"];
+
+        assert_eq!(
+            concatenate(parts),
+            b"BT /F1 12 Tf (Title) Tj\nET BT (Subtitle) Tj ET"
+        );
+        assert_eq!(texts(parts), ["Title", "Subtitle"]);
+    }
+}
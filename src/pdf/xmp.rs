@@ -0,0 +1,130 @@
+/// The values of the property `name` (like `dc:title`) in an XMP packet.
+///
+/// Properties can be written as attributes (`xmp:CreateDate="2019-03-04"`), as elements with text
+/// or as elements with an array (`<rdf:Seq>`, `<rdf:Bag>` or `<rdf:Alt>`) of items. The values of
+/// all of them are returned in the order they appear, so the first item of a language
+/// alternative is its default.
+pub fn property(xml: &str, name: &str) -> Vec<String> {
+    let mut values = attributes(xml, name);
+    let opening = format!("<{name}");
+    let closing = format!("</{name}>");
+    let mut rest = xml;
+
+    while let Some(start) = rest.find(&opening) {
+        let after = &rest[start + opening.len()..];
+        rest = after;
+        // Don't match longer names that start with `name`.
+        if !after.starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace()) {
+            continue;
+        }
+        let Some(tag_end) = after.find('>') else {
+            break;
+        };
+        if after[..tag_end].ends_with('/') {
+            continue;
+        }
+        let body = &after[tag_end + 1..];
+        let Some(end) = body.find(&closing) else {
+            break;
+        };
+        let content = &body[..end];
+        rest = &body[end + closing.len()..];
+
+        if content.contains("<rdf:li") {
+            values.extend(items(content));
+        } else if let Some(value) = text(content) {
+            values.push(value);
+        }
+    }
+
+    values
+}
+
+/// The values of attributes called `name`.
+fn attributes(xml: &str, name: &str) -> Vec<String> {
+    xml.match_indices(name)
+        .filter_map(|(start, _)| {
+            let is_whole_name = xml[..start].ends_with(char::is_whitespace);
+            let value = xml[start + name.len()..].trim_start().strip_prefix('=')?;
+            let value = value.trim_start();
+            let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+            let value = &value[1..];
+            let end = value.find(quote)?;
+
+            is_whole_name
+                .then(|| unescape(&value[..end]))
+                .filter(|value| !value.trim().is_empty())
+        })
+        .collect()
+}
+
+/// The text of the `<rdf:li>` items in `content`.
+fn items(content: &str) -> Vec<String> {
+    content
+        .split("<rdf:li")
+        .skip(1)
+        .filter_map(|item| {
+            let (tag, item) = item.split_once('>')?;
+            if tag.ends_with('/') {
+                return None;
+            }
+            text(item.split("</rdf:li>").next()?)
+        })
+        .collect()
+}
+
+/// The text in `content`, without any tags, or `None` if it is blank.
+fn text(content: &str) -> Option<String> {
+    let mut text = String::new();
+    let mut rest = content;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        rest = rest[start..]
+            .find('>')
+            .map_or("", |end| &rest[start + end + 1..]);
+    }
+    text.push_str(rest);
+
+    let text = unescape(text.trim());
+    (!text.is_empty()).then_some(text)
+}
+
+/// Replace the XML entities in `text` with the characters they stand for.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';').filter(|end| *end <= 10) else {
+            unescaped.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        if let Some(c) = c {
+            unescaped.push(c);
+            rest = &rest[end + 1..];
+        } else {
+            unescaped.push('&');
+            rest = &rest[1..];
+        }
+    }
+    unescaped.push_str(rest);
+
+    unescaped
+}
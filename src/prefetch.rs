@@ -0,0 +1,45 @@
+use std::{
+    fs::File,
+    io,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+/// Reads documents ahead in a background thread while the current one is being parsed.
+///
+/// The documents are read into the operating system's page cache and not kept in memory, so
+/// parsing them later doesn't wait for a slow disk or network share.
+pub struct Prefetcher {
+    /// Receives a message for every document that has been read.
+    read: Receiver<()>,
+}
+
+impl Prefetcher {
+    /// Start reading `paths` in order, staying at most `count` documents ahead of [`Self::next`].
+    pub fn spawn(paths: Vec<PathBuf>, count: usize) -> Self {
+        let (sender, read) = mpsc::sync_channel(count);
+
+        thread::spawn(move || {
+            for path in paths {
+                if let Err(err) =
+                    File::open(&path).and_then(|mut file| io::copy(&mut file, &mut io::sink()))
+                {
+                    log::debug!("could not prefetch {}: {err}", path.display());
+                }
+                // stop once the documents are no longer needed
+                if sender.send(()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self { read }
+    }
+
+    /// Wait until the next document has been read, which lets the prefetcher read another one.
+    pub fn next(&self) {
+        // if the thread is gone, the document is simply read when it is parsed
+        let _ = self.read.recv();
+    }
+}
@@ -0,0 +1,38 @@
+use std::sync::OnceLock;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use indicatif_log_bridge::LogWrapper;
+
+use crate::i18n::tr;
+
+/// The progress bars shown on stderr, which log messages are printed above.
+static BARS: OnceLock<MultiProgress> = OnceLock::new();
+
+fn bars() -> &'static MultiProgress {
+    BARS.get_or_init(MultiProgress::new)
+}
+
+/// Log to stderr without breaking up the progress bars.
+pub fn init_logger() {
+    let logger = pretty_env_logger::formatted_builder()
+        .filter_level(log::LevelFilter::Warn)
+        .parse_default_env()
+        .build();
+    let level = logger.filter();
+
+    if LogWrapper::new(bars().clone(), logger).try_init().is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+/// Show how many of `count` documents have been extracted. The bar is hidden if stderr is not a
+/// terminal.
+pub fn documents(count: usize) -> ProgressBar {
+    let style = ProgressStyle::with_template("{bar:40} {pos}/{len} {msg} ({eta})")
+        .unwrap_or_else(|_| ProgressStyle::default_bar());
+    let bar = ProgressBar::new(count as u64)
+        .with_style(style)
+        .with_message(tr!("progress-documents"));
+
+    bars().add(bar)
+}
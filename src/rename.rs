@@ -0,0 +1,385 @@
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+
+use crate::{i18n::tr, normalize};
+
+/// The maximum length of a file name in bytes on most file systems.
+const MAX_FILE_NAME_LENGTH: usize = 255;
+/// Prefixes of the temporary and lock files of sync clients and office suites, which sync clients
+/// ignore or treat specially.
+const SYNC_TEMPORARY_PREFIXES: [&str; 4] = [".syncthing.", "~syncthing~", ".~lock.", "~$"];
+/// Parts of the names sync clients give to copies of files that conflicted.
+const SYNC_CONFLICT_MARKERS: [&str; 2] = [".sync-conflict-", "(conflicted copy"];
+
+/// How words are separated in file names.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Separator {
+    /// `Some Title.pdf`
+    #[default]
+    Space,
+    /// `Some_Title.pdf`
+    Underscore,
+    /// `Some-Title.pdf`
+    Dash,
+    /// `Some.Title.pdf`
+    Dot,
+}
+
+impl Separator {
+    const fn char(self) -> char {
+        match self {
+            Self::Space => ' ',
+            Self::Underscore => '_',
+            Self::Dash => '-',
+            Self::Dot => '.',
+        }
+    }
+
+    /// Separate the words of a `name` separated with this separator with spaces again. Names with
+    /// dashes are left as they are, as dashes are also used within words.
+    pub fn restore(self, name: &str) -> String {
+        match self {
+            Self::Space | Self::Dash => name.to_string(),
+            Self::Underscore | Self::Dot => name.replace(self.char(), " "),
+        }
+    }
+
+    /// Separate the words of `name` with this separator, collapsing runs of whitespace and
+    /// separators into one.
+    fn apply(self, name: &str) -> String {
+        let separator = self.char();
+        name.split(|c: char| c.is_whitespace() || c == separator)
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join(&separator.to_string())
+    }
+}
+
+/// What to do with the colon between a title and its subtitle (`Title: Subtitle`), which is not
+/// allowed in file names on Windows.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Colon {
+    /// `Title Subtitle.pdf`
+    #[default]
+    Remove,
+    /// `Title - Subtitle.pdf`
+    Dash,
+    /// `Title — Subtitle.pdf`
+    EmDash,
+    /// `Title.pdf`
+    DropSubtitle,
+}
+
+impl Colon {
+    /// Replace the colons followed by whitespace in `title`. Other colons (`10:30`) are left to
+    /// sanitization.
+    pub fn apply(self, title: &str) -> String {
+        let mut replaced = String::with_capacity(title.len());
+        let mut rest = title;
+
+        while let Some((start, end)) = subtitle_colon(rest) {
+            let before = &rest[..start];
+            match self {
+                Self::Remove => replaced.push_str(before),
+                Self::Dash => {
+                    replaced.push_str(before.trim_end());
+                    replaced.push_str(" -");
+                }
+                Self::EmDash => {
+                    replaced.push_str(before.trim_end());
+                    replaced.push_str(" —");
+                }
+                // a title starting with a colon is all subtitle
+                Self::DropSubtitle if before.trim().is_empty() => {}
+                Self::DropSubtitle => {
+                    replaced.push_str(before);
+                    return replaced;
+                }
+            }
+            rest = &rest[end..];
+        }
+        replaced.push_str(rest);
+
+        replaced
+    }
+}
+
+/// The start and end of the first colon in `text` that is followed by whitespace or ends it.
+fn subtitle_colon(text: &str) -> Option<(usize, usize)> {
+    text.char_indices()
+        .filter(|(_, c)| matches!(c, ':' | '：'))
+        .map(|(start, c)| (start, start + c.len_utf8()))
+        .find(|(_, end)| text[*end..].chars().next().is_none_or(char::is_whitespace))
+}
+
+/// What to do if a file with the new name already exists.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Conflict {
+    /// Leave the document alone.
+    #[default]
+    Skip,
+    /// Add a number to the new name, `Title (2).pdf`.
+    Suffix,
+    /// Replace the existing file.
+    Overwrite,
+}
+
+/// What happened when renaming a file.
+pub enum Outcome {
+    Renamed(PathBuf),
+    Unchanged,
+    Skipped(String),
+}
+
+/// Compute the path a document at `path` should be renamed to, given its `title`.
+///
+/// The title is sanitized to be a valid file name without invisible characters, its words are
+/// separated by `separator` and the extension of the document is kept. Slashes in the title, which
+/// come from the template, put the document into subdirectories of its directory.
+/// Returns `None` if nothing is left of the title after sanitizing it.
+pub fn target(path: &Path, title: &str, separator: Separator) -> Option<PathBuf> {
+    let sanitize = |name| {
+        separator.apply(&sanitize_filename::sanitize(normalize::strip_invisible(
+            name,
+        )))
+    };
+    let (directories, title) = title.rsplit_once('/').unwrap_or(("", title));
+    let directory: PathBuf = directories
+        .split('/')
+        .map(sanitize)
+        .filter(|directory| !directory.is_empty())
+        .collect();
+
+    let extension = path.extension();
+    let max_length = MAX_FILE_NAME_LENGTH - extension.map_or(0, |extension| extension.len() + 1);
+    let mut name = sanitize(title);
+
+    if name.len() > max_length {
+        let mut end = max_length;
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        name.truncate(end);
+        name.truncate(name.trim_end_matches(separator.char()).len());
+    }
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut file_name = OsString::from(name);
+    if let Some(extension) = extension {
+        file_name.push(".");
+        file_name.push(extension);
+    }
+
+    Some(path.with_file_name(directory.join(file_name)))
+}
+
+/// Options controlling how files are renamed.
+pub struct Options {
+    /// Only report what would be renamed without touching any files.
+    pub dry_run: bool,
+    /// The normalized similarity (between 0 and 1) above which the current and the new file name
+    /// are considered the same, in which case the file is left alone.
+    pub similarity_threshold: f64,
+    /// Avoid renames that confuse sync clients like Syncthing and Nextcloud: case-only renames
+    /// and names that look like their temporary or conflict files are skipped, and files on
+    /// case-insensitive file systems are renamed in two steps.
+    pub sync_safe: bool,
+    pub on_conflict: Conflict,
+}
+
+/// Rename the file at `from` to `to`.
+///
+/// Files whose current name is already similar enough to the new one are left alone, unless they
+/// are moved to another directory. Existing files are only overwritten if `options` allow it.
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be renamed.
+pub fn rename(from: &Path, to: &Path, options: &Options) -> io::Result<Outcome> {
+    if from == to {
+        return Ok(Outcome::Unchanged);
+    }
+    if options.sync_safe {
+        if let Some(reason) = sync_unsafe(from, to) {
+            return Ok(Outcome::Skipped(reason));
+        }
+    }
+    // Names differing only in case are as similar as names can be, but fixing the case is what
+    // was asked for, as is moving the file to another directory.
+    let case_only = is_case_only(from, to);
+    let similarity = similarity(from, to);
+    if !case_only && from.parent() == to.parent() && similarity >= options.similarity_threshold {
+        log::debug!(
+            "{} is similar to {} ({similarity})",
+            from.display(),
+            to.display()
+        );
+        return Ok(Outcome::Unchanged);
+    }
+    // On case-insensitive file systems, the file found under the new name is the file itself.
+    let exists = to.exists() && !(case_only && is_case_insensitive(from));
+    let suffixed;
+    let to = match options.on_conflict {
+        Conflict::Skip if exists => {
+            return Ok(Outcome::Skipped(tr!(
+                "already-exists",
+                path = to.display().to_string()
+            )));
+        }
+        Conflict::Suffix if exists => {
+            suffixed = with_free_suffix(to);
+            &suffixed
+        }
+        _ => to,
+    };
+    if !options.dry_run {
+        if let Some(directory) = to.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        if (case_only || options.sync_safe) && is_case_insensitive(from) {
+            rename_in_two_steps(from, to)?;
+        } else {
+            fs::rename(from, to)?;
+        }
+    }
+
+    Ok(Outcome::Renamed(to.to_path_buf()))
+}
+
+/// The first of `Name (2).ext`, `Name (3).ext`, … next to `path` that does not exist yet.
+fn with_free_suffix(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    (2..u32::MAX)
+        .map(|n| path.with_file_name(format!("{stem} ({n}){extension}")))
+        .find(|path| !path.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Whether `from` and `to` only differ in the case of their file names. Renaming such files in
+/// one step does nothing or fails on case-insensitive file systems like the default ones of macOS
+/// and Windows.
+fn is_case_only(from: &Path, to: &Path) -> bool {
+    let (Some(from_name), Some(to_name)) = (from.file_name(), to.file_name()) else {
+        return false;
+    };
+    let (from_name, to_name) = (from_name.to_string_lossy(), to_name.to_string_lossy());
+
+    from.parent() == to.parent()
+        && from_name != to_name
+        && from_name.to_lowercase() == to_name.to_lowercase()
+}
+
+/// Why renaming `from` to `to` would confuse sync clients, if it would.
+fn sync_unsafe(from: &Path, to: &Path) -> Option<String> {
+    let to_name = to.file_name()?.to_string_lossy();
+
+    if is_case_only(from, to) {
+        Some(tr!("case-only-rename"))
+    } else if SYNC_TEMPORARY_PREFIXES
+        .iter()
+        .any(|prefix| to_name.starts_with(prefix))
+    {
+        Some(tr!("sync-temporary-name", name = format!("{to_name:?}")))
+    } else if SYNC_CONFLICT_MARKERS
+        .iter()
+        .any(|marker| to_name.contains(marker))
+    {
+        Some(tr!("sync-conflict-name", name = format!("{to_name:?}")))
+    } else {
+        None
+    }
+}
+
+/// Whether the file system `path` is on ignores case, judging by whether the file can be found
+/// under a name with a different case that is not listed in its directory.
+///
+/// Files whose names have no letters of different cases are assumed to be on one that does.
+fn is_case_insensitive(path: &Path) -> bool {
+    let Some(name) = path.file_name().map(|name| name.to_string_lossy()) else {
+        return true;
+    };
+    let swapped: String = name
+        .chars()
+        .map(|c| {
+            if c.is_lowercase() {
+                c.to_uppercase().next().unwrap_or(c)
+            } else {
+                c.to_lowercase().next().unwrap_or(c)
+            }
+        })
+        .collect();
+    if swapped == name {
+        return true;
+    }
+
+    path.with_file_name(&swapped).exists()
+        && path
+            .parent()
+            .and_then(|parent| fs::read_dir(parent).ok())
+            .is_none_or(|mut entries| {
+                !entries.any(|entry| entry.is_ok_and(|entry| entry.file_name() == swapped.as_str()))
+            })
+}
+
+/// Rename `from` to `to` through an intermediate name no other file has, so neither step can be
+/// mistaken for a case-only rename or a replacement of an existing file.
+fn rename_in_two_steps(from: &Path, to: &Path) -> io::Result<()> {
+    let mut intermediate = from.as_os_str().to_os_string();
+    intermediate.push(format!(".nomenclate-{}", std::process::id()));
+    let intermediate = PathBuf::from(intermediate);
+    if intermediate.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", intermediate.display()),
+        ));
+    }
+
+    fs::rename(from, &intermediate)?;
+    fs::rename(&intermediate, to).inspect_err(|_| {
+        if let Err(err) = fs::rename(&intermediate, from) {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-move-back",
+                    from = intermediate.display().to_string(),
+                    to = from.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+        }
+    })
+}
+
+/// Compute the normalized similarity between the names of two files.
+///
+/// Case, punctuation and whitespace are ignored so that e.g. `some_title.pdf` and
+/// `Some Title.pdf` are considered equal.
+pub fn similarity(a: &Path, b: &Path) -> f64 {
+    strsim::normalized_levenshtein(&normalize_name(a), &normalize_name(b))
+}
+
+fn normalize_name(path: &Path) -> String {
+    path.file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
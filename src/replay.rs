@@ -0,0 +1,138 @@
+use std::fs;
+
+use crate::{
+    cli::{JournalExportArgs, JournalImportArgs},
+    i18n::tr,
+    journal::{self, Journal, Rename, Run},
+};
+
+/// Export the journaled renames in the library at `--root` to a file.
+pub fn export(args: &JournalExportArgs) {
+    let Some(journal) = Journal::user() else {
+        log::error!("{}", tr!("error-journal-directory"));
+        return;
+    };
+    let runs = args
+        .since
+        .as_ref()
+        .map_or_else(|| journal.runs(), |since| journal.runs_since(since));
+    let result = runs.and_then(|runs| {
+        let count = runs.len();
+        journal::export(runs, &args.root, &args.output).map(|left_out| (count, left_out))
+    });
+
+    match result {
+        Ok((count, left_out)) => {
+            if left_out > 0 {
+                log::warn!(
+                    "{}",
+                    tr!(
+                        "warn-export-outside",
+                        count = left_out,
+                        root = args.root.display().to_string()
+                    )
+                );
+            }
+            println!(
+                "{}",
+                tr!(
+                    "journal-exported",
+                    count = count,
+                    path = args.output.display().to_string()
+                )
+            );
+        }
+        Err(err) => log::error!("{}", tr!("error-export-journal", error = err.to_string())),
+    }
+}
+
+/// Replay the renames of an exported journal in the library at `--root`, or undo them.
+///
+/// A rename is only replayed if the document is where it was before the rename and nothing is in
+/// the way, so importing the same export twice does nothing the second time. The replayed renames
+/// are journaled like the renames of a run.
+pub fn import(args: &JournalImportArgs) {
+    let mut renames: Vec<_> = match journal::import(&args.input, &args.root) {
+        Ok(runs) => runs.into_iter().flat_map(|run| run.renames).collect(),
+        Err(err) => {
+            log::error!("{}", tr!("error-import-journal", error = err.to_string()));
+            return;
+        }
+    };
+    if args.undo {
+        renames = renames
+            .into_iter()
+            .rev()
+            .map(|rename| Rename {
+                from: rename.to,
+                to: rename.from,
+            })
+            .collect();
+    }
+
+    let mut run = Run::start();
+    for rename in renames {
+        if replay(&rename, args.dry_run) {
+            run.renames.push(Rename::new(&rename.from, &rename.to));
+        }
+    }
+
+    if args.dry_run || run.renames.is_empty() {
+        return;
+    }
+    match Journal::user() {
+        Some(journal) => {
+            if let Err(err) = journal.append(run) {
+                log::warn!("{}", tr!("warn-write-journal", error = err.to_string()));
+            }
+        }
+        None => log::warn!("{}", tr!("error-journal-directory")),
+    }
+}
+
+/// Rename a document as the `rename` did on another machine and return whether it was renamed.
+fn replay(rename: &Rename, dry_run: bool) -> bool {
+    if !rename.from.exists() {
+        log::info!(
+            "{} is not there to be renamed, skipping",
+            rename.from.display()
+        );
+        return false;
+    }
+    if rename.to.exists() {
+        crate::skip(
+            &rename.from,
+            &tr!("already-exists", path = rename.to.display().to_string()),
+        );
+        return false;
+    }
+
+    if !dry_run {
+        let result = rename
+            .to
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::rename(&rename.from, &rename.to));
+        if let Err(err) = result {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-rename",
+                    path = rename.from.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+            return false;
+        }
+    }
+    println!(
+        "{}",
+        tr!(
+            "renamed",
+            from = rename.from.display().to_string(),
+            to = rename.to.display().to_string()
+        )
+    );
+
+    true
+}
@@ -0,0 +1,241 @@
+use std::{collections::BTreeMap, fs, io, path::Path, time::Duration};
+
+use clap::ValueEnum;
+use nomenclate::pdf::{self, Candidate, Field, Metadata, Provenance};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+/// Collects how long processing each document took and where its title came from.
+#[derive(Default)]
+pub struct Report {
+    total: Duration,
+    sources: BTreeMap<Provenance, usize>,
+    /// How many documents were left alone because their title was too uncertain.
+    uncertain: usize,
+}
+
+impl Report {
+    /// Record that processing the document at `path` took `elapsed` and print it, with how much
+    /// of its text could be decoded if its layout was parsed.
+    pub fn record(
+        &mut self,
+        path: &Path,
+        elapsed: Duration,
+        source: Provenance,
+        decoding: Option<&Decoding>,
+    ) {
+        eprintln!("{}: {elapsed:.1?} ({source})", path.display());
+        if let Some(decoding) = decoding {
+            decoding.print();
+        }
+
+        self.total += elapsed;
+        *self.sources.entry(source).or_default() += 1;
+    }
+
+    /// Record that extracting the candidates of the document at `path` took `elapsed`, but that it
+    /// was left alone because its title was too uncertain, and print it.
+    pub fn record_uncertain(&mut self, path: &Path, elapsed: Duration) {
+        eprintln!("{}: {elapsed:.1?} (uncertain)", path.display());
+
+        self.total += elapsed;
+        self.uncertain += 1;
+    }
+
+    /// Print a summary of all recorded documents.
+    pub fn summarize(&self) {
+        let count = self.sources.values().sum::<usize>() + self.uncertain;
+        if count == 0 {
+            return;
+        }
+
+        eprintln!(
+            "{}",
+            tr!(
+                "report-summary",
+                count = count,
+                elapsed = format!("{:.1?}", self.total)
+            )
+        );
+        for (source, count) in &self.sources {
+            eprintln!("  {source}: {count}");
+        }
+        if self.uncertain > 0 {
+            eprintln!("  uncertain: {}", self.uncertain);
+        }
+    }
+}
+
+/// How the outcome for every document is printed.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// A line of text for every renamed document.
+    Text,
+    /// A line of JSON for every renamed document, with the title candidates, the title and
+    /// metadata and where they came from.
+    Json,
+}
+
+/// How many glyphs could be mapped to text, see [`pdf::Coverage`].
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Coverage {
+    pub glyphs: usize,
+    pub unmapped: usize,
+    /// The share of glyphs that could be mapped, in percent.
+    pub percent: f64,
+}
+
+impl From<pdf::Coverage> for Coverage {
+    fn from(coverage: pdf::Coverage) -> Self {
+        Self {
+            glyphs: coverage.glyphs,
+            unmapped: coverage.unmapped,
+            percent: (coverage.percent() * 10.).round() / 10.,
+        }
+    }
+}
+
+/// How well the text of a document could be decoded, so a strange title can be told apart from
+/// text set in fonts that could not be decoded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Decoding {
+    #[serde(flatten)]
+    pub total: Coverage,
+    /// The coverage of every font text was set in, by the name of the font.
+    pub fonts: BTreeMap<String, Coverage>,
+}
+
+impl Decoding {
+    /// Print the coverage of the document and of the fonts not all glyphs could be mapped in.
+    fn print(&self) {
+        eprintln!(
+            "  {}",
+            tr!(
+                "report-coverage",
+                percent = format!("{:.1}", self.total.percent),
+                unmapped = self.total.unmapped,
+                glyphs = self.total.glyphs
+            )
+        );
+        for (font, coverage) in self
+            .fonts
+            .iter()
+            .filter(|(_, coverage)| coverage.unmapped > 0)
+        {
+            eprintln!(
+                "    {}",
+                tr!(
+                    "report-font-coverage",
+                    font = font.as_str(),
+                    percent = format!("{:.1}", coverage.percent),
+                    unmapped = coverage.unmapped,
+                    glyphs = coverage.glyphs
+                )
+            );
+        }
+    }
+}
+
+impl From<&pdf::Statistics> for Decoding {
+    fn from(statistics: &pdf::Statistics) -> Self {
+        Self {
+            total: statistics.coverage.into(),
+            fonts: statistics
+                .fonts
+                .iter()
+                .map(|(font, coverage)| (font.clone(), (*coverage).into()))
+                .collect(),
+        }
+    }
+}
+
+/// A title candidate that was passed over with `--fallback-titles`.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Skipped {
+    /// The number of the candidate, starting at 1.
+    pub candidate: usize,
+    pub reason: pdf::Implausible,
+}
+
+/// The version of the JSON records for documents, increased whenever a field of [`Record`] or
+/// [`Panicked`] is removed or changed. Fields may be added without increasing it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The JSON Schema of [`Record`] and [`Panicked`], printed by `--schema`.
+pub const SCHEMA: &str = include_str!("../schema/record.schema.json");
+
+/// The outcome for one document as printed by `--format json`, described by [`SCHEMA`].
+#[derive(Serialize)]
+pub struct Record<'a> {
+    /// Always [`SCHEMA_VERSION`].
+    pub schema_version: u32,
+    pub path: &'a Path,
+    /// Where the document was renamed to, which is `path` if it was left unchanged.
+    pub renamed: &'a Path,
+    pub title: Field<&'a str>,
+    /// The title candidates found in the layout, the most likely title first.
+    pub candidates: &'a [Candidate],
+    pub metadata: &'a Metadata,
+    /// The confidence (between 0 and 1) in the title, if it was found in the layout.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// How well the text of the document could be decoded, if its layout was parsed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoding: Option<&'a Decoding>,
+    /// The candidates passed over before the title, in order.
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub skipped: &'a [Skipped],
+}
+
+impl Record<'_> {
+    /// Write the record to a `.nomenclate.json` file next to the renamed document, so what was
+    /// extracted is kept for other tools.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the sidecar file could not be written.
+    pub fn write_sidecar(&self) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(self.renamed.with_extension(SIDECAR_EXTENSION), json + "\n")
+    }
+}
+
+/// The extension of the files written by [`Record::write_sidecar`].
+pub const SIDECAR_EXTENSION: &str = "nomenclate.json";
+
+/// The outcome for a document whose processing panicked, as printed by `--format json`,
+/// described by [`SCHEMA`].
+#[derive(Serialize)]
+pub struct Panicked<'a> {
+    /// Always [`SCHEMA_VERSION`].
+    pub schema_version: u32,
+    pub path: &'a Path,
+    /// The message of the panic.
+    pub error: &'a str,
+    /// Where in the code the panic happened.
+    pub location: Option<&'a str>,
+    pub backtrace: &'a str,
+}
+
+impl Panicked<'_> {
+    /// Print the record as a single line of JSON.
+    pub fn print(&self) {
+        print_json(self.path, self);
+    }
+}
+
+/// Print the `record` of the document at `path` as a single line of JSON.
+fn print_json(path: &Path, record: &impl Serialize) {
+    match serde_json::to_string(record) {
+        Ok(json) => println!("{json}"),
+        Err(err) => log::error!(
+            "{}",
+            tr!(
+                "error-serialize",
+                path = path.display().to_string(),
+                error = err.to_string()
+            )
+        ),
+    }
+}
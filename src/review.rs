@@ -0,0 +1,142 @@
+use std::{borrow::Cow, path::PathBuf, time::Duration};
+
+use rustyline::{
+    completion::Completer,
+    error::ReadlineError,
+    highlight::{CmdKind, Highlighter},
+    hint::Hinter,
+    history::DefaultHistory,
+    validate::Validator,
+    DefaultEditor, Editor, Helper,
+};
+
+use nomenclate::pdf::{Candidate, Metadata};
+
+use crate::i18n::tr;
+
+/// A document whose title could not be determined with enough confidence.
+pub struct Failure {
+    pub path: PathBuf,
+    pub candidates: Vec<Candidate>,
+    pub metadata: Metadata,
+    /// How long extracting the candidates took.
+    pub elapsed: Duration,
+}
+
+/// Ask the user to type a title for each of the `failures`.
+///
+/// The first `candidate_count` extracted candidates are listed and offered as editable defaults:
+/// The first one is prefilled and the others can be reached through the history (arrow keys) or
+/// picked by typing their number after a `#`, like `#2`, so titles that are numbers themselves can
+/// still be typed. An empty answer skips the document and ending the input (Ctrl-D or Ctrl-C) skips
+/// all remaining documents.
+///
+/// Returns the reviewed documents together with the titles chosen for them.
+///
+/// # Errors
+///
+/// This function will return an error if reading from the terminal fails.
+pub fn review(
+    failures: Vec<Failure>,
+    candidate_count: usize,
+) -> rustyline::Result<Vec<(Failure, String)>> {
+    let mut editor = DefaultEditor::new()?;
+    let mut titles = Vec::new();
+    let count = failures.len();
+
+    for (index, failure) in failures.into_iter().enumerate() {
+        println!("[{}/{count}] {}", index + 1, failure.path.display());
+        let candidates = &failure.candidates[..candidate_count.min(failure.candidates.len())];
+        for (number, candidate) in candidates.iter().enumerate() {
+            println!(
+                "  {}",
+                tr!(
+                    "review-candidate",
+                    number = number + 1,
+                    title = format!("{:?}", candidate.text),
+                    page = candidate.page + 1,
+                    y = format!("{:.0}", candidate.y),
+                    font_size = format!("{:.1}", candidate.font_size),
+                    score = format!("{:.2}", candidate.score)
+                )
+            );
+        }
+
+        editor.clear_history()?;
+        for candidate in candidates.iter().skip(1).rev() {
+            editor.add_history_entry(candidate.text.as_str())?;
+        }
+        let initial = failure
+            .candidates
+            .first()
+            .map_or("", |candidate| candidate.text.as_str());
+
+        match editor.readline_with_initial(&tr!("review-prompt"), (initial, "")) {
+            Ok(title) if title.trim().is_empty() => {
+                log::info!(
+                    "{}",
+                    tr!("review-skipping", path = failure.path.display().to_string())
+                );
+            }
+            Ok(title) => {
+                let title = pick(candidates, title.trim()).to_string();
+                titles.push((failure, title));
+            }
+            Err(ReadlineError::Eof | ReadlineError::Interrupted) => {
+                log::info!("{}", tr!("review-skipping-remaining"));
+                break;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(titles)
+}
+
+/// The text of the candidate whose number follows the `#` the `answer` starts with, or the `answer`
+/// itself if it does not refer to one of the `candidates`.
+fn pick<'a>(candidates: &'a [Candidate], answer: &'a str) -> &'a str {
+    answer
+        .strip_prefix('#')
+        .and_then(|number| number.trim().parse::<usize>().ok())
+        .and_then(|number| candidates.get(number.checked_sub(1)?))
+        .map_or(answer, |candidate| candidate.text.as_str())
+}
+
+/// Ask the user for the password to decrypt encrypted documents with, showing an asterisk for
+/// every character typed.
+///
+/// # Errors
+///
+/// This function will return an error if reading from the terminal fails.
+pub fn ask_password() -> rustyline::Result<String> {
+    let mut editor = Editor::<Masked, DefaultHistory>::new()?;
+    editor.set_helper(Some(Masked));
+
+    editor.readline(&tr!("password-prompt"))
+}
+
+/// Hides the line being edited.
+struct Masked;
+
+impl Highlighter for Masked {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        Cow::Owned("*".repeat(line.chars().count()))
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, _kind: CmdKind) -> bool {
+        true
+    }
+}
+
+impl Completer for Masked {
+    type Candidate = String;
+}
+
+impl Hinter for Masked {
+    type Hint = String;
+}
+
+impl Validator for Masked {}
+
+impl Helper for Masked {}
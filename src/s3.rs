@@ -0,0 +1,488 @@
+use std::{
+    collections::HashMap,
+    env,
+    fmt::{self, Display, Write as _},
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::{Duration, SystemTime},
+};
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use ureq::{
+    http::{self, Method, Response},
+    Agent, Body,
+};
+
+use crate::{cli::RunArgs, files, i18n::tr, rename, review, Titled};
+
+const SCHEME: &str = "s3://";
+
+/// How long to wait for object storage to answer, including downloading a document.
+const TIMEOUT: Duration = Duration::from_mins(5);
+
+/// The hash of an empty payload, which is all nomenclate ever sends.
+const EMPTY_PAYLOAD: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+static AGENT: LazyLock<Agent> = LazyLock::new(|| {
+    Agent::config_builder()
+        .timeout_global(Some(TIMEOUT))
+        .user_agent(concat!("nomenclate/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .into()
+});
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY")]
+    NoCredentials,
+    #[error("invalid request: {0}")]
+    Http(#[from] http::Error),
+    #[error("request failed: {0}")]
+    Request(#[from] ureq::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// An object, or all objects starting with a prefix, in a bucket, written as
+/// `s3://bucket/key`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Location {
+    pub bucket: String,
+    pub key: String,
+}
+
+impl Location {
+    /// The location `path` stands for if it starts with `s3://`.
+    pub fn parse(path: &Path) -> Option<Self> {
+        let rest = path.to_str()?.strip_prefix(SCHEME)?;
+        let (bucket, key) = rest.split_once('/').unwrap_or((rest, ""));
+
+        (!bucket.is_empty()).then(|| Self {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+        })
+    }
+
+    /// Whether this is a single document rather than a prefix of documents.
+    fn is_document(&self) -> bool {
+        Path::new(&self.key)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"))
+    }
+
+    fn file_name(&self) -> &str {
+        self.key.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{SCHEME}{}/{}", self.bucket, self.key)
+    }
+}
+
+/// Remove the `s3://` locations from `paths` and return them.
+pub fn take_locations(paths: &mut Vec<PathBuf>) -> Vec<Location> {
+    let mut locations = Vec::new();
+    paths.retain(|path| {
+        let location = Location::parse(path);
+        let local = location.is_none();
+        locations.extend(location);
+        local
+    });
+
+    locations
+}
+
+/// A client of S3 or S3-compatible object storage, addressing buckets by path.
+pub struct Client {
+    /// The scheme and authority of the service, like `https://s3.eu-central-1.amazonaws.com`.
+    endpoint: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+}
+
+impl Client {
+    /// A client configured by `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, `AWS_SESSION_TOKEN`,
+    /// `AWS_REGION` and `AWS_ENDPOINT_URL`, like the AWS command line interface.
+    pub fn from_env() -> Result<Self, Error> {
+        let var = |names: &[&str]| names.iter().find_map(|name| env::var(name).ok());
+        let (Some(access_key), Some(secret_key)) =
+            (var(&["AWS_ACCESS_KEY_ID"]), var(&["AWS_SECRET_ACCESS_KEY"]))
+        else {
+            return Err(Error::NoCredentials);
+        };
+        let region =
+            var(&["AWS_REGION", "AWS_DEFAULT_REGION"]).unwrap_or_else(|| "us-east-1".into());
+        let endpoint = var(&["AWS_ENDPOINT_URL_S3", "AWS_ENDPOINT_URL"]).map_or_else(
+            || format!("https://s3.{region}.amazonaws.com"),
+            |endpoint| endpoint.trim_end_matches('/').to_string(),
+        );
+
+        Ok(Self {
+            endpoint,
+            region,
+            access_key,
+            secret_key,
+            session_token: var(&["AWS_SESSION_TOKEN"]),
+        })
+    }
+
+    /// The PDF documents whose keys start with the key of `prefix`.
+    pub fn list(&self, prefix: &Location) -> Result<Vec<Location>, Error> {
+        let bucket = Location {
+            bucket: prefix.bucket.clone(),
+            key: String::new(),
+        };
+        let mut documents = Vec::new();
+        let mut token: Option<String> = None;
+
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix.key.as_str())];
+            if let Some(token) = &token {
+                query.push(("continuation-token", token.as_str()));
+            }
+            let listing = self
+                .send(Method::GET, &bucket, &query, &[])?
+                .into_body()
+                .read_to_string()?;
+
+            documents.extend(
+                elements(&listing, "Key")
+                    .into_iter()
+                    .map(|key| Location {
+                        bucket: prefix.bucket.clone(),
+                        key,
+                    })
+                    .filter(Location::is_document),
+            );
+            token = elements(&listing, "NextContinuationToken").pop();
+            if token.is_none() {
+                return Ok(documents);
+            }
+        }
+    }
+
+    /// Download the `object` to the file at `path`.
+    pub fn download(&self, object: &Location, path: &Path) -> Result<(), Error> {
+        let mut response = self.send(Method::GET, object, &[], &[])?;
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        io::copy(
+            &mut response.body_mut().as_reader(),
+            &mut File::create(path)?,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn exists(&self, object: &Location) -> Result<bool, Error> {
+        match self.send(Method::HEAD, object, &[], &[]) {
+            Ok(_) => Ok(true),
+            Err(Error::Request(ureq::Error::StatusCode(404))) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Copy the object at `from` to `to` and delete it at `from`, since objects cannot be renamed.
+    pub fn rename(&self, from: &Location, to: &Location) -> Result<(), Error> {
+        let source = format!("/{}/{}", from.bucket, encode(&from.key, true));
+        self.send(Method::PUT, to, &[], &[("x-amz-copy-source", &source)])?;
+        self.send(Method::DELETE, from, &[], &[])?;
+
+        Ok(())
+    }
+
+    /// Send a request without a body, signed with AWS Signature Version 4.
+    fn send(
+        &self,
+        method: Method,
+        location: &Location,
+        query: &[(&str, &str)],
+        headers: &[(&str, &str)],
+    ) -> Result<Response<Body>, Error> {
+        let path = format!("/{}/{}", location.bucket, encode(&location.key, true));
+        let mut query: Vec<_> = query
+            .iter()
+            .map(|(name, value)| format!("{}={}", encode(name, false), encode(value, false)))
+            .collect();
+        query.sort();
+        let query = query.join("&");
+
+        let time = humantime::format_rfc3339_seconds(SystemTime::now())
+            .to_string()
+            .replace(['-', ':'], "");
+        let date = &time[..8];
+        let host = self
+            .endpoint
+            .split_once("://")
+            .map_or(self.endpoint.as_str(), |(_, host)| host);
+        let mut headers: Vec<(String, String)> = headers
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+            .chain([
+                ("host".into(), host.into()),
+                ("x-amz-content-sha256".into(), EMPTY_PAYLOAD.into()),
+                ("x-amz-date".into(), time.clone()),
+            ])
+            .chain(
+                self.session_token
+                    .clone()
+                    .map(|token| ("x-amz-security-token".into(), token)),
+            )
+            .collect();
+        headers.sort();
+
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_headers =
+            headers
+                .iter()
+                .fold(String::new(), |mut canonical, (name, value)| {
+                    let _ = writeln!(canonical, "{name}:{}", value.trim());
+                    canonical
+                });
+        let canonical_request = format!(
+            "{method}\n{path}\n{query}\n{canonical_headers}\n{signed_headers}\n{EMPTY_PAYLOAD}"
+        );
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{time}\n{scope}\n{:x}",
+            Sha256::digest(canonical_request)
+        );
+        let key = [date, &self.region, "s3", "aws4_request"].into_iter().fold(
+            format!("AWS4{}", self.secret_key).into_bytes(),
+            |key, part| hmac(&key, part),
+        );
+        let signature =
+            hmac(&key, &string_to_sign)
+                .iter()
+                .fold(String::new(), |mut signature, byte| {
+                    let _ = write!(signature, "{byte:02x}");
+                    signature
+                });
+
+        let uri = if query.is_empty() {
+            format!("{}{path}", self.endpoint)
+        } else {
+            format!("{}{path}?{query}", self.endpoint)
+        };
+        let mut request = http::Request::builder().method(method).uri(uri).header(
+            "authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, \
+                 Signature={signature}",
+                self.access_key
+            ),
+        );
+        for (name, value) in headers.into_iter().filter(|(name, _)| name != "host") {
+            request = request.header(name, value);
+        }
+
+        Ok(AGENT.run(request.body(())?)?)
+    }
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any size");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encode everything but unreserved characters and, if `keep_slashes`, slashes.
+fn encode(value: &str, keep_slashes: bool) -> String {
+    value
+        .bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric()
+                || b"-_.~".contains(&byte)
+                || (keep_slashes && byte == b'/')
+            {
+                char::from(byte).to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect()
+}
+
+/// The unescaped text of every `<tag>` element in `xml`.
+fn elements(xml: &str, tag: &str) -> Vec<String> {
+    let close = format!("</{tag}>");
+
+    xml.split(&format!("<{tag}>"))
+        .skip(1)
+        .filter_map(|rest| {
+            rest.split_once(close.as_str()).map(|(text, _)| {
+                text.replace("&lt;", "<")
+                    .replace("&gt;", ">")
+                    .replace("&quot;", "\"")
+                    .replace("&apos;", "'")
+                    .replace("&amp;", "&")
+            })
+        })
+        .collect()
+}
+
+/// Rename the PDF documents at `locations` in object storage.
+///
+/// The documents are downloaded to a temporary directory to extract their titles and copied to
+/// their new keys. Journals, audit logs, counters and sidecar files are not supported.
+pub fn process(args: &RunArgs, locations: &[Location]) {
+    let client = match Client::from_env() {
+        Ok(client) => client,
+        Err(err) => {
+            log::error!("{}", tr!("error-s3", error = err.to_string()));
+            return;
+        }
+    };
+    let mut objects = Vec::new();
+    for location in locations {
+        if location.is_document() {
+            objects.push(location.clone());
+            continue;
+        }
+        match client.list(location) {
+            Ok(documents) => objects.extend(documents),
+            Err(err) => log::error!(
+                "{}",
+                tr!(
+                    "error-s3-list",
+                    location = location.to_string(),
+                    error = err.to_string()
+                )
+            ),
+        }
+    }
+
+    let directory = match files::temporary_directory("nomenclate-s3-") {
+        Ok(directory) => directory,
+        Err(err) => {
+            log::error!(
+                "{}",
+                tr!("error-temporary-directory", error = err.to_string())
+            );
+            return;
+        }
+    };
+    let mut paths = Vec::new();
+    let mut uris = HashMap::new();
+    for (index, object) in objects.iter().enumerate() {
+        let path = directory
+            .path()
+            .join(index.to_string())
+            .join(object.file_name());
+        match client.download(object, &path) {
+            Ok(()) => {
+                uris.insert(path.clone(), PathBuf::from(object.to_string()));
+                paths.push(path);
+            }
+            Err(err) => log::error!(
+                "{}",
+                tr!(
+                    "error-s3-download",
+                    location = object.to_string(),
+                    error = err.to_string()
+                )
+            ),
+        }
+    }
+
+    let (mut documents, mut failures) = crate::extract_documents(args, &paths, &HashMap::new());
+    if let Err(err) = directory.close() {
+        log::debug!("could not remove the downloaded documents: {err}");
+    }
+    for document in &mut documents {
+        document.path.clone_from(&uris[&document.path]);
+    }
+    for failure in &mut failures {
+        failure.path.clone_from(&uris[&failure.path]);
+    }
+
+    for document in &documents {
+        rename_object(args, &client, document);
+    }
+    if failures.is_empty() {
+        return;
+    }
+    match review::review(failures, args.candidate_count) {
+        Ok(titles) => {
+            for (failure, title) in titles {
+                rename_object(args, &client, &Titled::reviewed(failure, title));
+            }
+        }
+        Err(err) => log::error!("{}", tr!("error-review", error = err.to_string())),
+    }
+}
+
+/// Rename the object of `document` to its title, unless another object is in the way.
+fn rename_object(args: &RunArgs, client: &Client, document: &Titled) {
+    let Some(object) = Location::parse(&document.path) else {
+        return;
+    };
+    let name = crate::file_name(args, document, None);
+    let Some(key) = rename::target(Path::new(&object.key), &name, args.separator) else {
+        log::error!(
+            "{}",
+            tr!(
+                "error-invalid-file-name",
+                path = object.to_string(),
+                title = format!("{name:?}")
+            )
+        );
+        return;
+    };
+    let target = Location {
+        bucket: object.bucket.clone(),
+        key: key.to_string_lossy().into_owned(),
+    };
+
+    if target == object {
+        println!("{}", tr!("unchanged", path = object.to_string()));
+        return;
+    }
+    let result = client.exists(&target).and_then(|exists| {
+        if exists {
+            crate::skip(
+                &document.path,
+                &tr!("already-exists", path = target.to_string()),
+            );
+            Ok(false)
+        } else if args.dry_run {
+            Ok(true)
+        } else {
+            client.rename(&object, &target).map(|()| true)
+        }
+    });
+
+    match result {
+        Ok(true) => println!(
+            "{}",
+            tr!(
+                "renamed",
+                from = object.to_string(),
+                to = target.to_string()
+            )
+        ),
+        Ok(false) => {}
+        Err(err) => log::error!(
+            "{}",
+            tr!(
+                "error-s3-rename",
+                from = object.to_string(),
+                to = target.to_string(),
+                error = err.to_string()
+            )
+        ),
+    }
+}
@@ -0,0 +1,236 @@
+use std::{sync::Once, thread};
+
+use crate::{i18n::tr, panics};
+
+/// Documents larger than this are not parsed with `--sandbox`.
+pub const MAX_DOCUMENT_SIZE: u64 = 256 * 1024 * 1024;
+
+static WARN_UNCONFINED: Once = Once::new();
+
+/// Call `f`, on a thread of its own that may not write to files, open network connections or run
+/// programs if `sandbox` is set.
+///
+/// The restrictions are only available on Linux, and only as far as the kernel supports them
+/// (Landlock since 5.13). Where they cannot be applied, a warning is printed once and `f` runs
+/// without them.
+pub fn confine<T: Send>(sandbox: bool, f: impl FnOnce() -> T + Send) -> T {
+    if !sandbox {
+        return f();
+    }
+
+    thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                if let Err(err) = restrict() {
+                    WARN_UNCONFINED.call_once(|| {
+                        log::warn!("{}", tr!("warn-sandbox", error = err));
+                    });
+                }
+                panics::catch(f)
+            })
+            .join()
+            .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+            .unwrap_or_else(|panic| panics::resume(panic))
+    })
+}
+
+/// Restrict the calling thread and the threads it starts.
+#[cfg(target_os = "linux")]
+fn restrict() -> Result<(), String> {
+    linux::no_new_privileges()?;
+    let seccomp = linux::deny_syscalls();
+    let landlock = linux::deny_writes();
+
+    seccomp.and(landlock)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn restrict() -> Result<(), String> {
+    Err(tr!("sandbox-unsupported"))
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::io;
+
+    use libc::{c_long, c_void, sock_filter, sock_fprog};
+
+    /// The architecture system calls are checked for, as reported to seccomp filters.
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: u32 = 0xc000_003e;
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: u32 = 0xc000_00b7;
+
+    /// The system calls parsing a document has no business making.
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    const DENIED: [c_long; 6] = [
+        libc::SYS_socket,
+        libc::SYS_socketpair,
+        libc::SYS_connect,
+        libc::SYS_execve,
+        libc::SYS_execveat,
+        libc::SYS_ptrace,
+    ];
+
+    /// System calls of the x32 ABI have numbers with this bit set, which are otherwise the same
+    /// system calls.
+    #[cfg(target_arch = "x86_64")]
+    const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+    /// Everything the first version of Landlock can deny about files, except reading them and
+    /// listing directories.
+    const LANDLOCK_WRITES: u64 = ((1 << 13) - 1) & !((1 << 2) | (1 << 3));
+
+    /// The rights later versions of Landlock can deny about files, with the version they were
+    /// added in: moving and linking files to other directories, truncating them and using
+    /// `ioctl` on devices.
+    const LANDLOCK_LATER_WRITES: [(c_long, u64); 3] = [(2, 1 << 13), (3, 1 << 14), (5, 1 << 15)];
+
+    /// Makes `landlock_create_ruleset` return the highest version of Landlock the kernel supports.
+    const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1;
+
+    /// The first version of `struct landlock_ruleset_attr`.
+    #[repr(C)]
+    struct RulesetAttr {
+        handled_access_fs: u64,
+    }
+
+    fn last_error() -> String {
+        io::Error::last_os_error().to_string()
+    }
+
+    /// Keep the thread from gaining privileges, e.g. through setuid programs, which filtering
+    /// system calls and Landlock require.
+    pub(super) fn no_new_privileges() -> Result<(), String> {
+        // SAFETY: `prctl` with `PR_SET_NO_NEW_PRIVS` takes no pointers.
+        let result = unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(last_error())
+        }
+    }
+
+    /// Make the [`DENIED`] system calls fail with `EPERM`.
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    // The instruction codes, system call numbers and the length of the filter are all small.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub(super) fn deny_syscalls() -> Result<(), String> {
+        const ARCH_OFFSET: u32 = 4;
+        const NR_OFFSET: u32 = 0;
+        let load = |offset| sock_filter {
+            code: (libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as u16,
+            jt: 0,
+            jf: 0,
+            k: offset,
+        };
+        let ret = |k| sock_filter {
+            code: (libc::BPF_RET | libc::BPF_K) as u16,
+            jt: 0,
+            jf: 0,
+            k,
+        };
+        let jump = |k, jt, jf| sock_filter {
+            code: (libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K) as u16,
+            jt,
+            jf,
+            k,
+        };
+        #[cfg(target_arch = "x86_64")]
+        let jump_at_least = |k, jt, jf| sock_filter {
+            code: (libc::BPF_JMP | libc::BPF_JGE | libc::BPF_K) as u16,
+            jt,
+            jf,
+            k,
+        };
+        let deny = ret(libc::SECCOMP_RET_ERRNO | libc::EPERM as u32);
+
+        // Calls made for another architecture have other numbers, so they are denied.
+        let mut filter = vec![
+            load(ARCH_OFFSET),
+            jump(AUDIT_ARCH, 1, 0),
+            deny,
+            load(NR_OFFSET),
+        ];
+        // The x32 numbers of the denied calls would get past the checks below.
+        #[cfg(target_arch = "x86_64")]
+        filter.extend([jump_at_least(X32_SYSCALL_BIT, 0, 1), deny]);
+        for nr in DENIED {
+            filter.extend([jump(nr as u32, 0, 1), deny]);
+        }
+        filter.push(ret(libc::SECCOMP_RET_ALLOW));
+
+        let program = sock_fprog {
+            len: filter.len() as u16,
+            filter: filter.as_mut_ptr(),
+        };
+        // SAFETY: `program` points to `filter`, which lives until the call returns, and the
+        // kernel copies it.
+        let result = unsafe {
+            libc::prctl(
+                libc::PR_SET_SECCOMP,
+                libc::SECCOMP_MODE_FILTER,
+                (&raw const program).cast::<c_void>(),
+                0,
+                0,
+            )
+        };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(last_error())
+        }
+    }
+
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    pub(super) fn deny_syscalls() -> Result<(), String> {
+        Err(crate::i18n::tr!("sandbox-unsupported"))
+    }
+
+    /// Deny everything about files but reading them with Landlock, as far as the kernel supports.
+    pub(super) fn deny_writes() -> Result<(), String> {
+        // SAFETY: Querying the version takes no ruleset attribute.
+        let version = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<RulesetAttr>(),
+                0,
+                LANDLOCK_CREATE_RULESET_VERSION,
+            )
+        };
+        if version < 1 {
+            return Err(last_error());
+        }
+        let attr = RulesetAttr {
+            handled_access_fs: LANDLOCK_LATER_WRITES
+                .iter()
+                .filter(|(added, _)| version >= *added)
+                .fold(LANDLOCK_WRITES, |writes, (_, rights)| writes | rights),
+        };
+        // SAFETY: `attr` is a valid ruleset attribute of the size passed along with it.
+        let ruleset = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                &raw const attr,
+                size_of::<RulesetAttr>(),
+                0,
+            )
+        };
+        let Some(ruleset) = libc::c_int::try_from(ruleset).ok().filter(|fd| *fd >= 0) else {
+            return Err(last_error());
+        };
+
+        // SAFETY: `ruleset` is the file descriptor of the ruleset created above, which is closed
+        // right after restricting the thread with it.
+        let result = unsafe {
+            let result = libc::syscall(libc::SYS_landlock_restrict_self, ruleset, 0);
+            libc::close(ruleset);
+            result
+        };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(last_error())
+        }
+    }
+}
@@ -0,0 +1,177 @@
+#[cfg(feature = "ocr")]
+use std::process::{Command, Stdio};
+
+use nomenclate::Extractor;
+
+use crate::{cli::SelftestArgs, i18n::tr};
+
+/// A miniature document bundled with the binary, with the title it should get.
+struct Fixture {
+    name: &'static str,
+    data: &'static [u8],
+    title: &'static str,
+    /// The password the document is encrypted with.
+    password: Option<&'static str>,
+    /// Whether the document has no text, so its title can only be recognized with OCR.
+    scanned: bool,
+}
+
+const FIXTURES: &[Fixture] = &[
+    Fixture {
+        name: "winansi.pdf",
+        data: include_bytes!("../fixtures/winansi.pdf"),
+        title: "Über die Größe",
+        password: None,
+        scanned: false,
+    },
+    Fixture {
+        name: "composite.pdf",
+        data: include_bytes!("../fixtures/composite.pdf"),
+        title: "Composite Fonts",
+        password: None,
+        scanned: false,
+    },
+    Fixture {
+        name: "encrypted.pdf",
+        data: include_bytes!("../fixtures/encrypted.pdf"),
+        title: "Encrypted Document",
+        password: Some("selftest"),
+        scanned: false,
+    },
+    Fixture {
+        name: "scanned.pdf",
+        data: include_bytes!("../fixtures/scanned.pdf"),
+        title: "SCANNED",
+        password: None,
+        scanned: true,
+    },
+];
+
+/// How checking a fixture or a feature turned out.
+enum Outcome {
+    Passed,
+    Failed(String),
+    Skipped(String),
+}
+
+impl Outcome {
+    fn print(&self, name: &str) {
+        match self {
+            Self::Passed => println!("{}", tr!("selftest-passed", name = name)),
+            Self::Failed(reason) => println!(
+                "{}",
+                tr!("selftest-failed", name = name, reason = reason.as_str())
+            ),
+            Self::Skipped(reason) => println!(
+                "{}",
+                tr!("selftest-skipped", name = name, reason = reason.as_str())
+            ),
+        }
+    }
+}
+
+/// Extract the titles of the bundled fixtures and check the optional features. Exits with an
+/// error if any fixture did not get its title or a feature that was built in does not work.
+pub fn selftest(args: &SelftestArgs) {
+    let ocr = check_ocr();
+    let mut outcomes: Vec<_> = FIXTURES
+        .iter()
+        .map(|fixture| (fixture.name.to_string(), check_fixture(fixture, &ocr)))
+        .collect();
+    outcomes.push((tr!("selftest-feature-ocr"), ocr));
+    outcomes.push((tr!("selftest-feature-lookup"), check_lookup(args.offline)));
+
+    let mut failed = false;
+    for (name, outcome) in &outcomes {
+        outcome.print(name);
+        failed |= matches!(outcome, Outcome::Failed(_));
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Extract the title of `fixture`, with OCR if it is scanned and `ocr` works.
+fn check_fixture(fixture: &Fixture, ocr: &Outcome) -> Outcome {
+    let extractor = Extractor::new();
+    let extractor = match fixture.password {
+        Some(password) => extractor.password(password),
+        None => extractor,
+    };
+    let extractor = if fixture.scanned {
+        match ocr {
+            #[cfg(feature = "ocr")]
+            Outcome::Passed => extractor.ocr(true),
+            _ => return Outcome::Skipped(tr!("selftest-needs-ocr")),
+        }
+    } else {
+        extractor
+    };
+
+    let document = match extractor.extract_bytes(fixture.data) {
+        Ok(document) => document,
+        Err(err) => return Outcome::Failed(err.to_string()),
+    };
+    match document.title() {
+        // Recognized text might differ in case.
+        Some(title) if title.text.trim().eq_ignore_ascii_case(fixture.title) => Outcome::Passed,
+        Some(title) => Outcome::Failed(tr!(
+            "selftest-wrong-title",
+            title = format!("{:?}", title.text),
+            expected = format!("{:?}", fixture.title)
+        )),
+        None => Outcome::Failed(tr!(
+            "selftest-no-title",
+            expected = format!("{:?}", fixture.title)
+        )),
+    }
+}
+
+/// Check whether OCR was built in and the programs it runs are installed.
+fn check_ocr() -> Outcome {
+    #[cfg(feature = "ocr")]
+    {
+        let missing: Vec<_> = ["pdftoppm", "tesseract"]
+            .into_iter()
+            .filter(|program| {
+                Command::new(program)
+                    .arg("-v")
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status()
+                    .is_err()
+            })
+            .collect();
+        if missing.is_empty() {
+            Outcome::Passed
+        } else {
+            Outcome::Failed(tr!(
+                "selftest-missing-programs",
+                programs = missing.join(", ")
+            ))
+        }
+    }
+    #[cfg(not(feature = "ocr"))]
+    Outcome::Skipped(tr!("selftest-not-built", feature = "ocr"))
+}
+
+/// Check whether looking up identifiers was built in and Crossref can be reached, unless
+/// `offline`.
+fn check_lookup(offline: bool) -> Outcome {
+    #[cfg(feature = "lookup")]
+    {
+        if offline {
+            return Outcome::Skipped(tr!("selftest-offline"));
+        }
+        match crate::lookup::check() {
+            Ok(()) => Outcome::Passed,
+            Err(err) => Outcome::Failed(err.to_string()),
+        }
+    }
+    #[cfg(not(feature = "lookup"))]
+    {
+        let _ = offline;
+        Outcome::Skipped(tr!("selftest-not-built", feature = "lookup"))
+    }
+}
@@ -0,0 +1,407 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    io::{self, Read},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::AtomicBool,
+};
+
+use nomenclate::pdf::{self, Candidate, Metadata};
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{
+    audit::AuditLog,
+    cli::{RunArgs, ServeArgs},
+    counter::Counters,
+    documents_to_process,
+    i18n::tr,
+    journal::Journal,
+    lock_directories, rename_confident, rename_reviewed,
+    report::Report,
+    review::Failure,
+    uncertain, Processed,
+};
+
+/// The most bytes read from the body of a request.
+const MAX_BODY_LENGTH: u64 = 64 * 1024;
+
+/// The width and height in pixels that previews are scaled to fit.
+const PREVIEW_SIZE: &str = "800";
+
+/// A document waiting for its title to be confirmed.
+#[derive(Serialize)]
+struct Pending<'a> {
+    id: u64,
+    path: &'a Path,
+    confidence: f32,
+    /// The best title candidate, if any was found.
+    title: Option<&'a str>,
+}
+
+/// A document waiting for its title to be confirmed, with everything found out about it.
+#[derive(Serialize)]
+struct Details<'a> {
+    #[serde(flatten)]
+    pending: Pending<'a>,
+    candidates: &'a [Candidate],
+    metadata: &'a Metadata,
+}
+
+/// The body of a request to confirm the title of a document: either a title or the number of a
+/// candidate, starting at 1.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Confirmation {
+    title: Option<String>,
+    candidate: Option<NonZeroUsize>,
+}
+
+#[derive(Serialize)]
+struct Confirmed<'a> {
+    renamed: &'a Path,
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+/// Rename the documents in the given paths like a run with `--review-failures`, but wait for the
+/// titles of uncertain documents to be confirmed over HTTP instead of asking for them.
+///
+/// The API answers with JSON:
+///
+/// - `GET /api/pending` lists the documents waiting for review.
+/// - `GET /api/pending/<id>` returns the candidates and metadata of a document.
+/// - `GET /api/pending/<id>/preview` renders its first page as a PNG image with `pdftoppm`.
+/// - `POST /api/pending/<id>/confirm` renames it to `{"title": "..."}` or to a candidate with
+///   `{"candidate": 2}`.
+/// - `POST /api/scan` looks for new documents in the given paths.
+///
+/// `POST` requests must be sent with `Content-Type: application/json` and, if they come from a
+/// browser, from a page served at the same address, so other web pages can't rename documents.
+pub fn serve(args: &ServeArgs) {
+    let mut run = args.run.clone();
+    run.review_failures = true;
+    let Some(_locks) = lock_directories(&run) else {
+        return;
+    };
+    let server = match Server::http(args.listen) {
+        Ok(server) => server,
+        Err(err) => {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-serve",
+                    address = args.listen.to_string(),
+                    error = err.to_string()
+                )
+            );
+            return;
+        }
+    };
+
+    let mut review = Review::new(run, args.audit.audit_log());
+    review.scan();
+    log::info!("serving the review of documents on http://{}", args.listen);
+
+    for request in server.incoming_requests() {
+        review.handle(request);
+    }
+}
+
+/// The documents waiting for review and what is needed to rename them.
+struct Review {
+    args: RunArgs,
+    journal: Option<Journal>,
+    audit_log: Option<AuditLog>,
+    counters: Counters,
+    report: Report,
+    pending: BTreeMap<u64, Failure>,
+    next_id: u64,
+    /// Where reviewed documents were renamed to, so scanning again does not hold them for review
+    /// a second time.
+    reviewed: HashSet<PathBuf>,
+}
+
+impl Review {
+    fn new(args: RunArgs, audit_log: Option<AuditLog>) -> Self {
+        Self {
+            counters: Counters::new(args.counter, args.counter_start),
+            journal: Journal::user(),
+            audit_log,
+            report: Report::default(),
+            pending: BTreeMap::new(),
+            next_id: 1,
+            reviewed: HashSet::new(),
+            args,
+        }
+    }
+
+    /// Rename the documents with certain titles and hold the others for review.
+    fn scan(&mut self) {
+        let Some(paths) = documents_to_process(&self.args, self.journal.as_ref()) else {
+            return;
+        };
+        let paths: Vec<_> = paths
+            .into_iter()
+            .filter(|path| {
+                !self.reviewed.contains(path)
+                    && !self.pending.values().any(|failure| failure.path == *path)
+            })
+            .collect();
+        let (mut processed, failures) = rename_confident(
+            &self.args,
+            &paths,
+            &mut self.counters,
+            &mut self.report,
+            &AtomicBool::new(false),
+        );
+        processed.decisions.extend(failures.iter().map(uncertain));
+        processed.finish(
+            &self.args,
+            &paths,
+            self.journal.as_ref(),
+            self.audit_log.as_ref(),
+        );
+
+        for failure in failures {
+            log::info!("holding {} for review", failure.path.display());
+            self.pending.insert(self.next_id, failure);
+            self.next_id += 1;
+        }
+    }
+
+    fn handle(&mut self, mut request: Request) {
+        let url = request
+            .url()
+            .split('?')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let segments: Vec<_> = url
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        let id = segments.get(2).and_then(|id| id.parse::<u64>().ok());
+        log::debug!("{} {url}", request.method());
+
+        if *request.method() == Method::Post {
+            if let Err(response) = check_same_origin(&request) {
+                if let Err(err) = request.respond(response) {
+                    log::warn!("{}", tr!("warn-serve-respond", error = err.to_string()));
+                }
+                return;
+            }
+        }
+
+        let response = match (request.method(), segments.as_slice(), id) {
+            (Method::Get, ["api", "pending"], _) => self.list(),
+            (Method::Post, ["api", "scan"], _) => {
+                self.scan();
+                self.list()
+            }
+            (Method::Get, ["api", "pending", _], Some(id)) => self.details(id),
+            (Method::Get, ["api", "pending", _, "preview"], Some(id)) => self.preview(id),
+            (Method::Post, ["api", "pending", _, "confirm"], Some(id)) => {
+                let mut body = String::new();
+                match request
+                    .as_reader()
+                    .take(MAX_BODY_LENGTH)
+                    .read_to_string(&mut body)
+                {
+                    Ok(_) => self.confirm(id, &body),
+                    Err(err) => error(400, err.to_string()),
+                }
+            }
+            _ => error(404, "not found".to_string()),
+        };
+
+        if let Err(err) = request.respond(response) {
+            log::warn!("{}", tr!("warn-serve-respond", error = err.to_string()));
+        }
+    }
+
+    fn list(&self) -> Response<io::Cursor<Vec<u8>>> {
+        json(
+            200,
+            &self
+                .pending
+                .iter()
+                .map(|(id, failure)| pending(*id, failure))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn details(&self, id: u64) -> Response<io::Cursor<Vec<u8>>> {
+        let Some(failure) = self.pending.get(&id) else {
+            return unknown(id);
+        };
+
+        json(
+            200,
+            &Details {
+                pending: pending(id, failure),
+                candidates: &failure.candidates,
+                metadata: &failure.metadata,
+            },
+        )
+    }
+
+    fn preview(&self, id: u64) -> Response<io::Cursor<Vec<u8>>> {
+        let Some(failure) = self.pending.get(&id) else {
+            return unknown(id);
+        };
+
+        match render_first_page(&failure.path) {
+            Ok(image) => Response::from_data(image).with_header(content_type("image/png")),
+            Err(err) => error(503, tr!("serve-no-preview", error = err.to_string())),
+        }
+    }
+
+    fn confirm(&mut self, id: u64, body: &str) -> Response<io::Cursor<Vec<u8>>> {
+        let Some(failure) = self.pending.get(&id) else {
+            return unknown(id);
+        };
+        let confirmation: Confirmation = match serde_json::from_str(body) {
+            Ok(confirmation) => confirmation,
+            Err(err) => return error(400, err.to_string()),
+        };
+        let title = match (confirmation.title, confirmation.candidate) {
+            (Some(title), None) if !title.trim().is_empty() => title.trim().to_string(),
+            (None, Some(number)) => match failure.candidates.get(number.get() - 1) {
+                Some(candidate) => candidate.text.clone(),
+                None => return error(400, format!("there is no candidate {number}")),
+            },
+            _ => return error(400, "expected either a title or a candidate".to_string()),
+        };
+        let Some(failure) = self.pending.remove(&id) else {
+            return unknown(id);
+        };
+
+        let path = failure.path.clone();
+        let mut processed = Processed::new();
+        let renamed = rename_reviewed(
+            &self.args,
+            failure,
+            title,
+            &mut self.counters,
+            &mut processed,
+            &mut self.report,
+        );
+        processed.finish(
+            &self.args,
+            &[path],
+            self.journal.as_ref(),
+            self.audit_log.as_ref(),
+        );
+
+        let Some(renamed) = renamed else {
+            return error(500, "the document could not be renamed".to_string());
+        };
+        let response = json(200, &Confirmed { renamed: &renamed });
+        self.reviewed.insert(renamed);
+
+        response
+    }
+}
+
+/// Make sure a request that changes something comes from a script of the review itself or a
+/// client other than a browser, not from another web page the user happens to visit.
+///
+/// Web pages can send forms to any address, but not with a JSON body without asking first, which
+/// the server does not answer. Requests from browsers also say which page they come from.
+fn check_same_origin(request: &Request) -> Result<(), Response<io::Cursor<Vec<u8>>>> {
+    let header = |name: &'static str| {
+        request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv(name))
+            .map(|header| header.value.as_str())
+    };
+
+    let json = header("Content-Type").is_some_and(|value| {
+        value
+            .split(';')
+            .next()
+            .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case("application/json"))
+    });
+    if !json {
+        return Err(error(
+            415,
+            "requests that change something must be sent as application/json".to_string(),
+        ));
+    }
+
+    if let Some(origin) = header("Origin") {
+        let host = origin
+            .split_once("://")
+            .map_or(origin, |(_, host)| host)
+            .trim_end_matches('/');
+        if header("Host") != Some(host) {
+            return Err(error(
+                403,
+                format!("requests from {origin} are not allowed"),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn pending(id: u64, failure: &Failure) -> Pending<'_> {
+    Pending {
+        id,
+        path: &failure.path,
+        confidence: pdf::confidence(&failure.candidates),
+        title: failure
+            .candidates
+            .first()
+            .map(|candidate| candidate.text.as_str()),
+    }
+}
+
+/// Render the first page of the document at `path` as a PNG image.
+fn render_first_page(path: &Path) -> io::Result<Vec<u8>> {
+    let output = Command::new("pdftoppm")
+        .args(["-png", "-f", "1", "-l", "1", "-singlefile"])
+        .args(["-scale-to", PREVIEW_SIZE])
+        .arg(path)
+        .arg("-")
+        .output()?;
+
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+fn json(status: u16, body: &impl Serialize) -> Response<io::Cursor<Vec<u8>>> {
+    match serde_json::to_vec(body) {
+        Ok(body) => Response::from_data(body)
+            .with_status_code(status)
+            .with_header(content_type("application/json")),
+        Err(err) => error(500, err.to_string()),
+    }
+}
+
+fn error(status: u16, error: String) -> Response<io::Cursor<Vec<u8>>> {
+    let body = serde_json::to_vec(&ErrorBody { error }).unwrap_or_default();
+
+    Response::from_data(body)
+        .with_status_code(status)
+        .with_header(content_type("application/json"))
+}
+
+fn unknown(id: u64) -> Response<io::Cursor<Vec<u8>>> {
+    error(404, format!("no document {id} is waiting for review"))
+}
+
+fn content_type(value: &str) -> Header {
+    Header::from_bytes("Content-Type", value).expect("content types are valid header values")
+}
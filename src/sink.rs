@@ -0,0 +1,154 @@
+use std::{
+    fmt::Display,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::LazyLock,
+    time::Duration,
+};
+
+use thiserror::Error;
+use ureq::Agent;
+
+use crate::report::Record;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("could not serialize the outcome")]
+    Serialize(#[from] serde_json::Error),
+    #[error("could not write to {path}")]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not post to {url}")]
+    Request {
+        url: String,
+        #[source]
+        source: ureq::Error,
+    },
+}
+
+/// Where the outcome for every renamed document is sent.
+pub trait OutputSink {
+    /// Send the outcome for one document.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the outcome could not be sent.
+    fn send(&self, record: &Record) -> Result<(), Error>;
+}
+
+/// Print every outcome as a line of JSON, like `--format json`.
+pub struct Stdout;
+
+impl OutputSink for Stdout {
+    fn send(&self, record: &Record) -> Result<(), Error> {
+        println!("{}", serde_json::to_string(record)?);
+        Ok(())
+    }
+}
+
+/// Append every outcome as a line of JSON to a file.
+pub struct File<'a> {
+    pub path: &'a Path,
+}
+
+impl OutputSink for File<'_> {
+    fn send(&self, record: &Record) -> Result<(), Error> {
+        let write_error = |source| Error::Write {
+            path: self.path.to_path_buf(),
+            source,
+        };
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path)
+            .and_then(|mut file| file.write_all(&line))
+            .map_err(write_error)
+    }
+}
+
+/// How long to wait for a connection to a webhook, so an unreachable one fails quickly.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait for a webhook to answer, so a stuck one doesn't hold up the run.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+static AGENT: LazyLock<Agent> = LazyLock::new(|| {
+    Agent::config_builder()
+        .timeout_connect(Some(CONNECT_TIMEOUT))
+        .timeout_global(Some(TIMEOUT))
+        .build()
+        .into()
+});
+
+/// Post every outcome as JSON to a URL, one request per document.
+pub struct Webhook<'a> {
+    pub url: &'a str,
+}
+
+impl OutputSink for Webhook<'_> {
+    fn send(&self, record: &Record) -> Result<(), Error> {
+        AGENT
+            .post(self.url)
+            .send_json(record)
+            .map(drop)
+            .map_err(|source| Error::Request {
+                url: self.url.to_string(),
+                source,
+            })
+    }
+}
+
+/// An output given with `--output`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Output {
+    Stdout,
+    File(PathBuf),
+    Webhook(String),
+}
+
+impl Output {
+    pub fn sink(&self) -> Box<dyn OutputSink + '_> {
+        match self {
+            Self::Stdout => Box::new(Stdout),
+            Self::File(path) => Box::new(File { path }),
+            Self::Webhook(url) => Box::new(Webhook { url }),
+        }
+    }
+}
+
+impl Display for Output {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stdout => f.write_str("stdout"),
+            Self::File(path) => write!(f, "file:{}", path.display()),
+            Self::Webhook(url) => write!(f, "webhook:{url}"),
+        }
+    }
+}
+
+impl FromStr for Output {
+    type Err = String;
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        if output == "stdout" {
+            return Ok(Self::Stdout);
+        }
+        match output.split_once(':') {
+            Some(("file", path)) if !path.is_empty() => Ok(Self::File(path.into())),
+            Some(("webhook", url)) if url.starts_with("http://") || url.starts_with("https://") => {
+                Ok(Self::Webhook(url.to_string()))
+            }
+            _ => Err(format!(
+                "unknown output {output:?}, expected stdout, file:PATH or webhook:URL"
+            )),
+        }
+    }
+}
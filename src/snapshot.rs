@@ -0,0 +1,205 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use nomenclate::pdf::{self, Candidate, Metadata};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{cache::Extracted, cli::ReplayArgs, i18n::tr, report::Decoding};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("could not read {path}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not write {path}")]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("line {line} is not a snapshot")]
+    Parse {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("could not serialize the snapshot")]
+    Serialize(#[from] serde_json::Error),
+    #[error("the snapshot on line {line} has version {version} instead of {VERSION}")]
+    Version { line: usize, version: u32 },
+}
+
+/// The version of the snapshots written, increased whenever older snapshots could no longer be
+/// replayed.
+const VERSION: u32 = 1;
+
+/// Everything found in a document before its title was chosen, so the candidates can be scored
+/// again without the document, e.g. to reproduce a bug report.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    /// The version of nomenclate that took the snapshot.
+    nomenclate: String,
+    /// The file name of the document, without its directory.
+    document: String,
+    /// The options the document was parsed with.
+    options: pdf::Options,
+    /// The candidates as parsed, with their scores and the positioned pieces of text they were put
+    /// together from.
+    candidates: Vec<Candidate>,
+    metadata: Metadata,
+    /// How many glyphs could be decoded in each font.
+    decoding: Option<Decoding>,
+}
+
+/// Just the version of a snapshot.
+#[derive(Deserialize)]
+struct Versioned {
+    version: u32,
+}
+
+/// The file given with `--snapshot`, which gets a line of JSON for every document parsed.
+pub struct Snapshots {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl Snapshots {
+    /// Create the file at `path`, replacing the snapshots of an earlier run.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file could not be created.
+    pub fn create(path: &Path) -> Result<Self, Error> {
+        let file = File::create(path).map_err(|source| Error::Write {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Add the snapshot of what was `extracted` from the document at `path` with `options`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the snapshot could not be written.
+    pub fn add(
+        &self,
+        path: &Path,
+        options: &pdf::Options,
+        extracted: &Extracted,
+    ) -> Result<(), Error> {
+        let snapshot = Snapshot {
+            version: VERSION,
+            nomenclate: env!("CARGO_PKG_VERSION").to_string(),
+            document: path
+                .file_name()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy()
+                .into_owned(),
+            options: options.clone(),
+            candidates: extracted.candidates.clone(),
+            metadata: extracted.metadata.clone(),
+            decoding: extracted.decoding.clone(),
+        };
+        let mut line = serde_json::to_vec(&snapshot)?;
+        line.push(b'\n');
+
+        // Documents are parsed on several threads, whose lines must not be interleaved.
+        let mut file = self
+            .file
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        file.write_all(&line).map_err(|source| Error::Write {
+            path: self.path.clone(),
+            source,
+        })
+    }
+}
+
+/// Score the candidates of every document in the snapshot file again and print them in their new
+/// order, with the title that would be chosen.
+pub fn replay(args: &ReplayArgs) {
+    let snapshots = match read(&args.snapshot) {
+        Ok(snapshots) => snapshots,
+        Err(err) => {
+            log::error!(
+                "{}",
+                tr!(
+                    "error-replay",
+                    path = args.snapshot.display().to_string(),
+                    error = err.to_string()
+                )
+            );
+            std::process::exit(1);
+        }
+    };
+
+    for mut snapshot in snapshots {
+        let profile = args.profile.or(snapshot.options.profile);
+        pdf::rank(&mut snapshot.candidates, profile);
+
+        match snapshot.candidates.first() {
+            Some(title) => println!(
+                "{}",
+                tr!(
+                    "replay-title",
+                    document = snapshot.document.as_str(),
+                    title = title.text.as_str(),
+                    confidence = format!("{:.2}", pdf::confidence(&snapshot.candidates))
+                )
+            ),
+            None => println!(
+                "{}",
+                tr!("replay-no-candidate", document = snapshot.document.as_str())
+            ),
+        }
+        for (index, candidate) in snapshot.candidates.iter().enumerate() {
+            println!("  {}. {candidate}", index + 1);
+        }
+    }
+}
+
+/// Read the snapshots in the file at `path`.
+fn read(path: &Path) -> Result<Vec<Snapshot>, Error> {
+    let read_error = |source| Error::Read {
+        path: path.to_path_buf(),
+        source,
+    };
+    let file = File::open(path).map_err(read_error)?;
+
+    let mut snapshots = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(read_error)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parse_error = |source| Error::Parse {
+            line: index + 1,
+            source,
+        };
+        // The version is checked first, as snapshots of other versions may not parse.
+        let Versioned { version } = serde_json::from_str(&line).map_err(parse_error)?;
+        if version != VERSION {
+            return Err(Error::Version {
+                line: index + 1,
+                version,
+            });
+        }
+        snapshots.push(serde_json::from_str(&line).map_err(parse_error)?);
+    }
+
+    Ok(snapshots)
+}
@@ -0,0 +1,129 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+/// How many bytes at the start of a file are looked at to determine its kind.
+const SNIFF_LENGTH: u64 = 1024;
+/// How many bytes of an HTML document are searched for its title.
+const HTML_TITLE_SEARCH_LENGTH: u64 = 64 * 1024;
+
+/// The kind of a file, as determined from its content.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Pdf,
+    /// An HTML page, typically an error or login page of a paywall saved with a `.pdf` extension.
+    Html,
+    Empty,
+    Unknown,
+}
+
+/// Determine the kind of the file at `path` from its magic bytes instead of its extension.
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be read.
+pub fn sniff(path: &Path) -> io::Result<Kind> {
+    let head = read_head(path, SNIFF_LENGTH)?;
+
+    if head.is_empty() {
+        return Ok(Kind::Empty);
+    }
+    // the header doesn't have to be at the very start of the file
+    if head.windows(5).any(|window| window == b"%PDF-") {
+        return Ok(Kind::Pdf);
+    }
+
+    let text = String::from_utf8_lossy(&head).to_lowercase();
+    let text = text.trim_start_matches(['\u{feff}', ' ', '\t', '\r', '\n']);
+    if text.starts_with("<!doctype html")
+        || text.starts_with("<html")
+        || text.contains("<html")
+        || text.contains("<head")
+    {
+        return Ok(Kind::Html);
+    }
+
+    Ok(Kind::Unknown)
+}
+
+/// Extract the content of the `<title>` element of the HTML document at `path`.
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be read.
+pub fn html_title(path: &Path) -> io::Result<Option<String>> {
+    let head = read_head(path, HTML_TITLE_SEARCH_LENGTH)?;
+    let html = String::from_utf8_lossy(&head);
+    // lowercasing ASCII keeps byte offsets intact
+    let lowercase = html.to_ascii_lowercase();
+
+    let Some(start) = lowercase
+        .find("<title")
+        .and_then(|start| Some(start + lowercase[start..].find('>')? + 1))
+    else {
+        return Ok(None);
+    };
+    let Some(end) = lowercase[start..].find("</title").map(|end| start + end) else {
+        return Ok(None);
+    };
+    let title = decode_entities(&html[start..end])
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Ok((!title.is_empty()).then_some(title))
+}
+
+fn read_head(path: &Path, length: u64) -> io::Result<Vec<u8>> {
+    let mut head = Vec::new();
+    File::open(path)?.take(length).read_to_end(&mut head)?;
+
+    Ok(head)
+}
+
+/// Decode the most common named and all numeric HTML character references.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| Some((decode_entity(&rest[1..end])?, end)));
+        if let Some((c, end)) = entity {
+            decoded.push(c);
+            rest = &rest[end + 1..];
+        } else {
+            decoded.push('&');
+            rest = &rest[1..];
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let number = entity.strip_prefix('#')?;
+            let code = match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => number.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::{fs, io, path::Path};
+
+use clap::ValueEnum;
+
+/// How the sort key of a title is emitted.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Use the sort key as the file name, so that the sort relevant part of the title comes first.
+    Prefix,
+    /// Write the sort key to a `.sortkey` file next to the renamed document.
+    Sidecar,
+}
+
+/// Articles that are ignored when sorting, by language.
+const ARTICLES: &[(&str, &[&str])] = &[
+    ("en", &["the", "a", "an"]),
+    ("de", &["der", "die", "das", "ein", "eine"]),
+    ("fr", &["le", "la", "les", "l'", "un", "une"]),
+    ("es", &["el", "la", "los", "las", "un", "una"]),
+    (
+        "it",
+        &["il", "lo", "la", "i", "gli", "le", "l'", "un", "uno", "una"],
+    ),
+    ("nl", &["de", "het", "een"]),
+];
+
+/// Compute a key for `title` that sorts sensibly in file managers.
+///
+/// A leading article of the given `language` is moved to the end (`The Art of Computer
+/// Programming` becomes `Art of Computer Programming, The`) and the result is transliterated to
+/// ASCII so it collates the same in every locale.
+pub fn sort_key(title: &str, language: &str) -> String {
+    let title = title.trim();
+    let articles = ARTICLES
+        .iter()
+        .find(|(code, _)| language.eq_ignore_ascii_case(code))
+        .map_or(&[][..], |(_, articles)| articles);
+    let key = articles
+        .iter()
+        .find_map(|article| {
+            let (prefix, rest) = title.split_at_checked(article.len())?;
+            if !prefix.eq_ignore_ascii_case(article) {
+                return None;
+            }
+            let rest = if article.ends_with('\'') {
+                rest
+            } else {
+                rest.strip_prefix(char::is_whitespace)?
+            };
+            let rest = rest.trim_start();
+
+            (!rest.is_empty()).then(|| format!("{rest}, {prefix}"))
+        })
+        .unwrap_or_else(|| title.to_string());
+
+    deunicode::deunicode(&key)
+}
+
+/// Write the sort key of the document at `path` to a sidecar file next to it.
+///
+/// # Errors
+///
+/// This function will return an error if the sidecar file could not be written.
+pub fn write_sidecar(path: &Path, key: &str) -> io::Result<()> {
+    let mut sidecar = path.as_os_str().to_owned();
+    sidecar.push(".sortkey");
+
+    fs::write(sidecar, format!("{key}\n"))
+}
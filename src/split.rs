@@ -0,0 +1,207 @@
+use std::{
+    collections::HashSet,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use lopdf::{Document, Object};
+use thiserror::Error;
+
+use crate::{i18n::tr, rename};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("could not load document: {path}")]
+    Load {
+        path: PathBuf,
+        #[source]
+        source: lopdf::Error,
+    },
+    #[error("document has no outline entries at level {0}")]
+    NoChapters(usize),
+    #[error("could not write chapter: {path}")]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: lopdf::Error,
+    },
+}
+
+/// A chapter of a book, as found in its outline.
+pub struct Chapter {
+    pub title: String,
+    /// The one-based page numbers of the chapter.
+    pub pages: RangeInclusive<u32>,
+}
+
+/// Find the chapters of a document from the outline entries at the given `level` (starting at 1).
+///
+/// A chapter spans all pages until the next one starts. Pages before the first chapter are not
+/// part of any chapter.
+///
+/// # Errors
+///
+/// This function will return an error if the document has no outline entries at `level`.
+pub fn chapters(document: &Document, level: usize) -> Result<Vec<Chapter>, Error> {
+    let last_page = document.get_pages().keys().copied().max().unwrap_or(0);
+    let mut starts: Vec<(u32, String)> = document
+        .get_toc()
+        .map(|toc| toc.toc)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|entry| entry.level == level)
+        .filter_map(|entry| Some((u32::try_from(entry.page).ok()?, entry.title)))
+        .collect();
+    starts.sort_by_key(|(page, _)| *page);
+    starts.dedup_by_key(|(page, _)| *page);
+
+    if starts.is_empty() {
+        return Err(Error::NoChapters(level));
+    }
+
+    Ok(starts
+        .iter()
+        .enumerate()
+        .map(|(index, (start, title))| {
+            let end = starts
+                .get(index + 1)
+                .map_or(last_page, |(next, _)| next.saturating_sub(1).max(*start));
+
+            Chapter {
+                title: title.trim().to_string(),
+                pages: *start..=end,
+            }
+        })
+        .collect())
+}
+
+/// Split the book at `path` into one document per chapter in `output`.
+///
+/// The chapter documents are named after the chapter titles, prefixed with their number to keep
+/// them in order. Existing files are never overwritten. If `dry_run` is set, nothing is written.
+///
+/// Returns the paths of the chapter documents.
+///
+/// # Errors
+///
+/// This function will return an error if the book could not be loaded, has no chapters at `level`
+/// or a chapter could not be written.
+pub fn split(
+    path: &Path,
+    output: &Path,
+    level: usize,
+    colon: rename::Colon,
+    separator: rename::Separator,
+    dry_run: bool,
+) -> Result<Vec<PathBuf>, Error> {
+    let document = Document::load(path).map_err(|source| Error::Load {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let chapters = chapters(&document, level)?;
+    let width = chapters.len().to_string().len().max(2);
+    let mut written = Vec::new();
+
+    for (index, chapter) in chapters.iter().enumerate() {
+        let name = chapter_name(index, width, &chapter.title, colon);
+        let Some(target) = rename::target(&output.join("chapter.pdf"), &name, separator) else {
+            log::warn!(
+                "{}",
+                tr!(
+                    "split-invalid-title",
+                    title = format!("{:?}", chapter.title)
+                )
+            );
+            continue;
+        };
+        if target.exists() {
+            log::warn!(
+                "{}",
+                tr!(
+                    "split-exists",
+                    title = format!("{:?}", chapter.title),
+                    path = target.display().to_string()
+                )
+            );
+            continue;
+        }
+
+        log::info!(
+            "chapter {:?}: pages {} to {}",
+            chapter.title,
+            chapter.pages.start(),
+            chapter.pages.end()
+        );
+        if !dry_run {
+            write_chapter(&document, &chapter.pages, &target)?;
+        }
+        written.push(target);
+    }
+
+    Ok(written)
+}
+
+/// The name of the chapter with the zero-based `index` and `title`, with its number padded to
+/// `width` digits.
+///
+/// Slashes in chapter titles are removed, since [`rename::target`] would read them as
+/// subdirectories.
+fn chapter_name(index: usize, width: usize, title: &str, colon: rename::Colon) -> String {
+    format!(
+        "{:0width$} - {}",
+        index + 1,
+        colon.apply(&title.replace('/', ""))
+    )
+}
+
+fn write_chapter(
+    document: &Document,
+    pages: &RangeInclusive<u32>,
+    path: &Path,
+) -> Result<(), Error> {
+    let mut chapter = document.clone();
+    let other_pages: Vec<u32> = chapter
+        .get_pages()
+        .into_keys()
+        .filter(|page| !pages.contains(page))
+        .collect();
+    chapter.delete_pages(&other_pages);
+
+    // `delete_pages` leaves references to the deleted pages in the page tree
+    let existing: HashSet<_> = chapter.objects.keys().copied().collect();
+    for object in chapter.objects.values_mut() {
+        if let Ok(Object::Array(kids)) = object.as_dict_mut().and_then(|dict| dict.get_mut(b"Kids"))
+        {
+            kids.retain(|kid| kid.as_reference().is_ok_and(|id| existing.contains(&id)));
+        }
+    }
+    // the outline points to pages of other chapters
+    if let Ok(catalog) = chapter.catalog_mut() {
+        catalog.remove(b"Outlines");
+    }
+
+    chapter.prune_objects();
+    chapter.compress();
+    chapter
+        .save(path)
+        .map(|_| ())
+        .map_err(|source| Error::Write {
+            path: path.to_path_buf(),
+            source: source.into(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slashes_in_chapter_titles_do_not_make_subdirectories() {
+        let name = chapter_name(2, 2, "Input/Output", rename::Colon::Remove);
+        assert_eq!(name, "03 - InputOutput");
+
+        let output = Path::new("chapters");
+        let target = rename::target(&output.join("chapter.pdf"), &name, rename::Separator::Space);
+        assert_eq!(target, Some(output.join("03 - InputOutput.pdf")));
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use nomenclate::pdf::{self, Coverage, Provenance};
+
+use crate::{i18n::tr, overrides::Overrides};
+
+/// How many of the documents with the lowest glyph coverage are listed.
+const WORST_COVERAGE_COUNT: usize = 10;
+
+/// A document whose title candidates are nearly tied.
+struct Ambiguous {
+    path: PathBuf,
+    /// See [`pdf::margin`].
+    margin: f32,
+    first: String,
+    second: String,
+}
+
+/// Statistics about a library of documents.
+#[derive(Default)]
+pub struct Statistics {
+    documents: usize,
+    /// Documents that could not be loaded.
+    failed: usize,
+    /// Documents without a title.
+    untitled: usize,
+    sources: BTreeMap<Provenance, usize>,
+    /// Fonts whose encoding is missing or unsupported, with the number of documents using them.
+    failed_fonts: BTreeMap<String, usize>,
+    coverage: Coverage,
+    /// Documents and the fraction of their glyphs that could not be mapped to text.
+    unmapped: Vec<(PathBuf, f64)>,
+    /// The margin below which the candidates of a document are considered nearly tied.
+    ambiguity_threshold: f32,
+    ambiguous: Vec<Ambiguous>,
+}
+
+impl Statistics {
+    /// Analyze the documents at `paths`, considering the candidates of a document nearly tied if
+    /// the margin between them is below `ambiguity_threshold`.
+    pub fn collect(
+        paths: &[PathBuf],
+        overrides: &Overrides,
+        options: &pdf::Options,
+        ambiguity_threshold: f32,
+    ) -> Self {
+        let mut statistics = Self {
+            ambiguity_threshold,
+            ..Self::default()
+        };
+
+        for path in paths {
+            statistics.add(path, overrides, options);
+        }
+        statistics.unmapped.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        statistics.unmapped.truncate(WORST_COVERAGE_COUNT);
+        statistics
+            .ambiguous
+            .sort_by(|a, b| a.margin.total_cmp(&b.margin));
+
+        statistics
+    }
+
+    fn add(&mut self, path: &Path, overrides: &Overrides, options: &pdf::Options) {
+        self.documents += 1;
+
+        let parsed = match pdf::parse_pdf(path, options, &mut pdf::Callbacks::default()) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                log::warn!(
+                    "{}",
+                    tr!(
+                        "error-document",
+                        path = path.display().to_string(),
+                        error = err.to_string()
+                    )
+                );
+                self.failed += 1;
+                return;
+            }
+        };
+
+        // The title is taken from the same source as when renaming: an override, or else the
+        // most trusted source of the metadata, like recognized text or the XMP metadata.
+        let source = if matches!(overrides.get(path), Ok(Some(_))) {
+            Some(Provenance::Override)
+        } else {
+            parsed.metadata.title.first().map(|title| title.provenance)
+        };
+        match source {
+            Some(source) => *self.sources.entry(source).or_default() += 1,
+            None => self.untitled += 1,
+        }
+
+        if source == Some(Provenance::Layout) {
+            if let (Some(margin), [first, second, ..]) = (
+                pdf::margin(&parsed.candidates),
+                parsed.candidates.as_slice(),
+            ) {
+                if margin < self.ambiguity_threshold {
+                    self.ambiguous.push(Ambiguous {
+                        path: path.to_path_buf(),
+                        margin,
+                        first: first.text.clone(),
+                        second: second.text.clone(),
+                    });
+                }
+            }
+        }
+
+        for font in parsed.statistics.failed_fonts {
+            *self.failed_fonts.entry(font).or_default() += 1;
+        }
+
+        let coverage = parsed.statistics.coverage;
+        self.coverage += coverage;
+        if coverage.unmapped > 0 {
+            self.unmapped.push((
+                path.to_path_buf(),
+                ratio(coverage.unmapped, coverage.glyphs),
+            ));
+        }
+    }
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}",
+            tr!(
+                "stats-documents",
+                documents = self.documents,
+                failed = self.failed,
+                untitled = self.untitled
+            )
+        )?;
+
+        writeln!(f, "{}", tr!("stats-sources"))?;
+        for (source, count) in &self.sources {
+            writeln!(f, "  {source}: {count}")?;
+        }
+
+        writeln!(
+            f,
+            "{}",
+            tr!("stats-failed-fonts", count = self.failed_fonts.len())
+        )?;
+        let mut failed_fonts: Vec<_> = self.failed_fonts.iter().collect();
+        failed_fonts.sort_by(|(_, a), (_, b)| b.cmp(a));
+        for (font, count) in failed_fonts {
+            writeln!(
+                f,
+                "  {font}: {}",
+                tr!("stats-font-documents", count = *count)
+            )?;
+        }
+
+        writeln!(
+            f,
+            "{}",
+            tr!(
+                "stats-unmapped",
+                unmapped = self.coverage.unmapped,
+                glyphs = self.coverage.glyphs,
+                percent = format!(
+                    "{:.2}",
+                    100. * ratio(self.coverage.unmapped, self.coverage.glyphs)
+                )
+            )
+        )?;
+        for (path, unmapped) in &self.unmapped {
+            writeln!(f, "  {}: {:.2}%", path.display(), 100. * unmapped)?;
+        }
+
+        writeln!(
+            f,
+            "{}",
+            tr!(
+                "stats-ambiguous",
+                count = self.ambiguous.len(),
+                percent = format!("{:.0}", 100. * self.ambiguity_threshold)
+            )
+        )?;
+        for ambiguous in &self.ambiguous {
+            writeln!(
+                f,
+                "  {}: {:.1}% {:?} / {:?}",
+                ambiguous.path.display(),
+                100. * ambiguous.margin,
+                ambiguous.first,
+                ambiguous.second
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn ratio(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.
+    } else {
+        part as f64 / total as f64
+    }
+}
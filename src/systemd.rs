@@ -0,0 +1,71 @@
+use std::{env, ffi::OsStr, io, process, time::Duration};
+
+/// Tell the service manager about the state of the service, e.g. `READY=1`, if it asked for
+/// notifications by setting `$NOTIFY_SOCKET`. See `sd_notify(3)`.
+///
+/// # Errors
+///
+/// This function will return an error if the notification could not be sent.
+pub fn notify(state: &str) -> io::Result<()> {
+    let Some(socket) = env::var_os("NOTIFY_SOCKET") else {
+        return Ok(());
+    };
+
+    send(&socket, state)
+}
+
+/// How often the service manager expects `WATCHDOG=1`, if it enabled the watchdog for this
+/// process with `$WATCHDOG_USEC`.
+pub fn watchdog_interval() -> Option<Duration> {
+    let is_for_us = env::var("WATCHDOG_PID")
+        .ok()
+        .and_then(|pid| pid.parse().ok())
+        .is_none_or(|pid: u32| pid == process::id());
+
+    env::var("WATCHDOG_USEC")
+        .ok()
+        .and_then(|usec| usec.parse().ok())
+        .filter(|usec| *usec > 0 && is_for_us)
+        .map(Duration::from_micros)
+}
+
+#[cfg(unix)]
+fn send(socket: &OsStr, state: &str) -> io::Result<()> {
+    use std::os::unix::{ffi::OsStrExt, net::UnixDatagram};
+
+    let datagram = UnixDatagram::unbound()?;
+    // Sockets starting with `@` are in the abstract namespace.
+    if let Some(name) = socket.as_bytes().strip_prefix(b"@") {
+        return send_abstract(&datagram, name, state);
+    }
+
+    datagram.send_to(state.as_bytes(), socket).map(|_| ())
+}
+
+#[cfg(target_os = "linux")]
+fn send_abstract(
+    datagram: &std::os::unix::net::UnixDatagram,
+    name: &[u8],
+    state: &str,
+) -> io::Result<()> {
+    use std::os::{linux::net::SocketAddrExt, unix::net::SocketAddr};
+
+    let address = SocketAddr::from_abstract_name(name)?;
+    datagram
+        .send_to_addr(state.as_bytes(), &address)
+        .map(|_| ())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn send_abstract(
+    _datagram: &std::os::unix::net::UnixDatagram,
+    _name: &[u8],
+    _state: &str,
+) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(not(unix))]
+fn send(_socket: &OsStr, _state: &str) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
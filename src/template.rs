@@ -0,0 +1,858 @@
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    iter::Peekable,
+    str::{CharIndices, FromStr},
+};
+
+use nomenclate::pdf::{self, Metadata, Profile, Provenance};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum Error {
+    #[error("unclosed `{{` at position {0}")]
+    Unclosed(usize),
+    #[error("unmatched `}}` at position {0}, write `}}}}` for a literal one")]
+    Unmatched(usize),
+    #[error("unknown field {0:?}")]
+    UnknownField(String),
+    #[error("invalid format {format:?} for field {field:?}, expected {expected}")]
+    InvalidFormat {
+        field: String,
+        format: String,
+        expected: &'static str,
+    },
+    #[error("field {0:?} cannot be formatted")]
+    UnexpectedFormat(String),
+    #[error("unknown filter {0:?}, expected `default:<text>`, `upper`, `lower` or `max:<length>`")]
+    UnknownFilter(String),
+    #[error(
+        "invalid condition {0:?}, expected a field compared to a number like `confidence<0.6`"
+    )]
+    InvalidCondition(String),
+}
+
+/// A field of a document that can be used in a template.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Title,
+    /// The title in another language, for documents with a title in two languages.
+    TitleAlt,
+    /// The first author from the metadata of the document.
+    Author,
+    /// The family names of the authors from the metadata, `Doe & Roe` or `Doe et al.` if there are
+    /// more than `max`.
+    Authors {
+        max: usize,
+    },
+    /// The year the document was created in, from its metadata.
+    Year,
+    /// A number counting up with every document, padded with zeros to `width` digits.
+    Counter {
+        width: usize,
+    },
+    /// The name of the periodical the document is an issue of, from its masthead.
+    Publication,
+    /// The date of the issue of a periodical, `YYYY-MM` or `YYYY-MM-DD`.
+    IssueDate,
+    /// The identifier of a standard, `ISO 9001-2015`, with colons and slashes replaced as they are
+    /// not allowed in file names everywhere.
+    StandardId,
+    /// The number of the case of a court filing, with colons and slashes replaced like in
+    /// [`Field::StandardId`].
+    CaseNumber,
+    /// The type of a court filing, like `Motion to Dismiss`.
+    DocumentType,
+    /// The date of the document as `YYYY-MM-DD`: the date court filings were filed or the date of
+    /// the purchase on receipts, or else the date the document was created from its metadata.
+    Date,
+    /// The bank, utility or other institution that sent a statement.
+    Institution,
+    /// The period a statement covers, `YYYY-MM` or `YYYY-MM-DD - YYYY-MM-DD`.
+    Period,
+    /// The last four digits of the number of the account of a statement.
+    Account,
+    /// The maker of the component a datasheet describes.
+    Manufacturer,
+    /// The number of the part a datasheet describes.
+    PartNumber,
+    /// The shop or business that issued a receipt.
+    Vendor,
+    /// The total of a receipt, like `23.45`.
+    Amount,
+    /// The code of the currency of the total of a receipt, like `EUR`.
+    Currency,
+    /// The composer of a piece of sheet music.
+    Composer,
+    /// The title of a piece of sheet music.
+    Piece,
+    /// The kind of document the heuristics were tuned for, the profile given with `--profile` or
+    /// else the type the document was recognized as, like `paper`.
+    Preset,
+    /// The confidence (between 0 and 1) in a title found in the layout, like `0.85`.
+    Confidence,
+}
+
+impl FromStr for Field {
+    type Err = Error;
+
+    fn from_str(field: &str) -> Result<Self, Self::Err> {
+        let (name, format) = field
+            .split_once(':')
+            .map_or((field, None), |(name, format)| (name, Some(format)));
+        let invalid_format = |expected| Error::InvalidFormat {
+            field: name.to_string(),
+            format: format.unwrap_or_default().to_string(),
+            expected,
+        };
+
+        match (name, format) {
+            ("title", None) => Ok(Self::Title),
+            ("title_alt", None) => Ok(Self::TitleAlt),
+            ("author", None) => Ok(Self::Author),
+            ("authors", None) => Ok(Self::Authors {
+                max: DEFAULT_AUTHORS,
+            }),
+            ("authors", Some(format)) => format
+                .parse()
+                .ok()
+                .filter(|max| *max > 0)
+                .map(|max| Self::Authors { max })
+                .ok_or_else(|| invalid_format("a number of authors like `3`")),
+            ("year", None) => Ok(Self::Year),
+            ("n", None) => Ok(Self::Counter { width: 0 }),
+            // Counters are always padded with zeros, so the leading zero is optional.
+            ("n", Some(format)) => format
+                .strip_prefix('0')
+                .filter(|width| !width.is_empty())
+                .unwrap_or(format)
+                .parse()
+                .map(|width| Self::Counter { width })
+                .map_err(|_| invalid_format("a width like `03` or `3`")),
+            ("publication", None) => Ok(Self::Publication),
+            ("issue_date", None) => Ok(Self::IssueDate),
+            ("standard_id", None) => Ok(Self::StandardId),
+            ("case_no", None) => Ok(Self::CaseNumber),
+            ("doc_type", None) => Ok(Self::DocumentType),
+            ("date", None) => Ok(Self::Date),
+            ("institution", None) => Ok(Self::Institution),
+            ("period", None) => Ok(Self::Period),
+            ("account", None) => Ok(Self::Account),
+            ("mfr", None) => Ok(Self::Manufacturer),
+            ("part", None) => Ok(Self::PartNumber),
+            ("vendor", None) => Ok(Self::Vendor),
+            ("amount", None) => Ok(Self::Amount),
+            ("currency", None) => Ok(Self::Currency),
+            ("composer", None) => Ok(Self::Composer),
+            ("piece", None) => Ok(Self::Piece),
+            ("preset", None) => Ok(Self::Preset),
+            ("confidence", None) => Ok(Self::Confidence),
+            (_, Some(_)) if name.parse::<Self>().is_ok() => {
+                Err(Error::UnexpectedFormat(name.to_string()))
+            }
+            _ => Err(Error::UnknownField(name.to_string())),
+        }
+    }
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Title => write!(f, "title"),
+            Self::TitleAlt => write!(f, "title_alt"),
+            Self::Author => write!(f, "author"),
+            Self::Authors {
+                max: DEFAULT_AUTHORS,
+            } => write!(f, "authors"),
+            Self::Authors { max } => write!(f, "authors:{max}"),
+            Self::Year => write!(f, "year"),
+            Self::Counter { width: 0 } => write!(f, "n"),
+            Self::Counter { width } => write!(f, "n:0{width}"),
+            Self::Publication => write!(f, "publication"),
+            Self::IssueDate => write!(f, "issue_date"),
+            Self::StandardId => write!(f, "standard_id"),
+            Self::CaseNumber => write!(f, "case_no"),
+            Self::DocumentType => write!(f, "doc_type"),
+            Self::Date => write!(f, "date"),
+            Self::Institution => write!(f, "institution"),
+            Self::Period => write!(f, "period"),
+            Self::Account => write!(f, "account"),
+            Self::Manufacturer => write!(f, "mfr"),
+            Self::PartNumber => write!(f, "part"),
+            Self::Vendor => write!(f, "vendor"),
+            Self::Amount => write!(f, "amount"),
+            Self::Currency => write!(f, "currency"),
+            Self::Composer => write!(f, "composer"),
+            Self::Piece => write!(f, "piece"),
+            Self::Preset => write!(f, "preset"),
+            Self::Confidence => write!(f, "confidence"),
+        }
+    }
+}
+
+impl Field {
+    /// Whether only some documents have a value for the field.
+    pub const fn is_optional(self) -> bool {
+        matches!(
+            self,
+            Self::TitleAlt
+                | Self::Author
+                | Self::Authors { .. }
+                | Self::Year
+                | Self::Publication
+                | Self::IssueDate
+                | Self::StandardId
+                | Self::CaseNumber
+                | Self::DocumentType
+                | Self::Date
+                | Self::Institution
+                | Self::Period
+                | Self::Account
+                | Self::Manufacturer
+                | Self::PartNumber
+                | Self::Vendor
+                | Self::Amount
+                | Self::Currency
+                | Self::Composer
+                | Self::Piece
+                | Self::Preset
+                | Self::Confidence
+        )
+    }
+}
+
+/// A transformation of the value of a field, written after a `|`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Filter {
+    /// Use the text if the field is missing, `default:unknown`.
+    Default(String),
+    Upper,
+    Lower,
+    /// Shorten the value to at most this many characters, cutting it at a word boundary if
+    /// possible, `max:40`.
+    Max(usize),
+}
+
+impl FromStr for Filter {
+    type Err = Error;
+
+    fn from_str(filter: &str) -> Result<Self, Self::Err> {
+        match filter.split_once(':') {
+            Some(("default", text)) => Ok(Self::Default(text.to_string())),
+            Some(("max", length)) => length
+                .parse()
+                .ok()
+                .filter(|length| *length > 0)
+                .map(Self::Max)
+                .ok_or_else(|| Error::UnknownFilter(filter.to_string())),
+            None if filter == "upper" => Ok(Self::Upper),
+            None if filter == "lower" => Ok(Self::Lower),
+            _ => Err(Error::UnknownFilter(filter.to_string())),
+        }
+    }
+}
+
+impl Filter {
+    fn apply<'a>(&self, value: Option<Cow<'a, str>>) -> Option<Cow<'a, str>> {
+        match self {
+            Self::Default(text) => value.or_else(|| Some(text.clone().into())),
+            Self::Upper => value.map(|value| value.to_uppercase().into()),
+            Self::Lower => value.map(|value| value.to_lowercase().into()),
+            Self::Max(length) => value.map(|value| shorten(value, *length)),
+        }
+    }
+}
+
+/// Shorten `value` to at most `length` characters, at the end of a word if there is one in the
+/// second half.
+fn shorten(value: Cow<'_, str>, length: usize) -> Cow<'_, str> {
+    let Some((end, _)) = value.char_indices().nth(length) else {
+        return value;
+    };
+    let cut = &value[..end];
+    let cut = match cut.rfind(char::is_whitespace) {
+        Some(space) if cut[..space].chars().count() >= length / 2 => &cut[..space],
+        _ => cut,
+    };
+
+    cut.trim_end_matches(|c: char| c.is_whitespace() || c.is_ascii_punctuation())
+        .to_string()
+        .into()
+}
+
+/// When the parts of a conditional are rendered.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Condition {
+    /// The field is present, `{author?…}`.
+    Present(Field),
+    /// The field is a number below this one, `{confidence<0.6?…}`.
+    Below(Field, f64),
+    /// The field is a number above this one, `{year>1999?…}`.
+    Above(Field, f64),
+}
+
+impl FromStr for Condition {
+    type Err = Error;
+
+    fn from_str(condition: &str) -> Result<Self, Self::Err> {
+        let Some(position) = condition.find(['<', '>']) else {
+            return Ok(Self::Present(condition.trim().parse()?));
+        };
+        let field = condition[..position].trim().parse()?;
+        let number = condition[position + 1..]
+            .trim()
+            .parse()
+            .map_err(|_| Error::InvalidCondition(condition.to_string()))?;
+
+        Ok(if condition[position..].starts_with('<') {
+            Self::Below(field, number)
+        } else {
+            Self::Above(field, number)
+        })
+    }
+}
+
+impl Condition {
+    const fn field(self) -> Field {
+        match self {
+            Self::Present(field) | Self::Below(field, _) | Self::Above(field, _) => field,
+        }
+    }
+
+    fn holds(self, values: &Values) -> bool {
+        let number = || {
+            values
+                .get(self.field())
+                .and_then(|value| value.parse::<f64>().ok())
+        };
+
+        match self {
+            Self::Present(field) => values.get(field).is_some(),
+            Self::Below(_, threshold) => number().is_some_and(|number| number < threshold),
+            Self::Above(_, threshold) => number().is_some_and(|number| number > threshold),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Literal(String),
+    /// A field, `{year|default:unknown}`.
+    Field(Field, Vec<Filter>),
+    /// Parts only rendered if the condition holds, like the field being present in
+    /// `{author?{author} - }`.
+    Conditional(Condition, Vec<Self>),
+}
+
+/// A template for file names like `{title} ({title_alt})`, `{year} - {authors} - {title}` or
+/// `{n:03} - {title}`, with fields in braces.
+///
+/// Fields can be followed by filters (`{year|default:unknown}`, `{title|max:60}`) and parts of the
+/// template can be left out if a field is missing (`{author?{author} - }{title}`) or a number is
+/// out of range (`{confidence<0.6?_review/}{title}`). Slashes in the template put documents into
+/// subdirectories. Braces are written twice to use them literally.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Default for Template {
+    fn default() -> Self {
+        Self {
+            parts: vec![Part::Field(Field::Title, Vec::new())],
+        }
+    }
+}
+
+impl Template {
+    /// The template to use if none is given, `{standard_id?{standard_id} - }{title}` for
+    /// standards, `{date?{date} - }{case_no?{case_no} - }{title}` for court filings,
+    /// `{date?{date} - }{title}{amount? {amount}}{currency? {currency}}` for receipts,
+    /// `{composer?{composer} - }{title}` for sheet music and `{title}` otherwise.
+    pub fn for_profile(profile: Option<Profile>) -> Self {
+        // `{field?{field} - }`
+        let prefix = |field| {
+            Part::Conditional(
+                Condition::Present(field),
+                vec![
+                    Part::Field(field, Vec::new()),
+                    Part::Literal(" - ".to_string()),
+                ],
+            )
+        };
+        let prefixes = match profile {
+            Some(Profile::Standard) => vec![prefix(Field::StandardId)],
+            Some(Profile::Legal) => vec![prefix(Field::Date), prefix(Field::CaseNumber)],
+            Some(Profile::Receipt) => vec![prefix(Field::Date)],
+            Some(Profile::Music) => vec![prefix(Field::Composer)],
+            Some(Profile::Book | Profile::Magazine | Profile::Statement | Profile::Datasheet)
+            | None => Vec::new(),
+        };
+        // `{field? {field}}`
+        let suffix = |field| {
+            Part::Conditional(
+                Condition::Present(field),
+                vec![
+                    Part::Literal(" ".to_string()),
+                    Part::Field(field, Vec::new()),
+                ],
+            )
+        };
+        let suffixes = match profile {
+            Some(Profile::Receipt) => vec![suffix(Field::Amount), suffix(Field::Currency)],
+            _ => Vec::new(),
+        };
+
+        Self {
+            parts: prefixes
+                .into_iter()
+                .chain([Part::Field(Field::Title, Vec::new())])
+                .chain(suffixes)
+                .collect(),
+        }
+    }
+}
+
+impl FromStr for Template {
+    type Err = Error;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            chars: template.char_indices().peekable(),
+        };
+
+        Ok(Self {
+            parts: parser.parts(None)?,
+        })
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    /// Parse parts until the end of the template or, if `opening` is the position of the `{` of a
+    /// conditional, until the `}` closing it.
+    fn parts(&mut self, opening: Option<usize>) -> Result<Vec<Part>, Error> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+
+        loop {
+            let Some((position, c)) = self.chars.next() else {
+                if let Some(opening) = opening {
+                    return Err(Error::Unclosed(opening));
+                }
+                break;
+            };
+
+            match c {
+                '{' if self.chars.next_if(|(_, c)| *c == '{').is_some() => literal.push('{'),
+                '}' if self.chars.next_if(|(_, c)| *c == '}').is_some() => literal.push('}'),
+                '{' => {
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(self.placeholder(position)?);
+                }
+                '}' if opening.is_some() => break,
+                '}' => return Err(Error::Unmatched(position)),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        Ok(parts)
+    }
+
+    /// Parse the placeholder after the `{` at `opening`.
+    fn placeholder(&mut self, opening: usize) -> Result<Part, Error> {
+        let text = self.until(opening, &['}', '|', '?'])?;
+        if self.chars.next_if(|(_, c)| *c == '?').is_some() {
+            return Ok(Part::Conditional(text.parse()?, self.parts(Some(opening))?));
+        }
+        let field = text.trim().parse()?;
+
+        match self.chars.next().map(|(_, c)| c) {
+            Some('|') => {
+                let mut filters = Vec::new();
+                loop {
+                    filters.push(self.until(opening, &['}', '|'])?.trim().parse()?);
+                    if self.chars.next().is_some_and(|(_, c)| c == '}') {
+                        return Ok(Part::Field(field, filters));
+                    }
+                }
+            }
+            _ => Ok(Part::Field(field, Vec::new())),
+        }
+    }
+
+    /// Take the text up to the next of the `delimiters`, which is left to be consumed.
+    fn until(&mut self, opening: usize, delimiters: &[char]) -> Result<String, Error> {
+        let mut text = String::new();
+        while let Some((_, c)) = self.chars.next_if(|(_, c)| !delimiters.contains(c)) {
+            text.push(c);
+        }
+
+        if self.chars.peek().is_none() {
+            Err(Error::Unclosed(opening))
+        } else {
+            Ok(text)
+        }
+    }
+}
+
+/// How many authors `{authors}` names before abbreviating them with `et al.`.
+const DEFAULT_AUTHORS: usize = 2;
+
+/// The values of the fields of a document.
+pub struct Values<'a> {
+    pub title: &'a str,
+    pub title_alt: Option<&'a str>,
+    /// The family names of all authors.
+    pub authors: &'a [&'a str],
+    pub counter: Option<u64>,
+    /// The confidence in the title, if it was found in the layout.
+    pub confidence: Option<f32>,
+    /// The metadata of the document, which the other fields are taken from.
+    pub metadata: &'a Metadata,
+}
+
+impl<'a> Values<'a> {
+    /// The value of `field`, `None` if it is missing or blank.
+    pub fn get(&self, field: Field) -> Option<Cow<'a, str>> {
+        let first = |fields: &'a [pdf::Field<String>]| {
+            fields.first().map(|field| Cow::from(field.value.as_str()))
+        };
+
+        match field {
+            Field::Title => Some(self.title.into()),
+            Field::TitleAlt => self.title_alt.map(Cow::from),
+            Field::Author => first(&self.metadata.author),
+            Field::Authors { max } => abbreviate(self.authors, max).map(Cow::from),
+            Field::Year => self
+                .metadata
+                .year
+                .first()
+                .map(|year| year.value.to_string().into()),
+            Field::Counter { width } => self
+                .counter
+                .map(|counter| format!("{counter:0width$}").into()),
+            Field::Publication => first(&self.metadata.publication),
+            Field::IssueDate => first(&self.metadata.issue_date),
+            Field::StandardId => first(&self.metadata.standard_id)
+                .map(|standard_id| standard_id.replace([':', '/'], "-").into()),
+            Field::CaseNumber => first(&self.metadata.case_number)
+                .map(|case_number| case_number.replace([':', '/'], "-").into()),
+            Field::DocumentType => first(&self.metadata.document_type),
+            Field::Date => first(&self.metadata.filed)
+                .or_else(|| first(&self.metadata.purchased))
+                .or_else(|| first(&self.metadata.created)),
+            Field::Institution => first(&self.metadata.institution),
+            Field::Period => first(&self.metadata.period),
+            Field::Account => first(&self.metadata.account_suffix),
+            Field::Manufacturer => first(&self.metadata.manufacturer),
+            Field::PartNumber => first(&self.metadata.part_number)
+                .map(|part_number| part_number.replace('/', "-").into()),
+            Field::Vendor => first(&self.metadata.vendor),
+            Field::Amount => first(&self.metadata.amount),
+            Field::Currency => first(&self.metadata.currency),
+            Field::Composer => first(&self.metadata.composer),
+            Field::Piece => first(&self.metadata.piece),
+            Field::Preset => self.metadata.class.map(|class| class.to_string().into()),
+            Field::Confidence => self
+                .confidence
+                .map(|confidence| format!("{confidence:.2}").into()),
+        }
+        .filter(|value| !value.trim().is_empty())
+    }
+}
+
+/// List the family names of `authors` as `Doe`, `Doe & Roe` or `Doe, Roe & Poe`, or as
+/// `Doe et al.` if there are more than `max`.
+fn abbreviate(authors: &[&str], max: usize) -> Option<String> {
+    match authors {
+        [] => None,
+        [first, ..] if authors.len() > max => Some(format!("{first} et al.")),
+        [author] => Some((*author).to_string()),
+        [rest @ .., last] => Some(format!("{} & {last}", rest.join(", "))),
+    }
+}
+
+impl Template {
+    /// Whether the template numbers documents with `{n}`.
+    pub fn uses_counter(&self) -> bool {
+        self.fields()
+            .iter()
+            .any(|field| matches!(field, Field::Counter { .. }))
+    }
+
+    /// The fields used in the template, in the order they first appear.
+    pub fn fields(&self) -> Vec<Field> {
+        let mut fields = Vec::new();
+        for (field, _) in uses(&self.parts, &[]) {
+            if !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+
+        fields
+    }
+
+    /// The optional fields that are left empty for documents without them, because they are
+    /// neither given a default nor inside a conditional on themselves.
+    pub fn unguarded(&self) -> Vec<Field> {
+        let mut fields = Vec::new();
+        for (field, guarded) in uses(&self.parts, &[]) {
+            if field.is_optional() && !guarded && !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+
+        fields
+    }
+
+    /// Fill in the fields of the template. Missing fields are left empty unless they have a
+    /// default, and conditional parts are left out. Brackets left empty by missing fields, like
+    /// the ones of `{title} ({title_alt})` for documents with a title in one language, are removed.
+    pub fn render(&self, values: &Values) -> String {
+        remove_empty_brackets(&render(&self.parts, values))
+    }
+}
+
+/// The values of the fields of a name a template renders, as read back from the name by
+/// [`Template::parse`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Parsed {
+    /// The values of the fields in the order they appear in the name. Fields left out by a
+    /// conditional or filled with their default are missing.
+    pub fields: Vec<(Field, String)>,
+    /// Whether any literal text of the template was found in the name.
+    literal: bool,
+}
+
+impl Parsed {
+    /// The value of `field`, if it is in the name.
+    pub fn get(&self, field: Field) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(parsed, _)| *parsed == field)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The metadata read from the name. Fields that don't belong to one metadata field, like
+    /// `{authors}` or `{n}`, are left out.
+    pub fn metadata(&self) -> Metadata {
+        let mut metadata = Metadata::default();
+        for (field, value) in &self.fields {
+            let value = value.clone();
+            let fields = match field {
+                Field::Title => &mut metadata.title,
+                Field::Author => &mut metadata.author,
+                Field::Year => {
+                    metadata.year.extend(
+                        value
+                            .parse()
+                            .ok()
+                            .map(|year| pdf::Field::new(year, Provenance::FileName)),
+                    );
+                    continue;
+                }
+                Field::Publication => &mut metadata.publication,
+                Field::IssueDate => &mut metadata.issue_date,
+                Field::StandardId => &mut metadata.standard_id,
+                Field::CaseNumber => &mut metadata.case_number,
+                Field::DocumentType => &mut metadata.document_type,
+                Field::Date => &mut metadata.created,
+                Field::Institution => &mut metadata.institution,
+                Field::Period => &mut metadata.period,
+                Field::Account => &mut metadata.account_suffix,
+                Field::Manufacturer => &mut metadata.manufacturer,
+                Field::PartNumber => &mut metadata.part_number,
+                Field::Vendor => &mut metadata.vendor,
+                Field::Amount => &mut metadata.amount,
+                Field::Currency => &mut metadata.currency,
+                Field::Composer => &mut metadata.composer,
+                Field::Piece => &mut metadata.piece,
+                Field::TitleAlt
+                | Field::Authors { .. }
+                | Field::Counter { .. }
+                | Field::Preset
+                | Field::Confidence => continue,
+            };
+            fields.push(pdf::Field::new(value, Provenance::FileName));
+        }
+
+        metadata
+    }
+
+    /// Whether the name has more than a title, which any name could be read as.
+    pub fn is_distinctive(&self) -> bool {
+        self.literal
+            || self
+                .fields
+                .iter()
+                .any(|(field, _)| !matches!(field, Field::Title))
+    }
+}
+
+impl Template {
+    /// Read the values of the fields back from `name`, if the template could have rendered it.
+    ///
+    /// Fields are read as short as possible, and values that can't be in a field, like a year that
+    /// is not a number, are passed over. Filters other than defaults are not undone, so the value
+    /// of `{title|upper}` is read in upper case.
+    pub fn parse(&self, name: &str) -> Option<Parsed> {
+        let mut parsed = Parsed::default();
+        let parts: Vec<_> = self.parts.iter().collect();
+
+        parse(&parts, name, &mut parsed).then_some(parsed)
+    }
+}
+
+/// Match `name` against `parts` and add the values of their fields to `parsed`. Returns whether
+/// all of `name` matched, leaving `parsed` as it was otherwise.
+fn parse(parts: &[&Part], name: &str, parsed: &mut Parsed) -> bool {
+    let Some((part, rest)) = parts.split_first() else {
+        return name.is_empty();
+    };
+
+    match part {
+        Part::Literal(literal) => name.strip_prefix(literal.as_str()).is_some_and(|name| {
+            let matched = parsed.literal;
+            parsed.literal |= !literal.trim().is_empty();
+            parse(rest, name, parsed) || {
+                parsed.literal = matched;
+                false
+            }
+        }),
+        Part::Field(field, filters) => name
+            .char_indices()
+            .skip(1)
+            .map(|(end, _)| end)
+            .chain([name.len()])
+            .filter(|end| *end > 0)
+            .any(|end| {
+                let value = &name[..end];
+                let default = filters
+                    .iter()
+                    .any(|filter| matches!(filter, Filter::Default(text) if text == value));
+                if default {
+                    return parse(rest, &name[end..], parsed);
+                }
+                if !field.accepts(value) {
+                    return false;
+                }
+                parsed.fields.push((*field, value.to_string()));
+                parse(rest, &name[end..], parsed) || {
+                    parsed.fields.pop();
+                    false
+                }
+            }),
+        Part::Conditional(_, parts) => {
+            let present: Vec<_> = parts.iter().chain(rest.iter().copied()).collect();
+            parse(&present, name, parsed) || parse(rest, name, parsed)
+        }
+    }
+}
+
+impl Field {
+    /// Whether `value` could be the value of the field in a file name.
+    fn accepts(self, value: &str) -> bool {
+        let digits = |value: &str| value.chars().all(|c| c.is_ascii_digit());
+        if value.trim() != value || value.is_empty() {
+            return false;
+        }
+
+        match self {
+            Self::Year => value.len() == 4 && digits(value),
+            Self::Counter { width } => value.len() >= width && digits(value),
+            Self::Account => digits(value),
+            Self::Confidence => value.chars().all(|c| c.is_ascii_digit() || c == '.'),
+            Self::Date | Self::IssueDate | Self::Period => {
+                value.starts_with(|c: char| c.is_ascii_digit())
+            }
+            Self::Amount => value
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == '.' || c == ','),
+            Self::Currency => value.len() == 3 && value.chars().all(|c| c.is_ascii_uppercase()),
+            _ => true,
+        }
+    }
+}
+
+/// Every use of a field in `parts` and whether it is guarded against the field missing, with the
+/// fields of the conditionals the parts are in being `guards`.
+fn uses(parts: &[Part], guards: &[Field]) -> Vec<(Field, bool)> {
+    parts
+        .iter()
+        .flat_map(|part| match part {
+            Part::Literal(_) => Vec::new(),
+            Part::Field(field, filters) => {
+                let has_default = filters
+                    .iter()
+                    .any(|filter| matches!(filter, Filter::Default(_)));
+                vec![(*field, has_default || guards.contains(field))]
+            }
+            Part::Conditional(condition, parts) => {
+                let field = condition.field();
+                let guards = [guards, &[field]].concat();
+                std::iter::once((field, true))
+                    .chain(uses(parts, &guards))
+                    .collect()
+            }
+        })
+        .collect()
+}
+
+/// Remove the parentheses and square brackets with nothing but whitespace between them from
+/// `name`, together with the whitespace in front of them.
+fn remove_empty_brackets(name: &str) -> String {
+    let mut removed = String::with_capacity(name.len());
+    let mut rest = name;
+    while let Some(start) = rest.find(['(', '[']) {
+        let closing = if rest[start..].starts_with('(') {
+            ')'
+        } else {
+            ']'
+        };
+        let inside = &rest[start + 1..];
+        if let Some(after) = inside.trim_start().strip_prefix(closing) {
+            removed.push_str(rest[..start].trim_end());
+            // Brackets at the start leave no whitespace in front of what follows.
+            rest = if removed.is_empty() {
+                after.trim_start()
+            } else {
+                after
+            };
+        } else {
+            removed.push_str(&rest[..=start]);
+            rest = inside;
+        }
+    }
+    removed.push_str(rest);
+
+    removed
+}
+
+fn render(parts: &[Part], values: &Values) -> String {
+    parts
+        .iter()
+        .map(|part| match part {
+            Part::Literal(literal) => literal.into(),
+            // Slashes in the template put documents into subdirectories, but not those in
+            // values.
+            Part::Field(field, filters) => filters
+                .iter()
+                .fold(values.get(*field), |value, filter| filter.apply(value))
+                .map(|value| value.replace('/', "").into())
+                .unwrap_or_default(),
+            Part::Conditional(condition, parts) if condition.holds(values) => {
+                render(parts, values).into()
+            }
+            Part::Conditional(..) => Cow::default(),
+        })
+        .collect()
+}
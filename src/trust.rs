@@ -0,0 +1,41 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use nomenclate::pdf::TrustPolicy;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("could not read {path}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not parse trust policy {path}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+}
+
+/// Load a trust policy from a TOML file, see [`TrustPolicy`].
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be read or parsed.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<TrustPolicy, Error> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(|source| Error::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    toml::from_str(&content).map_err(|source| Error::Parse {
+        path: path.to_path_buf(),
+        source,
+    })
+}
@@ -0,0 +1,326 @@
+use std::{
+    ffi::OsString,
+    fs, iter,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    cli::{Args, RunArgs, TuningExportArgs, TuningImportArgs},
+    corrections::{self, Correction, Corrections},
+    i18n::tr,
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("could not read {path}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not write {path}")]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not parse tuning file {path}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("could not serialize tuning file")]
+    Serialize(#[from] toml::ser::Error),
+    #[error("{path} was exported by a newer version (format {version})")]
+    UnsupportedVersion { path: PathBuf, version: u32 },
+    #[error("invalid options: {0}")]
+    Arguments(clap::Error),
+    #[error("{0:?} is not an option of a run")]
+    NotAnOption(String),
+    #[error("{path} exists already with other content")]
+    Exists { path: PathBuf },
+    #[error(transparent)]
+    Corrections(#[from] corrections::Error),
+}
+
+/// The version of the format of tuning files, which is increased on changes older versions cannot
+/// read.
+const VERSION: u32 = 1;
+
+/// The options whose value is a file, which is exported along with them.
+const FILE_OPTIONS: [&str; 3] = ["--trust-policy", "--overrides", "--acronyms"];
+
+/// A setup tuned for a kind of documents, to be shared with others renaming the same kind.
+#[derive(Serialize, Deserialize)]
+struct Tuning {
+    version: u32,
+    /// The options to run with, as they are given on the command line.
+    arguments: Vec<String>,
+    /// The content of the files the options refer to, which are not there on other machines.
+    #[serde(default)]
+    files: Vec<Embedded>,
+    /// The titles learned with `--learn`.
+    #[serde(default)]
+    corrections: Vec<Correction>,
+}
+
+/// A file given to one of the [`FILE_OPTIONS`].
+#[derive(Serialize, Deserialize)]
+struct Embedded {
+    option: String,
+    content: String,
+}
+
+/// Export the options given after `--` together with the titles learned with `--learn` to a file.
+pub fn export(args: &TuningExportArgs) {
+    match write(args) {
+        Ok(count) => println!(
+            "{}",
+            tr!(
+                "tuning-exported",
+                count = count,
+                path = args.output.display().to_string()
+            )
+        ),
+        Err(err) => log::error!("{}", tr!("error-export-tuning", error = err.to_string())),
+    }
+}
+
+/// Add the titles learned in an exported tuning file to the ones learned here, write out the
+/// files its options refer to, and print the options to run with.
+pub fn import(args: &TuningImportArgs) {
+    let directory = args.files.clone().unwrap_or_else(|| {
+        args.input
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .to_path_buf()
+    });
+    match read(&args.input, &directory) {
+        Ok((count, arguments)) => println!(
+            "{}",
+            tr!(
+                "tuning-imported",
+                count = count,
+                arguments = arguments
+                    .iter()
+                    .map(|argument| quote(argument))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+        ),
+        Err(err) => log::error!("{}", tr!("error-import-tuning", error = err.to_string())),
+    }
+}
+
+/// Write the tuning file of `args`. Returns the number of learned titles written to it.
+fn write(args: &TuningExportArgs) -> Result<usize, Error> {
+    validate(&args.arguments)?;
+    let corrections = Corrections::user()
+        .map(|corrections| corrections.load())
+        .transpose()?
+        .map(|learned| learned.corrections().to_vec())
+        .unwrap_or_default();
+    let mut arguments = args.arguments.clone();
+    // The learned titles are only used with `--learn`.
+    if !corrections.is_empty() && !arguments.iter().any(|argument| argument == "--learn") {
+        arguments.push("--learn".to_string());
+    }
+    let count = corrections.len();
+    let files = file_arguments(&arguments)
+        .into_iter()
+        .rev()
+        .map(|(_, option, path)| {
+            let content = fs::read_to_string(&path).map_err(|source| Error::Read {
+                path: path.clone(),
+                source,
+            })?;
+            Ok(Embedded {
+                option: option.to_string(),
+                content,
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+    let content = toml::to_string(&Tuning {
+        version: VERSION,
+        arguments,
+        files,
+        corrections,
+    })?;
+
+    fs::write(&args.output, content).map_err(|source| Error::Write {
+        path: args.output.clone(),
+        source,
+    })?;
+
+    Ok(count)
+}
+
+/// Read the tuning file at `path` and add its learned titles to the ones learned here, leaving out
+/// those that were already learned, and write the files its options refer to to `directory`.
+/// Returns the number of added titles and the options of the tuning file, which refer to the
+/// written files.
+fn read(path: &Path, directory: &Path) -> Result<(usize, Vec<String>), Error> {
+    let mut tuning = load(path)?;
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    for (index, option, original) in file_arguments(&tuning.arguments) {
+        let Some(embedded) = tuning.files.iter().find(|file| file.option == option) else {
+            continue;
+        };
+        let name = original.file_name().unwrap_or_default().to_string_lossy();
+        let written = directory.join(format!("{stem}.{name}"));
+        write_file(&written, &embedded.content)?;
+        println!(
+            "{}",
+            tr!(
+                "tuning-file-written",
+                option = option,
+                path = written.display().to_string()
+            )
+        );
+        tuning.arguments[index] = format!("{option}={}", written.display());
+        if index > 0 && tuning.arguments[index - 1] == option {
+            tuning.arguments.remove(index - 1);
+        }
+    }
+
+    let added = match Corrections::user() {
+        Some(corrections) => {
+            let learned = corrections.load()?;
+            let added: Vec<_> = tuning
+                .corrections
+                .into_iter()
+                .filter(|correction| !learned.corrections().contains(correction))
+                .collect();
+            corrections.append(&added)?;
+            added.len()
+        }
+        None if tuning.corrections.is_empty() => 0,
+        None => {
+            log::warn!("{}", tr!("warn-no-corrections-directory"));
+            0
+        }
+    };
+
+    Ok((added, tuning.arguments))
+}
+
+/// The options of a run of the tuning file at `path` on the documents at `paths`, leaving its
+/// learned titles alone.
+pub fn options(path: &Path, paths: &[PathBuf]) -> Result<RunArgs, Error> {
+    let tuning = load(path)?;
+    let args = Args::try_parse_from(
+        iter::once("nomenclate".into())
+            .chain(tuning.arguments.iter().map(OsString::from))
+            .chain(iter::once("--".into()))
+            .chain(paths.iter().map(OsString::from)),
+    )
+    .map_err(Error::Arguments)?;
+
+    Ok(args.run)
+}
+
+/// Read the tuning file at `path` and make sure it can be run with.
+fn load(path: &Path) -> Result<Tuning, Error> {
+    let content = fs::read_to_string(path).map_err(|source| Error::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let tuning: Tuning = toml::from_str(&content).map_err(|source| Error::Parse {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    if tuning.version > VERSION {
+        return Err(Error::UnsupportedVersion {
+            path: path.to_path_buf(),
+            version: tuning.version,
+        });
+    }
+    validate(&tuning.arguments)?;
+
+    Ok(tuning)
+}
+
+/// The files given to the [`FILE_OPTIONS`] in `arguments`, as `--option FILE` or `--option=FILE`,
+/// with the index of the argument with the file and the option, the last file first.
+fn file_arguments(arguments: &[String]) -> Vec<(usize, &'static str, PathBuf)> {
+    let mut files = Vec::new();
+    for (index, argument) in arguments.iter().enumerate() {
+        // Everything after `--` is a document.
+        if argument == "--" {
+            break;
+        }
+        for option in FILE_OPTIONS {
+            if let Some(path) = argument
+                .strip_prefix(option)
+                .and_then(|rest| rest.strip_prefix('='))
+            {
+                files.push((index, option, PathBuf::from(path)));
+            } else if index > 0 && arguments[index - 1] == option {
+                files.push((index, option, PathBuf::from(argument)));
+            }
+        }
+    }
+    // The last file first, so removing the option before one does not shift the others.
+    files.reverse();
+
+    files
+}
+
+/// Write `content` to a new file at `path`, unless a file with other content is there already.
+fn write_file(path: &Path, content: &str) -> Result<(), Error> {
+    match fs::read_to_string(path) {
+        Ok(existing) if existing == content => return Ok(()),
+        Ok(_) => {
+            return Err(Error::Exists {
+                path: path.to_path_buf(),
+            })
+        }
+        Err(_) => {}
+    }
+
+    fs::write(path, content).map_err(|source| Error::Write {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Make sure the `arguments` are options of a run, without documents to rename.
+fn validate(arguments: &[String]) -> Result<(), Error> {
+    let placeholder = PathBuf::from(".");
+    let args = Args::try_parse_from(
+        iter::once("nomenclate")
+            .chain(arguments.iter().map(String::as_str))
+            .chain(["--", "."]),
+    )
+    .map_err(Error::Arguments)?;
+
+    // Subcommands come first.
+    if args.command.is_some() {
+        return Err(Error::NotAnOption(arguments[0].clone()));
+    }
+    args.run
+        .paths
+        .iter()
+        .find(|path| **path != placeholder)
+        .map_or(Ok(()), |path| {
+            Err(Error::NotAnOption(path.display().to_string()))
+        })
+}
+
+/// Quote `argument` for a shell if it has anything but letters, digits and a few safe characters.
+fn quote(argument: &str) -> String {
+    let safe = !argument.is_empty()
+        && argument
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '/' | '=' | ':' | ','));
+    if safe {
+        argument.to_string()
+    } else {
+        format!("'{}'", argument.replace('\'', r"'\''"))
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use crate::{cli::VerifyArgs, i18n::tr, rename};
+
+/// Extract the titles of the documents again and print the ones whose names differ from the
+/// names they would get by more than `--max-distance`. Nothing is renamed. Exits with an error if
+/// any document does not match its name.
+pub fn verify(args: &VerifyArgs) {
+    let mut run = args.run.clone();
+    // Names after the template are what is verified, so they are neither skipped nor read.
+    run.rename_templated = true;
+    run.name_template = None;
+
+    let Some(paths) = crate::documents_to_process(&run, None) else {
+        return;
+    };
+    let (documents, failures) = crate::extract_documents(&run, &paths, &HashMap::new());
+
+    let mut mismatched = 0;
+    for document in &documents {
+        let name = crate::file_name(&run, document, None);
+        let Some(expected) = rename::target(&document.path, &name, run.separator) else {
+            continue;
+        };
+        let distance = 1.0 - rename::similarity(&document.path, &expected);
+        if distance > args.max_distance {
+            mismatched += 1;
+            println!(
+                "{}",
+                tr!(
+                    "verify-mismatch",
+                    path = document.path.display().to_string(),
+                    title = document.title.as_str(),
+                    distance = format!("{distance:.2}")
+                )
+            );
+        }
+    }
+    for failure in &failures {
+        log::warn!(
+            "{}",
+            tr!(
+                "verify-uncertain",
+                path = failure.path.display().to_string()
+            )
+        );
+    }
+
+    println!(
+        "{}",
+        tr!(
+            "verify-summary",
+            mismatched = mismatched,
+            count = documents.len(),
+            uncertain = failures.len()
+        )
+    );
+    if mismatched > 0 {
+        std::process::exit(1);
+    }
+}
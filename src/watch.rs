@@ -0,0 +1,246 @@
+use std::{
+    cell::Cell,
+    collections::{HashMap, HashSet},
+    path::{self, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use notify::{Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use signal_hook::consts::{SIGINT, SIGTERM};
+
+use crate::{
+    cli::WatchArgs,
+    counter::Counters,
+    desktop, documents_to_process, files,
+    i18n::tr,
+    journal::{Journal, Rename},
+    lock_directories, process,
+    report::Report,
+    systemd,
+};
+
+/// How long to sleep at a time while waiting for changes to documents, so a request to stop is
+/// noticed quickly.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Who to tell about the progress of a watch.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Notify {
+    /// The systemd service manager, through `$NOTIFY_SOCKET`.
+    Systemd,
+    /// The user, with a desktop notification for the documents renamed close together.
+    Desktop,
+}
+
+/// Rename documents in the watched directories as they are added or modified, until the process
+/// is asked to stop with `SIGTERM` or `SIGINT`.
+///
+/// A document is renamed once it was left alone for `--settle`, so it is not renamed while it is
+/// still being written. Stopping finishes the document being renamed and journals the renames of
+/// the current pass.
+pub fn watch(args: &WatchArgs) {
+    let stop = Arc::new(AtomicBool::new(false));
+    for signal in [SIGTERM, SIGINT] {
+        if let Err(err) = signal_hook::flag::register(signal, Arc::clone(&stop)) {
+            log::error!("{}", tr!("error-watch-signal", error = err.to_string()));
+            return;
+        }
+    }
+
+    let Some(_locks) = lock_directories(&args.run) else {
+        return;
+    };
+    let (sender, events) = mpsc::channel();
+    let watcher = match watch_paths(args, sender) {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            log::error!("{}", tr!("error-watch", error = err.to_string()));
+            return;
+        }
+    };
+    let journal = Journal::user();
+    let audit_log = args.audit.audit_log();
+    // Documents changed before the watch started are only renamed if asked for.
+    let mut paths = if args.run.changed_since.is_some() {
+        let Some(paths) = documents_to_process(&args.run, journal.as_ref()) else {
+            return;
+        };
+        paths
+    } else {
+        Vec::new()
+    };
+    let mut counters = Counters::new(args.run.counter, args.run.counter_start);
+    let mut report = Report::default();
+    let notifier = Notifier::new(&args.notify);
+
+    // The last time each document was changed, until it is left alone for long enough.
+    let mut changed = HashMap::new();
+    // The documents renamed by the watch, which should not be renamed again.
+    let mut own = HashSet::new();
+
+    notifier.send("READY=1\nSTATUS=Watching for documents");
+    log::info!("watching for documents");
+    let mut processed = 0;
+
+    loop {
+        if !paths.is_empty() {
+            log::info!("found {} new or modified documents", paths.len());
+            let renames = process(
+                &args.run,
+                &paths,
+                journal.as_ref(),
+                audit_log.as_ref(),
+                &mut counters,
+                &mut report,
+                &stop,
+            );
+            own.extend(renames.iter().map(|rename| rename.to.clone()));
+            if !args.run.dry_run {
+                notifier.renamed(renames);
+            }
+            processed += paths.len();
+            notifier.send(&format!("STATUS=Processed {processed} documents"));
+        }
+
+        if !notifier.wait(TICK, &stop) {
+            break;
+        }
+        note_changes(&events, &own, &mut changed);
+        paths = settled(&mut changed, args.settle);
+    }
+
+    drop(watcher);
+    notifier.send("STOPPING=1");
+    log::info!("stopped watching");
+    if args.run.verbose {
+        report.summarize();
+    }
+}
+
+/// Start watching the paths of `args` recursively, sending the events to `sender`.
+fn watch_paths(
+    args: &WatchArgs,
+    sender: mpsc::Sender<notify::Result<Event>>,
+) -> notify::Result<Box<dyn Watcher>> {
+    let mut watcher: Box<dyn Watcher> = if args.poll {
+        let config = notify::Config::default().with_poll_interval(args.interval);
+        Box::new(PollWatcher::new(sender, config)?)
+    } else {
+        Box::new(RecommendedWatcher::new(sender, notify::Config::default())?)
+    };
+    for path in &args.run.paths {
+        // the paths of events start with the watched path, which is compared to absolute paths
+        let path = path::absolute(path).unwrap_or_else(|_| path.clone());
+        watcher.watch(&path, RecursiveMode::Recursive)?;
+    }
+
+    Ok(watcher)
+}
+
+/// Note when the documents in the received `events` were changed, leaving out the `own` documents
+/// renamed by the watch.
+fn note_changes(
+    events: &Receiver<notify::Result<Event>>,
+    own: &HashSet<PathBuf>,
+    changed: &mut HashMap<PathBuf, Instant>,
+) {
+    for event in events.try_iter() {
+        match event {
+            Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+                for path in event.paths {
+                    if files::is_pdf(&path) && !own.contains(&path) {
+                        changed.insert(path, Instant::now());
+                    }
+                }
+            }
+            Ok(_) => {}
+            Err(err) => log::warn!("{}", tr!("warn-watch-event", error = err.to_string())),
+        }
+    }
+}
+
+/// Take the documents out of `changed` that were left alone for `settle` and still exist.
+fn settled(changed: &mut HashMap<PathBuf, Instant>, settle: Duration) -> Vec<PathBuf> {
+    let mut settled: Vec<_> = changed
+        .extract_if(|_, time| time.elapsed() >= settle)
+        .map(|(path, _)| path)
+        .filter(|path| path.is_file())
+        .collect();
+    settled.sort();
+
+    settled
+}
+
+/// Sends notifications to the targets of `--notify`.
+struct Notifier {
+    systemd: bool,
+    desktop: Option<desktop::Notifications>,
+    /// How often to ping the systemd watchdog, if it is enabled.
+    watchdog: Option<Duration>,
+    /// When the watchdog was last pinged.
+    pinged: Cell<Instant>,
+}
+
+impl Notifier {
+    fn new(targets: &[Notify]) -> Self {
+        let systemd = targets.contains(&Notify::Systemd);
+
+        Self {
+            systemd,
+            desktop: targets
+                .contains(&Notify::Desktop)
+                .then(desktop::Notifications::spawn),
+            // Ping twice as often as required, as `sd_watchdog_enabled(3)` recommends.
+            watchdog: systemd
+                .then(systemd::watchdog_interval)
+                .flatten()
+                .map(|interval| interval / 2),
+            pinged: Cell::new(Instant::now()),
+        }
+    }
+
+    fn send(&self, state: &str) {
+        if !self.systemd {
+            return;
+        }
+        if let Err(err) = systemd::notify(state) {
+            log::warn!("{}", tr!("warn-notify-systemd", error = err.to_string()));
+        }
+    }
+
+    /// Show a desktop notification for the `renames`.
+    fn renamed(&self, renames: Vec<Rename>) {
+        if let Some(desktop) = &self.desktop {
+            desktop.renamed(renames);
+        }
+    }
+
+    /// Wait for `duration` while pinging the watchdog when it is due. Returns `false` if the
+    /// watch should stop because `stop` was set.
+    fn wait(&self, duration: Duration, stop: &AtomicBool) -> bool {
+        let start = Instant::now();
+
+        while start.elapsed() < duration {
+            if stop.load(Ordering::Relaxed) {
+                return false;
+            }
+            if self
+                .watchdog
+                .is_some_and(|watchdog| self.pinged.get().elapsed() >= watchdog)
+            {
+                self.send("WATCHDOG=1");
+                self.pinged.set(Instant::now());
+            }
+            thread::sleep(TICK.min(duration.saturating_sub(start.elapsed())));
+        }
+
+        !stop.load(Ordering::Relaxed)
+    }
+}